@@ -0,0 +1,105 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::SequentialGraph;
+use lender::*;
+
+/// The result of [`audit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AuditReport {
+    /// The number of arcs, counting duplicates.
+    pub num_arcs: u64,
+    /// Whether every successor list is strictly increasing (i.e., sorted and
+    /// without duplicates).
+    pub sorted: bool,
+    /// Whether some node is a successor of itself.
+    pub has_loops: bool,
+    /// The largest successor, or `None` if the graph has no arcs.
+    pub max_node: Option<usize>,
+}
+
+/// Scans a graph once, counting its arcs and checking its successor lists.
+///
+/// The successor lists of the graphs of this crate are expected to be
+/// strictly increasing, and the exact number of arcs is not always available
+/// (see [`num_arcs_hint`](crate::traits::SequentialLabeling::num_arcs_hint)):
+/// this function gathers in a single pass the information usually needed to
+/// validate a graph before further processing. The largest successor is
+/// reported even if it is not smaller than the number of nodes, so that
+/// out-of-range successors can be detected by comparing it with
+/// [`num_nodes`](crate::traits::SequentialLabeling::num_nodes).
+///
+/// Memory usage is constant.
+pub fn audit(graph: impl SequentialGraph) -> AuditReport {
+    let mut report = AuditReport {
+        sorted: true,
+        ..AuditReport::default()
+    };
+    for_!((node, succ) in graph.iter() {
+        let mut prev = None;
+        for s in succ {
+            if prev.is_some_and(|p| p >= s) {
+                report.sorted = false;
+            }
+            if s == node {
+                report.has_loops = true;
+            }
+            report.max_node = report.max_node.max(Some(s));
+            report.num_arcs += 1;
+            prev = Some(s);
+        }
+    });
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::arc_list_graph::ArcListGraph;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_audit() {
+        let mut graph = VecGraph::<()>::empty(6);
+        for (u, v) in [(0, 0), (0, 2), (0, 4), (1, 3), (3, 3), (4, 0)] {
+            graph.add_arc(u, v);
+        }
+        assert_eq!(
+            audit(Left(graph)),
+            AuditReport {
+                num_arcs: 6,
+                sorted: true,
+                has_loops: true,
+                max_node: Some(4),
+            }
+        );
+
+        let graph = Left(VecGraph::from_arcs([(0, 1), (1, 2)]));
+        let report = audit(&graph);
+        assert!(report.sorted);
+        assert!(!report.has_loops);
+        assert_eq!(report.max_node, Some(2));
+
+        assert_eq!(
+            audit(Left(VecGraph::<()>::empty(3))),
+            AuditReport {
+                num_arcs: 0,
+                sorted: true,
+                has_loops: false,
+                max_node: None,
+            }
+        );
+
+        // Unsorted lists and duplicates
+        let report = audit(Left(ArcListGraph::new(3, [(0, 2), (0, 1), (2, 1)])));
+        assert!(!report.sorted);
+        assert_eq!(report.num_arcs, 3);
+        let report = audit(Left(ArcListGraph::new(3, [(0, 1), (0, 1)])));
+        assert!(!report.sorted);
+        assert_eq!(report.num_arcs, 2);
+    }
+}
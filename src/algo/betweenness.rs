@@ -0,0 +1,268 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Betweenness centrality.
+//!
+//! An implementation of the algorithm described by Ulrik Brandes in “A faster
+//! algorithm for betweenness centrality”, _Journal of Mathematical Sociology_,
+//! 25(2):163−177, 2001, which computes the exact betweenness of all nodes of
+//! an unweighted graph with _n_ nodes and _m_ arcs in time _O_(_nm_) by a
+//! breadth-first visit from each node, followed by an accumulation of
+//! dependencies in reverse order of distance.
+//!
+//! Visits are run in parallel, and each thread needs memory for six arrays of
+//! _n_ elements of eight bytes (distances, numbers of shortest paths,
+//! dependencies, the visit queue, the visited nodes, and its partial
+//! centralities), that is, 48_n_ bytes, besides the graph. When this is too
+//! much, or _nm_ too large, [`betweenness_sampled`] estimates the betweenness
+//! using a sample of the sources.
+
+use crate::traits::RandomAccessGraph;
+use rand::rngs::SmallRng;
+use rand::SeedableRng;
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+/// The state of a single-source step of Brandes's algorithm.
+///
+/// Arrays are allocated once and reset after each visit by scanning only the
+/// nodes that have been reached.
+struct Brandes {
+    dist: Vec<usize>,
+    /// The number of shortest paths from the source.
+    sigma: Vec<f64>,
+    /// The dependency of the source on each node.
+    delta: Vec<f64>,
+    queue: VecDeque<usize>,
+    /// The nodes reached, in nondecreasing order of distance.
+    visited: Vec<usize>,
+}
+
+impl Brandes {
+    fn new(num_nodes: usize) -> Self {
+        Self {
+            dist: vec![usize::MAX; num_nodes],
+            sigma: vec![0.0; num_nodes],
+            delta: vec![0.0; num_nodes],
+            queue: VecDeque::new(),
+            visited: Vec::new(),
+        }
+    }
+
+    /// Adds to `centrality` the dependencies of `source` on the other nodes.
+    fn accumulate(&mut self, graph: impl RandomAccessGraph, source: usize, centrality: &mut [f64]) {
+        self.dist[source] = 0;
+        self.sigma[source] = 1.0;
+        self.queue.push_back(source);
+        while let Some(node) = self.queue.pop_front() {
+            self.visited.push(node);
+            let d = self.dist[node] + 1;
+            for succ in graph.successors(node) {
+                if self.dist[succ] == usize::MAX {
+                    self.dist[succ] = d;
+                    self.queue.push_back(succ);
+                }
+                if self.dist[succ] == d {
+                    self.sigma[succ] += self.sigma[node];
+                }
+            }
+        }
+
+        // Nodes are scanned by nondecreasing distance, so the successors of a
+        // node on a shortest path are complete when the node is processed;
+        // scanning successors again avoids storing predecessor lists
+        for &node in self.visited.iter().rev() {
+            let d = self.dist[node] + 1;
+            let mut delta = 0.0;
+            for succ in graph.successors(node) {
+                if self.dist[succ] == d {
+                    delta += self.sigma[node] / self.sigma[succ] * (1.0 + self.delta[succ]);
+                }
+            }
+            self.delta[node] = delta;
+            if node != source {
+                centrality[node] += delta;
+            }
+        }
+
+        for &node in &self.visited {
+            self.dist[node] = usize::MAX;
+            self.sigma[node] = 0.0;
+            self.delta[node] = 0.0;
+        }
+        self.visited.clear();
+    }
+}
+
+/// Runs Brandes's accumulation from the given sources in parallel, returning
+/// the sum of the dependencies.
+fn brandes(
+    graph: &(impl RandomAccessGraph + Sync),
+    sources: impl ParallelIterator<Item = usize>,
+) -> Box<[f64]> {
+    let num_nodes = graph.num_nodes();
+    sources
+        .fold(
+            || (Brandes::new(num_nodes), vec![0.0; num_nodes]),
+            |(mut state, mut centrality), source| {
+                state.accumulate(graph, source, &mut centrality);
+                (state, centrality)
+            },
+        )
+        .map(|(_, centrality)| centrality)
+        .reduce(
+            || vec![0.0; num_nodes],
+            |mut a, b| {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x += y);
+                a
+            },
+        )
+        .into_boxed_slice()
+}
+
+/// Returns the betweenness centrality of the nodes of a graph.
+///
+/// The betweenness of a node _x_ is the sum, over all pairs of nodes _s_ ≠
+/// _x_ ≠ _t_, of the fraction of shortest paths from _s_ to _t_ passing
+/// through _x_. It is computed exactly using Brandes's algorithm, which
+/// performs a breadth-first visit from each node and then accumulates
+/// dependencies in reverse order of distance.
+///
+/// Pairs are ordered, so on a symmetric graph each unordered pair is counted
+/// twice, and values must be halved to obtain the betweenness of the
+/// corresponding undirected graph.
+///
+/// Visits are performed in parallel using the current [`rayon`] thread pool,
+/// each thread accumulating into a separate array.
+///
+/// # Cost
+///
+/// The algorithm needs time _O_(_nm_) on a graph with _n_ nodes and _m_ arcs,
+/// and memory proportional to the number of nodes times the number of
+/// threads. It is thus usable only on small and medium-sized graphs: on
+/// large graphs, consider [`betweenness_sampled`].
+pub fn betweenness(graph: impl RandomAccessGraph + Sync) -> Box<[f64]> {
+    brandes(&graph, (0..graph.num_nodes()).into_par_iter())
+}
+
+/// An estimate of the betweenness centrality computed by
+/// [`betweenness_sampled`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SampledBetweenness {
+    /// The estimated betweenness of each node.
+    pub centrality: Box<[f64]>,
+    /// The sources used for the estimate, in increasing order.
+    pub sources: Box<[usize]>,
+}
+
+/// Estimates the betweenness centrality of the nodes of a graph using
+/// `num_sources` distinct sources chosen uniformly at random.
+///
+/// The dependencies of the sampled sources are accumulated as in
+/// [`betweenness`], and then multiplied by _n_ / `num_sources`, where _n_ is
+/// the number of nodes, which gives an unbiased estimate of the betweenness of
+/// each node. The cost is thus _O_(`num_sources` · _m_) on a graph with _m_
+/// arcs. Sources are chosen using a [`SmallRng`] seeded with `seed`, and
+/// they are returned together with the estimate for reproducibility.
+///
+/// If `num_sources` is at least the number of nodes, all nodes are used as
+/// sources and the result is exact.
+pub fn betweenness_sampled(
+    graph: impl RandomAccessGraph + Sync,
+    num_sources: usize,
+    seed: u64,
+) -> SampledBetweenness {
+    let num_nodes = graph.num_nodes();
+    let num_sources = num_sources.min(num_nodes);
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut sources = rand::seq::index::sample(&mut rng, num_nodes, num_sources).into_vec();
+    sources.sort_unstable();
+
+    let mut centrality = brandes(&graph, sources.par_iter().copied());
+    if num_sources != 0 {
+        let scale = num_nodes as f64 / num_sources as f64;
+        centrality.iter_mut().for_each(|x| *x *= scale);
+    }
+    SampledBetweenness {
+        centrality,
+        sources: sources.into_boxed_slice(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    fn undirected_path(n: usize) -> Left<VecGraph> {
+        Left(VecGraph::from_arcs(
+            (0..n - 1).flat_map(|x| [(x, x + 1), (x + 1, x)]),
+        ))
+    }
+
+    #[test]
+    fn test_betweenness_path() {
+        // On a path, node x lies on the shortest path of x (n - 1 - x) pairs
+        let n = 7;
+        let g = Left(VecGraph::from_arcs((0..n - 1).map(|x| (x, x + 1))));
+        let expected = (0..n).map(|x| (x * (n - 1 - x)) as f64).collect::<Vec<_>>();
+        assert_eq!(&*betweenness(&g), &expected[..]);
+
+        // Each unordered pair is counted twice on a symmetric graph
+        let doubled = expected.iter().map(|x| 2.0 * x).collect::<Vec<_>>();
+        assert_eq!(&*betweenness(undirected_path(n)), &doubled[..]);
+    }
+
+    #[test]
+    fn test_betweenness_multiple_paths() {
+        // 0 -> {1, 2} -> 3 -> 4: 1 and 2 split the paths from 0 to 3 and 4
+        let g = Left(VecGraph::from_arcs([
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+        ]));
+        assert_eq!(&*betweenness(&g), &[0.0, 1.0, 1.0, 3.0, 0.0]);
+
+        assert!(betweenness(Left(VecGraph::<()>::empty(0))).is_empty());
+    }
+
+    #[test]
+    fn test_betweenness_sampled() {
+        // A star with center 0 whose leaves are connected in pairs: every
+        // source other than the center depends only on the center
+        let n = 21;
+        let mut g = VecGraph::<()>::empty(n);
+        for x in 1..n {
+            g.add_arc(0, x);
+            g.add_arc(x, 0);
+        }
+        for x in (1..n).step_by(2) {
+            g.add_arc(x, x + 1);
+            g.add_arc(x + 1, x);
+        }
+        let g = Left(g);
+        let estimate = betweenness_sampled(&g, 5, 0);
+        assert_eq!(estimate.sources.len(), 5);
+        assert!(estimate.sources.windows(2).all(|w| w[0] < w[1]));
+        let center = (0..n)
+            .max_by(|&x, &y| estimate.centrality[x].total_cmp(&estimate.centrality[y]))
+            .unwrap();
+        assert_eq!(center, 0);
+        assert_eq!(estimate, betweenness_sampled(&g, 5, 0));
+
+        // Using all nodes as sources gives the exact values
+        let exact = betweenness_sampled(&g, 2 * n, 0);
+        assert_eq!(&*exact.sources, &(0..n).collect::<Vec<_>>()[..]);
+        assert_eq!(exact.centrality, betweenness(&g));
+
+        let estimate = betweenness_sampled(&g, 0, 0);
+        assert!(estimate.sources.is_empty());
+        assert!(estimate.centrality.iter().all(|&x| x == 0.0));
+    }
+}
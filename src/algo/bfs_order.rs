@@ -77,3 +77,57 @@ impl<'a, G: RandomAccessGraph> ExactSizeIterator for BfsOrder<'a, G> {
         self.graph.num_nodes()
     }
 }
+
+/// Returns the permutation mapping each node to its position in the
+/// [BFS order](BfsOrder) of the graph.
+///
+/// Visits restart from the smallest unvisited node whenever the queue is
+/// empty, so the result is a permutation even if the graph has several
+/// components, and the BFS orders of the components are concatenated. The
+/// element of index _x_ of the result is the new id of node _x_, which is the
+/// convention used by
+/// [`PermutedGraph`](crate::graphs::permuted_graph::PermutedGraph) and by the
+/// `--permutation` option of `webgraph recompress`.
+///
+/// Nodes visited consecutively are close in the new numbering, so the
+/// permutation is a cheap alternative to [LLP](crate::algo::llp) for
+/// improving locality.
+pub fn bfs_permutation(graph: impl RandomAccessGraph) -> Box<[usize]> {
+    let num_nodes = graph.num_nodes();
+    let mut perm = vec![0; num_nodes].into_boxed_slice();
+    if num_nodes == 0 {
+        return perm;
+    }
+    for (i, node) in BfsOrder::new(&graph).enumerate() {
+        perm[node] = i;
+    }
+    perm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_bfs_permutation() {
+        // Two components: 0 -> {3, 5} -> 1 and 2 <-> 4, plus the isolated 6
+        let mut g = VecGraph::<()>::empty(7);
+        for (u, v) in [(0, 3), (0, 5), (3, 1), (5, 1), (2, 4), (4, 2)] {
+            g.add_arc(u, v);
+        }
+        let g = Left(g);
+        let perm = bfs_permutation(&g);
+        assert_eq!(&*perm, &[0, 3, 4, 1, 5, 2, 6]);
+
+        let mut inverse = vec![usize::MAX; perm.len()];
+        for (node, &pos) in perm.iter().enumerate() {
+            assert_eq!(inverse[pos], usize::MAX, "Position {} is repeated", pos);
+            inverse[pos] = node;
+        }
+        assert_eq!(inverse, BfsOrder::new(&g).collect::<Vec<_>>());
+
+        assert!(bfs_permutation(Left(VecGraph::<()>::empty(0))).is_empty());
+    }
+}
@@ -0,0 +1,190 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Semi-supervised node classification by label propagation.
+
+use crate::traits::RandomAccessGraph;
+use anyhow::{ensure, Result};
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+/// Returns the classes of the nodes of a graph computed by propagating the
+/// classes of a set of seed nodes.
+///
+/// `seeds` contains pairs given by a node and its class. At each iteration,
+/// every other node takes the class that is most frequent among its
+/// successors, ignoring successors that have not a class yet; ties are broken
+/// in favor of the current class of the node, if it is among the most
+/// frequent ones, and then in favor of the smallest class. Seed nodes never
+/// change class. Updates are synchronous, that is, they use the classes of
+/// the previous iteration, so the result is deterministic.
+///
+/// Iterations stop when no node changes class, or after `max_iters`
+/// iterations. As in [layered label propagation](crate::algo::llp), only
+/// nodes with a successor that changed class in the previous iteration are
+/// examined, and each iteration is performed in parallel using the current
+/// [`rayon`] thread pool.
+///
+/// The nodes to examine are the predecessors of the nodes that changed class,
+/// so `transpose` must be the transpose of `graph`; if `graph` is symmetric,
+/// which is usually the case, you can pass it twice. The class of nodes that
+/// cannot reach a seed node is [`usize::MAX`].
+///
+/// # Errors
+///
+/// If the two graphs have a different number of nodes, if a seed node is not
+/// smaller than the number of nodes, if a node is given two different
+/// classes, or if a class is [`usize::MAX`].
+pub fn classify(
+    graph: impl RandomAccessGraph + Sync,
+    transpose: impl RandomAccessGraph + Sync,
+    seeds: &[(usize, usize)],
+    max_iters: usize,
+) -> Result<Box<[usize]>> {
+    let num_nodes = graph.num_nodes();
+    ensure!(
+        transpose.num_nodes() == num_nodes,
+        "The graph has {} nodes, but its transpose has {} nodes",
+        num_nodes,
+        transpose.num_nodes()
+    );
+    let mut labels = vec![usize::MAX; num_nodes];
+    let mut is_seed = vec![false; num_nodes];
+    for &(node, label) in seeds {
+        ensure!(
+            node < num_nodes,
+            "Seed node {} does not exist (the graph has {} nodes)",
+            node,
+            num_nodes
+        );
+        ensure!(label != usize::MAX, "Class {} is reserved", usize::MAX);
+        ensure!(
+            !is_seed[node] || labels[node] == label,
+            "Seed node {} has classes {} and {}",
+            node,
+            labels[node],
+            label
+        );
+        labels[node] = label;
+        is_seed[node] = true;
+    }
+
+    // whether a node might change class in the current iteration
+    let mut can_change = (0..num_nodes)
+        .map(|node| AtomicBool::new(!is_seed[node]))
+        .collect::<Vec<_>>();
+    // whether a node might change class in the next iteration
+    let mut next_can_change = (0..num_nodes)
+        .map(|_| AtomicBool::new(false))
+        .collect::<Vec<_>>();
+
+    for _ in 0..max_iters {
+        let modified = AtomicUsize::new(0);
+        let next_labels = (0..num_nodes)
+            .into_par_iter()
+            .with_min_len(1024)
+            .map(|node| {
+                let curr_label = labels[node];
+                // this leaves can_change cleared at the end of the iteration
+                if !can_change[node].swap(false, Ordering::Relaxed) {
+                    return curr_label;
+                }
+
+                let mut map = HashMap::new();
+                for succ in graph.successors(node) {
+                    let label = labels[succ];
+                    if label != usize::MAX {
+                        *map.entry(label).or_insert(0_usize) += 1;
+                    }
+                }
+                let Some(&max) = map.values().max() else {
+                    return curr_label;
+                };
+                if map.get(&curr_label) == Some(&max) {
+                    return curr_label;
+                }
+                let new_label = map
+                    .iter()
+                    .filter(|&(_, &count)| count == max)
+                    .map(|(&label, _)| label)
+                    .min()
+                    .unwrap();
+
+                modified.fetch_add(1, Ordering::Relaxed);
+                for pred in transpose.successors(node) {
+                    if !is_seed[pred] {
+                        next_can_change[pred].store(true, Ordering::Relaxed);
+                    }
+                }
+                new_label
+            })
+            .collect::<Vec<_>>();
+
+        labels = next_labels;
+        core::mem::swap(&mut can_change, &mut next_can_change);
+        if modified.load(Ordering::Relaxed) == 0 {
+            break;
+        }
+    }
+
+    Ok(labels.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_classify() -> Result<()> {
+        // Two 5-cliques connected by the edge 4 - 5, and an isolated node
+        let mut g = VecGraph::empty(11);
+        for clique in [0..5, 5..10] {
+            for x in clique.clone() {
+                for y in clique.clone() {
+                    if x != y {
+                        g.add_arc(x, y);
+                    }
+                }
+            }
+        }
+        g.add_arc(4, 5);
+        g.add_arc(5, 4);
+        let g = Left(g);
+
+        let classes = classify(&g, &g, &[(0, 7), (9, 3)], 100)?;
+        assert_eq!(&*classes, &[7, 7, 7, 7, 7, 3, 3, 3, 3, 3, usize::MAX]);
+
+        // Seeds do not change class, even if all their neighbors disagree
+        let classes = classify(&g, &g, &[(0, 7), (1, 3), (2, 3), (3, 3)], 100)?;
+        assert_eq!(classes[0], 7);
+        assert!(classes[4..10].iter().all(|&c| c == 3));
+
+        // No iterations
+        let classes = classify(&g, &g, &[(0, 7)], 0)?;
+        assert_eq!(classes[0], 7);
+        assert!(classes[1..].iter().all(|&c| c == usize::MAX));
+
+        assert!(classify(&g, &g, &[(11, 0)], 10).is_err());
+        assert!(classify(&g, &g, &[(0, 0), (0, 1)], 10).is_err());
+        assert!(classify(&g, &g, &[(0, usize::MAX)], 10).is_err());
+        assert!(classify(&g, Left(VecGraph::<()>::empty(10)), &[(0, 7)], 10).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_classify_directed() -> Result<()> {
+        // The path 0 -> 1 -> 2: node 0 can be examined only after its
+        // successor 1 has been classified
+        let g = Left(VecGraph::from_arcs([(0, 1), (1, 2)]));
+        let t = Left(VecGraph::from_arcs([(1, 0), (2, 1)]));
+        let classes = classify(&g, &t, &[(2, 5)], 100)?;
+        assert_eq!(&*classes, &[5, 5, 5]);
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Structural comparison of graphs.
+
+use crate::traits::{Pair, SequentialGraph, SequentialLabeling};
+use anyhow::{ensure, Result};
+use lender::*;
+use std::collections::VecDeque;
+
+/// The first difference between two graphs found by [`graphs_equal_verbose`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraphDiffReport {
+    /// The graphs have a different number of nodes.
+    NumNodes(usize, usize),
+    /// The first node whose sorted successor lists differ, followed by the two
+    /// sorted successor lists.
+    Successors(usize, Vec<usize>, Vec<usize>),
+}
+
+/// Returns whether two graphs have the same number of nodes and, for every
+/// node, the same sorted successor lists.
+///
+/// The graphs are scanned sequentially in parallel, and the scan stops at the
+/// first difference. See [`graphs_equal_verbose`] to know where the graphs
+/// differ.
+pub fn graphs_equal(a: impl SequentialGraph, b: impl SequentialGraph) -> bool {
+    graphs_equal_verbose(a, b).is_none()
+}
+
+/// Compares two graphs as [`graphs_equal`], but returns the first difference
+/// found, if any.
+///
+/// The number of nodes is checked first; then, the successor lists of the two
+/// graphs are sorted and compared node by node.
+pub fn graphs_equal_verbose(
+    a: impl SequentialGraph,
+    b: impl SequentialGraph,
+) -> Option<GraphDiffReport> {
+    if a.num_nodes() != b.num_nodes() {
+        return Some(GraphDiffReport::NumNodes(a.num_nodes(), b.num_nodes()));
+    }
+
+    let mut iter_a = a.iter();
+    let mut iter_b = b.iter();
+    let mut succ_a = vec![];
+    let mut succ_b = vec![];
+    let mut num_nodes = 0;
+    loop {
+        match (iter_a.next(), iter_b.next()) {
+            (Some(lend_a), Some(lend_b)) => {
+                let (node, s_a) = lend_a.into_pair();
+                let (_, s_b) = lend_b.into_pair();
+                succ_a.clear();
+                succ_a.extend(s_a);
+                succ_a.sort_unstable();
+                succ_b.clear();
+                succ_b.extend(s_b);
+                succ_b.sort_unstable();
+                if succ_a != succ_b {
+                    return Some(GraphDiffReport::Successors(node, succ_a, succ_b));
+                }
+                num_nodes += 1;
+            }
+            (None, None) => return None,
+            // The lenders returned a different number of nodes
+            (Some(_), None) => return Some(GraphDiffReport::NumNodes(num_nodes + 1, num_nodes)),
+            (None, Some(_)) => return Some(GraphDiffReport::NumNodes(num_nodes, num_nodes + 1)),
+        }
+    }
+}
+
+/// An arc present in just one of two graphs, as returned by [`ArcDiff`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArcChange {
+    /// An arc of the new graph that is not in the old graph.
+    Added(usize, usize),
+    /// An arc of the old graph that is not in the new graph.
+    Removed(usize, usize),
+}
+
+/// The difference between two graphs on the same set of nodes, returned by
+/// [`diff`].
+///
+/// The arcs added and removed are computed on the fly each time one of
+/// [`iter`](Self::iter), [`added`](Self::added), or
+/// [`removed`](Self::removed) is called, by scanning sequentially both graphs
+/// and merging their sorted successor lists node by node, so only the
+/// successor lists of a pair of nodes are in memory at any time.
+pub struct ArcDiff<A, B> {
+    old: A,
+    new: B,
+}
+
+/// Returns the difference between an old and a new graph.
+///
+/// Successor lists are treated as sets, so their order and duplicate arcs are
+/// irrelevant.
+///
+/// # Errors
+///
+/// If the two graphs have a different number of nodes.
+pub fn diff<A: SequentialGraph, B: SequentialGraph>(old: A, new: B) -> Result<ArcDiff<A, B>> {
+    ensure!(
+        old.num_nodes() == new.num_nodes(),
+        "The old graph has {} nodes, but the new graph has {} nodes",
+        old.num_nodes(),
+        new.num_nodes()
+    );
+    Ok(ArcDiff { old, new })
+}
+
+impl<A: SequentialGraph, B: SequentialGraph> ArcDiff<A, B> {
+    /// Returns an iterator over the arcs added and removed, in lexicographical
+    /// order.
+    pub fn iter(&self) -> ArcChanges<'_, A, B> {
+        ArcChanges {
+            old: self.old.iter(),
+            new: self.new.iter(),
+            succ_old: vec![],
+            succ_new: vec![],
+            changes: VecDeque::new(),
+        }
+    }
+
+    /// Returns an iterator over the arcs of the new graph that are not in the
+    /// old graph, in lexicographical order.
+    pub fn added(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.iter().filter_map(|change| match change {
+            ArcChange::Added(src, dst) => Some((src, dst)),
+            ArcChange::Removed(..) => None,
+        })
+    }
+
+    /// Returns an iterator over the arcs of the old graph that are not in the
+    /// new graph, in lexicographical order.
+    pub fn removed(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.iter().filter_map(|change| match change {
+            ArcChange::Removed(src, dst) => Some((src, dst)),
+            ArcChange::Added(..) => None,
+        })
+    }
+}
+
+/// The iterator returned by [`ArcDiff::iter`].
+pub struct ArcChanges<'a, A: SequentialGraph + 'a, B: SequentialGraph + 'a> {
+    old: <A as SequentialLabeling>::Lender<'a>,
+    new: <B as SequentialLabeling>::Lender<'a>,
+    succ_old: Vec<usize>,
+    succ_new: Vec<usize>,
+    /// The changes of the current node not yet returned.
+    changes: VecDeque<ArcChange>,
+}
+
+impl<'a, A: SequentialGraph + 'a, B: SequentialGraph + 'a> Iterator for ArcChanges<'a, A, B> {
+    type Item = ArcChange;
+
+    fn next(&mut self) -> Option<ArcChange> {
+        loop {
+            if let Some(change) = self.changes.pop_front() {
+                return Some(change);
+            }
+
+            let (node, succ) = self.old.next()?.into_pair();
+            self.succ_old.clear();
+            self.succ_old.extend(succ);
+            let (_, succ) = self.new.next()?.into_pair();
+            self.succ_new.clear();
+            self.succ_new.extend(succ);
+            self.succ_old.sort_unstable();
+            self.succ_old.dedup();
+            self.succ_new.sort_unstable();
+            self.succ_new.dedup();
+
+            let (old, new) = (&self.succ_old, &self.succ_new);
+            let (mut i, mut j) = (0, 0);
+            loop {
+                match (old.get(i), new.get(j)) {
+                    (Some(x), Some(y)) if x == y => {
+                        i += 1;
+                        j += 1;
+                    }
+                    (Some(&x), Some(&y)) if x < y => {
+                        self.changes.push_back(ArcChange::Removed(node, x));
+                        i += 1;
+                    }
+                    (_, Some(&y)) => {
+                        self.changes.push_back(ArcChange::Added(node, y));
+                        j += 1;
+                    }
+                    (Some(&x), None) => {
+                        self.changes.push_back(ArcChange::Removed(node, x));
+                        i += 1;
+                    }
+                    (None, None) => break,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_graphs_equal() {
+        let arcs = [(0, 1), (0, 2), (1, 2), (2, 0), (3, 1)];
+        let g = Left(VecGraph::from_arcs(arcs));
+        assert!(graphs_equal(&g, &g));
+        assert!(graphs_equal(&g, Left(VecGraph::from_arcs(arcs))));
+        assert_eq!(graphs_equal_verbose(&g, &g), None);
+
+        // A single missing arc
+        let h = Left(VecGraph::from_arcs([(0, 1), (0, 2), (2, 0), (3, 1)]));
+        assert!(!graphs_equal(&g, &h));
+        assert_eq!(
+            graphs_equal_verbose(&g, &h),
+            Some(GraphDiffReport::Successors(1, vec![2], vec![]))
+        );
+
+        // A single additional node
+        let mut h = VecGraph::from_arcs(arcs);
+        h.add_node(4);
+        let h = Left(h);
+        assert!(!graphs_equal(&g, &h));
+        assert_eq!(
+            graphs_equal_verbose(&g, &h),
+            Some(GraphDiffReport::NumNodes(4, 5))
+        );
+    }
+
+    #[test]
+    fn test_diff() -> Result<()> {
+        let old = Left(VecGraph::from_arcs([(0, 1), (0, 2), (1, 2), (2, 0)]));
+        let new = Left(VecGraph::from_arcs([(0, 1), (1, 2), (2, 0), (2, 1)]));
+        let changes = diff(&old, &new)?;
+        assert_eq!(
+            changes.iter().collect::<Vec<_>>(),
+            vec![ArcChange::Removed(0, 2), ArcChange::Added(2, 1)]
+        );
+        assert_eq!(changes.added().collect::<Vec<_>>(), vec![(2, 1)]);
+        assert_eq!(changes.removed().collect::<Vec<_>>(), vec![(0, 2)]);
+
+        // A graph has no difference with itself
+        assert_eq!(diff(&old, &old)?.iter().count(), 0);
+
+        // Different numbers of nodes
+        let mut bigger = VecGraph::from_arcs([(0, 1), (0, 2), (1, 2), (2, 0)]);
+        bigger.add_node(3);
+        assert!(diff(&old, Left(bigger)).is_err());
+        Ok(())
+    }
+}
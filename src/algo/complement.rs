@@ -0,0 +1,84 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::RandomAccessGraph;
+use std::ops::Range;
+
+/// Returns the complement of a graph restricted to a window of nodes.
+///
+/// The returned iterator yields, for each node `x` in `window`, in increasing
+/// order, the pair `(x, missing)`, where `missing` contains, in increasing
+/// order, the nodes of `window` that are not successors of `x` (loops
+/// included). The complement of a node is computed by walking its successor
+/// list against the window range, so the full complement, which is dense, is
+/// never materialized: memory usage is linear in the length of the window.
+///
+/// Successor lists must be sorted, as it happens for all graphs in this
+/// crate.
+///
+/// # Panics
+///
+/// Panics if `window` is not contained in the set of nodes of the graph.
+pub fn complement_within<G: RandomAccessGraph>(
+    graph: G,
+    window: Range<usize>,
+) -> impl Iterator<Item = (usize, Vec<usize>)> {
+    assert!(
+        window.end <= graph.num_nodes() || window.is_empty(),
+        "The window {:?} exceeds the number of nodes ({})",
+        window,
+        graph.num_nodes()
+    );
+    let nodes = window.clone();
+    nodes.map(move |node| {
+        let mut missing = Vec::new();
+        let mut next = window.start;
+        for succ in graph.successors(node) {
+            if succ < next {
+                continue;
+            }
+            if succ >= window.end {
+                break;
+            }
+            missing.extend(next..succ);
+            next = succ + 1;
+        }
+        missing.extend(next..window.end);
+        (node, missing)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_complement_within() {
+        let g = Left(VecGraph::from_arcs([
+            (0, 1),
+            (0, 5),
+            (1, 1),
+            (1, 2),
+            (1, 3),
+            (2, 0),
+            (2, 4),
+            (3, 2),
+            (4, 3),
+        ]));
+
+        assert_eq!(
+            complement_within(&g, 1..4).collect::<Vec<_>>(),
+            vec![(1, vec![]), (2, vec![1, 2, 3]), (3, vec![1, 3])]
+        );
+        assert_eq!(
+            complement_within(&g, 0..6).next(),
+            Some((0, vec![0, 2, 3, 4]))
+        );
+        assert_eq!(complement_within(&g, 3..3).count(), 0);
+    }
+}
@@ -0,0 +1,202 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::RandomAccessGraph;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::VecDeque;
+
+/// Returns the distances from a source computed by a breadth-first visit.
+///
+/// The value at index _x_ of the result is the length of a shortest path from
+/// `source` to _x_, or [`usize::MAX`] if _x_ is not reachable from `source`.
+///
+/// # Panics
+///
+/// If `source` is not smaller than the number of nodes.
+pub fn bfs_distances(graph: impl RandomAccessGraph, source: usize) -> Box<[usize]> {
+    let mut dist = vec![usize::MAX; graph.num_nodes()].into_boxed_slice();
+    let mut queue = VecDeque::new();
+    dist[source] = 0;
+    queue.push_back(source);
+    while let Some(node) = queue.pop_front() {
+        let d = dist[node] + 1;
+        for succ in graph.successors(node) {
+            if dist[succ] == usize::MAX {
+                dist[succ] = d;
+                queue.push_back(succ);
+            }
+        }
+    }
+    dist
+}
+
+/// Returns the distances from each of the given sources.
+///
+/// The element of index _i_ of the result is the array of distances from
+/// `sources[i]` returned by [`bfs_distances`]; in particular, unreachable
+/// nodes have distance [`usize::MAX`]. The visits are performed in parallel
+/// using the current [`rayon`] thread pool, so memory usage is proportional
+/// to the number of sources times the number of nodes.
+///
+/// # Panics
+///
+/// If some source is not smaller than the number of nodes.
+pub fn multi_source_bfs(
+    graph: impl RandomAccessGraph + Sync,
+    sources: &[usize],
+) -> Vec<Box<[usize]>> {
+    sources
+        .par_iter()
+        .map(|&source| bfs_distances(&graph, source))
+        .collect()
+}
+
+/// An estimate of the average distance of a graph computed by
+/// [`avg_distance_sampled`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct AvgDistance {
+    /// The average of the finite distances between a sampled source and
+    /// another node, or zero if no such distance was found.
+    pub average: f64,
+    /// The fraction of pairs made of a sampled source and another node such
+    /// that the node is reachable from the source.
+    pub reachable_fraction: f64,
+}
+
+/// Estimates the average distance of a graph using breadth-first visits from
+/// `num_samples` sources chosen uniformly at random (with replacement).
+///
+/// The result contains the average of the finite distances from the sources
+/// to the other nodes, and the fraction of pairs (source, node), with node
+/// different from the source, in which the node is reachable. Sources are
+/// chosen using a [`SmallRng`] seeded with `seed`, and visits are performed
+/// in parallel using the current [`rayon`] thread pool, with memory usage
+/// proportional to the number of threads times the number of nodes.
+///
+/// The sampled average is an unbiased estimator of the average distance
+/// between reachable pairs when the graph is strongly connected, and it is
+/// much cheaper than computing all distances: each visit costs linear time
+/// in the size of the graph. If there are no samples, or the graph has less
+/// than two nodes, both fields of the result are zero.
+pub fn avg_distance_sampled(
+    graph: impl RandomAccessGraph + Sync,
+    num_samples: usize,
+    seed: u64,
+) -> AvgDistance {
+    let num_nodes = graph.num_nodes();
+    if num_nodes < 2 || num_samples == 0 {
+        return AvgDistance::default();
+    }
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let sources = (0..num_samples)
+        .map(|_| rng.gen_range(0..num_nodes))
+        .collect::<Vec<_>>();
+
+    let (sum, reachable) = sources
+        .par_iter()
+        .map(|&source| {
+            bfs_distances(&graph, source)
+                .iter()
+                .filter(|&&d| d != 0 && d != usize::MAX)
+                .fold((0_u64, 0_u64), |(sum, count), &d| {
+                    (sum + d as u64, count + 1)
+                })
+        })
+        .reduce(|| (0, 0), |(s0, c0), (s1, c1)| (s0 + s1, c0 + c1));
+
+    AvgDistance {
+        average: if reachable == 0 {
+            0.0
+        } else {
+            sum as f64 / reachable as f64
+        },
+        reachable_fraction: reachable as f64 / (num_samples as f64 * (num_nodes - 1) as f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::random::ErdosRenyi;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+    use crate::traits::*;
+
+    #[test]
+    fn test_multi_source_bfs() {
+        // 4 -> 0 -> 2
+        //       `-> 3
+        // 1 -> 5
+        let g = Left(VecGraph::from_arcs([(4, 0), (0, 2), (0, 3), (1, 5)]));
+        let dist = multi_source_bfs(&g, &[4, 1]);
+        const M: usize = usize::MAX;
+        assert_eq!(dist.len(), 2);
+        assert_eq!(&*dist[0], &[1, M, 2, 2, 0, M]);
+        assert_eq!(&*dist[1], &[M, 0, M, M, M, 1]);
+        assert!(multi_source_bfs(&g, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_distances_are_consistent() {
+        let g = Left(VecGraph::from_lender(ErdosRenyi::new(200, 0.01, 0).iter()));
+        let sources = [0, 1, 2, 3, 4, 5, 6, 7];
+        for (&source, dist) in sources.iter().zip(multi_source_bfs(&g, &sources)) {
+            assert_eq!(dist[source], 0);
+            for node in 0..g.num_nodes() {
+                if dist[node] == usize::MAX {
+                    continue;
+                }
+                // Successors of reachable nodes are at most one step farther
+                for succ in g.successors(node) {
+                    assert!(dist[succ] <= dist[node] + 1);
+                }
+                // Nodes at positive distance have a predecessor one step closer
+                if node != source {
+                    assert!((0..g.num_nodes()).any(|pred| dist[pred] != usize::MAX
+                        && dist[pred] + 1 == dist[node]
+                        && g.successors(pred).into_iter().any(|s| s == node)));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_avg_distance_sampled() {
+        // The average distance of an undirected path with n nodes is (n + 1) / 3
+        let n = 100;
+        let mut g = VecGraph::<()>::empty(n);
+        for x in 0..n - 1 {
+            g.add_arc(x, x + 1);
+            g.add_arc(x + 1, x);
+        }
+        let g = Left(g);
+        let estimate = avg_distance_sampled(&g, 2000, 0);
+        let expected = (n + 1) as f64 / 3.0;
+        assert!(
+            (estimate.average - expected).abs() / expected < 0.05,
+            "{:?}",
+            estimate
+        );
+        assert_eq!(estimate.reachable_fraction, 1.0);
+        assert_eq!(
+            avg_distance_sampled(&g, 10, 1),
+            avg_distance_sampled(&g, 10, 1)
+        );
+
+        // In a directed path, half of the pairs are reachable
+        let g = Left(VecGraph::from_arcs((0..n - 1).map(|x| (x, x + 1))));
+        let estimate = avg_distance_sampled(&g, 2000, 0);
+        assert!(
+            (estimate.reachable_fraction - 0.5).abs() < 0.05,
+            "{:?}",
+            estimate
+        );
+
+        assert_eq!(avg_distance_sampled(&g, 0, 0).average, 0.0);
+    }
+}
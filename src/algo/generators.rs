@@ -0,0 +1,64 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Deterministic random graphs for tests and benchmarks.
+//!
+//! These functions are shorthands for the generators of
+//! [`graphs::random`](crate::graphs::random) that need just a seed, so that
+//! tests and benchmarks can build graphs of tunable size without shipping
+//! large data files. The pseudorandom number generator is
+//! [`Xoshiro256PlusPlus`](rand_xoshiro::Xoshiro256PlusPlus), which is the
+//! algorithm behind [`SmallRng`](rand::rngs::SmallRng) on 64-bit platforms,
+//! but, differently from the latter, yields the same graphs on all platforms
+//! and versions of `rand`.
+
+use crate::graphs::random::{self, GeneratorConfig};
+use crate::graphs::vec_graph::VecGraph;
+
+/// Returns an Erdös-Rényi random graph with exactly `num_arcs` arcs and no
+/// loops or multiple arcs.
+///
+/// See [`graphs::random::erdos_renyi`](random::erdos_renyi), which this
+/// function calls with [`GeneratorConfig::with_seed`].
+///
+/// # Panics
+///
+/// If `num_arcs` is larger than the number of possible arcs.
+pub fn erdos_renyi(num_nodes: usize, num_arcs: u64, seed: u64) -> VecGraph {
+    random::erdos_renyi(num_nodes, num_arcs, GeneratorConfig::with_seed(seed))
+}
+
+/// Returns a Barabási-Albert preferential-attachment random graph in which
+/// each node, starting from node `m`, has `m` successors.
+///
+/// See [`graphs::random::barabasi_albert`](random::barabasi_albert).
+///
+/// # Panics
+///
+/// If `m` is zero or not smaller than `num_nodes`.
+pub fn barabasi_albert(num_nodes: usize, m: usize, seed: u64) -> VecGraph {
+    random::barabasi_albert(num_nodes, m, seed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::Left;
+    use crate::traits::SequentialLabeling;
+
+    #[test]
+    fn test_generators() {
+        let er = Left(erdos_renyi(1000, 5000, 0));
+        assert_eq!(er.num_nodes(), 1000);
+        assert_eq!(er.num_arcs_hint(), Some(5000));
+        assert_eq!(er, Left(erdos_renyi(1000, 5000, 0)));
+
+        let ba = Left(barabasi_albert(1000, 5, 0));
+        assert_eq!(ba.num_nodes(), 1000);
+        assert_eq!(ba.num_arcs_hint(), Some(995 * 5));
+        assert_eq!(ba, Left(barabasi_albert(1000, 5, 0)));
+    }
+}
@@ -5,48 +5,111 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use crate::prelude::PermutedGraph;
 use crate::traits::*;
+use anyhow::{Context, Result};
 use dsi_progress_logger::prelude::*;
 use lender::prelude::*;
 use sux::prelude::*;
 
+/// Returns the gap cost of a node, given its sorted successors.
+///
+/// The cost of the first successor is computed with respect to the node
+/// itself; the cost of the following successors with respect to the previous
+/// one.
+#[inline(always)]
+pub(crate) fn node_log_gap_cost(x: usize, sorted: &[usize]) -> usize {
+    if sorted.is_empty() {
+        return 0;
+    }
+    ((x as isize - sorted[0] as isize).unsigned_abs() + 1).ilog2() as usize
+        + sorted
+            .windows(2)
+            .map(|w| (w[1] - w[0]).ilog2() as usize)
+            .sum::<usize>()
+}
+
 /// Computes the gap cost, that is, the sum of the costs of the logarithms
 /// of the differences between successors.
 ///
 /// Note that this implementation uses the _base_ of the base-2 logarithm
 /// as a measure of cost, where as the Java implementation uses the _ceiling_.
+///
+/// If `thread_pool` is `None`, the graph is scanned sequentially by the
+/// current thread.
 pub(crate) fn compute_log_gap_cost<G: SequentialGraph + Sync>(
     graph: &G,
     arc_granularity: usize,
     deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
-    thread_pool: &rayon::ThreadPool,
+    thread_pool: Option<&rayon::ThreadPool>,
     pr: Option<&mut ProgressLogger>,
 ) -> f64 {
-    graph.par_apply(
-        |range| {
-            graph
-                .iter_from(range.start)
-                .take(range.len())
-                .map_into_iter(|(x, succ)| {
-                    let mut cost = 0;
-                    let mut sorted: Vec<_> = succ.into_iter().collect();
-                    if !sorted.is_empty() {
-                        sorted.sort();
-                        cost +=
-                            ((x as isize - sorted[0] as isize).unsigned_abs() + 1).ilog2() as usize;
-                        cost += sorted
-                            .windows(2)
-                            .map(|w| (w[1] - w[0]).ilog2() as usize)
-                            .sum::<usize>();
-                    }
-                    cost
-                })
-                .sum::<usize>() as f64
-        },
-        |a, b| a + b,
-        arc_granularity,
+    let range_cost = |range: std::ops::Range<usize>| {
+        graph
+            .iter_from(range.start)
+            .take(range.len())
+            .map_into_iter(|(x, succ)| {
+                let mut sorted: Vec<_> = succ.into_iter().collect();
+                sorted.sort();
+                node_log_gap_cost(x, &sorted)
+            })
+            .sum::<usize>() as f64
+    };
+    match thread_pool {
+        Some(thread_pool) => graph.par_apply(
+            range_cost,
+            |a, b| a + b,
+            arc_granularity,
+            deg_cumul,
+            thread_pool,
+            pr,
+        ),
+        None => {
+            let cost = range_cost(0..graph.num_nodes());
+            if let Some(pr) = pr {
+                pr.update_with_count(graph.num_nodes());
+            }
+            cost
+        }
+    }
+}
+
+/// Computes the log-gap cost of the provided graph renumbered using the
+/// provided permutation, that is, the cost minimized by
+/// [LLP](super::layered_label_propagation).
+///
+/// The cost is the sum over all nodes of the base-2 logarithms of the gaps
+/// between consecutive sorted successors, where the first gap is computed with
+/// respect to the node itself. It estimates the number of bits necessary to
+/// represent the graph, so, when comparing orderings, lower is better.
+///
+/// # Arguments
+///
+/// * `graph` - The graph.
+/// * `perm` - The permutation: node `x` is renumbered as `perm[x]`. It can be
+///   owned or borrowed.
+/// * `deg_cumul` - The degree cumulative distribution of the graph, as in
+///   [par_apply](crate::traits::SequentialLabeling::par_apply).
+/// * `num_threads` - The number of threads to use. If `None`, the number of
+///   threads is set to [`num_cpus::get`].
+pub fn log_gap_cost<G: SequentialGraph + Sync>(
+    graph: &G,
+    perm: impl AsRef<[usize]>,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    num_threads: Option<usize>,
+) -> Result<f64> {
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.unwrap_or_else(num_cpus::get))
+        .build()
+        .context("Could not create thread pool")?;
+    let perm = perm.as_ref();
+    let num_arcs = deg_cumul.get(graph.num_nodes()) as u64;
+
+    Ok(compute_log_gap_cost(
+        &PermutedGraph { graph, perm: &perm },
+        ((num_arcs >> 9) as usize).max(1024),
         deg_cumul,
-        thread_pool,
-        pr,
-    )
+        Some(&thread_pool),
+        None,
+    ))
 }
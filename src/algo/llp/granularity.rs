@@ -0,0 +1,261 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Tommaso Fontana
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Auto-tuning of the granularity of parallel updates.
+//!
+//! The default arc granularity of [LLP](super::layered_label_propagation) is a
+//! fixed fraction of the number of arcs, which might be too small or too large
+//! on graphs with a skewed degree distribution. A [`GranularityTuner`]
+//! measures the throughput of the chunks processed during an update, and
+//! suggests a granularity for the next update so that each chunk takes
+//! approximately a target amount of time, while leaving enough chunks for
+//! each thread of the pool to balance the load.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// A monotonic source of time.
+///
+/// This trait makes it possible to replace the system clock with a mock
+/// implementation in tests.
+pub(crate) trait Clock: Sync {
+    /// Returns the time elapsed from an arbitrary, fixed origin.
+    fn now(&self) -> Duration;
+}
+
+impl<C: Clock> Clock for &C {
+    #[inline(always)]
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+}
+
+/// A [`Clock`] based on [`Instant`].
+pub(crate) struct SystemClock(Instant);
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Clock for SystemClock {
+    #[inline(always)]
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Adjusts the arc granularity of parallel updates using the measured
+/// throughput of chunks.
+///
+/// Threads call [`start`](GranularityTuner::start) before processing a chunk
+/// and [`record`](GranularityTuner::record) after, passing the number of arcs
+/// in the chunk. At the end of each update,
+/// [`adjust`](GranularityTuner::adjust) computes the granularity for the next
+/// update. Once the suggested granularity changes by less than
+/// [`STABILITY_THRESHOLD`](GranularityTuner::STABILITY_THRESHOLD), the tuner
+/// becomes stable and stops measuring.
+pub(crate) struct GranularityTuner<C: Clock = SystemClock> {
+    /// The current granularity.
+    granularity: usize,
+    /// The maximum granularity, which guarantees enough chunks per thread.
+    max_granularity: usize,
+    /// The desired duration of a chunk.
+    target_chunk_time: Duration,
+    /// Whether the granularity has converged.
+    stable: bool,
+    /// The source of time.
+    clock: C,
+    /// The number of arcs processed since the last adjustment.
+    arcs: AtomicU64,
+    /// The nanoseconds spent since the last adjustment.
+    nanos: AtomicU64,
+}
+
+impl<C: Clock> GranularityTuner<C> {
+    /// The default desired duration of a chunk.
+    pub(crate) const DEFAULT_TARGET_CHUNK_TIME: Duration = Duration::from_millis(10);
+    /// The minimum granularity.
+    pub(crate) const MIN_GRANULARITY: usize = 1024;
+    /// The minimum number of chunks per thread in an update.
+    pub(crate) const CHUNKS_PER_THREAD: u64 = 16;
+    /// The maximum factor by which the granularity can change in a single
+    /// adjustment.
+    pub(crate) const MAX_STEP: f64 = 8.0;
+    /// The relative change below which the granularity is considered stable.
+    pub(crate) const STABILITY_THRESHOLD: f64 = 0.1;
+
+    /// Creates a new tuner.
+    ///
+    /// # Arguments
+    ///
+    /// * `granularity` - The initial granularity.
+    /// * `num_arcs` - The number of arcs of the graph.
+    /// * `num_threads` - The number of threads of the pool.
+    /// * `target_chunk_time` - The desired duration of a chunk.
+    /// * `clock` - The source of time.
+    pub(crate) fn new(
+        granularity: usize,
+        num_arcs: u64,
+        num_threads: usize,
+        target_chunk_time: Duration,
+        clock: C,
+    ) -> Self {
+        let max_granularity = (num_arcs / (num_threads.max(1) as u64 * Self::CHUNKS_PER_THREAD))
+            .max(Self::MIN_GRANULARITY as u64) as usize;
+        Self {
+            granularity: granularity.clamp(Self::MIN_GRANULARITY, max_granularity),
+            max_granularity,
+            target_chunk_time,
+            stable: false,
+            clock,
+            arcs: AtomicU64::new(0),
+            nanos: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the current granularity.
+    #[inline(always)]
+    pub(crate) fn granularity(&self) -> usize {
+        self.granularity
+    }
+
+    /// Returns whether the granularity has converged.
+    #[inline(always)]
+    pub(crate) fn is_stable(&self) -> bool {
+        self.stable
+    }
+
+    /// Returns the starting time of a chunk, to be passed to
+    /// [`record`](GranularityTuner::record).
+    #[inline(always)]
+    pub(crate) fn start(&self) -> Duration {
+        if self.stable {
+            Duration::ZERO
+        } else {
+            self.clock.now()
+        }
+    }
+
+    /// Records that a chunk containing `arcs` arcs started at `start` has
+    /// been processed.
+    #[inline(always)]
+    pub(crate) fn record(&self, start: Duration, arcs: u64) {
+        if self.stable {
+            return;
+        }
+        let elapsed = self.clock.now().saturating_sub(start);
+        self.arcs.fetch_add(arcs, Ordering::Relaxed);
+        self.nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Computes the granularity for the next update using the measurements
+    /// recorded since the last call, and returns it.
+    pub(crate) fn adjust(&mut self) -> usize {
+        let arcs = self.arcs.swap(0, Ordering::Relaxed);
+        let nanos = self.nanos.swap(0, Ordering::Relaxed).max(1);
+        if self.stable || arcs == 0 {
+            return self.granularity;
+        }
+        let current = self.granularity as f64;
+        // Arcs per nanosecond times the target duration
+        let ideal = arcs as f64 / nanos as f64 * self.target_chunk_time.as_nanos() as f64;
+        let next = ideal
+            .clamp(current / Self::MAX_STEP, current * Self::MAX_STEP)
+            .clamp(Self::MIN_GRANULARITY as f64, self.max_granularity as f64)
+            .round() as usize;
+
+        self.stable = (next as f64 - current).abs() <= Self::STABILITY_THRESHOLD * current;
+        self.granularity = next;
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A clock that moves only when told to.
+    #[derive(Default)]
+    struct MockClock(AtomicU64);
+
+    impl MockClock {
+        fn advance(&self, d: Duration) {
+            self.0.fetch_add(d.as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> Duration {
+            Duration::from_nanos(self.0.load(Ordering::Relaxed))
+        }
+    }
+
+    /// Simulates an update in which each arc takes `nanos_per_arc`
+    /// nanoseconds to process.
+    fn simulate(tuner: &GranularityTuner<&MockClock>, clock: &MockClock, nanos_per_arc: u64) {
+        for _ in 0..10 {
+            let start = tuner.start();
+            let arcs = tuner.granularity() as u64;
+            clock.advance(Duration::from_nanos(arcs * nanos_per_arc));
+            tuner.record(start, arcs);
+        }
+    }
+
+    #[test]
+    fn test_increase() {
+        let clock = MockClock::default();
+        let mut tuner = GranularityTuner::new(1024, 1 << 40, 4, Duration::from_millis(10), &clock);
+        // 1024 arcs take about 1µs, way below the target
+        simulate(&tuner, &clock, 1);
+        let next = tuner.adjust();
+        assert!(next > 1024);
+        assert!(!tuner.is_stable());
+    }
+
+    #[test]
+    fn test_decrease() {
+        let clock = MockClock::default();
+        let mut tuner =
+            GranularityTuner::new(1 << 20, 1 << 40, 4, Duration::from_millis(10), &clock);
+        // 2²⁰ arcs take about one second, way above the target
+        simulate(&tuner, &clock, 1000);
+        let next = tuner.adjust();
+        assert!(next < 1 << 20);
+        assert!(!tuner.is_stable());
+    }
+
+    #[test]
+    fn test_converge() {
+        let clock = MockClock::default();
+        let mut tuner = GranularityTuner::new(1024, 1 << 40, 4, Duration::from_millis(10), &clock);
+        for _ in 0..100 {
+            if tuner.is_stable() {
+                break;
+            }
+            simulate(&tuner, &clock, 100);
+            tuner.adjust();
+        }
+        assert!(tuner.is_stable());
+        // 10ms at 100ns per arc
+        assert_eq!(tuner.granularity(), 100_000);
+        // Once stable, the granularity does not change anymore
+        simulate(&tuner, &clock, 1);
+        assert_eq!(tuner.adjust(), 100_000);
+    }
+
+    #[test]
+    fn test_load_balance() {
+        let clock = MockClock::default();
+        let mut tuner = GranularityTuner::new(1024, 1 << 20, 4, Duration::from_secs(1), &clock);
+        simulate(&tuner, &clock, 1);
+        // Capped so that each of the four threads gets 16 chunks
+        assert!(tuner.adjust() <= (1 << 20) / 64);
+    }
+}
@@ -5,23 +5,55 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use common_traits::{Atomic, CastableFrom, CastableInto, IntoAtomic, UnsignedInt};
 use rayon::prelude::*;
 use std::{
     cell::UnsafeCell,
-    sync::atomic::{AtomicUsize, Ordering},
+    sync::atomic::{AtomicU16, AtomicU32, AtomicU64, AtomicUsize, Ordering},
 };
 
-pub(crate) struct LabelStore {
-    labels: Box<[UnsafeCell<usize>]>,
-    volumes: Box<[AtomicUsize]>,
+/// An unsigned integer type that can be used to store labels (and volumes)
+/// during [layered label propagation](super).
+///
+/// Using a type smaller than `usize`, such as `u32`, reduces the memory used
+/// by LLP, but the number of nodes of the graph must be representable by the
+/// type.
+pub trait LabelType: UnsignedInt + IntoAtomic + CastableInto<usize> + CastableFrom<usize> {
+    /// Adds to an atomic value, as in [`AtomicUsize::fetch_add`].
+    fn fetch_add(atomic: &Self::AtomicType, value: Self, order: Ordering) -> Self;
+    /// Subtracts from an atomic value, as in [`AtomicUsize::fetch_sub`].
+    fn fetch_sub(atomic: &Self::AtomicType, value: Self, order: Ordering) -> Self;
 }
 
-impl LabelStore {
+macro_rules! impl_label_type {
+    ($($ty:ty => $aty:ty),*) => {$(
+        impl LabelType for $ty {
+            #[inline(always)]
+            fn fetch_add(atomic: &$aty, value: Self, order: Ordering) -> Self {
+                atomic.fetch_add(value, order)
+            }
+
+            #[inline(always)]
+            fn fetch_sub(atomic: &$aty, value: Self, order: Ordering) -> Self {
+                atomic.fetch_sub(value, order)
+            }
+        }
+    )*};
+}
+
+impl_label_type!(u16 => AtomicU16, u32 => AtomicU32, u64 => AtomicU64, usize => AtomicUsize);
+
+pub(crate) struct LabelStore<L: LabelType = usize> {
+    labels: Box<[UnsafeCell<L>]>,
+    volumes: Box<[L::AtomicType]>,
+}
+
+impl<L: LabelType> LabelStore<L> {
     pub(crate) fn new(n: usize) -> Self {
         let mut labels = Vec::with_capacity(n);
-        labels.extend((0..n).map(|_| UnsafeCell::new(0)));
+        labels.extend((0..n).map(|_| UnsafeCell::new(L::ZERO)));
         let mut volumes = Vec::with_capacity(n);
-        volumes.extend((0..n).map(|_| AtomicUsize::new(0)));
+        volumes.extend((0..n).map(|_| L::ZERO.to_atomic()));
 
         Self {
             labels: labels.into_boxed_slice(),
@@ -33,36 +65,167 @@ impl LabelStore {
         self.volumes
             .par_iter()
             .with_min_len(1024)
-            .for_each(|v| v.store(1, Ordering::Relaxed));
+            .for_each(|v| v.store(L::ONE, Ordering::Relaxed));
         self.labels
             .par_iter_mut()
             .enumerate()
             .with_min_len(1024)
-            .for_each(|(i, l)| *l.get_mut() = i);
+            .for_each(|(i, l)| *l.get_mut() = L::cast_from(i));
     }
 
     #[inline(always)]
     pub(crate) fn label(&self, node: usize) -> usize {
-        unsafe { *self.labels[node].get() }
+        unsafe { *self.labels[node].get() }.cast()
     }
 
     #[inline(always)]
     pub(crate) fn volume(&self, node: usize) -> usize {
-        self.volumes[node].load(Ordering::Relaxed)
+        self.volumes[node].load(Ordering::Relaxed).cast()
+    }
+
+    /// Initializes the store so that each node has its own label, and the
+    /// volume of each label is the weight of the corresponding node.
+    ///
+    /// Volumes are then the sum of the weights of the nodes with a given
+    /// label, provided that labels are changed using
+    /// [`update_weighted`](Self::update_weighted).
+    pub(crate) fn init_weighted(&mut self, weights: &[usize]) {
+        self.volumes
+            .par_iter()
+            .zip(weights)
+            .with_min_len(1024)
+            .for_each(|(v, &w)| v.store(L::cast_from(w), Ordering::Relaxed));
+        self.labels
+            .par_iter_mut()
+            .enumerate()
+            .with_min_len(1024)
+            .for_each(|(i, l)| *l.get_mut() = L::cast_from(i));
     }
 
     /// Updates the label of a node.
     #[inline(always)]
     pub(crate) fn update(&self, node: usize, new_label: usize) {
-        let old_label = unsafe { core::mem::replace(&mut *self.labels[node].get(), new_label) };
-        self.volumes[old_label].fetch_sub(1, Ordering::Relaxed);
-        self.volumes[new_label].fetch_add(1, Ordering::Relaxed);
+        self.update_weighted(node, new_label, 1);
+    }
+
+    /// Updates the label of a node, moving the given weight from the volume
+    /// of the old label to the volume of the new label.
+    #[inline(always)]
+    pub(crate) fn update_weighted(&self, node: usize, new_label: usize, weight: usize) {
+        let weight = L::cast_from(weight);
+        let old_label: usize =
+            unsafe { core::mem::replace(&mut *self.labels[node].get(), L::cast_from(new_label)) }
+                .cast();
+        L::fetch_sub(&self.volumes[old_label], weight, Ordering::Relaxed);
+        L::fetch_add(&self.volumes[new_label], weight, Ordering::Relaxed);
+    }
+
+    pub(crate) fn labels(&mut self) -> &mut [L] {
+        // UnsafeCell<L> has the same in-memory representation as L, and we
+        // have exclusive access to the labels
+        let len = self.labels.len();
+        unsafe { core::slice::from_raw_parts_mut(self.labels.as_mut_ptr() as *mut L, len) }
+    }
+
+    /// Returns the labels as a slice of `usize` that can be used as temporary
+    /// storage, if `L` has the same size as `usize`, or `None` otherwise.
+    pub(crate) fn labels_as_usize(&mut self) -> Option<&mut [usize]> {
+        let labels = self.labels();
+        // Unsigned integers of the same size have the same in-memory
+        // representation, and every bit pattern is valid for both
+        (core::mem::size_of::<L>() == core::mem::size_of::<usize>()).then(|| unsafe {
+            core::slice::from_raw_parts_mut(labels.as_mut_ptr() as *mut usize, labels.len())
+        })
+    }
+
+    /// Copies the current labels into `snapshot`.
+    pub(crate) fn snapshot(&mut self, snapshot: &mut [L]) {
+        snapshot.copy_from_slice(self.labels());
     }
 
-    pub(crate) fn labels(&mut self) -> &mut [usize] {
-        unsafe { std::mem::transmute::<&mut [UnsafeCell<usize>], &mut [usize]>(&mut self.labels) }
+    /// Initializes the store with the given labels, computing the volumes.
+    ///
+    /// Labels must be smaller than the number of nodes.
+    pub(crate) fn init_from(&mut self, labels: &[usize]) {
+        self.labels()
+            .par_iter_mut()
+            .zip(labels)
+            .with_min_len(1024)
+            .for_each(|(l, &label)| *l = L::cast_from(label));
+        self.compute_volumes();
+    }
+
+    /// Restores the labels saved by [`snapshot`](Self::snapshot), recomputing
+    /// the volumes.
+    pub(crate) fn restore(&mut self, snapshot: &[L]) {
+        self.labels().copy_from_slice(snapshot);
+        self.compute_volumes();
+    }
+
+    fn compute_volumes(&mut self) {
+        self.volumes
+            .par_iter()
+            .with_min_len(1024)
+            .for_each(|v| v.store(L::ZERO, Ordering::Relaxed));
+        let volumes = &self.volumes;
+        let labels = unsafe {
+            core::slice::from_raw_parts(self.labels.as_ptr() as *const L, self.labels.len())
+        };
+        labels.par_iter().with_min_len(1024).for_each(|&l| {
+            let l: usize = l.cast();
+            L::fetch_add(&volumes[l], L::ONE, Ordering::Relaxed);
+        });
     }
 }
 
-unsafe impl Send for LabelStore {}
-unsafe impl Sync for LabelStore {}
+unsafe impl<L: LabelType> Send for LabelStore<L> {}
+unsafe impl<L: LabelType> Sync for LabelStore<L> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_restore() {
+        let mut label_store = <LabelStore>::new(5);
+        label_store.init();
+        label_store.update(0, 1);
+        let mut snapshot = vec![0; 5];
+        label_store.snapshot(&mut snapshot);
+        assert_eq!(snapshot, [1, 1, 2, 3, 4]);
+
+        label_store.update(2, 1);
+        label_store.update(3, 4);
+        assert_eq!(label_store.volume(1), 3);
+        label_store.restore(&snapshot);
+        assert_eq!(label_store.labels(), &[1, 1, 2, 3, 4]);
+        assert!((0..5).map(|l| label_store.volume(l)).eq([0, 2, 1, 1, 1]));
+    }
+
+    #[test]
+    fn test_u32_labels() {
+        let mut label_store = LabelStore::<u32>::new(5);
+        assert!(label_store.labels_as_usize().is_none());
+        label_store.init_from(&[0, 0, 2, 2, 2]);
+        assert!((0..5).map(|l| label_store.volume(l)).eq([2, 0, 3, 0, 0]));
+        label_store.update(4, 1);
+        assert_eq!(label_store.label(4), 1);
+        assert_eq!(label_store.labels(), &[0, 0, 2, 2, 1]);
+        assert!((0..5).map(|l| label_store.volume(l)).eq([2, 1, 2, 0, 0]));
+
+        let mut label_store = LabelStore::<usize>::new(3);
+        label_store.init();
+        assert_eq!(label_store.labels_as_usize().unwrap(), &[0, 1, 2]);
+    }
+
+    #[test]
+    fn test_weighted() {
+        let mut label_store = <LabelStore>::new(4);
+        label_store.init_weighted(&[3, 1, 4, 2]);
+        assert!((0..4).map(|l| label_store.volume(l)).eq([3, 1, 4, 2]));
+        label_store.update_weighted(0, 2, 3);
+        label_store.update_weighted(3, 2, 2);
+        assert_eq!(label_store.labels(), &[2, 1, 2, 2]);
+        assert!((0..4).map(|l| label_store.volume(l)).eq([0, 1, 9, 0]));
+    }
+}
@@ -4,7 +4,23 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-/// A hasher that mixes `usize` values.
+/// Mixes the bits of a 64-bit value.
+///
+/// The value is offset by the 64-bit golden ratio before applying the
+/// finalizer of MurmurHash3, so that, differently from the bare finalizer,
+/// zero is not a fixed point. This function is shared by all hashing needs of
+/// the crate that require a fast, good-quality mix of integers.
+#[inline(always)]
+pub(crate) fn mix64(x: u64) -> u64 {
+    let mut x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ (x >> 33)
+}
+
+/// A hasher that mixes `usize` values using [`mix64`].
 ///
 /// This can only be used to hash `usize` values and it's not a general purpose
 /// hasher. It is used by the label hash maps.
@@ -20,12 +36,7 @@ impl core::hash::Hasher for Mix64 {
     }
     #[inline(always)]
     fn write_usize(&mut self, i: usize) {
-        self.state = i as u64;
-        self.state ^= self.state >> 33;
-        self.state = self.state.overflowing_mul(0xff51_afd7_ed55_8ccd).0;
-        self.state ^= self.state >> 33;
-        self.state = self.state.overflowing_mul(0xc4ce_b9fe_1a85_ec53).0;
-        self.state ^= self.state >> 33;
+        self.state = mix64(i as u64);
     }
     #[inline(always)]
     fn finish(&self) -> u64 {
@@ -16,7 +16,10 @@
 //! The function [`layered_label_propagation`] returns a permutation of the
 //! provided symmetric graph which will (hopefully) increase locality (see the
 //! paper). Usually, the permutation is fed to [`perm`] to permute the original
-//! graph.
+//! graph. The function [`layered_label_propagation_with_params`] makes it
+//! possible to set all [parameters](LlpParams), such as initial labels, and
+//! the function [`layered_label_propagation_with_details`] returns also the
+//! ordering computed for each ɣ and its cost.
 //!
 //! Note that the graph provided should be _symmetric_ and _loopless_. If this
 //! is not the case, please use [crate::transform::simplify] to generate a
@@ -24,14 +27,22 @@
 //!
 //! # Memory requirements
 //!
-//! LLP requires three `usize` and a boolean per node, plus the memory that is
-//! necessary to load the graph.
+//! LLP requires a `usize`, two labels, and a boolean per node, plus the memory
+//! that is necessary to load the graph. Labels are `usize` by default, but
+//! [`layered_label_propagation_generic`] makes it possible to use a smaller
+//! [label type](LabelType), such as `u32`, if the number of nodes of the graph
+//! is representable by the type; in this case, an additional temporary `usize`
+//! per node is needed at the end of the computation for each ɣ. If LLP is asked
+//! to stop on negative gains, an additional label and an additional `usize` per
+//! node are needed to store a snapshot of the labels and to evaluate their
+//! cost. In deterministic mode, an additional boolean per node is needed.
 //!
 use crate::prelude::*;
 use crate::traits::*;
-use anyhow::{Context, Result};
+use anyhow::{ensure, Context, Result};
 use dsi_progress_logger::prelude::*;
 use epserde::prelude::*;
+use granularity::{GranularityTuner, SystemClock};
 use llp::preds::PredParams;
 use predicates::Predicate;
 
@@ -44,21 +55,160 @@ use rayon::prelude::*;
 use std::collections::HashMap;
 use std::collections::VecDeque;
 use std::env::temp_dir;
-use std::path::PathBuf;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
 use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize};
+use std::time::Instant;
+use sux::prelude::BitVec;
 use sux::traits::IndexedDict;
 use sux::traits::Succ;
 
 pub(crate) mod gap_cost;
+pub use gap_cost::log_gap_cost;
+pub(crate) mod granularity;
 pub(crate) mod label_store;
-mod mix64;
+pub use label_store::LabelType;
+pub(crate) mod mix64;
 pub mod preds;
 
-fn labels_path(gamma_index: usize) -> PathBuf {
-    [temp_dir(), format!("labels_{}.bin", gamma_index).into()]
-        .iter()
-        .collect()
+/// The default of [`LlpParams::sequential_threshold`]: graphs with fewer nodes
+/// than this are processed by a single thread without parallel decomposition,
+/// and no thread pool is built.
+///
+/// The serial and the parallel update loops can be compared with the `bench
+/// llp` command, which times the updates of both on random graphs of
+/// increasing size. On a single-core virtual machine (two threads, average
+/// degree 16, ten updates, best of five runs) we measured:
+///
+/// | nodes  | serial (ms) | parallel (ms) |
+/// |-------:|------------:|--------------:|
+/// |    256 |         4.8 |           7.5 |
+/// |   1024 |         8.0 |           8.3 |
+/// |   2048 |        13.1 |          13.6 |
+/// |   4096 |        26.1 |          30.2 |
+/// |   8192 |        53.9 |          63.5 |
+/// |  16384 |       168.6 |         190.8 |
+/// |  65536 |       886.6 |         857.0 |
+///
+/// Below a thousand nodes the fixed overhead of the parallel loop dominates
+/// (and the time to build the thread pool, which is not included, is
+/// comparable to that of the updates); above, the parallel loop pays just the
+/// contention of two threads on one core, which disappears on multicore
+/// hardware, where only the parallel loop can scale.
+pub const LLP_SEQUENTIAL_THRESHOLD: usize = 1 << 10;
+
+/// The number of nodes of the chunks in which updates are split in
+/// deterministic mode (see [`LlpParams::deterministic`]), independently of the
+/// number of threads.
+pub const LLP_DETERMINISTIC_CHUNK_SIZE: usize = 1 << 10;
+
+fn labels_path(dir: &Path, gamma_index: usize) -> PathBuf {
+    dir.join(format!("labels_{}.bin", gamma_index))
+}
+
+/// The parameters of [layered label propagation](layered_label_propagation_with_params).
+///
+/// The [default](LlpParams::default) parameters are those of the `llp`
+/// command, and the fields can be changed using the struct update syntax:
+/// ```
+/// # use webgraph::algo::llp::LlpParams;
+/// let params = LlpParams {
+///     num_threads: Some(1),
+///     seed: 42,
+///     ..Default::default()
+/// };
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct LlpParams<'a> {
+    /// The ɣ values to use in the LLP algorithm.
+    pub gammas: Vec<f64>,
+    /// The number of threads to use. If `None`, the number of threads is set
+    /// to [`num_cpus::get`].
+    pub num_threads: Option<usize>,
+    /// The chunk size used to randomize the permutation. If `None`, it is set
+    /// to 1000000. This is an advanced option: see
+    /// [par_apply](crate::traits::SequentialLabeling::par_apply).
+    pub chunk_size: Option<usize>,
+    /// The granularity of the parallel processing expressed as the number of
+    /// arcs to process at a time. If `None`, the granularity is auto-tuned:
+    /// the throughput of the chunks of each update is measured, and the
+    /// granularity of the next update is adjusted so that chunks take
+    /// approximately the same time and each thread gets enough chunks, until
+    /// it converges to a stable value. If `Some`, the granularity is fixed.
+    /// This is an advanced option: see
+    /// [par_apply](crate::traits::SequentialLabeling::par_apply).
+    pub granularity: Option<usize>,
+    /// The seed to use for pseudorandom number generation.
+    pub seed: u64,
+    /// If true, the updates of each ɣ are also stopped as soon as an update
+    /// has a negative gain, that is, it increases the [log-gap
+    /// cost](log_gap_cost) of the ordering obtained by sorting the nodes by
+    /// their labels, and the labels are reverted to those preceding the update.
+    /// This requires taking a snapshot of the labels before each update and
+    /// computing the log-gap cost after each update, which costs an additional
+    /// label and an additional `usize` per node, a copy of the labels, and a
+    /// sort and a scan of the graph per update.
+    pub stop_on_negative_gain: bool,
+    /// If not `None`, the labels used to initialize LLP for each ɣ, in place
+    /// of the identity, making it possible to warm-start the algorithm with a
+    /// previous assignment. Labels must be smaller than the number of nodes.
+    pub initial_labels: Option<&'a [usize]>,
+    /// If true, the result depends only on the parameters, and not on the
+    /// number of threads, on the granularity, or on scheduling.
+    ///
+    /// The pseudorandom number generators used to shuffle nodes and to break
+    /// ties are seeded using only the seed, the current ɣ, the current update,
+    /// and the node (or the index of the shuffled chunk). Moreover, each
+    /// update is split in chunks of [`LLP_DETERMINISTIC_CHUNK_SIZE`] nodes, and
+    /// the nodes of a chunk see the labels as they were at the start of the
+    /// update, except for those modified by the chunk itself; the changes of
+    /// all chunks are applied at the end of the update. This mode requires an
+    /// additional boolean per node, and the granularity is ignored.
+    pub deterministic: bool,
+    /// The directory in which the labels computed for each ɣ are stored. If
+    /// `None`, the system temporary directory is used.
+    ///
+    /// The labels are stored in a subdirectory with a unique name, so
+    /// concurrent runs can use the same directory. The subdirectory is deleted
+    /// when LLP returns, also in case of error.
+    pub work_dir: Option<PathBuf>,
+    /// Graphs with fewer nodes than this threshold are processed by a single
+    /// thread without parallel decomposition. The default is
+    /// [`LLP_SEQUENTIAL_THRESHOLD`].
+    pub sequential_threshold: usize,
+}
+
+impl Default for LlpParams<'_> {
+    /// Returns the parameters of the `llp` command, that is, ɣ's 0 and
+    /// 2<sup>-_k_</sup> for 0 ≤ _k_ ≤ 10, seed zero, and default values for
+    /// all other parameters.
+    fn default() -> Self {
+        Self {
+            gammas: std::iter::once(0.0)
+                .chain((0..=10).rev().map(|k| 0.5_f64.powi(k)))
+                .collect(),
+            num_threads: None,
+            chunk_size: None,
+            granularity: None,
+            seed: 0,
+            stop_on_negative_gain: false,
+            initial_labels: None,
+            deterministic: false,
+            work_dir: None,
+            sequential_threshold: LLP_SEQUENTIAL_THRESHOLD,
+        }
+    }
+}
+
+impl LlpParams<'_> {
+    /// Returns the default parameters with the given ɣ's.
+    pub fn with_gammas(gammas: Vec<f64>) -> Self {
+        Self {
+            gammas,
+            ..Default::default()
+        }
+    }
 }
 
 /// Runs layered label propagation on the provided symmetric graph and returns
@@ -67,6 +217,9 @@ fn labels_path(gamma_index: usize) -> PathBuf {
 /// Note that no symmetry check is performed, but in that case the algorithm
 /// usually will not give satisfactory results.
 ///
+/// All other [parameters](LlpParams) have their default values: use
+/// [`layered_label_propagation_with_params`] to set them, too.
+///
 /// # Arguments
 ///
 /// * `sym_graph` - The symmetric graph to run LLP on.
@@ -74,16 +227,16 @@ fn labels_path(gamma_index: usize) -> PathBuf {
 ///   [par_apply](crate::traits::SequentialLabeling::par_apply).
 /// * `gammas` - The ɣ values to use in the LLP algorithm.
 /// * `num_threads` - The number of threads to use. If `None`, the number of
-/// threads is set to [`num_cpus::get`].
+///   threads is set to [`num_cpus::get`].
 /// * `chunk_size` - The chunk size used to randomize the permutation. This is
-/// an advanced option: see
+///   an advanced option: see
 ///   [par_apply](crate::traits::SequentialLabeling::par_apply).
 /// * `granularity` - The granularity of the parallel processing expressed as
 ///   the number of arcs to process at a time. If `None`, the granularity is
-///   computed adaptively. This is an advanced option: see
+///   auto-tuned. This is an advanced option: see
 ///   [par_apply](crate::traits::SequentialLabeling::par_apply).
 /// * `seed` - The seed to use for pseudorandom number generation.
-#[allow(clippy::type_complexity)]
+/// * `predicate` - The stopping criterion for the updates of each ɣ.
 #[allow(clippy::too_many_arguments)]
 pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
     sym_graph: &R,
@@ -95,27 +248,216 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
     seed: u64,
     predicate: impl Predicate<preds::PredParams>,
 ) -> Result<Box<[usize]>> {
-    const IMPROV_WINDOW: usize = 10;
+    layered_label_propagation_with_params(
+        sym_graph,
+        deg_cumul,
+        predicate,
+        &LlpParams {
+            gammas,
+            num_threads,
+            chunk_size,
+            granularity,
+            seed,
+            ..Default::default()
+        },
+    )
+}
+
+/// Runs layered label propagation on the provided symmetric graph using the
+/// given [parameters](LlpParams) and returns the resulting labels.
+///
+/// # Arguments
+///
+/// * `sym_graph` - The symmetric graph to run LLP on.
+/// * `deg_cumul` - The degree cumulative distribution of the graph, as in
+///   [par_apply](crate::traits::SequentialLabeling::par_apply).
+/// * `predicate` - The stopping criterion for the updates of each ɣ.
+/// * `params` - The [parameters](LlpParams) of the algorithm.
+///
+/// If [`num_threads`](LlpParams::num_threads) is `Some(1)`, or if the graph has
+/// fewer than [`sequential_threshold`](LlpParams::sequential_threshold) nodes,
+/// each update is a serial loop over all nodes, avoiding the overhead of
+/// [parallel processing](crate::traits::SequentialLabeling::par_apply) and of
+/// granularity tuning.
+pub fn layered_label_propagation_with_params<R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    predicate: impl Predicate<preds::PredParams>,
+    params: &LlpParams,
+) -> Result<Box<[usize]>> {
+    layered_label_propagation_generic::<usize, _>(sym_graph, deg_cumul, predicate, params)
+}
+
+/// Runs layered label propagation on the provided symmetric graph and returns
+/// the resulting labels, storing labels and volumes during the computation
+/// using the [label type](LabelType) `L`.
+///
+/// Using `u32` in place of `usize` as label type halves the memory used by the
+/// labels and their volumes on 64-bit platforms, but the number of nodes of the
+/// graph must be representable by the label type, or an error will be
+/// returned. The result does not depend on the label type.
+///
+/// See [`layered_label_propagation_with_params`] for the arguments.
+pub fn layered_label_propagation_generic<L: LabelType, R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    predicate: impl Predicate<preds::PredParams>,
+    params: &LlpParams,
+) -> Result<Box<[usize]>> {
+    Ok(layered_label_propagation_impl::<L, _>(sym_graph, deg_cumul, predicate, params, false)?.0)
+}
+
+/// Runs layered label propagation on the provided symmetric graph and returns
+/// the resulting labels, together with the details of the computation for
+/// each ɣ.
+///
+/// The second element of the result contains, for each ɣ, in the same order
+/// of [`gammas`](LlpParams::gammas), a triple made of ɣ, the log-gap cost of
+/// the ordering obtained by sorting the nodes by the labels computed for ɣ
+/// (see [`log_gap_cost`]), and the associated permutation (node `x` is
+/// renumbered as `perm[x]`). The labels of the ɣ with minimum cost are the
+/// starting point of the combination that yields the resulting labels.
+///
+/// Storing the permutations requires an additional `usize` per node per ɣ.
+///
+/// See [`layered_label_propagation_with_params`] for the arguments.
+#[allow(clippy::type_complexity)]
+pub fn layered_label_propagation_with_details<R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    predicate: impl Predicate<preds::PredParams>,
+    params: &LlpParams,
+) -> Result<(Box<[usize]>, Vec<(f64, f64, Box<[usize]>)>)> {
+    layered_label_propagation_impl::<usize, _>(sym_graph, deg_cumul, predicate, params, true)
+}
+
+/// The implementation of layered label propagation, returning the resulting
+/// labels and, if `with_details` is true, the ɣ, cost, and permutation of each
+/// ɣ (otherwise, an empty vector).
+#[allow(clippy::type_complexity)]
+fn layered_label_propagation_impl<L: LabelType, R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    predicate: impl Predicate<preds::PredParams>,
+    params: &LlpParams,
+    with_details: bool,
+) -> Result<(Box<[usize]>, Vec<(f64, f64, Box<[usize]>)>)> {
+    let &LlpParams {
+        ref gammas,
+        num_threads,
+        chunk_size,
+        granularity,
+        seed,
+        stop_on_negative_gain,
+        initial_labels,
+        deterministic,
+        ref work_dir,
+        sequential_threshold,
+    } = params;
+    let work_dir = work_dir.clone().unwrap_or_else(temp_dir);
     let num_nodes = sym_graph.num_nodes();
+    // volumes can be as large as the number of nodes
+    let label_bits = core::mem::size_of::<L>() * 8;
+    ensure!(
+        label_bits >= usize::BITS as usize || num_nodes >> label_bits == 0,
+        "The number of nodes ({}) is not representable using {}-bit labels",
+        num_nodes,
+        label_bits
+    );
+    if let Some(initial_labels) = initial_labels {
+        ensure!(
+            initial_labels.len() == num_nodes,
+            "The number of initial labels ({}) is not equal to the number of nodes ({})",
+            initial_labels.len(),
+            num_nodes
+        );
+        ensure!(
+            initial_labels.par_iter().all(|&l| l < num_nodes),
+            "Initial labels must be smaller than the number of nodes ({})",
+            num_nodes
+        );
+    }
+
+    // removed when dropped, that is, on return or on error
+    let labels_tmp_dir = tempfile::Builder::new()
+        .prefix("llp_labels_")
+        .tempdir_in(&work_dir)
+        .with_context(|| {
+            format!(
+                "Could not create a directory for labels in {}",
+                work_dir.display()
+            )
+        })?;
+    let labels_dir = labels_tmp_dir.path();
+
+    const IMPROV_WINDOW: usize = 10;
     let chunk_size = chunk_size.unwrap_or(1_000_000);
-    let granularity = granularity.unwrap_or(((sym_graph.num_arcs() >> 9) as usize).max(1024));
+    let default_granularity = ((sym_graph.num_arcs() >> 9) as usize).max(1024);
 
     // init the permutation with the indices
     let mut update_perm = (0..num_nodes).collect::<Vec<_>>();
 
     let mut can_change = Vec::with_capacity(num_nodes as _);
     can_change.extend((0..num_nodes).map(|_| AtomicBool::new(true)));
-    let mut label_store = label_store::LabelStore::new(num_nodes as _);
+    // the flags for the next update, if we are in deterministic mode
+    let mut next_can_change = Vec::with_capacity(if deterministic { num_nodes } else { 0 });
+    if deterministic {
+        next_can_change.extend((0..num_nodes).map(|_| AtomicBool::new(false)));
+    }
+    let mut label_store = label_store::LabelStore::<L>::new(num_nodes as _);
+    // the labels before the current update, if we stop on negative gains
+    let mut label_snapshot = if stop_on_negative_gain {
+        vec![L::ZERO; num_nodes]
+    } else {
+        vec![]
+    };
+    // the inverse permutation used to evaluate the log-gap cost of the labels,
+    // if we stop on negative gains
+    let mut cost_inv_perm = if stop_on_negative_gain {
+        vec![0; num_nodes]
+    } else {
+        vec![]
+    };
     let stack_size = std::env::var("RUST_MIN_STACK")
         .map(|value| value.parse().unwrap())
-        .unwrap_or(1024 * num_nodes.ilog2_ceil() as usize);
-    // build a thread_pool so we avoid having to re-create the threads
+        // small graphs would get a stack too small even for the thread itself
+        .unwrap_or_else(|_| (1024 * num_nodes.ilog2_ceil() as usize).max(2 << 20));
     let num_threads = num_threads.unwrap_or_else(num_cpus::get);
-    let thread_pool = rayon::ThreadPoolBuilder::new()
-        .num_threads(num_threads)
-        .stack_size(stack_size)
-        .build()
-        .context("Could not create thread pool")?;
+    // small graphs are not worth the overhead of parallel processing
+    let sequential = num_threads == 1 || num_nodes < sequential_threshold;
+    // build a thread_pool so we avoid having to re-create the threads; no
+    // pool is needed if we run sequentially
+    let thread_pool = if sequential {
+        None
+    } else {
+        Some(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .stack_size(stack_size)
+                .build()
+                .context("Could not create thread pool")?,
+        )
+    };
+
+    // auto-tune the granularity only if the user did not provide one and we
+    // are using par_apply
+    let mut tuner = (granularity.is_none() && !sequential && !deterministic).then(|| {
+        GranularityTuner::new(
+            default_granularity,
+            sym_graph.num_arcs(),
+            num_threads,
+            GranularityTuner::<SystemClock>::DEFAULT_TARGET_CHUNK_TIME,
+            SystemClock::default(),
+        )
+    });
+    // the user-provided granularity, or the current auto-tuned one
+    let current_granularity = |tuner: &Option<GranularityTuner<SystemClock>>| {
+        granularity.unwrap_or_else(|| {
+            tuner
+                .as_ref()
+                .map_or(default_granularity, |t| t.granularity())
+        })
+    };
 
     // init the gamma progress logger
     let mut gamma_pl = progress_logger!(
@@ -132,10 +474,16 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
     // init the update progress logger
     let mut update_pl = progress_logger!(item_name = "node", local_speed = true);
 
+    let initial_seed = seed;
     let seed = AtomicU64::new(seed);
     let mut costs = Vec::with_capacity(gammas.len());
+    let mut details = Vec::with_capacity(if with_details { gammas.len() } else { 0 });
 
-    gamma_pl.start(format!("Running {} threads", num_threads));
+    if sequential {
+        gamma_pl.start("Running sequentially");
+    } else {
+        gamma_pl.start(format!("Running {} threads", num_threads));
+    }
     info!("Stopping criterion: {predicate}");
 
     for (gamma_index, gamma) in gammas.iter().enumerate() {
@@ -146,7 +494,11 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
             gamma_index + 1,
             gammas.len(),
         ));
-        label_store.init();
+        let gamma_start = Instant::now();
+        match initial_labels {
+            Some(initial_labels) => label_store.init_from(initial_labels),
+            None => label_store.init(),
+        }
         can_change
             .par_iter()
             .with_min_len(1024)
@@ -154,6 +506,20 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
 
         let mut obj_func = 0.0;
         let mut prev_gain = f64::MAX;
+        // the log-gap cost of the labels, if we stop on negative gains
+        let mut labels_cost = if stop_on_negative_gain {
+            log_gap_cost_of_labels(
+                sym_graph,
+                &label_store,
+                &mut update_perm,
+                &mut cost_inv_perm,
+                current_granularity(&tuner),
+                deg_cumul,
+                thread_pool.as_ref(),
+            )
+        } else {
+            0.0
+        };
         let mut improv_window: VecDeque<_> = vec![1.0; IMPROV_WINDOW].into();
 
         for update in 0.. {
@@ -161,110 +527,224 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
             update_pl.start(format!("Starting update {}...", update));
 
             update_perm.iter_mut().enumerate().for_each(|(i, x)| *x = i);
-            thread_pool.install(|| {
+            let shuffle = |(i, chunk): (usize, &mut [usize])| {
+                let seed = if deterministic {
+                    mix_seed(initial_seed, &[gamma_index, update, i])
+                } else {
+                    seed.fetch_add(1, Ordering::Relaxed)
+                };
+                let mut rand = SmallRng::seed_from_u64(seed);
+                chunk.shuffle(&mut rand);
+            };
+            match &thread_pool {
                 // parallel shuffle
-                update_perm.par_chunks_mut(chunk_size).for_each(|chunk| {
-                    let seed = seed.fetch_add(1, Ordering::Relaxed);
-                    let mut rand = SmallRng::seed_from_u64(seed);
-                    chunk.shuffle(&mut rand);
-                });
-            });
+                Some(thread_pool) => thread_pool.install(|| {
+                    update_perm
+                        .par_chunks_mut(chunk_size)
+                        .enumerate()
+                        .for_each(shuffle)
+                }),
+                None => update_perm
+                    .chunks_mut(chunk_size)
+                    .enumerate()
+                    .for_each(shuffle),
+            }
+
+            if stop_on_negative_gain {
+                label_store.snapshot(&mut label_snapshot);
+            }
+            next_can_change
+                .par_iter()
+                .with_min_len(1024)
+                .for_each(|c| c.store(false, Ordering::Relaxed));
 
             // If this iteration modified anything (early stop)
             let modified = AtomicUsize::new(0);
+            let arc_granularity = current_granularity(&tuner);
 
-            let delta_obj_func = sym_graph.par_apply(
-                |range| {
-                    let mut rand = SmallRng::seed_from_u64(range.start as u64);
-                    let mut local_obj_func = 0.0;
-                    for &node in &update_perm[range] {
-                        // Note that here we are using a heuristic optimization:
-                        // if no neighbor has changed, the label of a node
-                        // cannot change. If gamma != 0, this is not necessarily
-                        // true, as a node might need to change its value just
-                        // because of a change of volume of the adjacent labels.
-                        if !can_change[node].load(Ordering::Relaxed) {
-                            continue;
-                        }
-                        // set that the node can't change by default and we'll unset later it if it can
-                        can_change[node].store(false, Ordering::Relaxed);
-
-                        let successors = sym_graph.successors(node);
-                        if sym_graph.outdegree(node) == 0 {
-                            continue;
-                        }
+            let update_chunk = |range: Range<usize>| {
+                let chunk_start = tuner.as_ref().map(|t| t.start());
+                let (start, end) = (range.start, range.end);
+                let mut rand = SmallRng::seed_from_u64(range.start as u64);
+                let mut local_obj_func = 0.0;
+                for &node in &update_perm[range] {
+                    // Note that here we are using a heuristic optimization:
+                    // if no neighbor has changed, the label of a node
+                    // cannot change. If gamma != 0, this is not necessarily
+                    // true, as a node might need to change its value just
+                    // because of a change of volume of the adjacent labels.
+                    if !can_change[node].load(Ordering::Relaxed) {
+                        continue;
+                    }
+                    // set that the node can't change by default and we'll unset later it if it can
+                    can_change[node].store(false, Ordering::Relaxed);
 
-                        // get the label of this node
-                        let curr_label = label_store.label(node);
+                    if sym_graph.outdegree(node) == 0 {
+                        continue;
+                    }
 
-                        // compute the frequency of successor labels
-                        let mut map =
-                            HashMap::with_capacity_and_hasher(hash_map_init, mix64::Mix64Builder);
-                        for succ in successors {
-                            map.entry(label_store.label(succ))
-                                .and_modify(|counter| *counter += 1)
-                                .or_insert(1_usize);
+                    // get the label of this node
+                    let curr_label = label_store.label(node);
+                    let (next_label, delta) = best_label(
+                        sym_graph,
+                        node,
+                        curr_label,
+                        *gamma,
+                        hash_map_init,
+                        |x| label_store.label(x),
+                        |label| label_store.volume(label),
+                        // randomly break ties
+                        |majorities| *majorities.choose(&mut rand).unwrap(),
+                    );
+                    // if the label changed we need to update the label store
+                    // and signal that this could change the neighbour nodes
+                    if next_label != curr_label {
+                        modified.fetch_add(1, Ordering::Relaxed);
+                        for succ in sym_graph.successors(node) {
+                            can_change[succ].store(true, Ordering::Relaxed);
                         }
-                        // add the current label to the map
-                        map.entry(curr_label).or_insert(0_usize);
-
-                        let mut max = f64::NEG_INFINITY;
-                        let mut old = 0.0;
-                        let mut majorities = vec![];
-                        // compute the most entropic label
-                        for (&label, &count) in map.iter() {
-                            // For replication of the results of the Java
-                            // version, one needs to decrement the volume of
-                            // the current value the Java version does
-                            // (see the commented code below).
-                            //
-                            // Note that this is not exactly equivalent to the
-                            // behavior of the Java version, as during the
-                            // execution of this loop if another thread reads
-                            // the volume of the current label it will get a
-                            // value larger by one WRT the Java version.
-                            let volume = label_store.volume(label); // - (label == curr_label) as usize;
-                            let val = (1.0 + gamma) * count as f64 - gamma * (volume + 1) as f64;
-
-                            if max == val {
-                                majorities.push(label);
-                            }
-
-                            if val > max {
-                                majorities.clear();
-                                max = val;
-                                majorities.push(label);
-                            }
+                        label_store.update(node, next_label);
+                    }
+                    local_obj_func += delta;
+                }
+                if let (Some(tuner), Some(chunk_start)) = (tuner.as_ref(), chunk_start) {
+                    tuner.record(
+                        chunk_start,
+                        (deg_cumul.get(end) - deg_cumul.get(start)) as u64,
+                    );
+                }
+                local_obj_func
+            };
 
-                            if label == curr_label {
-                                old = val;
-                            }
-                        }
-                        // randomly break ties
-                        let next_label = *majorities.choose(&mut rand).unwrap();
-                        // if the label changed we need to update the label store
-                        // and signal that this could change the neighbour nodes
-                        if next_label != curr_label {
-                            modified.fetch_add(1, Ordering::Relaxed);
-                            for succ in sym_graph.successors(node) {
-                                can_change[succ].store(true, Ordering::Relaxed);
+            // In deterministic mode, chunks do not modify the label store:
+            // each chunk sees the labels and volumes at the start of the
+            // update, plus its own changes, which are applied at the end of
+            // the update. Flags are double buffered for the same reason.
+            let update_chunk_deterministic = |range: Range<usize>| {
+                let mut labels = HashMap::with_hasher(mix64::Mix64Builder);
+                let mut volume_deltas = HashMap::with_hasher(mix64::Mix64Builder);
+                let mut local_obj_func = 0.0;
+                for &node in &update_perm[range] {
+                    if !can_change[node].load(Ordering::Relaxed) || sym_graph.outdegree(node) == 0 {
+                        continue;
+                    }
+                    let label = |x| *labels.get(&x).unwrap_or(&label_store.label(x));
+                    let curr_label = label(node);
+                    let (next_label, delta) = best_label(
+                        sym_graph,
+                        node,
+                        curr_label,
+                        *gamma,
+                        hash_map_init,
+                        label,
+                        |label| {
+                            label_store
+                                .volume(label)
+                                .wrapping_add_signed(*volume_deltas.get(&label).unwrap_or(&0))
+                        },
+                        // break ties using a generator depending only on the
+                        // seed, on the update, and on the node
+                        |majorities| {
+                            if majorities.len() == 1 {
+                                return majorities[0];
                             }
-                            label_store.update(node, next_label);
+                            let seed = mix_seed(initial_seed, &[gamma_index, update, node]);
+                            *majorities
+                                .choose(&mut SmallRng::seed_from_u64(seed))
+                                .unwrap()
+                        },
+                    );
+                    if next_label != curr_label {
+                        for succ in sym_graph.successors(node) {
+                            next_can_change[succ].store(true, Ordering::Relaxed);
                         }
-                        local_obj_func += max - old;
+                        labels.insert(node, next_label);
+                        *volume_deltas.entry(curr_label).or_insert(0_isize) -= 1;
+                        *volume_deltas.entry(next_label).or_insert(0_isize) += 1;
                     }
-                    local_obj_func
-                },
-                |delta_obj_func_0: f64, delta_obj_func_1| delta_obj_func_0 + delta_obj_func_1,
-                granularity,
-                deg_cumul,
-                &thread_pool,
-                Some(&mut update_pl),
-            );
+                    local_obj_func += delta;
+                }
+                (local_obj_func, labels)
+            };
+
+            let delta_obj_func = if deterministic {
+                let num_chunks = num_nodes.div_ceil(LLP_DETERMINISTIC_CHUNK_SIZE);
+                let chunk = |i: usize| {
+                    update_chunk_deterministic(
+                        i * LLP_DETERMINISTIC_CHUNK_SIZE
+                            ..((i + 1) * LLP_DETERMINISTIC_CHUNK_SIZE).min(num_nodes),
+                    )
+                };
+                let results: Vec<_> = match &thread_pool {
+                    Some(thread_pool) => {
+                        thread_pool.install(|| (0..num_chunks).into_par_iter().map(chunk).collect())
+                    }
+                    None => (0..num_chunks).map(chunk).collect(),
+                };
+                // chunks update disjoint sets of nodes, so the order of
+                // application does not matter
+                let mut delta_obj_func = 0.0;
+                for (local_obj_func, labels) in results {
+                    delta_obj_func += local_obj_func;
+                    modified.fetch_add(labels.len(), Ordering::Relaxed);
+                    for (node, label) in labels {
+                        label_store.update(node, label);
+                    }
+                }
+                core::mem::swap(&mut can_change, &mut next_can_change);
+                delta_obj_func
+            } else {
+                match &thread_pool {
+                    Some(thread_pool) => sym_graph.par_apply(
+                        update_chunk,
+                        |delta_obj_func_0: f64, delta_obj_func_1| {
+                            delta_obj_func_0 + delta_obj_func_1
+                        },
+                        arc_granularity,
+                        deg_cumul,
+                        thread_pool,
+                        Some(&mut update_pl),
+                    ),
+                    None => update_chunk(0..num_nodes),
+                }
+            };
 
             update_pl.done_with_count(num_nodes);
             iter_pl.update_and_display();
 
+            if let Some(tuner) = tuner.as_mut() {
+                if !tuner.is_stable() {
+                    let next = tuner.adjust();
+                    info!(
+                        "Granularity: {} -> {}{}",
+                        arc_granularity,
+                        next,
+                        if tuner.is_stable() { " (stable)" } else { "" }
+                    );
+                }
+            }
+
+            if stop_on_negative_gain {
+                // the objective function is a heuristic local measure, so we
+                // check the actual cost of the new labels
+                let cost = log_gap_cost_of_labels(
+                    sym_graph,
+                    &label_store,
+                    &mut update_perm,
+                    &mut cost_inv_perm,
+                    current_granularity(&tuner),
+                    deg_cumul,
+                    thread_pool.as_ref(),
+                );
+                info!("Log-gap cost: {} -> {}", labels_cost, cost);
+                if cost > labels_cost {
+                    info!("Negative gain: reverting to the labels before the update");
+                    label_store.restore(&label_snapshot);
+                    break;
+                }
+                labels_cost = cost;
+            }
+
             obj_func += delta_obj_func;
             let gain = delta_obj_func / obj_func;
             let gain_impr = (prev_gain - gain) / prev_gain;
@@ -285,6 +765,7 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
                 avg_gain_impr,
                 modified: modified.load(Ordering::Relaxed),
                 update,
+                elapsed: gamma_start.elapsed(),
             }) || modified.load(Ordering::Relaxed) == 0
             {
                 break;
@@ -303,17 +784,32 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
         // Sort by label
         perm.par_sort_by(|&a, &b| label_store.label(a as _).cmp(&label_store.label(b as _)));
 
+        // If labels are smaller than usize, we need a temporary copy of the
+        // labels as usize, which we drop at the end of the iteration
+        let mut labels_copy = vec![];
+        if label_store.labels_as_usize().is_none() {
+            labels_copy.resize(num_nodes, 0);
+            labels_copy
+                .par_iter_mut()
+                .enumerate()
+                .with_min_len(1024)
+                .for_each(|(i, l)| *l = label_store.label(i));
+        }
+
         // Save labels
-        let labels = label_store.labels();
-        let mut file =
-            std::fs::File::create(labels_path(gamma_index)).context("Could not write labels")?;
+        let labels: &mut [usize] = match label_store.labels_as_usize() {
+            Some(labels) => labels,
+            None => &mut labels_copy,
+        };
+        let mut file = std::fs::File::create(labels_path(labels_dir, gamma_index))
+            .context("Could not write labels")?;
         labels
             .serialize(&mut file)
             .context("Could not serialize labels")?;
 
-        // We temporarily use the label array from the label store to compute
-        // the inverse permutation. It will be reinitialized at the next
-        // iteration anyway.
+        // We temporarily use the label array (from the label store, or its
+        // copy) to compute the inverse permutation. It will be reinitialized
+        // at the next iteration anyway.
         let inv_perm = labels;
         invert_permutation(perm, inv_perm);
 
@@ -325,9 +821,9 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
                 graph: sym_graph,
                 perm: &inv_perm,
             },
-            granularity,
+            current_granularity(&tuner),
             deg_cumul,
-            &thread_pool,
+            thread_pool.as_ref(),
             Some(&mut update_pl),
         );
 
@@ -335,6 +831,9 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
 
         info!("Log-gap cost: {}", cost);
         costs.push(cost);
+        if with_details {
+            details.push((*gamma, cost, inv_perm.to_vec().into_boxed_slice()));
+        }
 
         gamma_pl.update_and_display();
     }
@@ -362,27 +861,129 @@ pub fn layered_label_propagation<R: RandomAccessGraph + Sync>(
     // reuse the update_perm to store the final permutation
     let mut temp_perm = update_perm;
 
-    let mut result_labels = <Vec<usize>>::load_mem(labels_path(best_gamma_index))
+    let mut result_labels = <Vec<usize>>::load_mem(labels_path(labels_dir, best_gamma_index))
         .context("Could not load labels from best gammar")?
         .to_vec();
 
     for (i, gamma_index) in gamma_indices.iter().enumerate() {
         info!("Starting step {}...", i);
-        let labels =
-            <Vec<usize>>::load_mem(labels_path(*gamma_index)).context("Could not load labels")?;
+        let labels = <Vec<usize>>::load_mem(labels_path(labels_dir, *gamma_index))
+            .context("Could not load labels")?;
         combine(&mut result_labels, *labels, &mut temp_perm).context("Could not combine labels")?;
         // This recombination with the best labels does not appear in the paper, but
         // it is not harmful and fixes a few corner cases in which experimentally
         // LLP does not perform well. It was introduced by Marco Rosa in the Java
         // LAW code.
-        let best_labels = <Vec<usize>>::load_mem(labels_path(best_gamma_index))
+        let best_labels = <Vec<usize>>::load_mem(labels_path(labels_dir, best_gamma_index))
             .context("Could not load labels from best gamma")?;
         let number_of_labels = combine(&mut result_labels, *best_labels, &mut temp_perm)?;
         info!("Number of labels: {}", number_of_labels);
         info!("Finished step {}.", i);
     }
 
-    Ok(result_labels.into_boxed_slice())
+    Ok((result_labels.into_boxed_slice(), details))
+}
+
+/// Returns the label of `node` maximizing the LLP objective for the given ɣ,
+/// and the resulting change of the objective.
+///
+/// The closures `label` and `volume` return the label of a node and the
+/// volume of a label, whereas `choose` breaks ties among the best labels.
+#[allow(clippy::too_many_arguments)]
+fn best_label<R: RandomAccessGraph>(
+    sym_graph: &R,
+    node: usize,
+    curr_label: usize,
+    gamma: f64,
+    hash_map_init: usize,
+    label: impl Fn(usize) -> usize,
+    volume: impl Fn(usize) -> usize,
+    choose: impl FnOnce(&[usize]) -> usize,
+) -> (usize, f64) {
+    // compute the frequency of successor labels
+    let mut map = HashMap::with_capacity_and_hasher(hash_map_init, mix64::Mix64Builder);
+    for succ in sym_graph.successors(node) {
+        map.entry(label(succ))
+            .and_modify(|counter| *counter += 1)
+            .or_insert(1_usize);
+    }
+    // add the current label to the map
+    map.entry(curr_label).or_insert(0_usize);
+
+    let mut max = f64::NEG_INFINITY;
+    let mut old = 0.0;
+    let mut majorities = vec![];
+    // compute the most entropic label
+    for (&label, &count) in map.iter() {
+        // For replication of the results of the Java
+        // version, one needs to decrement the volume of
+        // the current value the Java version does
+        // (see the commented code below).
+        //
+        // Note that this is not exactly equivalent to the
+        // behavior of the Java version, as during the
+        // execution of this loop if another thread reads
+        // the volume of the current label it will get a
+        // value larger by one WRT the Java version.
+        let volume = volume(label); // - (label == curr_label) as usize;
+        let val = (1.0 + gamma) * count as f64 - gamma * (volume + 1) as f64;
+
+        if max == val {
+            majorities.push(label);
+        }
+
+        if val > max {
+            majorities.clear();
+            max = val;
+            majorities.push(label);
+        }
+
+        if label == curr_label {
+            old = val;
+        }
+    }
+    (choose(&majorities), max - old)
+}
+
+/// Returns the log-gap cost of the ordering obtained by sorting the nodes by
+/// their current labels, using `perm` and `inv_perm` as temporary storage.
+///
+/// If `thread_pool` is `None`, the cost is computed sequentially.
+fn log_gap_cost_of_labels<L: LabelType, R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    label_store: &label_store::LabelStore<L>,
+    perm: &mut [usize],
+    inv_perm: &mut [usize],
+    arc_granularity: usize,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    thread_pool: Option<&rayon::ThreadPool>,
+) -> f64 {
+    perm.par_iter_mut()
+        .enumerate()
+        .with_min_len(1024)
+        .for_each(|(i, x)| *x = i);
+    perm.par_sort_by(|&a, &b| label_store.label(a).cmp(&label_store.label(b)));
+    invert_permutation(perm, inv_perm);
+    gap_cost::compute_log_gap_cost(
+        &PermutedGraph {
+            graph: sym_graph,
+            perm: &inv_perm,
+        },
+        arc_granularity,
+        deg_cumul,
+        thread_pool,
+        None,
+    )
+}
+
+/// Mixes a seed with a sequence of values, returning a new seed.
+fn mix_seed(seed: u64, values: &[usize]) -> u64 {
+    use core::hash::Hasher;
+    values.iter().fold(seed, |state, &value| {
+        let mut hasher = mix64::Mix64::default();
+        hasher.write_usize((state.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ value as u64) as usize);
+        hasher.finish()
+    })
 }
 
 /// combine the labels from two permutations into a single one
@@ -438,6 +1039,18 @@ impl<'a, T> UnsafeSlice<'a, T> {
         *ptr = value;
     }
 }
+
+/// Stores in `inv_perm` the inverse of the permutation `perm`, in parallel.
+///
+/// `perm` must be a permutation of the integers in `0..perm.len()`, and
+/// `inv_perm` must have the same length. No check is performed, and since
+/// positions of `inv_perm` are written concurrently, the behavior on an input
+/// with duplicate entries is undefined: use
+/// [`invert_permutation_checked`] if the input has not been validated.
+///
+/// # Panics
+///
+/// If some element of `perm` is not smaller than the length of `inv_perm`.
 pub fn invert_permutation(perm: &[usize], inv_perm: &mut [usize]) {
     let unsafe_slice = UnsafeSlice::new(inv_perm);
     perm.par_iter()
@@ -447,3 +1060,90 @@ pub fn invert_permutation(perm: &[usize], inv_perm: &mut [usize]) {
             unsafe_slice.write(x, i);
         });
 }
+
+/// Stores in `inv_perm` the inverse of the permutation `perm`, after checking
+/// that `perm` is a permutation.
+///
+/// This function is the same as [`invert_permutation`], but returns an error,
+/// leaving `inv_perm` untouched, if `perm` and `inv_perm` have different
+/// lengths, or if `perm` contains an element not smaller than its length or
+/// a duplicate element. The check requires a bit per element.
+pub fn invert_permutation_checked(perm: &[usize], inv_perm: &mut [usize]) -> Result<()> {
+    let n = perm.len();
+    ensure!(
+        inv_perm.len() == n,
+        "The permutation has length {}, but the inverse has length {}",
+        n,
+        inv_perm.len()
+    );
+    let mut seen = BitVec::new(n);
+    for (i, &x) in perm.iter().enumerate() {
+        ensure!(
+            x < n,
+            "Element {} at position {} is out of range (the length is {})",
+            x,
+            i,
+            n
+        );
+        ensure!(!seen[x], "Element {} at position {} is a duplicate", x, i);
+        seen.set(x, true);
+    }
+    invert_permutation(perm, inv_perm);
+    Ok(())
+}
+
+/// Remaps in place the distinct labels of `labels` to `0..k`, where _k_ is
+/// the number of distinct labels, and returns _k_.
+///
+/// New labels are assigned in order of first appearance, so labels in `0..k`
+/// in which each label first appears after all smaller labels are left
+/// unchanged. This is useful to compact the output of clustering algorithms,
+/// whose labels might be sparse.
+pub fn compact_labels(labels: &mut [usize]) -> usize {
+    let mut map = HashMap::new();
+    for label in labels.iter_mut() {
+        let next = map.len();
+        *label = *map.entry(*label).or_insert(next);
+    }
+    map.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invert_permutation_checked() -> Result<()> {
+        let perm = [2, 0, 3, 1];
+        let mut inv_perm = [0; 4];
+        invert_permutation_checked(&perm, &mut inv_perm)?;
+        assert_eq!(inv_perm, [1, 3, 0, 2]);
+
+        let mut inv_perm = [usize::MAX; 4];
+        // Duplicate entries
+        assert!(invert_permutation_checked(&[2, 0, 2, 1], &mut inv_perm).is_err());
+        // Out-of-range entries
+        assert!(invert_permutation_checked(&[2, 0, 4, 1], &mut inv_perm).is_err());
+        // Mismatched lengths
+        assert!(invert_permutation_checked(&[0, 1, 2], &mut inv_perm).is_err());
+        // The output is untouched on error
+        assert_eq!(inv_perm, [usize::MAX; 4]);
+
+        invert_permutation_checked(&[], &mut [])?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_compact_labels() {
+        let mut labels = [5, 5, 2, 9];
+        assert_eq!(compact_labels(&mut labels), 3);
+        assert_eq!(labels, [0, 0, 1, 2]);
+
+        // Already compact labels are left unchanged
+        let mut labels = [0, 1, 1, 2, 0, 3];
+        assert_eq!(compact_labels(&mut labels), 4);
+        assert_eq!(labels, [0, 1, 1, 2, 0, 3]);
+
+        assert_eq!(compact_labels(&mut []), 0);
+    }
+}
@@ -29,6 +29,7 @@
 use anyhow::ensure;
 use predicates::{reflection::PredicateReflection, Predicate};
 use std::fmt::Display;
+use std::time::Duration;
 
 #[doc(hidden)]
 /// This structure is passed to predicates to provide the
@@ -40,6 +41,8 @@ pub struct PredParams {
     pub avg_gain_impr: f64,
     pub modified: usize,
     pub update: usize,
+    /// The time elapsed since the start of the updates for the current ɣ.
+    pub elapsed: Duration,
 }
 
 /// Stop after at most the provided number of updates for a given ɣ.
@@ -86,6 +89,35 @@ impl Predicate<PredParams> for MaxUpdates {
     }
 }
 
+/// Stop when the time elapsed since the start of the updates for a given ɣ
+/// exceeds the provided duration.
+///
+/// The check is performed at the end of each update, so the time limit can be
+/// exceeded by the duration of an update.
+#[derive(Debug, Clone)]
+pub struct MaxTime {
+    max_time: Duration,
+}
+
+impl From<Duration> for MaxTime {
+    fn from(max_time: Duration) -> Self {
+        MaxTime { max_time }
+    }
+}
+
+impl Display for MaxTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("(max time: {:?})", self.max_time))
+    }
+}
+
+impl PredicateReflection for MaxTime {}
+impl Predicate<PredParams> for MaxTime {
+    fn eval(&self, pred_params: &PredParams) -> bool {
+        pred_params.elapsed >= self.max_time
+    }
+}
+
 #[derive(Debug, Clone)]
 /// Stop if the gain of the objective function is below the given threshold.
 ///
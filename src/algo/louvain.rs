@@ -0,0 +1,328 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Community detection by modularity maximization.
+//!
+//! An implementation of the _Louvain method_ described by Vincent D. Blondel,
+//! Jean-Loup Guillaume, Renaud Lambiotte, and Etienne Lefebvre in “Fast
+//! unfolding of communities in large networks”, _Journal of Statistical
+//! Mechanics: Theory and Experiment_, 2008.
+//!
+//! Differently from [layered label propagation](crate::algo::llp), which
+//! computes an ordering that improves compression, the purpose of the Louvain
+//! method is to find communities.
+
+use crate::algo::llp::label_store::LabelStore;
+use crate::traits::RandomAccessGraph;
+use anyhow::{Context, Result};
+use log::info;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// A weighted graph obtained by collapsing communities into single nodes.
+struct Aggregated {
+    /// For each node, the (sorted) list of adjacent nodes and the weight of
+    /// the arc, loops excluded.
+    succ: Vec<Vec<(usize, usize)>>,
+    /// For each node, the weight of its loop.
+    loops: Vec<usize>,
+    /// For each node, its weighted degree (loop included).
+    degrees: Vec<usize>,
+}
+
+/// Runs the Louvain method on the provided symmetric graph and returns, for
+/// each node, the index of its community.
+///
+/// Community indices are contiguous and start from zero. Each pass moves nodes
+/// between communities until no move increases modularity, and then collapses
+/// each community into a single node; the method stops after `max_passes`
+/// passes or when a pass does not move any node.
+///
+/// Nodes are examined in order and ties are broken in favor of the current
+/// community and then of the community first met along the successor list, so
+/// the result is deterministic.
+///
+/// Note that no symmetry check is performed, but in that case the algorithm
+/// usually will not give satisfactory results. Loops are taken into
+/// consideration.
+///
+/// # Arguments
+///
+/// * `sym_graph` - The symmetric graph to run the Louvain method on.
+/// * `resolution` - The resolution parameter of modularity: values larger
+///   than one yield more, smaller communities, whereas values smaller than one
+///   yield fewer, larger communities.
+/// * `max_passes` - The maximum number of passes.
+/// * `num_threads` - The number of threads to use. If `None`, the number of
+///   threads is set to [`num_cpus::get`].
+pub fn louvain<R: RandomAccessGraph + Sync>(
+    sym_graph: &R,
+    resolution: f64,
+    max_passes: usize,
+    num_threads: Option<usize>,
+) -> Result<Box<[usize]>> {
+    let num_nodes = sym_graph.num_nodes();
+    // build a thread_pool so we avoid having to re-create the threads
+    let num_threads = num_threads.unwrap_or_else(num_cpus::get);
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .context("Could not create thread pool")?;
+
+    // the community of each node of the original graph
+    let mut result = (0..num_nodes).collect::<Vec<_>>();
+
+    let degrees: Vec<usize> = thread_pool.install(|| {
+        (0..num_nodes)
+            .into_par_iter()
+            .with_min_len(1024)
+            .map(|node| sym_graph.outdegree(node))
+            .collect()
+    });
+    let total_weight = degrees.iter().sum::<usize>();
+    if max_passes == 0 || total_weight == 0 {
+        return Ok(result.into_boxed_slice());
+    }
+
+    let succ = |node: usize| sym_graph.successors(node).into_iter().map(|s| (s, 1));
+    let (communities, num_communities) = match local_moving(
+        num_nodes,
+        succ,
+        &degrees,
+        total_weight,
+        resolution,
+        &thread_pool,
+    ) {
+        Some(x) => x,
+        None => return Ok(result.into_boxed_slice()),
+    };
+    info!("Pass 0: {} communities", num_communities);
+    result.copy_from_slice(&communities);
+
+    let mut graph = thread_pool.install(|| aggregate(succ, |_| 0, &communities, num_communities));
+
+    for pass in 1..max_passes {
+        let succ = |node: usize| graph.succ[node].iter().copied();
+        let (communities, num_communities) = match local_moving(
+            graph.succ.len(),
+            succ,
+            &graph.degrees,
+            total_weight,
+            resolution,
+            &thread_pool,
+        ) {
+            Some(x) => x,
+            None => break,
+        };
+        info!("Pass {}: {} communities", pass, num_communities);
+
+        thread_pool.install(|| {
+            result
+                .par_iter_mut()
+                .with_min_len(1024)
+                .for_each(|c| *c = communities[*c]);
+        });
+
+        graph = thread_pool.install(|| {
+            aggregate(
+                succ,
+                |node| graph.loops[node],
+                &communities,
+                num_communities,
+            )
+        });
+    }
+
+    Ok(result.into_boxed_slice())
+}
+
+/// Moves nodes between communities until no move increases modularity,
+/// returning the (compacted) community of each node and the number of
+/// communities, or `None` if no node was moved.
+///
+/// `succ` must return the weighted arcs of a node; loops are ignored, but they
+/// must be included in `degrees`.
+///
+/// Communities and their volumes (the sums of the degrees of their nodes) are
+/// kept in a [`LabelStore`], as labels and volumes in [layered label
+/// propagation](crate::algo::llp). Differently from LLP, however, nodes are
+/// moved by a single thread: the gain of a move depends on the volumes of the
+/// communities involved, and concurrent moves based on stale volumes may
+/// decrease modularity and make the process oscillate instead of converging.
+/// Serial moves guarantee that every move strictly increases modularity, and
+/// make the result deterministic; the thread pool is used to initialize the
+/// store.
+fn local_moving<I: IntoIterator<Item = (usize, usize)>>(
+    num_nodes: usize,
+    succ: impl Fn(usize) -> I,
+    degrees: &[usize],
+    total_weight: usize,
+    resolution: f64,
+    thread_pool: &rayon::ThreadPool,
+) -> Option<(Vec<usize>, usize)> {
+    let total_weight = total_weight as f64;
+    let mut label_store = <LabelStore>::new(num_nodes);
+    thread_pool.install(|| label_store.init_weighted(degrees));
+    // the weight of the arcs from the current node to each community
+    let mut weights = vec![0_usize; num_nodes];
+    let mut touched = vec![];
+    let mut moved_any = false;
+
+    loop {
+        let mut moved = 0;
+        for (node, &node_degree) in degrees.iter().enumerate() {
+            let curr = label_store.label(node);
+            let degree = node_degree as f64;
+            for (s, w) in succ(node) {
+                if s == node {
+                    continue;
+                }
+                let c = label_store.label(s);
+                if weights[c] == 0 {
+                    touched.push(c);
+                }
+                weights[c] += w;
+            }
+
+            // the volume of each community without the node
+            let volume = |c: usize| label_store.volume(c) - if c == curr { node_degree } else { 0 };
+            let gain = |c: usize| {
+                weights[c] as f64 - resolution * volume(c) as f64 * degree / total_weight
+            };
+            let mut best = curr;
+            let mut best_gain = gain(curr);
+            for &c in &touched {
+                let g = gain(c);
+                if g > best_gain {
+                    best = c;
+                    best_gain = g;
+                }
+            }
+            if best != curr {
+                label_store.update_weighted(node, best, node_degree);
+                moved += 1;
+            }
+
+            for &c in &touched {
+                weights[c] = 0;
+            }
+            touched.clear();
+        }
+
+        if moved == 0 {
+            break;
+        }
+        moved_any = true;
+    }
+
+    if !moved_any {
+        return None;
+    }
+
+    let mut community = label_store.labels().to_vec();
+    // renumber communities by first appearance
+    let mut map = vec![usize::MAX; num_nodes];
+    let mut num_communities = 0;
+    for c in community.iter_mut() {
+        if map[*c] == usize::MAX {
+            map[*c] = num_communities;
+            num_communities += 1;
+        }
+        *c = map[*c];
+    }
+    Some((community, num_communities))
+}
+
+/// Collapses each community into a single node, summing the weights of the
+/// arcs between communities and turning arcs within a community into a loop.
+fn aggregate<I: IntoIterator<Item = (usize, usize)>>(
+    succ: impl Fn(usize) -> I + Sync,
+    loops: impl Fn(usize) -> usize + Sync,
+    communities: &[usize],
+    num_communities: usize,
+) -> Aggregated {
+    let mut members = vec![vec![]; num_communities];
+    for (node, &c) in communities.iter().enumerate() {
+        members[c].push(node);
+    }
+
+    let lists = members
+        .par_iter()
+        .enumerate()
+        .map(|(c, nodes)| {
+            let mut map = HashMap::new();
+            let mut loop_weight = 0;
+            for &node in nodes {
+                loop_weight += loops(node);
+                for (s, w) in succ(node) {
+                    let d = communities[s];
+                    if d == c {
+                        loop_weight += w;
+                    } else {
+                        *map.entry(d).or_insert(0) += w;
+                    }
+                }
+            }
+            let mut list = map.into_iter().collect::<Vec<_>>();
+            list.sort_unstable();
+            (list, loop_weight)
+        })
+        .collect::<Vec<_>>();
+
+    let mut result = Aggregated {
+        succ: Vec::with_capacity(num_communities),
+        loops: Vec::with_capacity(num_communities),
+        degrees: Vec::with_capacity(num_communities),
+    };
+    for (list, loop_weight) in lists {
+        result
+            .degrees
+            .push(loop_weight + list.iter().map(|&(_, w)| w).sum::<usize>());
+        result.succ.push(list);
+        result.loops.push(loop_weight);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_two_cliques() -> Result<()> {
+        let mut arcs = vec![];
+        for base in [0, 4] {
+            for x in base..base + 4 {
+                for y in base..base + 4 {
+                    if x != y {
+                        arcs.push((x, y));
+                    }
+                }
+            }
+        }
+        // A bridge between the two cliques
+        arcs.push((3, 4));
+        arcs.push((4, 3));
+        let g = Left(VecGraph::from_arcs(arcs));
+
+        let communities = louvain(&g, 1.0, 10, Some(2))?;
+        assert_eq!(communities.len(), 8);
+        assert!(communities[..4].iter().all(|&c| c == communities[0]));
+        assert!(communities[4..].iter().all(|&c| c == communities[4]));
+        assert_ne!(communities[0], communities[4]);
+
+        // With a very low resolution everything collapses
+        let communities = louvain(&g, 0.01, 10, Some(2))?;
+        assert!(communities.iter().all(|&c| c == 0));
+
+        // No passes, no communities
+        let communities = louvain(&g, 1.0, 0, None)?;
+        assert_eq!(&*communities, &[0, 1, 2, 3, 4, 5, 6, 7]);
+        Ok(())
+    }
+}
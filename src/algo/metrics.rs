@@ -0,0 +1,536 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Tommaso Fontana
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Simple graph metrics, mostly computed with a single sequential scan.
+
+use crate::graphs::bvgraph::{get_endianness, BVGraphSeq};
+use crate::prelude::CodeRead;
+use crate::traits::{RandomAccessGraph, SequentialGraph};
+use anyhow::{bail, Result};
+use core::cmp::{Ordering, Reverse};
+use dsi_bitstream::prelude::*;
+use lender::*;
+use std::collections::{BTreeMap, BinaryHeap};
+use std::path::Path;
+use sux::prelude::BitVec;
+
+/// Returns the number of nodes of the graph with outdegree `d`.
+///
+/// See [`count_nodes_with_degree_in`].
+pub fn count_nodes_with_degree(graph: &impl SequentialGraph, d: usize) -> usize {
+    count_nodes_with_degree_in(graph, d, d)
+}
+
+/// Returns the number of nodes of the graph whose outdegree is between `lo`
+/// and `hi` (both inclusive).
+///
+/// The graph is scanned once, and successors are enumerated only up to
+/// `hi` + 1, so no histogram is built.
+pub fn count_nodes_with_degree_in(graph: &impl SequentialGraph, lo: usize, hi: usize) -> usize {
+    let mut count = 0;
+    for_!((_node, succ) in graph.iter() {
+        let d = succ.into_iter().take(hi.saturating_add(1)).count();
+        if (lo..=hi).contains(&d) {
+            count += 1;
+        }
+    });
+    count
+}
+
+/// Returns the indegrees of the nodes of the graph.
+///
+/// The indegrees are computed with a single sequential scan, incrementing a
+/// counter for each successor, so there is no need to
+/// [transpose](crate::transform::transpose()) the graph when only degrees are
+/// needed. The length of the result is always the number of nodes of the
+/// graph, even if some nodes have no predecessors.
+pub fn indegrees(graph: impl SequentialGraph) -> Box<[usize]> {
+    let mut indegrees = vec![0; graph.num_nodes()];
+    for_!((_node, succ) in graph.iter() {
+        for s in succ {
+            indegrees[s] += 1;
+        }
+    });
+    indegrees.into_boxed_slice()
+}
+
+/// Returns the `k` nodes with the largest outdegree, as pairs (node,
+/// outdegree) sorted by decreasing outdegree; ties are broken by increasing
+/// node.
+///
+/// The graph is scanned once, keeping the best `k` nodes found so far in a
+/// bounded heap, so memory usage is proportional to `k` and not to the number
+/// of nodes. If the graph has fewer than `k` nodes, all nodes are returned.
+pub fn top_outdegree(graph: impl SequentialGraph, k: usize) -> Vec<(usize, usize)> {
+    if k == 0 {
+        return vec![];
+    }
+    // A min-heap on (outdegree, Reverse(node)), so the top is the worst node
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for_!((node, succ) in graph.iter() {
+        let d = succ.into_iter().count();
+        if heap.len() < k {
+            heap.push(Reverse((d, Reverse(node))));
+        } else if let Some(Reverse((min_d, _))) = heap.peek() {
+            // Nodes come in increasing order, so a node with the same
+            // outdegree as the worst one loses the tie
+            if d > *min_d {
+                heap.pop();
+                heap.push(Reverse((d, Reverse(node))));
+            }
+        }
+    });
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((d, Reverse(node)))| (node, d))
+        .collect()
+}
+
+/// Basic statistics about a graph, computed by [`graph_stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GraphStats {
+    /// The number of nodes.
+    pub num_nodes: usize,
+    /// The number of arcs.
+    pub num_arcs: u64,
+    /// The minimum outdegree, or zero if the graph has no nodes.
+    pub min_outdegree: usize,
+    /// The maximum outdegree, or zero if the graph has no nodes.
+    pub max_outdegree: usize,
+    /// The number of nodes without incoming or outgoing arcs.
+    pub num_isolated: usize,
+    /// The number of arcs from a node to itself.
+    pub num_loops: u64,
+}
+
+impl GraphStats {
+    /// Returns the average outdegree, or zero if the graph has no nodes.
+    pub fn avg_outdegree(&self) -> f64 {
+        if self.num_nodes == 0 {
+            0.0
+        } else {
+            self.num_arcs as f64 / self.num_nodes as f64
+        }
+    }
+}
+
+/// Returns basic statistics about the graph.
+///
+/// The statistics are computed with a single sequential scan; besides
+/// counters, only two bits per node are necessary to record which nodes have
+/// predecessors and which nodes have successors.
+pub fn graph_stats(graph: impl SequentialGraph) -> GraphStats {
+    let num_nodes = graph.num_nodes();
+    let mut has_pred = BitVec::new(num_nodes);
+    let mut stats = GraphStats {
+        num_nodes,
+        min_outdegree: if num_nodes == 0 { 0 } else { usize::MAX },
+        ..GraphStats::default()
+    };
+    let mut has_succ = BitVec::new(num_nodes);
+    for_!((node, succ) in graph.iter() {
+        let mut d = 0;
+        for s in succ {
+            has_pred.set(s, true);
+            if s == node {
+                stats.num_loops += 1;
+            }
+            d += 1;
+        }
+        if d != 0 {
+            has_succ.set(node, true);
+        }
+        stats.num_arcs += d as u64;
+        stats.min_outdegree = stats.min_outdegree.min(d);
+        stats.max_outdegree = stats.max_outdegree.max(d);
+    });
+    stats.num_isolated = (0..num_nodes)
+        .filter(|&x| !has_pred[x] && !has_succ[x])
+        .count();
+    stats
+}
+
+/// Counts of reciprocal arcs of a graph, computed by [`reciprocity_counts`].
+///
+/// Loops are not considered arcs for the purpose of reciprocity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Reciprocity {
+    /// The number of arcs that are not loops.
+    pub num_arcs: u64,
+    /// The number of arcs (_u_, _v_) that are not loops and such that
+    /// (_v_, _u_) is an arc, too.
+    pub num_reciprocal_arcs: u64,
+}
+
+impl Reciprocity {
+    /// Returns the arc-level reciprocity, that is, the fraction of arcs whose
+    /// reverse is an arc, too, or zero if the graph has no arcs.
+    pub fn arc_reciprocity(&self) -> f64 {
+        if self.num_arcs == 0 {
+            0.0
+        } else {
+            self.num_reciprocal_arcs as f64 / self.num_arcs as f64
+        }
+    }
+
+    /// Returns the dyad-level reciprocity, that is, the fraction of pairs of
+    /// adjacent nodes that are connected in both directions, or zero if the
+    /// graph has no arcs.
+    pub fn dyad_reciprocity(&self) -> f64 {
+        // each mutual dyad accounts for two reciprocal arcs
+        let num_dyads = self.num_arcs - self.num_reciprocal_arcs / 2;
+        if num_dyads == 0 {
+            0.0
+        } else {
+            (self.num_reciprocal_arcs / 2) as f64 / num_dyads as f64
+        }
+    }
+}
+
+/// Returns the arc-level reciprocity of the graph, that is, the fraction of
+/// arcs whose reverse is an arc, too.
+///
+/// See [`reciprocity_counts`].
+pub fn reciprocity(graph: impl RandomAccessGraph) -> f64 {
+    reciprocity_counts(graph).arc_reciprocity()
+}
+
+/// Returns the counts of reciprocal arcs of the graph, from which both
+/// arc-level and dyad-level reciprocity can be computed.
+///
+/// The graph is scanned sequentially, and for each arc (_u_, _v_) with _u_ <
+/// _v_ the existence of the arc (_v_, _u_) is checked using
+/// [`has_arc`](RandomAccessGraph::has_arc), so no transpose is necessary.
+///
+/// The cost of each check is thus that of `has_arc`: logarithmic in the
+/// outdegree of _v_ for a [`CsrGraph`](crate::graphs::csr_graph::CsrGraph)
+/// with sorted successor lists, proportional to the number of successors of
+/// _v_ smaller than _u_ for a [`BVGraph`](crate::graphs::bvgraph::BVGraph),
+/// whose successor lists are sorted, and linear in the outdegree of _v_ for
+/// graphs using the default implementation.
+pub fn reciprocity_counts(graph: impl RandomAccessGraph) -> Reciprocity {
+    let mut counts = Reciprocity::default();
+    for_!((node, succ) in graph.iter() {
+        for s in succ {
+            if s == node {
+                continue;
+            }
+            counts.num_arcs += 1;
+            if node < s && graph.has_arc(s, node) {
+                counts.num_reciprocal_arcs += 2;
+            }
+        }
+    });
+    counts
+}
+
+/// Returns the Jaccard similarity of the successor sets of two nodes, that
+/// is, the size of their intersection divided by the size of their union.
+///
+/// The two successor lists are merged in time proportional to the sum of the
+/// outdegrees, so they must be sorted and without duplicates. By convention,
+/// the similarity of two nodes without successors is zero.
+pub fn jaccard(graph: impl RandomAccessGraph, u: usize, v: usize) -> f64 {
+    let mut a = graph.successors(u).into_iter().peekable();
+    let mut b = graph.successors(v).into_iter().peekable();
+    let (mut intersection, mut union) = (0_usize, 0_usize);
+    loop {
+        match (a.peek(), b.peek()) {
+            (Some(&x), Some(&y)) => {
+                union += 1;
+                match x.cmp(&y) {
+                    Ordering::Less => {
+                        a.next();
+                    }
+                    Ordering::Greater => {
+                        b.next();
+                    }
+                    Ordering::Equal => {
+                        intersection += 1;
+                        a.next();
+                        b.next();
+                    }
+                }
+            }
+            (Some(_), None) => {
+                union += a.by_ref().count();
+                break;
+            }
+            (None, Some(_)) => {
+                union += b.by_ref().count();
+                break;
+            }
+            (None, None) => break,
+        }
+    }
+    if union == 0 {
+        0.0
+    } else {
+        intersection as f64 / union as f64
+    }
+}
+
+/// Returns the outdegree histogram of the BVGraph with the given basename:
+/// the value at index `d` is the number of nodes with outdegree `d`.
+///
+/// The histogram is computed in a single pass using an
+/// [`OffsetDegIter`](crate::graphs::bvgraph::OffsetDegIter), which decodes
+/// outdegrees without materializing successor lists. The length of the
+/// result is the maximum outdegree plus one; if a few nodes have a very large
+/// outdegree, [`degree_histogram_sparse`] might be more appropriate.
+pub fn degree_histogram(basename: impl AsRef<Path>) -> Result<Vec<u64>> {
+    let mut histogram = vec![];
+    for_each_outdegree(basename.as_ref(), |d| {
+        if d >= histogram.len() {
+            histogram.resize(d + 1, 0);
+        }
+        histogram[d] += 1;
+    })?;
+    Ok(histogram)
+}
+
+/// Returns the outdegree histogram of the BVGraph with the given basename as a
+/// map from outdegrees to the (nonzero) number of nodes with that outdegree.
+///
+/// See [`degree_histogram`].
+pub fn degree_histogram_sparse(basename: impl AsRef<Path>) -> Result<BTreeMap<u64, u64>> {
+    let mut histogram = BTreeMap::new();
+    for_each_outdegree(basename.as_ref(), |d| {
+        *histogram.entry(d as u64).or_insert(0) += 1;
+    })?;
+    Ok(histogram)
+}
+
+/// Calls `f` on the outdegree of each node of the BVGraph with the given
+/// basename, in order.
+fn for_each_outdegree(basename: &Path, f: impl FnMut(usize)) -> Result<()> {
+    match get_endianness(basename)?.as_str() {
+        BE::NAME => for_each_outdegree_with_endianness::<BE>(basename, f),
+        LE::NAME => for_each_outdegree_with_endianness::<LE>(basename, f),
+        e => bail!("Unknown endianness: {}", e),
+    }
+}
+
+fn for_each_outdegree_with_endianness<E: Endianness + 'static>(
+    basename: &Path,
+    mut f: impl FnMut(usize),
+) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let graph = BVGraphSeq::with_basename(basename)
+        .endianness::<E>()
+        .load()?;
+    for (_offset, d) in graph.offset_deg_iter() {
+        f(d);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+    use crate::traits::SequentialLabeling;
+
+    #[test]
+    fn test_count_nodes_with_degree() {
+        // Outdegrees are 2, 1, 2, 0, 3
+        let g = Left(VecGraph::from_arc_list([
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (4, 0),
+            (4, 1),
+            (4, 3),
+        ]));
+        assert_eq!(count_nodes_with_degree(&g, 0), 1);
+        assert_eq!(count_nodes_with_degree(&g, 2), 2);
+        assert_eq!(count_nodes_with_degree(&g, 4), 0);
+        assert_eq!(count_nodes_with_degree_in(&g, 1, 2), 3);
+        assert_eq!(count_nodes_with_degree_in(&g, 0, usize::MAX), 5);
+        assert_eq!(count_nodes_with_degree_in(&g, 2, 1), 0);
+    }
+
+    #[test]
+    fn test_top_outdegree() {
+        // Outdegrees are 2, 1, 3, 0, 3
+        let g = Left(VecGraph::from_arc_list([
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 0),
+            (2, 1),
+            (2, 3),
+            (4, 0),
+            (4, 1),
+            (4, 3),
+        ]));
+        assert_eq!(top_outdegree(&g, 2), vec![(2, 3), (4, 3)]);
+        assert_eq!(top_outdegree(&g, 3), vec![(2, 3), (4, 3), (0, 2)]);
+        assert_eq!(
+            top_outdegree(&g, 10),
+            vec![(2, 3), (4, 3), (0, 2), (1, 1), (3, 0)]
+        );
+        assert!(top_outdegree(&g, 0).is_empty());
+    }
+
+    #[test]
+    fn test_indegrees() -> Result<()> {
+        use crate::transform::transpose;
+        // node 0 has no predecessors, node 3 is isolated, and node 5 has
+        // no successors
+        let mut g = VecGraph::empty(6);
+        g.add_arc_list([(0, 1), (1, 2), (2, 1), (4, 5), (1, 5)]);
+        let g = Left(g);
+
+        let indegrees = indegrees(&g);
+        assert_eq!(indegrees.len(), 6);
+        assert_eq!(&*indegrees, &[0, 2, 1, 0, 0, 2]);
+
+        let trans = transpose(&g, 2)?;
+        for_!((node, succ) in trans.iter() {
+            assert_eq!(indegrees[node], succ.into_iter().count());
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn test_graph_stats() {
+        // node 1 has a loop, node 3 is isolated, and node 4 has only a
+        // predecessor
+        let mut g = VecGraph::empty(5);
+        g.add_arc_list([(0, 1), (0, 4), (1, 1), (1, 2), (2, 0)]);
+        let stats = graph_stats(Left(g));
+        assert_eq!(
+            stats,
+            GraphStats {
+                num_nodes: 5,
+                num_arcs: 5,
+                min_outdegree: 0,
+                max_outdegree: 2,
+                num_isolated: 1,
+                num_loops: 1,
+            }
+        );
+        assert_eq!(stats.avg_outdegree(), 1.0);
+
+        assert_eq!(
+            graph_stats(Left(VecGraph::<()>::new())),
+            GraphStats::default()
+        );
+    }
+
+    #[test]
+    fn test_degree_histogram() -> Result<()> {
+        use crate::graphs::bvgraph::{BVComp, CompFlags};
+        use crate::graphs::random::ErdosRenyi;
+
+        let tmp_dir = tempfile::tempdir()?;
+        for (i, g) in [
+            Left(VecGraph::from_arc_list([
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (4, 0),
+                (4, 1),
+                (4, 3),
+            ])),
+            Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter())),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let basename = tmp_dir.path().join(format!("graph-{}", i));
+            BVComp::single_thread::<BE, _>(&basename, g, CompFlags::default(), false, None)?;
+
+            let mut expected = BTreeMap::new();
+            for_!((_node, succ) in g.iter() {
+                *expected.entry(succ.into_iter().count() as u64).or_insert(0) += 1;
+            });
+            assert_eq!(degree_histogram_sparse(&basename)?, expected);
+
+            let histogram = degree_histogram(&basename)?;
+            assert_eq!(histogram.len() as u64, expected.keys().max().unwrap() + 1);
+            for (d, &count) in histogram.iter().enumerate() {
+                assert_eq!(count, expected.get(&(d as u64)).copied().unwrap_or(0));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_reciprocity() {
+        // 0 <-> 1 and 2 <-> 3 are mutual, 1 -> 2 and 0 -> 3 are not, and the
+        // loop on 3 is ignored
+        let g = Left(VecGraph::from_arc_list([
+            (0, 1),
+            (0, 3),
+            (1, 0),
+            (1, 2),
+            (2, 3),
+            (3, 2),
+            (3, 3),
+        ]));
+        let counts = reciprocity_counts(&g);
+        assert_eq!(
+            counts,
+            Reciprocity {
+                num_arcs: 6,
+                num_reciprocal_arcs: 4
+            }
+        );
+        assert_eq!(reciprocity(&g), 4.0 / 6.0);
+        assert_eq!(counts.dyad_reciprocity(), 0.5);
+        assert_eq!(
+            reciprocity_counts(crate::graphs::csr_graph::CsrGraph::from_graph(&g)),
+            counts
+        );
+        // same graph with unsorted successor lists
+        let csr =
+            crate::graphs::csr_graph::CsrGraph::new(vec![0, 2, 4, 5, 7], vec![3, 1, 2, 0, 3, 3, 2]);
+        assert_eq!(reciprocity_counts(&csr), counts);
+
+        let g = Left(VecGraph::from_arc_list([(0, 0), (0, 1), (2, 1)]));
+        assert_eq!(reciprocity(&g), 0.0);
+        assert_eq!(reciprocity_counts(&g).dyad_reciprocity(), 0.0);
+        assert_eq!(reciprocity(Left(VecGraph::<()>::empty(3))), 0.0);
+    }
+
+    #[test]
+    fn test_jaccard() {
+        let g = Left(VecGraph::from_arc_list([
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (0, 4),
+            (5, 3),
+            (5, 4),
+            (5, 6),
+            (7, 1),
+            (7, 2),
+            (7, 3),
+            (7, 4),
+            (8, 9),
+        ]));
+        // Identical neighborhoods
+        assert_eq!(jaccard(&g, 0, 7), 1.0);
+        assert_eq!(jaccard(&g, 5, 5), 1.0);
+        // Disjoint neighborhoods
+        assert_eq!(jaccard(&g, 0, 8), 0.0);
+        // {1, 2, 3, 4} and {3, 4, 6} share two of five successors
+        assert_eq!(jaccard(&g, 0, 5), 0.4);
+        assert_eq!(jaccard(&g, 5, 0), 0.4);
+        // Both neighborhoods are empty
+        assert_eq!(jaccard(&g, 1, 2), 0.0);
+        // One neighborhood is empty
+        assert_eq!(jaccard(&g, 1, 0), 0.0);
+    }
+}
@@ -8,7 +8,56 @@
 //! Algorithmic utilities.
 
 mod bfs_order;
-pub use bfs_order::BfsOrder;
+pub use bfs_order::{bfs_permutation, BfsOrder};
+
+mod distances;
+pub use distances::*;
 
 pub mod llp;
 pub use llp::*;
+
+mod louvain;
+pub use louvain::*;
+
+mod classify;
+pub use classify::*;
+
+pub mod pagerank;
+
+pub mod generators;
+
+mod hyperball;
+pub use hyperball::*;
+
+mod ordering_report;
+pub use ordering_report::*;
+
+mod metrics;
+pub use metrics::*;
+
+mod quantiles;
+pub use quantiles::*;
+
+mod near_duplicates;
+pub use near_duplicates::*;
+
+mod sample;
+pub use sample::*;
+
+mod triangles;
+pub use triangles::*;
+
+mod compare;
+pub use compare::*;
+
+mod complement;
+pub use complement::*;
+
+mod audit;
+pub use audit::*;
+
+mod betweenness;
+pub use betweenness::*;
+
+mod random_permutation;
+pub use random_permutation::*;
@@ -0,0 +1,183 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Near-duplicate detection.
+//!
+//! Nodes with similar successor lists (e.g., mirrored pages in a web graph) are
+//! detected using the _simhash_ technique described by Moses S. Charikar in
+//! “Similarity estimation techniques from rounding algorithms”, _Proceedings of
+//! the 34th annual ACM Symposium on Theory of Computing_, pages 380–388, ACM,
+//! 2002.
+
+use crate::algo::llp::mix64::mix64;
+use crate::traits::RandomAccessGraph;
+use anyhow::{ensure, Result};
+use rayon::prelude::*;
+
+/// Returns groups of nodes with near-identical successor lists.
+///
+/// A 64-bit simhash of the successor list of each node is computed in parallel
+/// using the current [`rayon`] thread pool; the fraction of differing bits
+/// between the simhashes of two nodes estimates the angle between their
+/// successor sets, so nodes with similar successors have simhashes at a small
+/// Hamming distance. Two nodes are considered duplicates if the Hamming
+/// distance of their simhashes is at most (1 − `threshold`) · 64, rounded
+/// down, and groups are the connected components of this relation; thus, with
+/// `threshold` equal to one only nodes with identical simhashes are grouped.
+///
+/// To avoid comparing all pairs of nodes, simhashes are split into as many
+/// blocks as the maximum distance plus one: by the pigeonhole principle, two
+/// simhashes within the maximum distance must agree on at least one block,
+/// so only nodes sharing a block are compared. Lower thresholds imply more,
+/// and shorter, blocks, and thus more comparisons.
+///
+/// The result is a list of _candidate_ groups, as simhashes are a
+/// probabilistic sketch: distinct groups are sorted by their smallest node,
+/// each group is sorted and contains at least two nodes, and nodes without
+/// successors are never part of a group.
+///
+/// # Errors
+///
+/// If `threshold` is not in [0..1].
+pub fn near_duplicates(
+    graph: impl RandomAccessGraph + Sync,
+    threshold: f64,
+) -> Result<Vec<Vec<usize>>> {
+    ensure!(
+        (0.0..=1.0).contains(&threshold),
+        "The threshold must be in [0..1], but it is {}",
+        threshold
+    );
+    let max_dist = ((1.0 - threshold) * 64.0).floor() as u32;
+    let num_nodes = graph.num_nodes();
+
+    let hashes = (0..num_nodes)
+        .into_par_iter()
+        .with_min_len(1024)
+        .map(|node| simhash(graph.successors(node)))
+        .collect::<Vec<_>>();
+
+    let mut parent = (0..num_nodes).collect::<Vec<_>>();
+    let num_blocks = max_dist as usize + 1;
+    let mut bucket = Vec::with_capacity(num_nodes);
+    for block in 0..num_blocks {
+        let start = block * 64 / num_blocks;
+        let len = (block + 1) * 64 / num_blocks - start;
+        let mask = if len == 64 { u64::MAX } else { (1 << len) - 1 };
+
+        // sort the nodes with successors by the value of the block
+        bucket.clear();
+        bucket.extend(
+            hashes
+                .iter()
+                .enumerate()
+                .filter_map(|(node, hash)| hash.map(|hash| ((hash >> start) & mask, node))),
+        );
+        bucket.par_sort_unstable();
+
+        for run in bucket.chunk_by(|a, b| a.0 == b.0) {
+            for (i, &(_, u)) in run.iter().enumerate() {
+                for &(_, v) in &run[i + 1..] {
+                    let dist = (hashes[u].unwrap() ^ hashes[v].unwrap()).count_ones();
+                    if dist <= max_dist {
+                        union(&mut parent, u, v);
+                    }
+                }
+            }
+        }
+    }
+
+    // collect the components with at least two nodes
+    let mut groups = vec![vec![]; num_nodes];
+    for node in 0..num_nodes {
+        let root = find(&mut parent, node);
+        groups[root].push(node);
+    }
+    let mut groups = groups
+        .into_iter()
+        .filter(|group| group.len() > 1)
+        .collect::<Vec<_>>();
+    groups.sort_unstable_by_key(|group| group[0]);
+    Ok(groups)
+}
+
+/// Returns the 64-bit simhash of a list of successors, or `None` if the list
+/// is empty.
+fn simhash(succ: impl IntoIterator<Item = usize>) -> Option<u64> {
+    let mut weights = [0_i64; 64];
+    let mut empty = true;
+    for s in succ {
+        empty = false;
+        let hash = mix64(s as u64);
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            *weight += if hash & (1 << bit) != 0 { 1 } else { -1 };
+        }
+    }
+    (!empty).then(|| {
+        weights.iter().enumerate().fold(0, |hash, (bit, &weight)| {
+            hash | (((weight > 0) as u64) << bit)
+        })
+    })
+}
+
+/// Returns the representative of the set of `x`, compressing paths.
+fn find(parent: &mut [usize], mut x: usize) -> usize {
+    let mut root = x;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    while parent[x] != root {
+        x = core::mem::replace(&mut parent[x], root);
+    }
+    root
+}
+
+/// Merges the sets of `x` and `y`, using the smallest representative.
+fn union(parent: &mut [usize], x: usize, y: usize) {
+    let (x, y) = (find(parent, x), find(parent, y));
+    parent[x.max(y)] = x.min(y);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_near_duplicates() -> Result<()> {
+        let mut g = VecGraph::<()>::empty(1000);
+        // Nodes 0 and 1 share all successors but one; node 3 is an exact copy
+        // of node 0; node 2 is unrelated
+        for s in 10..210 {
+            g.add_arc(0, s);
+            g.add_arc(1, s);
+            g.add_arc(3, s);
+        }
+        g.add_arc(0, 500);
+        g.add_arc(3, 500);
+        g.add_arc(1, 501);
+        for s in 300..500 {
+            g.add_arc(2, s);
+        }
+        let g = Left(g);
+
+        assert_eq!(near_duplicates(&g, 0.75)?, vec![vec![0, 1, 3]]);
+
+        // Exact copies have the same simhash
+        let groups = near_duplicates(&g, 1.0)?;
+        assert!(groups
+            .iter()
+            .any(|group| group.contains(&0) && group.contains(&3)));
+        assert!(groups.iter().all(|group| !group.contains(&2)));
+
+        // Isolated nodes are never grouped
+        assert!(near_duplicates(Left(VecGraph::<()>::empty(10)), 0.0)?.is_empty());
+
+        assert!(near_duplicates(&g, 1.5).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,165 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Tommaso Fontana
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::llp::gap_cost::node_log_gap_cost;
+use crate::prelude::*;
+use crate::utils::int2nat;
+use anyhow::{Context, Result};
+use dsi_bitstream::prelude::*;
+use dsi_progress_logger::prelude::*;
+use lender::prelude::*;
+use std::fmt::Display;
+use sux::traits::{BitFieldSlice, Succ};
+
+/// Statistics describing the quality of a node ordering.
+///
+/// Reports are computed by [`ordering_report`], and make it possible to
+/// compare objectively orderings obtained by different means (e.g.,
+/// [LLP](super::llp::layered_label_propagation) or [BFS](super::BfsOrder)).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderingReport {
+    /// The number of nodes of the graph.
+    pub num_nodes: usize,
+    /// The number of arcs of the graph.
+    pub num_arcs: u64,
+    /// The log-gap cost, as computed by
+    /// [LLP](super::llp::layered_label_propagation).
+    pub log_gap_cost: f64,
+    /// The average gap between a node and its first successor, and between
+    /// consecutive successors.
+    pub avg_gap: f64,
+    /// An estimate of the number of bits per arc, obtained by coding gaps
+    /// using [γ codes](dsi_bitstream::codes::GammaWrite), as BVGraph does for
+    /// residuals.
+    pub bits_per_arc: f64,
+    /// The window used to compute [`locality`](OrderingReport::locality).
+    pub locality_window: usize,
+    /// The fraction of arcs `x → y` such that |_x_ − _y_| is smaller than
+    /// [`locality_window`](OrderingReport::locality_window).
+    pub locality: f64,
+}
+
+impl OrderingReport {
+    /// The default locality window, which is the default compression window
+    /// of BVGraph.
+    pub const DEFAULT_LOCALITY_WINDOW: usize = 7;
+}
+
+impl Display for OrderingReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "log-gap cost: {}, average gap: {:.3}, bits/arc: {:.3}, locality (window {}): {:.3}%",
+            self.log_gap_cost,
+            self.avg_gap,
+            self.bits_per_arc,
+            self.locality_window,
+            self.locality * 100.0
+        )
+    }
+}
+
+/// Partial statistics of a chunk of nodes.
+#[derive(Default)]
+struct Partial {
+    log_gap_cost: usize,
+    gaps: u64,
+    bits: u64,
+    local: u64,
+    arcs: u64,
+}
+
+/// Computes an [`OrderingReport`] for the provided graph renumbered using the
+/// provided permutation, in a single parallel scan.
+///
+/// # Arguments
+///
+/// * `graph` - The graph.
+/// * `perm` - The permutation: node `x` is renumbered as `perm[x]`.
+/// * `deg_cumul` - The degree cumulative distribution of the graph, as in
+///   [par_apply](crate::traits::SequentialLabeling::par_apply).
+/// * `locality_window` - The window used to decide whether an arc is local
+///   (see [`OrderingReport::DEFAULT_LOCALITY_WINDOW`]).
+/// * `num_threads` - The number of threads to use. If `None`, the number of
+///   threads is set to [`num_cpus::get`].
+pub fn ordering_report<G: SequentialGraph + Sync, P: BitFieldSlice<usize> + Sync>(
+    graph: &G,
+    perm: &P,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    locality_window: usize,
+    num_threads: Option<usize>,
+) -> Result<OrderingReport> {
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads.unwrap_or_else(num_cpus::get))
+        .build()
+        .context("Could not create thread pool")?;
+
+    let permuted = PermutedGraph { graph, perm };
+    let num_arcs = deg_cumul.get(graph.num_nodes()) as u64;
+    let arc_granularity = ((num_arcs >> 9) as usize).max(1024);
+
+    let mut pl = progress_logger!(
+        item_name = "node",
+        expected_updates = Some(graph.num_nodes()),
+        display_memory = true
+    );
+    pl.start("Computing ordering report...");
+
+    let total = permuted.par_apply(
+        |range| {
+            let mut partial = Partial::default();
+            let mut sorted = vec![];
+            for_!((x, succ) in permuted.iter_from(range.start).take(range.len()) {
+                sorted.clear();
+                sorted.extend(succ);
+                if sorted.is_empty() {
+                    continue;
+                }
+                sorted.sort();
+                partial.log_gap_cost += node_log_gap_cost(x, &sorted);
+                partial.arcs += sorted.len() as u64;
+                partial.gaps += (x as isize - sorted[0] as isize).unsigned_abs() as u64;
+                partial.bits += len_gamma(int2nat(sorted[0] as i64 - x as i64)) as u64;
+                for w in sorted.windows(2) {
+                    partial.gaps += (w[1] - w[0]) as u64;
+                    // successors are distinct, so gaps are positive
+                    partial.bits += len_gamma((w[1] - w[0] - 1) as u64) as u64;
+                }
+                partial.local += sorted
+                    .iter()
+                    .filter(|&&y| (x as isize - y as isize).unsigned_abs() < locality_window)
+                    .count() as u64;
+            });
+            partial
+        },
+        |mut a: Partial, b: Partial| {
+            a.log_gap_cost += b.log_gap_cost;
+            a.gaps += b.gaps;
+            a.bits += b.bits;
+            a.local += b.local;
+            a.arcs += b.arcs;
+            a
+        },
+        arc_granularity,
+        deg_cumul,
+        &thread_pool,
+        Some(&mut pl),
+    );
+
+    pl.done();
+
+    let arcs = total.arcs.max(1) as f64;
+    Ok(OrderingReport {
+        num_nodes: graph.num_nodes(),
+        num_arcs: total.arcs,
+        log_gap_cost: total.log_gap_cost as f64,
+        avg_gap: total.gaps as f64 / arcs,
+        bits_per_arc: total.bits as f64 / arcs,
+        locality_window,
+        locality: total.local as f64 / arcs,
+    })
+}
@@ -0,0 +1,177 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! PageRank.
+//!
+//! An approximation of PageRank, as defined by Sergey Brin and Lawrence Page
+//! in “The anatomy of a large-scale hypertextual Web search engine”,
+//! _Computer Networks and ISDN Systems_, 1998, computed by the power method.
+
+use crate::traits::*;
+use anyhow::{ensure, Context, Result};
+use common_traits::{Atomic, AtomicF64};
+use dsi_progress_logger::prelude::*;
+use log::info;
+use rayon::prelude::*;
+use std::sync::atomic::Ordering;
+use sux::traits::Succ;
+
+/// Computes PageRank by the power method and returns the score of each node.
+///
+/// The initial (and preference) vector is uniform. At each iteration the rank
+/// of each node is pushed in parallel to its successors using
+/// [`par_apply`](crate::traits::SequentialLabeling::par_apply); the rank of
+/// dangling nodes (i.e., nodes with outdegree zero) is redistributed uniformly
+/// to all nodes. The scores sum to one.
+///
+/// # Arguments
+///
+/// * `graph` - The graph.
+/// * `deg_cumul` - The degree cumulative distribution of the graph, as in
+///   [par_apply](crate::traits::SequentialLabeling::par_apply).
+/// * `alpha` - The damping factor, between zero and one (excluded); with
+///   `alpha` equal to zero the result is the uniform distribution.
+/// * `max_iters` - The maximum number of iterations.
+/// * `tolerance` - The computation stops as soon as the ℓ₁ norm of the
+///   difference between the scores of two consecutive iterations is smaller
+///   than this value.
+/// * `num_threads` - The number of threads to use. If `None`, the number of
+///   threads is set to [`num_cpus::get`].
+pub fn power_iteration<G: RandomAccessGraph + Sync>(
+    graph: &G,
+    deg_cumul: &(impl Succ<Input = usize, Output = usize> + Send + Sync),
+    alpha: f64,
+    max_iters: usize,
+    tolerance: f64,
+    num_threads: Option<usize>,
+) -> Result<Box<[f64]>> {
+    ensure!(
+        (0.0..1.0).contains(&alpha),
+        "The damping factor must be in [0..1), but it is {}",
+        alpha
+    );
+    let num_nodes = graph.num_nodes();
+    if num_nodes == 0 {
+        return Ok(Box::new([]));
+    }
+    // build a thread_pool so we avoid having to re-create the threads
+    let num_threads = num_threads.unwrap_or_else(num_cpus::get);
+    let thread_pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .context("Could not create thread pool")?;
+    let granularity = ((graph.num_arcs() >> 9) as usize).max(1024);
+
+    let uniform = 1.0 / num_nodes as f64;
+    let mut rank = vec![uniform; num_nodes];
+    let mut next = Vec::with_capacity(num_nodes);
+    next.extend((0..num_nodes).map(|_| AtomicF64::new(0.0)));
+
+    let mut pl = progress_logger!(item_name = "node", local_speed = true);
+
+    for iter in 0..max_iters {
+        pl.expected_updates(Some(num_nodes));
+        pl.start(format!("Starting iteration {}...", iter));
+
+        let dangling = graph.par_apply(
+            |range| {
+                let mut dangling = 0.0;
+                for node in range {
+                    let outdegree = graph.outdegree(node);
+                    if outdegree == 0 {
+                        dangling += rank[node];
+                        continue;
+                    }
+                    let contrib = rank[node] / outdegree as f64;
+                    for succ in graph.successors(node) {
+                        // AtomicF64::fetch_add adds the bit patterns, so we
+                        // need a compare-and-swap loop
+                        next[succ]
+                            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |x| {
+                                Some(x + contrib)
+                            })
+                            .unwrap();
+                    }
+                }
+                dangling
+            },
+            |dangling_0: f64, dangling_1| dangling_0 + dangling_1,
+            granularity,
+            deg_cumul,
+            &thread_pool,
+            Some(&mut pl),
+        );
+
+        let teleport = (1.0 - alpha) * uniform + alpha * dangling * uniform;
+        let norm_delta = thread_pool.install(|| {
+            rank.par_iter_mut()
+                .zip(next.par_iter())
+                .with_min_len(1024)
+                .map(|(r, n)| {
+                    let new_rank = alpha * n.load(Ordering::Relaxed) + teleport;
+                    n.store(0.0, Ordering::Relaxed);
+                    let delta = (new_rank - *r).abs();
+                    *r = new_rank;
+                    delta
+                })
+                .sum::<f64>()
+        });
+
+        pl.done_with_count(num_nodes);
+        info!("Norm of the difference: {}", norm_delta);
+        if norm_delta < tolerance {
+            break;
+        }
+    }
+
+    Ok(rank.into_boxed_slice())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::bvgraph::build_dcf;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_power_iteration() -> Result<()> {
+        // A symmetric 4-cycle
+        let g = Left(VecGraph::from_arcs([
+            (0, 1),
+            (1, 0),
+            (1, 2),
+            (2, 1),
+            (2, 3),
+            (3, 2),
+            (3, 0),
+            (0, 3),
+        ]));
+        let deg_cumul = build_dcf(&g)?;
+        let rank = power_iteration(&g, &deg_cumul, 0.85, 100, 1E-12, Some(2))?;
+        assert_eq!(rank.len(), 4);
+        for &r in rank.iter() {
+            assert!((r - 0.25).abs() < 1E-9);
+        }
+
+        // A star with dangling leaves, and alpha = 0
+        let g = Left(VecGraph::from_arcs([(0, 1), (0, 2), (0, 3), (0, 4)]));
+        let deg_cumul = build_dcf(&g)?;
+        let rank = power_iteration(&g, &deg_cumul, 0.0, 100, 1E-12, Some(2))?;
+        for &r in rank.iter() {
+            assert!((r - 0.2).abs() < 1E-12);
+        }
+
+        // With a positive alpha the leaves get more than the center, and the
+        // scores still sum to one
+        let rank = power_iteration(&g, &deg_cumul, 0.85, 100, 1E-12, Some(2))?;
+        assert!(rank[1] > rank[0]);
+        assert!((rank.iter().sum::<f64>() - 1.0).abs() < 1E-9);
+
+        assert!(power_iteration(&g, &deg_cumul, 1.0, 100, 1E-12, None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,184 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::SequentialGraph;
+use lender::*;
+
+/// The rank error of [`degree_quantiles`], as a fraction of the number of
+/// nodes.
+pub const DEGREE_QUANTILES_EPS: f64 = 0.001;
+
+/// Returns approximate quantiles of the outdegree distribution of a graph.
+///
+/// The outdegrees are inserted in a Greenwald–Khanna summary during a single
+/// sequential scan, and for each φ in `quantiles` the result contains an
+/// outdegree whose rank in the sorted list of outdegrees differs from ⌈φ*n*⌉
+/// by at most ε*n*, where *n* is the number of nodes and ε is
+/// [`DEGREE_QUANTILES_EPS`]. The summary uses
+/// O((1/ε) log(ε*n*)) memory, independently of the distribution, and usually
+/// much less, as outdegrees contain many duplicates.
+///
+/// For example, `degree_quantiles(&graph, &[0.5, 0.9, 0.99])` returns
+/// estimates of the median, of the 90th percentile, and of the 99th
+/// percentile. If the graph has no nodes, all quantiles are zero.
+///
+/// # Panics
+///
+/// Panics if some φ is not in the interval [0 . . 1].
+pub fn degree_quantiles(graph: impl SequentialGraph, quantiles: &[f64]) -> Vec<u64> {
+    for &phi in quantiles {
+        assert!((0.0..=1.0).contains(&phi), "Invalid quantile {}", phi);
+    }
+    let mut summary = GkSummary::new(DEGREE_QUANTILES_EPS);
+    for_!((_node, succ) in graph.iter() {
+        summary.insert(succ.into_iter().count() as u64);
+    });
+    quantiles.iter().map(|&phi| summary.query(phi)).collect()
+}
+
+/// A Greenwald–Khanna summary.
+///
+/// Each tuple (*v*, *g*, Δ) records a value *v* whose minimum possible rank is
+/// the sum of the *g*'s up to the tuple, and whose maximum possible rank
+/// exceeds the minimum by at most Δ. The invariant *g* + Δ ≤ ⌊2ε*n*⌋ makes
+/// it possible to answer queries within ε*n* ranks.
+struct GkSummary {
+    eps: f64,
+    n: u64,
+    /// Tuples (value, g, Δ), sorted by value.
+    tuples: Vec<(u64, u64, u64)>,
+    /// Insertions before the next compression.
+    until_compress: u64,
+}
+
+impl GkSummary {
+    fn new(eps: f64) -> Self {
+        Self {
+            eps,
+            n: 0,
+            tuples: vec![],
+            until_compress: Self::period(eps),
+        }
+    }
+
+    fn period(eps: f64) -> u64 {
+        ((1.0 / (2.0 * eps)).floor() as u64).max(1)
+    }
+
+    fn threshold(&self) -> u64 {
+        (2.0 * self.eps * self.n as f64).floor() as u64
+    }
+
+    fn insert(&mut self, value: u64) {
+        let pos = self.tuples.partition_point(|&(v, _, _)| v <= value);
+        // the new minimum and the new maximum have exact ranks
+        let delta = if pos == 0 || pos == self.tuples.len() {
+            0
+        } else {
+            self.threshold().saturating_sub(1)
+        };
+        self.tuples.insert(pos, (value, 1, delta));
+        self.n += 1;
+
+        self.until_compress -= 1;
+        if self.until_compress == 0 {
+            self.compress();
+            self.until_compress = Self::period(self.eps);
+        }
+    }
+
+    /// Merges adjacent tuples, preserving the invariant; the first and the
+    /// last tuple are never merged away.
+    fn compress(&mut self) {
+        let threshold = self.threshold();
+        let mut i = self.tuples.len().saturating_sub(2);
+        while i >= 1 {
+            let (_, g, _) = self.tuples[i];
+            let (_, next_g, next_delta) = self.tuples[i + 1];
+            if g + next_g + next_delta <= threshold {
+                self.tuples[i + 1].1 += g;
+                self.tuples.remove(i);
+            }
+            i -= 1;
+        }
+    }
+
+    fn query(&self, phi: f64) -> u64 {
+        if self.tuples.is_empty() {
+            return 0;
+        }
+        let rank = ((phi * self.n as f64).ceil() as u64).max(1);
+        let slack = self.eps * self.n as f64;
+        let mut rmin = 0;
+        for &(value, g, delta) in &self.tuples {
+            rmin += g;
+            let rmax = rmin + delta;
+            if rank as f64 - rmin as f64 <= slack && rmax as f64 - rank as f64 <= slack {
+                return value;
+            }
+        }
+        self.tuples.last().unwrap().0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::random::ErdosRenyi;
+    use crate::traits::SequentialLabeling;
+
+    #[test]
+    fn test_gk_summary() {
+        let n = 100_000_u64;
+        let eps = 0.01;
+        let mut summary = GkSummary::new(eps);
+        // A permutation of 0..n
+        for i in 0..n {
+            summary.insert(i * 7919 % n);
+        }
+        assert!(summary.tuples.len() < n as usize / 20);
+        for phi in [0.0, 0.1, 0.5, 0.9, 0.99, 1.0] {
+            let exact = ((phi * n as f64).ceil() as u64).max(1) - 1;
+            let estimate = summary.query(phi);
+            assert!(
+                estimate.abs_diff(exact) as f64 <= eps * n as f64,
+                "φ = {}: estimate {}, exact {}",
+                phi,
+                estimate,
+                exact
+            );
+        }
+    }
+
+    #[test]
+    fn test_degree_quantiles() {
+        let g = ErdosRenyi::new(10_000, 0.005, 0);
+        let mut degrees = vec![];
+        for_!((_node, succ) in g.iter() {
+            degrees.push(succ.len() as u64);
+        });
+        degrees.sort();
+        let n = degrees.len();
+        let slack = (DEGREE_QUANTILES_EPS * n as f64).ceil() as usize;
+
+        let estimates = degree_quantiles(&g, &[0.5, 0.9, 0.99]);
+        for (phi, estimate) in [0.5_f64, 0.9, 0.99].into_iter().zip(estimates) {
+            let rank = (phi * n as f64).ceil() as usize - 1;
+            assert!(
+                (degrees[rank - slack]..=degrees[rank + slack]).contains(&estimate),
+                "φ = {}: estimate {}, exact {}",
+                phi,
+                estimate,
+                degrees[rank]
+            );
+        }
+
+        assert_eq!(
+            degree_quantiles(ErdosRenyi::new(0, 0.5, 0), &[0.5]),
+            vec![0]
+        );
+    }
+}
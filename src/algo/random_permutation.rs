@@ -0,0 +1,49 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+/// Returns a uniformly random permutation of the nodes of a graph with
+/// `num_nodes` nodes.
+///
+/// The identity is shuffled in place by a Fisher–Yates shuffle driven by a
+/// [`SmallRng`] seeded with `seed`, so the result is always a permutation, and
+/// it depends only on `num_nodes` and `seed`. Relabeling a graph with such a
+/// permutation (e.g., with the `--permutation` option of `webgraph
+/// recompress`) destroys locality, which is useful to measure the gains of
+/// orderings such as [LLP](crate::algo::llp).
+pub fn random_permutation(num_nodes: usize, seed: u64) -> Box<[usize]> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut perm = (0..num_nodes).collect::<Box<[usize]>>();
+    perm.shuffle(&mut rng);
+    perm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_permutation() {
+        let n = 1000;
+        let perm = random_permutation(n, 0);
+        assert_eq!(perm.len(), n);
+        let mut seen = vec![false; n];
+        for &x in perm.iter() {
+            assert!(!seen[x], "{} appears twice", x);
+            seen[x] = true;
+        }
+        assert_ne!(&*perm, &(0..n).collect::<Vec<_>>()[..]);
+
+        assert_eq!(perm, random_permutation(n, 0));
+        assert_ne!(perm, random_permutation(n, 1));
+
+        assert!(random_permutation(0, 0).is_empty());
+        assert_eq!(&*random_permutation(1, 0), &[0]);
+    }
+}
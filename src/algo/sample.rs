@@ -0,0 +1,79 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::SequentialGraph;
+use lender::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+/// Samples uniformly at random `k` arcs of a graph.
+///
+/// The sample is computed with a single sequential scan of the graph using
+/// reservoir sampling, so it uses O(`k`) memory. The result contains exactly
+/// min(`k`, _m_) arcs, where _m_ is the number of arcs of the graph, in no
+/// particular order, and it depends only on the graph and on `seed`.
+pub fn sample_arcs(graph: impl SequentialGraph, k: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = SmallRng::seed_from_u64(seed);
+    let mut reservoir = Vec::with_capacity(k);
+    let mut seen = 0_u64;
+    for_!((node, succ) in graph.iter() {
+        for s in succ {
+            if reservoir.len() < k {
+                reservoir.push((node, s));
+            } else {
+                // the arc replaces a sampled arc with probability k / (seen + 1)
+                let i = rng.gen_range(0..=seen);
+                if i < k as u64 {
+                    reservoir[i as usize] = (node, s);
+                }
+            }
+            seen += 1;
+        }
+    });
+    reservoir
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_sample_arcs() {
+        let arcs = [(0, 1), (0, 2), (1, 2), (2, 0), (2, 3), (3, 3)];
+        let g = Left(VecGraph::from_arcs(arcs));
+
+        assert!(sample_arcs(&g, 0, 0).is_empty());
+        let mut all = sample_arcs(&g, 10, 0);
+        all.sort();
+        assert_eq!(all, arcs);
+        assert_eq!(sample_arcs(&g, 3, 42), sample_arcs(&g, 3, 42));
+
+        // Each arc should be sampled with probability k / m
+        let (k, trials) = (2, 30_000);
+        let mut counts = HashMap::new();
+        for seed in 0..trials {
+            let sample = sample_arcs(&g, k, seed);
+            assert_eq!(sample.len(), k);
+            for arc in sample {
+                *counts.entry(arc).or_insert(0) += 1;
+            }
+        }
+        let expected = (k * trials as usize) as f64 / arcs.len() as f64;
+        for arc in arcs {
+            let count = counts[&arc] as f64;
+            assert!(
+                (count - expected).abs() / expected < 0.05,
+                "Arc {:?} sampled {} times, expected {}",
+                arc,
+                count,
+                expected
+            );
+        }
+    }
+}
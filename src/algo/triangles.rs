@@ -0,0 +1,108 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Triangle counting.
+//!
+//! [`count_triangles`] computes the number of triangles of an undirected
+//! graph, that is, of unordered triples of distinct nodes that are pairwise
+//! adjacent, and the number of triangles each node belongs to, from which, for
+//! example, clustering coefficients can be derived.
+//!
+//! The graph must be symmetric and loopless, and its successor lists must be
+//! sorted, as it happens, for example, for a
+//! [`VecGraph`](crate::graphs::vec_graph::VecGraph) built from the output of
+//! [`simplify`](crate::transform::simplify). Loops are anyway ignored, as they
+//! cannot be part of a triangle.
+
+use crate::traits::RandomAccessGraph;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts the triangles of a symmetric graph, returning the global count and
+/// the number of triangles each node belongs to.
+///
+/// The graph must be symmetric (i.e., it must represent an undirected graph)
+/// and its successor lists must be sorted; loops are ignored. On a directed
+/// graph, the result is the number of triangles of the undirected graph
+/// containing an edge {_u_, _v_}, with _u_ < _v_, if and only if (_u_, _v_) is
+/// an arc.
+///
+/// Each triangle _u_ < _v_ < _w_ is found exactly once by intersecting, for
+/// each arc (_u_, _v_) with _u_ < _v_, the successors of _u_ and _v_ larger
+/// than _v_. Nodes are processed in parallel using the current
+/// [`rayon`] thread pool.
+pub fn count_triangles(graph: impl RandomAccessGraph + Sync) -> (u64, Box<[u64]>) {
+    let num_nodes = graph.num_nodes();
+    let mut counts = Vec::with_capacity(num_nodes);
+    counts.extend((0..num_nodes).map(|_| AtomicU64::new(0)));
+
+    let total = (0..num_nodes)
+        .into_par_iter()
+        .map_init(Vec::new, |forward, u| {
+            // the successors of u larger than u
+            forward.clear();
+            forward.extend(graph.successors(u).into_iter().filter(|&v| v > u));
+            let mut triangles = 0;
+            for (i, &v) in forward.iter().enumerate() {
+                let mut common = forward[i + 1..].iter().peekable();
+                for w in graph.successors(v).into_iter().filter(|&w| w > v) {
+                    while common.next_if(|&&x| x < w).is_some() {}
+                    if common.next_if_eq(&&w).is_some() {
+                        counts[v].fetch_add(1, Ordering::Relaxed);
+                        counts[w].fetch_add(1, Ordering::Relaxed);
+                        triangles += 1;
+                    }
+                }
+            }
+            counts[u].fetch_add(triangles, Ordering::Relaxed);
+            triangles
+        })
+        .sum::<u64>();
+
+    (
+        total,
+        counts.into_iter().map(AtomicU64::into_inner).collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    fn symmetric(edges: &[(usize, usize)]) -> Left<VecGraph> {
+        Left(VecGraph::from_arcs(
+            edges.iter().flat_map(|&(u, v)| [(u, v), (v, u)]),
+        ))
+    }
+
+    #[test]
+    fn test_count_triangles() {
+        let (total, counts) = count_triangles(symmetric(&[(0, 1), (1, 2), (2, 0)]));
+        assert_eq!(total, 1);
+        assert_eq!(&*counts, &[1, 1, 1]);
+
+        // A 4-clique with a pendant node and a loop
+        let (total, counts) = count_triangles(symmetric(&[
+            (0, 1),
+            (0, 2),
+            (0, 3),
+            (1, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+            (4, 4),
+        ]));
+        assert_eq!(total, 4);
+        assert_eq!(&*counts, &[3, 3, 3, 3, 0]);
+
+        // A 4-cycle has no triangles
+        let (total, counts) = count_triangles(symmetric(&[(0, 1), (1, 2), (2, 3), (3, 0)]));
+        assert_eq!(total, 0);
+        assert_eq!(&*counts, &[0, 0, 0, 0]);
+    }
+}
@@ -48,6 +48,19 @@ struct CliArgs {
     /// Do not test speed, but check that the sequential and random-access successor lists are the same.
     #[arg(short = 'c', long)]
     check: bool,
+
+    /// In the sequential test, also time a scan that collects each successor list in a new vector.
+    #[arg(short = 'a', long)]
+    alloc: bool,
+
+    /// In the sequential test, also time a scan that does not sort successor lists, and the same scan sorting them.
+    #[arg(short = 'u', long)]
+    unsorted: bool,
+
+    /// In the sequential test, also time a scan that stores successors as u32 if the graph
+    /// has at most 2³² nodes, and as usize otherwise.
+    #[arg(long = "u32")]
+    narrow: bool,
 }
 
 pub fn cli(command: Command) -> Command {
@@ -114,7 +127,7 @@ fn bench_random(graph: impl RandomAccessGraph, samples: usize, repeats: usize, f
     }
 }
 
-fn bench_seq(graph: impl SequentialGraph, repeats: usize) {
+fn bench_seq(graph: impl SequentialGraph, repeats: usize, alloc: bool) {
     for _ in 0..repeats {
         let mut c: u64 = 0;
 
@@ -128,10 +141,85 @@ fn bench_seq(graph: impl SequentialGraph, repeats: usize) {
             (start.elapsed().as_secs_f64() / c as f64) * 1e9
         );
 
-        assert_eq!(c, graph.num_arcs_hint().unwrap());
+        assert_eq!(c, graph.num_arcs_exact().unwrap());
+
+        if alloc {
+            // The same scan, but allocating a vector for each node
+            let mut c: u64 = 0;
+            let start = std::time::Instant::now();
+            let mut iter = graph.iter();
+            while let Some((_, succ)) = iter.next() {
+                c += black_box(succ.into_iter().collect::<Vec<_>>()).len() as u64;
+            }
+            println!(
+                "Allocating:{:>20} ns/arc",
+                (start.elapsed().as_secs_f64() / c as f64) * 1e9
+            );
+
+            assert_eq!(c, graph.num_arcs_exact().unwrap());
+        }
+    }
+}
+
+fn bench_unsorted<F: SequentialDecoderFactory>(graph: &BVGraphSeq<F>, repeats: usize) {
+    for _ in 0..repeats {
+        // The same scan through the slice interface, sorting successor lists,
+        // as a baseline for the unsorted scan
+        let mut c: u64 = 0;
+        let start = std::time::Instant::now();
+        let mut iter = graph.iter();
+        for _ in 0..graph.num_nodes() {
+            c += black_box(iter.next_successors().unwrap()).len() as u64;
+        }
+        println!(
+            "Sorted:{:>20} ns/arc",
+            (start.elapsed().as_secs_f64() / c as f64) * 1e9
+        );
+
+        assert_eq!(c, graph.num_arcs_exact().unwrap());
+
+        let mut c: u64 = 0;
+        let start = std::time::Instant::now();
+        let mut iter = graph.iter();
+        for _ in 0..graph.num_nodes() {
+            c += black_box(iter.next_successors_unsorted().unwrap()).len() as u64;
+        }
+        println!(
+            "Unsorted:{:>20} ns/arc",
+            (start.elapsed().as_secs_f64() / c as f64) * 1e9
+        );
+
+        assert_eq!(c, graph.num_arcs_exact().unwrap());
     }
 }
 
+fn bench_u32<F: SequentialDecoderFactory>(graph: &BVGraphSeq<F>, repeats: usize) -> Result<()> {
+    for _ in 0..repeats {
+        // The type of successors is chosen at runtime from the number of nodes
+        let start = std::time::Instant::now();
+        let (width, c) = match graph.iter_narrowest()? {
+            NarrowestIter::U32(iter) => ("u32", scan(iter, graph.num_nodes())?),
+            NarrowestIter::Usize(iter) => ("usize", scan(iter, graph.num_nodes())?),
+        };
+        println!(
+            "{}:{:>20} ns/arc",
+            width,
+            (start.elapsed().as_secs_f64() / c as f64) * 1e9
+        );
+
+        assert_eq!(c, graph.num_arcs_exact().unwrap());
+    }
+    Ok(())
+}
+
+fn scan<D: Decode, N: NodeId>(mut iter: sequential::Iter<D, N>, num_nodes: usize) -> Result<u64> {
+    let mut c: u64 = 0;
+    for _ in 0..num_nodes {
+        c += black_box(iter.next_successors()?).len() as u64;
+    }
+    Ok(c)
+}
+
 fn bench_webgraph<E: Endianness, D: Dispatch>(args: CliArgs) -> Result<()>
 where
     for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
@@ -172,7 +260,7 @@ where
                 (start.elapsed().as_secs_f64() / c as f64) * 1e9
             );
 
-            assert_eq!(c, seq_graph.num_arcs_hint().unwrap());
+            assert_eq!(c, seq_graph.num_arcs_exact().unwrap());
         }
     } else {
         match (
@@ -206,26 +294,34 @@ where
                 );
             }
             (None, true) => {
-                bench_seq(
-                    BVGraphSeq::with_basename(&args.basename)
-                        .endianness::<E>()
-                        .dispatch::<Dynamic>()
-                        .mode::<Mmap>()
-                        .flags(MemoryFlags::TRANSPARENT_HUGE_PAGES | MemoryFlags::SEQUENTIAL)
-                        .load()?,
-                    args.repeats,
-                );
+                let seq_graph = BVGraphSeq::with_basename(&args.basename)
+                    .endianness::<E>()
+                    .dispatch::<Dynamic>()
+                    .mode::<Mmap>()
+                    .flags(MemoryFlags::TRANSPARENT_HUGE_PAGES | MemoryFlags::SEQUENTIAL)
+                    .load()?;
+                bench_seq(&seq_graph, args.repeats, args.alloc);
+                if args.unsorted {
+                    bench_unsorted(&seq_graph, args.repeats);
+                }
+                if args.narrow {
+                    bench_u32(&seq_graph, args.repeats)?;
+                }
             }
             (None, false) => {
-                bench_seq(
-                    BVGraphSeq::with_basename(&args.basename)
-                        .endianness::<E>()
-                        .dispatch::<Static>()
-                        .mode::<Mmap>()
-                        .flags(MemoryFlags::TRANSPARENT_HUGE_PAGES | MemoryFlags::SEQUENTIAL)
-                        .load()?,
-                    args.repeats,
-                );
+                let seq_graph = BVGraphSeq::with_basename(&args.basename)
+                    .endianness::<E>()
+                    .dispatch::<Static>()
+                    .mode::<Mmap>()
+                    .flags(MemoryFlags::TRANSPARENT_HUGE_PAGES | MemoryFlags::SEQUENTIAL)
+                    .load()?;
+                bench_seq(&seq_graph, args.repeats, args.alloc);
+                if args.unsorted {
+                    bench_unsorted(&seq_graph, args.repeats);
+                }
+                if args.narrow {
+                    bench_u32(&seq_graph, args.repeats)?;
+                }
             }
         }
     }
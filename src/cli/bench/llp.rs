@@ -0,0 +1,124 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::graphs::random::ErdosRenyi;
+use crate::prelude::*;
+use anyhow::Result;
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+use llp::preds::PredParams;
+use predicates::reflection::PredicateReflection;
+use predicates::Predicate;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+pub const COMMAND_NAME: &str = "llp";
+
+#[derive(Args, Debug)]
+#[command(about = "Compares the serial and parallel update loops of LLP on random graphs, to choose llp::LLP_SEQUENTIAL_THRESHOLD.", long_about = None)]
+struct CliArgs {
+    /// The base-2 logarithms of the numbers of nodes of the graphs.
+    #[arg(short, long, use_value_delimiter = true, value_delimiter = ',', default_values_t = vec![8, 10, 11, 12, 13, 14, 16])]
+    log_sizes: Vec<u32>,
+
+    /// The average degree of the graphs.
+    #[arg(short, long, default_value_t = 16.0)]
+    degree: f64,
+
+    /// The number of threads of the parallel update loop.
+    #[arg(short, long, default_value_t = 2)]
+    threads: usize,
+
+    /// The number of updates.
+    #[arg(short, long, default_value_t = 10)]
+    updates: usize,
+
+    /// The number of repeats (the minimum time is reported).
+    #[arg(short = 'R', long, default_value_t = 5)]
+    repeats: usize,
+}
+
+/// A predicate stopping after a given number of updates and recording the
+/// time spent in the updates.
+struct TimeUpdates {
+    updates: usize,
+    elapsed: Arc<Mutex<Duration>>,
+}
+
+impl std::fmt::Display for TimeUpdates {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(time {} updates)", self.updates)
+    }
+}
+
+impl PredicateReflection for TimeUpdates {}
+impl Predicate<PredParams> for TimeUpdates {
+    fn eval(&self, pred_params: &PredParams) -> bool {
+        *self.elapsed.lock().unwrap() = pred_params.elapsed;
+        pred_params.update + 1 >= self.updates
+    }
+}
+
+pub fn cli(command: Command) -> Command {
+    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+}
+
+pub fn main(submatches: &ArgMatches) -> Result<()> {
+    let args = CliArgs::from_arg_matches(submatches)?;
+
+    println!("nodes\tarcs\tserial (ms)\tparallel (ms)\tratio");
+    for &log_size in &args.log_sizes {
+        let n = 1_usize << log_size;
+        // a symmetric Erdős–Rényi graph
+        let mut arcs = vec![];
+        lender::for_!((x, succ) in ErdosRenyi::new(n, args.degree / 2.0 / n as f64, 0).iter() {
+            for y in succ {
+                arcs.push((x, y));
+                arcs.push((y, x));
+            }
+        });
+        let mut graph = VecGraph::empty(n);
+        graph.add_arc_list(arcs);
+        let graph = Left(graph);
+        let deg_cumul = build_dcf(&graph)?;
+
+        // the only difference between the two runs is the update loop, and
+        // we time just the updates (i.e., not the final log-gap cost and the
+        // combination of labels)
+        let time = |sequential_threshold| -> Result<Duration> {
+            let mut min = Duration::MAX;
+            for _ in 0..args.repeats {
+                let elapsed = Arc::new(Mutex::new(Duration::ZERO));
+                llp::layered_label_propagation_with_params(
+                    &graph,
+                    &deg_cumul,
+                    TimeUpdates {
+                        updates: args.updates,
+                        elapsed: elapsed.clone(),
+                    },
+                    &llp::LlpParams {
+                        gammas: vec![0.0],
+                        num_threads: Some(args.threads),
+                        sequential_threshold,
+                        ..Default::default()
+                    },
+                )?;
+                min = min.min(*elapsed.lock().unwrap());
+            }
+            Ok(min)
+        };
+        let serial = time(usize::MAX)?;
+        let parallel = time(0)?;
+        println!(
+            "{}\t{}\t{:.3}\t{:.3}\t{:.2}",
+            n,
+            graph.num_arcs(),
+            serial.as_secs_f64() * 1E3,
+            parallel.as_secs_f64() * 1E3,
+            parallel.as_secs_f64() / serial.as_secs_f64()
+        );
+    }
+    Ok(())
+}
@@ -9,6 +9,7 @@ use clap::{ArgMatches, Command};
 
 pub mod bf_visit;
 pub mod bvgraph;
+pub mod llp;
 
 pub const COMMAND_NAME: &str = "bench";
 
@@ -20,6 +21,7 @@ pub fn cli(command: Command) -> Command {
         .allow_external_subcommands(true);
     let sub_command = bvgraph::cli(sub_command);
     let sub_command = bf_visit::cli(sub_command);
+    let sub_command = llp::cli(sub_command);
     command.subcommand(sub_command)
 }
 
@@ -27,6 +29,7 @@ pub fn main(submatches: &ArgMatches) -> Result<()> {
     match submatches.subcommand() {
         Some((bvgraph::COMMAND_NAME, sub_m)) => bvgraph::main(sub_m),
         Some((bf_visit::COMMAND_NAME, sub_m)) => bf_visit::main(sub_m),
+        Some((llp::COMMAND_NAME, sub_m)) => llp::main(sub_m),
         Some((command_name, _)) => {
             eprintln!("Unknown command: {:?}", command_name);
             std::process::exit(1);
@@ -63,10 +63,7 @@ where
         .load()?;
 
     // create the permutation
-    let mut perm = vec![0; graph.num_nodes()];
-    for (i, node_id) in crate::algo::BfsOrder::new(&graph).enumerate() {
-        perm[node_id] = i;
-    }
+    let perm = crate::algo::bfs_permutation(&graph).into_vec();
 
     if args.epserde {
         perm.store(&args.perm)
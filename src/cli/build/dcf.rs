@@ -61,8 +61,26 @@ where
         )
     })?;
     let map = java_properties::read(BufReader::new(f))?;
-    let num_nodes = map.get("nodes").unwrap().parse::<usize>()?;
-    let num_arcs = map.get("arcs").unwrap().parse::<usize>()?;
+    let num_nodes = map
+        .get("nodes")
+        .with_context(|| format!("Missing 'nodes' property in {}", properties_path.display()))?
+        .parse::<usize>()
+        .with_context(|| {
+            format!(
+                "Cannot parse 'nodes' property in {}",
+                properties_path.display()
+            )
+        })?;
+    let num_arcs = map
+        .get("arcs")
+        .with_context(|| format!("Missing 'arcs' property in {}", properties_path.display()))?
+        .parse::<usize>()
+        .with_context(|| {
+            format!(
+                "Cannot parse 'arcs' property in {}",
+                properties_path.display()
+            )
+        })?;
 
     // TODO : not +1
     let mut efb = EliasFanoBuilder::new(num_nodes + 1, num_arcs + 1);
@@ -125,7 +125,16 @@ where
         )
     })?;
     let map = java_properties::read(BufReader::new(f))?;
-    let num_nodes = map.get("nodes").unwrap().parse::<usize>()?;
+    let num_nodes = map
+        .get("nodes")
+        .with_context(|| format!("Missing 'nodes' property in {}", properties_path.display()))?
+        .parse::<usize>()
+        .with_context(|| {
+            format!(
+                "Cannot parse 'nodes' property in {}",
+                properties_path.display()
+            )
+        })?;
 
     let graph_path = basename.with_extension(GRAPH_EXTENSION);
     let mut file = File::open(&graph_path)
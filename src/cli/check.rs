@@ -0,0 +1,200 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::graphs::bvgraph::sequential::Iter;
+use crate::graphs::bvgraph::{
+    get_endianness, parse_properties, Code, CodeRead, CompFlags, Decode, GRAPH_EXTENSION,
+    PROPERTIES_EXTENSION,
+};
+use crate::utils::MmapHelper;
+use anyhow::{bail, ensure, Context, Result};
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+use dsi_bitstream::prelude::*;
+use dsi_progress_logger::prelude::*;
+use mmap_rs::MmapFlags;
+use std::path::{Path, PathBuf};
+
+pub const COMMAND_NAME: &str = "check";
+
+#[derive(Args, Debug)]
+#[command(about = "Decodes the whole bitstream of a graph, reporting the first node that cannot be decoded correctly.", long_about = None)]
+struct CliArgs {
+    /// The basename of the graph.
+    basename: PathBuf,
+}
+
+pub fn cli(command: Command) -> Command {
+    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+}
+
+pub fn main(submatches: &ArgMatches) -> Result<()> {
+    let args = CliArgs::from_arg_matches(submatches)?;
+
+    let num_arcs = match get_endianness(&args.basename)?.as_str() {
+        #[cfg(any(
+            feature = "be_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        BE::NAME => check::<BE>(&args.basename)?,
+        #[cfg(any(
+            feature = "le_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        LE::NAME => check::<LE>(&args.basename)?,
+        e => panic!("Unknown endianness: {}", e),
+    };
+    println!("{}: OK ({} arcs)", args.basename.display(), num_arcs);
+    Ok(())
+}
+
+/// A decoder that, differently from the decoders used when loading a graph,
+/// does not panic when a code cannot be read (e.g., past the end of the data).
+///
+/// The first error is stored, and from then on all values are zero, which
+/// makes the decoding of the current node end quickly; the caller must check
+/// [`error`](StrictDecoder::error) after each node.
+struct StrictDecoder<E: Endianness, CR: CodeRead<E>> {
+    code_reader: CR,
+    comp_flags: CompFlags,
+    error: Option<CR::Error>,
+    _marker: core::marker::PhantomData<E>,
+}
+
+impl<E: Endianness, CR: CodeRead<E>> StrictDecoder<E, CR> {
+    fn new(code_reader: CR, comp_flags: CompFlags) -> Self {
+        Self {
+            code_reader,
+            comp_flags,
+            error: None,
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    #[inline(always)]
+    fn read(&mut self, code: Code) -> u64 {
+        if self.error.is_some() {
+            return 0;
+        }
+        let result = match code {
+            Code::Unary => self.code_reader.read_unary(),
+            Code::Gamma | Code::Zeta { k: 1 } => self.code_reader.read_gamma(),
+            Code::Delta => self.code_reader.read_delta(),
+            Code::Zeta { k: 3 } => self.code_reader.read_zeta3(),
+            Code::Zeta { k } => self.code_reader.read_zeta(k as u64),
+        };
+        result.unwrap_or_else(|e| {
+            self.error = Some(e);
+            0
+        })
+    }
+}
+
+impl<E: Endianness, CR: CodeRead<E>> Decode for StrictDecoder<E, CR> {
+    fn read_outdegree(&mut self) -> u64 {
+        self.read(self.comp_flags.outdegrees)
+    }
+    fn read_reference_offset(&mut self) -> u64 {
+        self.read(self.comp_flags.references)
+    }
+    fn read_block_count(&mut self) -> u64 {
+        self.read(self.comp_flags.blocks)
+    }
+    fn read_block(&mut self) -> u64 {
+        self.read(self.comp_flags.blocks)
+    }
+    fn read_interval_count(&mut self) -> u64 {
+        self.read(self.comp_flags.intervals)
+    }
+    fn read_interval_start(&mut self) -> u64 {
+        self.read(self.comp_flags.intervals)
+    }
+    fn read_interval_len(&mut self) -> u64 {
+        self.read(self.comp_flags.intervals)
+    }
+    fn read_first_residual(&mut self) -> u64 {
+        self.read(self.comp_flags.residuals)
+    }
+    fn read_residual(&mut self) -> u64 {
+        self.read(self.comp_flags.residuals)
+    }
+}
+
+/// Decodes sequentially the graph with given basename, returning the number
+/// of arcs, or an error describing the first node that could not be decoded.
+///
+/// The check detects truncated or corrupted streams, successors out of range,
+/// successor lists that are not strictly increasing (e.g., because of
+/// overlapping intervals), reference offsets exceeding the compression
+/// window, and a number of arcs different from that in the `.properties` file.
+pub fn check<E: Endianness + 'static>(basename: impl AsRef<Path>) -> Result<u64>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32], false>>: CodeRead<E>,
+{
+    let basename = basename.as_ref();
+    let (num_nodes, expected_arcs, comp_flags) =
+        parse_properties::<E>(basename.with_extension(PROPERTIES_EXTENSION))?;
+    let graph_path = basename.with_extension(GRAPH_EXTENSION);
+    let data = MmapHelper::<u32>::mmap(&graph_path, MmapFlags::SEQUENTIAL)
+        .with_context(|| format!("Could not map {}", graph_path.display()))?;
+    // A strict reader returns an error when reading past the end of the data,
+    // rather than an infinite sequence of zeroes, on which a unary code would
+    // never end
+    let mut iter = Iter::new(
+        StrictDecoder::new(
+            BufBitReader::<E, _>::new(MemWordReader::new_strict(data.as_ref())),
+            comp_flags,
+        ),
+        num_nodes,
+        comp_flags.compression_window,
+        comp_flags.min_interval_length,
+    );
+
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true)
+        .item_name("node")
+        .expected_updates(Some(num_nodes));
+    pl.start("Checking graph...");
+
+    let mut num_arcs = 0_u64;
+    for node in 0..num_nodes {
+        let result = iter.next_successors().and_then(|succ| {
+            for (i, &s) in succ.iter().enumerate() {
+                ensure!(
+                    s < num_nodes,
+                    "successor {} is out of range (the graph has {} nodes)",
+                    s,
+                    num_nodes
+                );
+                ensure!(
+                    i == 0 || succ[i - 1] < s,
+                    "successor {} follows successor {}: the successor list is not strictly increasing",
+                    s,
+                    succ[i - 1]
+                );
+            }
+            Ok(succ.len())
+        });
+        // A read error makes the decoded values meaningless, so it takes
+        // precedence over the errors detected on them
+        if let Some(e) = iter.decoder.error.take() {
+            bail!("Node {}: truncated or corrupted stream ({})", node, e);
+        }
+        match result {
+            Ok(outdegree) => num_arcs += outdegree as u64,
+            Err(e) => bail!("Node {}: {:#}", node, e),
+        }
+        pl.light_update();
+    }
+    pl.done();
+
+    ensure!(
+        num_arcs == expected_arcs,
+        "The graph has {} arcs, but the properties file says {}",
+        num_arcs,
+        expected_arcs
+    );
+    Ok(num_arcs)
+}
@@ -44,7 +44,16 @@ pub fn main(submatches: &ArgMatches) -> Result<()> {
         )
     })?;
     let map = java_properties::read(BufReader::new(f))?;
-    let num_nodes = map.get("nodes").unwrap().parse::<usize>()?;
+    let num_nodes = map
+        .get("nodes")
+        .with_context(|| format!("Missing 'nodes' property in {}", properties_path.display()))?
+        .parse::<usize>()
+        .with_context(|| {
+            format!(
+                "Cannot parse 'nodes' property in {}",
+                properties_path.display()
+            )
+        })?;
 
     // Create the offsets file
     let of_file_path = args.basename.with_extension(OFFSETS_EXTENSION);
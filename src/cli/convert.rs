@@ -5,7 +5,7 @@
 */
 
 use crate::prelude::*;
-use anyhow::{Context, Result};
+use anyhow::{bail, Context, Result};
 use clap::{ArgMatches, Args, Command, FromArgMatches};
 use dsi_bitstream::prelude::*;
 use dsi_progress_logger::prelude::*;
@@ -26,7 +26,9 @@ struct CliArgs {
 }
 
 pub fn cli(command: Command) -> Command {
-    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+    command.subcommand(CliArgs::augment_args(
+        Command::new(COMMAND_NAME).visible_alias("convert-endianness"),
+    ))
 }
 
 macro_rules! impl_convert {
@@ -42,21 +44,22 @@ macro_rules! impl_convert {
         let mut pl = ProgressLogger::default();
         pl.display_memory(true)
             .item_name("node")
-            .expected_updates(Some(num_arcs as usize));
+            .expected_updates(Some(num_nodes));
 
         let seq_graph = BVGraphSeq::with_basename(&$args.src_basename)
             .endianness::<$src>()
             .load()
             .with_context(|| format!("Could not load graph {}", $args.src_basename.display()))?;
         // build the encoder with the opposite endianness
+        let target_properties_path = $args.dst_basename.with_extension(PROPERTIES_EXTENSION);
         std::fs::write(
-            &properties_path,
+            &target_properties_path,
             comp_flags.to_properties::<$dst>(num_nodes, num_arcs)?,
         )
         .with_context(|| {
             format!(
                 "Could not write properties to {}",
-                properties_path.display()
+                target_properties_path.display()
             )
         })?;
         let target_graph_path = $args.dst_basename.with_extension(GRAPH_EXTENSION);
@@ -64,20 +67,59 @@ macro_rules! impl_convert {
             File::create(&target_graph_path)
                 .with_context(|| format!("Could not create {}", target_graph_path.display()))?,
         )));
-        let encoder = <DynCodesEncoder<$dst, _>>::new(writer, &comp_flags);
-        // build the iterator that will read the graph and write it to the encoder
+        let mut encoder = <DynCodesEncoder<$dst, _>>::new(writer, &comp_flags);
+        // codes have the same length in both endiannesses, so the offsets
+        // are the same as those of the source graph; as everywhere else, they
+        // are written in big-endian order, independently of the endianness of
+        // the graph
+        let target_offsets_path = $args.dst_basename.with_extension(OFFSETS_EXTENSION);
+        let mut offsets_writer =
+            <BufBitWriter<BE, _>>::new(<WordAdapter<usize, _>>::new(BufWriter::new(
+                File::create(&target_offsets_path).with_context(|| {
+                    format!("Could not create {}", target_offsets_path.display())
+                })?,
+            )));
+        offsets_writer
+            .write_gamma(0)
+            .context("Could not write initial delta")?;
 
         pl.start("Inverting endianness...");
 
+        // build the iterator that will read the graph and write it to the encoder
         let mut iter = seq_graph
             .offset_deg_iter()
-            .map_decoder(move |decoder| Converter { decoder, encoder });
-        // consume the graph iterator reading all codes, but do nothing with them
+            .map_decoder(|decoder| Converter {
+                decoder,
+                encoder: &mut encoder,
+            });
+        let mut offset = 0;
         for _ in 0..num_nodes {
             iter.next_degree()?;
+            let new_offset = iter.get_pos();
+            offsets_writer
+                .write_gamma(new_offset - offset)
+                .context("Could not write delta")?;
+            offset = new_offset;
             pl.light_update();
         }
+        drop(iter);
         pl.done();
+
+        encoder.flush().context("Could not flush the graph")?;
+        BitWrite::flush(&mut offsets_writer).context("Could not flush the offsets")?;
+
+        // Elias-Fano offsets do not depend on the endianness of the graph
+        let ef_path = $args.src_basename.with_extension(EF_EXTENSION);
+        if ef_path.exists() {
+            let target_ef_path = $args.dst_basename.with_extension(EF_EXTENSION);
+            std::fs::copy(&ef_path, &target_ef_path).with_context(|| {
+                format!(
+                    "Could not copy {} to {}",
+                    ef_path.display(),
+                    target_ef_path.display()
+                )
+            })?;
+        }
     };
 }
 
@@ -99,7 +141,7 @@ pub fn main(submatches: &ArgMatches) -> Result<()> {
         LE::NAME => {
             impl_convert!(args, LE, BE);
         }
-        e => panic!("Unknown endianness: {}", e),
+        e => bail!("Unknown endianness: {}", e),
     };
 
     Ok(())
@@ -111,6 +153,20 @@ pub struct Converter<D: Decode, E: Encode> {
     encoder: E,
 }
 
+/// Positions are those of the decoder; as codes are just copied, they are
+/// also the positions of the encoder.
+impl<D: Decode + BitSeek, E: Encode> BitSeek for Converter<D, E> {
+    type Error = <D as BitSeek>::Error;
+
+    fn bit_pos(&mut self) -> Result<u64, Self::Error> {
+        self.decoder.bit_pos()
+    }
+
+    fn set_bit_pos(&mut self, bit_pos: u64) -> Result<(), Self::Error> {
+        self.decoder.set_bit_pos(bit_pos)
+    }
+}
+
 impl<D: Decode, E: Encode> Decode for Converter<D, E> {
     // TODO: implement correctly start_node/end_node
     #[inline(always)]
@@ -168,3 +224,58 @@ impl<D: Decode, E: Encode> Decode for Converter<D, E> {
         res
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::graphs_equal;
+    use std::path::Path;
+
+    fn convert(src: &Path, dst: &Path) -> Result<()> {
+        let matches = cli(Command::new("webgraph")).try_get_matches_from([
+            "webgraph",
+            "convert-endianness",
+            src.to_str().unwrap(),
+            dst.to_str().unwrap(),
+        ])?;
+        main(matches.subcommand_matches(COMMAND_NAME).unwrap())
+    }
+
+    #[test]
+    fn test_convert() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let src = Path::new("tests/data/cnr-2000");
+        let le = tmp_dir.path().join("le");
+        let be = tmp_dir.path().join("be");
+        let src_graph = BVGraphSeq::with_basename(src).endianness::<BE>().load()?;
+
+        convert(src, &le)?;
+        assert_eq!(get_endianness(&le)?, LE::NAME);
+        let le_graph = BVGraphSeq::with_basename(&le).endianness::<LE>().load()?;
+        assert!(graphs_equal(&src_graph, &le_graph));
+
+        // The offsets are those of the source graph, in big-endian order
+        let factory = MemoryFactory::<BE, _>::new_mem(le.with_extension(OFFSETS_EXTENSION))?;
+        let mut reader = factory.new_reader();
+        let mut offset = reader.read_gamma()?;
+        for (expected, _) in src_graph.offset_deg_iter() {
+            assert_eq!(offset, expected);
+            offset += reader.read_gamma()?;
+        }
+
+        // Random access works using the copied Elias-Fano offsets
+        let le_graph = BVGraph::with_basename(&le).endianness::<LE>().load()?;
+        assert!(graphs_equal(&src_graph, &le_graph));
+
+        convert(&le, &be)?;
+        assert_eq!(get_endianness(&be)?, BE::NAME);
+        let be_graph = BVGraphSeq::with_basename(&be).endianness::<BE>().load()?;
+        assert!(graphs_equal(&src_graph, &be_graph));
+        // The offsets do not depend on the endianness of the graph
+        assert_eq!(
+            std::fs::read(le.with_extension(OFFSETS_EXTENSION))?,
+            std::fs::read(be.with_extension(OFFSETS_EXTENSION))?
+        );
+        Ok(())
+    }
+}
@@ -63,6 +63,18 @@ struct CliArgs {
     /// improvement used to stop the computation (-Inf to disable).
     improv_threshold: f64,
 
+    #[arg(long)]
+    /// Stop the updates for a given ɣ when an update increases the log-gap
+    /// cost, reverting it (requires an additional snapshot of the labels and
+    /// computing the log-gap cost after each update).
+    stop_on_negative_gain: bool,
+
+    #[arg(long)]
+    /// Make the result independent of the number of threads and of the
+    /// granularity (updates are split in fixed chunks whose changes are
+    /// applied at the end of the update).
+    deterministic: bool,
+
     #[clap(flatten)]
     num_cpus: NumCpusArg,
 
@@ -170,15 +182,22 @@ where
     }
 
     // compute the LLP
-    let labels = llp::layered_label_propagation(
+    let labels = llp::layered_label_propagation_with_params(
         &graph,
         &*deg_cumul,
-        gammas,
-        Some(args.num_cpus.num_cpus),
-        args.chunk_size,
-        args.granularity,
-        args.seed,
         predicate,
+        &llp::LlpParams {
+            gammas,
+            num_threads: Some(args.num_cpus.num_cpus),
+            chunk_size: args.chunk_size,
+            granularity: args.granularity,
+            seed: args.seed,
+            stop_on_negative_gain: args.stop_on_negative_gain,
+            initial_labels: None,
+            deterministic: args.deterministic,
+            work_dir: None,
+            sequential_threshold: llp::LLP_SEQUENTIAL_THRESHOLD,
+        },
     )
     .context("Could not compute the LLP")?;
 
@@ -13,6 +13,7 @@ pub mod ascii_convert;
 pub mod bench;
 pub mod bfs;
 pub mod build;
+pub mod check;
 pub mod check_ef;
 pub mod convert;
 pub mod from_csv;
@@ -22,7 +23,10 @@ pub mod optimize_codes;
 pub mod pad;
 pub mod rand_perm;
 pub mod recompress;
+pub mod sample;
 pub mod simplify;
+pub mod stats;
+pub mod subgraph;
 pub mod to_csv;
 pub mod transpose;
 pub mod utils;
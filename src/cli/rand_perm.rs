@@ -10,7 +10,6 @@ use anyhow::{Context, Result};
 use clap::{ArgMatches, Args, Command, FromArgMatches};
 use dsi_bitstream::prelude::*;
 use epserde::ser::Serialize;
-use rand::prelude::SliceRandom;
 use std::io::prelude::*;
 use std::path::PathBuf;
 
@@ -27,6 +26,10 @@ struct CliArgs {
     #[arg(short = 'e', long)]
     /// Load the permutation from ε-serde format.
     epserde: bool,
+
+    #[arg(short, long)]
+    /// The seed of the pseudorandom number generator (random if not given).
+    seed: Option<u64>,
 }
 
 pub fn cli(command: Command) -> Command {
@@ -60,9 +63,8 @@ where
         .load()
         .with_context(|| format!("Could not read graph from {}", args.source.display()))?;
 
-    let mut rng = rand::thread_rng();
-    let mut perm = (0..graph.num_nodes()).collect::<Vec<_>>();
-    perm.shuffle(&mut rng);
+    let seed = args.seed.unwrap_or_else(rand::random);
+    let perm = crate::algo::random_permutation(graph.num_nodes(), seed).into_vec();
 
     if args.epserde {
         perm.store(&args.perm)
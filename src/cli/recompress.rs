@@ -17,7 +17,7 @@ use tempfile::Builder;
 pub const COMMAND_NAME: &str = "recompress";
 
 #[derive(Args, Debug)]
-#[command(about = "Recompress a BVGraph", long_about = None)]
+#[command(about = "Recompress a BVGraph, possibly with different compression parameters; unspecified parameters are those of the source graph.", long_about = None)]
 struct CliArgs {
     /// The basename of the graph.
     basename: PathBuf,
@@ -31,7 +31,70 @@ struct CliArgs {
     pa: PermutationArgs,
 
     #[clap(flatten)]
-    ca: CompressArgs,
+    ca: CompressOverrides,
+}
+
+/// Compression parameters overriding those of the source graph.
+#[derive(Args, Debug)]
+struct CompressOverrides {
+    /// The endianess of the graph to write (default: that of the source graph)
+    #[clap(short = 'E', long)]
+    endianess: Option<String>,
+    /// The compression window (default: that of the source graph)
+    #[clap(short = 'w', long)]
+    compression_window: Option<usize>,
+    /// The minimum interval length, or zero to disable intervals (default:
+    /// that of the source graph)
+    #[clap(short = 'l', long)]
+    min_interval_length: Option<usize>,
+    /// The maximum recursion depth for references, or -1 for infinite
+    /// recursion depth (default: that of the source graph)
+    #[clap(short = 'c', long)]
+    max_ref_count: Option<isize>,
+    /// The code to use for the outdegrees (default: that of the source graph)
+    #[clap(long, value_enum)]
+    outdegrees: Option<PrivCode>,
+    /// The code to use for the reference offsets (default: that of the source
+    /// graph)
+    #[clap(long, value_enum)]
+    references: Option<PrivCode>,
+    /// The code to use for the blocks (default: that of the source graph)
+    #[clap(long, value_enum)]
+    blocks: Option<PrivCode>,
+    /// The code to use for the intervals (default: that of the source graph)
+    #[clap(long, value_enum)]
+    intervals: Option<PrivCode>,
+    /// The code to use for the residuals (default: that of the source graph)
+    #[clap(long, value_enum)]
+    residuals: Option<PrivCode>,
+}
+
+impl CompressOverrides {
+    /// Returns the compression flags of the source graph with the overrides
+    /// applied.
+    fn apply(&self, mut cf: CompFlags) -> CompFlags {
+        if let Some(compression_window) = self.compression_window {
+            cf.compression_window = compression_window;
+        }
+        if let Some(min_interval_length) = self.min_interval_length {
+            cf.min_interval_length = min_interval_length;
+        }
+        if let Some(max_ref_count) = self.max_ref_count {
+            cf.max_ref_count = match max_ref_count {
+                -1 => usize::MAX,
+                _ => max_ref_count as usize,
+            };
+        }
+        macro_rules! apply_code {
+            ($($field:ident),*) => {$(
+                if let Some(code) = self.$field {
+                    cf.$field = code.into();
+                }
+            )*};
+        }
+        apply_code!(outdegrees, references, blocks, intervals, residuals);
+        cf
+    }
 }
 
 pub fn cli(command: Command) -> Command {
@@ -79,6 +142,10 @@ where
     for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
 {
     let dir = Builder::new().prefix("Recompress").tempdir()?;
+    let (_, _, source_flags) =
+        parse_properties::<E>(args.basename.with_extension(PROPERTIES_EXTENSION))?;
+    let comp_flags = args.ca.apply(source_flags);
+    log::info!("Compression flags: {:?}", comp_flags);
 
     if args.basename.with_extension(EF_EXTENSION).exists() {
         let graph = BVGraph::with_basename(&args.basename)
@@ -86,7 +153,7 @@ where
             .load()?;
 
         if let Some(permutation) = permutation {
-            let batch_size = args.pa.batch_size;
+            let batch_size = args.pa.probed_batch_size();
 
             log::info!("Permuting graph with batch size {}", batch_size);
             let start = std::time::Instant::now();
@@ -121,7 +188,7 @@ where
                 args.new_basename,
                 &sorted,
                 sorted.num_nodes(),
-                args.ca.into(),
+                comp_flags,
                 Threads::Num(args.num_cpus.num_cpus),
                 dir,
                 &target_endianness.unwrap_or_else(|| E::NAME.into()),
@@ -131,7 +198,7 @@ where
                 args.new_basename,
                 &graph,
                 graph.num_nodes(),
-                args.ca.into(),
+                comp_flags,
                 Threads::Num(args.num_cpus.num_cpus),
                 dir,
                 &target_endianness.unwrap_or_else(|| E::NAME.into()),
@@ -144,7 +211,7 @@ where
             .load()?;
 
         if let Some(permutation) = permutation {
-            let batch_size = args.pa.batch_size;
+            let batch_size = args.pa.probed_batch_size();
 
             log::info!("Permuting graph with batch size {}", batch_size);
             let start = std::time::Instant::now();
@@ -158,7 +225,7 @@ where
                 args.new_basename,
                 &permuted,
                 permuted.num_nodes(),
-                args.ca.into(),
+                comp_flags,
                 Threads::Num(args.num_cpus.num_cpus),
                 dir,
                 &target_endianness.unwrap_or_else(|| E::NAME.into()),
@@ -168,7 +235,7 @@ where
                 args.new_basename,
                 &seq_graph,
                 seq_graph.num_nodes(),
-                args.ca.into(),
+                comp_flags,
                 Threads::Num(args.num_cpus.num_cpus),
                 dir,
                 &target_endianness.unwrap_or_else(|| E::NAME.into()),
@@ -177,3 +244,67 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::random::ErdosRenyi;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+    use std::path::Path;
+
+    fn recompress(src: &Path, dst: &Path, options: &[&str]) -> Result<()> {
+        let matches = cli(Command::new("webgraph")).try_get_matches_from(
+            ["webgraph", COMMAND_NAME, "-j", "1"]
+                .into_iter()
+                .chain([src.to_str().unwrap(), dst.to_str().unwrap()])
+                .chain(options.iter().copied()),
+        )?;
+        main(matches.subcommand_matches(COMMAND_NAME).unwrap())
+    }
+
+    fn load(basename: &Path) -> Result<impl SequentialGraph> {
+        BVGraphSeq::with_basename(basename)
+            .endianness::<BE>()
+            .load()
+    }
+
+    #[test]
+    fn test_recompress() -> Result<()> {
+        let tmp_dir = Builder::new().prefix("test_recompress").tempdir()?;
+        let src = tmp_dir.path().join("src");
+        let graph = Left(VecGraph::from_lender(ErdosRenyi::new(200, 0.05, 0).iter()));
+        let source_flags = CompFlags {
+            compression_window: 3,
+            min_interval_length: 2,
+            residuals: Code::Gamma,
+            ..CompFlags::default()
+        };
+        BVComp::single_thread::<BE, _>(&src, &graph, source_flags, false, None)?;
+
+        // Identical parameters
+        let same = tmp_dir.path().join("same");
+        recompress(&src, &same, &[])?;
+        assert_eq!(
+            std::fs::read(src.with_extension(GRAPH_EXTENSION))?,
+            std::fs::read(same.with_extension(GRAPH_EXTENSION))?
+        );
+
+        // Different codes and parameters
+        let other = tmp_dir.path().join("other");
+        recompress(
+            &src,
+            &other,
+            &["--residuals", "delta", "--outdegrees", "zeta2", "-w", "10"],
+        )?;
+        let (num_nodes, _, cf) =
+            parse_properties::<BE>(other.with_extension(PROPERTIES_EXTENSION))?;
+        assert_eq!(num_nodes, 200);
+        assert_eq!(cf.residuals, Code::Delta);
+        assert_eq!(cf.outdegrees, Code::Zeta { k: 2 });
+        assert_eq!(cf.compression_window, 10);
+        assert_eq!(cf.min_interval_length, 2);
+        assert!(crate::algo::graphs_equal(load(&src)?, load(&other)?));
+        Ok(())
+    }
+}
@@ -0,0 +1,92 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use anyhow::Result;
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+use dsi_bitstream::prelude::*;
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+use std::path::PathBuf;
+
+pub const COMMAND_NAME: &str = "sample";
+
+#[derive(Args, Debug)]
+#[command(about = "Prints the successors of randomly chosen nodes.", long_about = None)]
+struct CliArgs {
+    /// The basename of the graph.
+    basename: PathBuf,
+
+    #[arg(short, long, default_value_t = 10)]
+    /// The number of nodes to sample.
+    n: usize,
+
+    #[arg(short, long, default_value_t = 0)]
+    /// The seed of the pseudorandom number generator.
+    seed: u64,
+}
+
+pub fn cli(command: Command) -> Command {
+    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+}
+
+pub fn main(submatches: &ArgMatches) -> Result<()> {
+    let args = CliArgs::from_arg_matches(submatches)?;
+
+    match get_endianness(&args.basename)?.as_str() {
+        #[cfg(any(
+            feature = "be_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        BE::NAME => sample_impl::<BE>(args),
+        #[cfg(any(
+            feature = "le_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        LE::NAME => sample_impl::<LE>(args),
+        e => panic!("Unknown endianness: {}", e),
+    }
+}
+
+fn sample_impl<E: Endianness + 'static>(args: CliArgs) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let graph = BVGraph::with_basename(&args.basename)
+        .mode::<LoadMmap>()
+        .flags(MemoryFlags::RANDOM_ACCESS)
+        .endianness::<E>()
+        .load()?;
+
+    for (node, succ) in sample_successors(&graph, args.n, args.seed) {
+        println!("{}: {:?}", node, succ);
+    }
+    Ok(())
+}
+
+/// Returns `n` nodes of a graph chosen uniformly at random (with
+/// replacement) and their successors.
+///
+/// Nodes are chosen using a [`SmallRng`] seeded with `seed`, as in the
+/// random-access benchmark, so the result depends only on the graph and on
+/// `seed`. If the graph has no nodes, the result is empty.
+pub fn sample_successors(
+    graph: impl RandomAccessGraph,
+    n: usize,
+    seed: u64,
+) -> Vec<(usize, Vec<usize>)> {
+    let num_nodes = graph.num_nodes();
+    if num_nodes == 0 {
+        return vec![];
+    }
+    let mut rng = SmallRng::seed_from_u64(seed);
+    (0..n)
+        .map(|_| {
+            let node = rng.gen_range(0..num_nodes);
+            (node, graph.successors(node).into_iter().collect())
+        })
+        .collect()
+}
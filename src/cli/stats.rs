@@ -0,0 +1,62 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::algo::graph_stats;
+use crate::graphs::bvgraph::{get_endianness, BVGraphSeq, CodeRead};
+use anyhow::Result;
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+use dsi_bitstream::prelude::*;
+use std::path::PathBuf;
+
+pub const COMMAND_NAME: &str = "stats";
+
+#[derive(Args, Debug)]
+#[command(about = "Prints basic statistics about a graph, computed with a single sequential scan.", long_about = None)]
+struct CliArgs {
+    /// The basename of the graph.
+    basename: PathBuf,
+}
+
+pub fn cli(command: Command) -> Command {
+    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+}
+
+pub fn main(submatches: &ArgMatches) -> Result<()> {
+    let args = CliArgs::from_arg_matches(submatches)?;
+
+    match get_endianness(&args.basename)?.as_str() {
+        #[cfg(any(
+            feature = "be_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        BE::NAME => stats::<BE>(args),
+        #[cfg(any(
+            feature = "le_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        LE::NAME => stats::<LE>(args),
+        e => panic!("Unknown endianness: {}", e),
+    }
+}
+
+fn stats<E: Endianness + 'static>(args: CliArgs) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let graph = BVGraphSeq::with_basename(&args.basename)
+        .endianness::<E>()
+        .load()?;
+    let stats = graph_stats(&graph);
+
+    println!("nodes: {}", stats.num_nodes);
+    println!("arcs: {}", stats.num_arcs);
+    println!("min outdegree: {}", stats.min_outdegree);
+    println!("max outdegree: {}", stats.max_outdegree);
+    println!("avg outdegree: {:.3}", stats.avg_outdegree());
+    println!("isolated nodes: {}", stats.num_isolated);
+    println!("loops: {}", stats.num_loops);
+    Ok(())
+}
@@ -0,0 +1,99 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::utils::*;
+use crate::prelude::*;
+use anyhow::{Context, Result};
+use clap::{ArgMatches, Args, Command, FromArgMatches};
+use dsi_bitstream::prelude::*;
+use std::io::BufRead;
+use std::path::PathBuf;
+use tempfile::Builder;
+
+pub const COMMAND_NAME: &str = "subgraph";
+
+#[derive(Args, Debug)]
+#[command(about = "Extract the subgraph of a BVGraph induced by a set of nodes", long_about = None)]
+struct CliArgs {
+    /// The basename of the graph.
+    src: PathBuf,
+    /// The basename of the subgraph.
+    dst: PathBuf,
+    /// A file containing the nodes of the subgraph, one per line; the node
+    /// on the i-th line becomes node i of the subgraph.
+    #[arg(short, long)]
+    nodes: PathBuf,
+
+    #[clap(flatten)]
+    num_cpus: NumCpusArg,
+
+    #[clap(flatten)]
+    pa: PermutationArgs,
+
+    #[clap(flatten)]
+    ca: CompressArgs,
+}
+
+pub fn cli(command: Command) -> Command {
+    command.subcommand(CliArgs::augment_args(Command::new(COMMAND_NAME)))
+}
+
+pub fn main(submatches: &ArgMatches) -> Result<()> {
+    let args = CliArgs::from_arg_matches(submatches)?;
+
+    match get_endianness(&args.src)?.as_str() {
+        #[cfg(any(
+            feature = "be_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        BE::NAME => subgraph::<BE>(args),
+        #[cfg(any(
+            feature = "le_bins",
+            not(any(feature = "be_bins", feature = "le_bins"))
+        ))]
+        LE::NAME => subgraph::<LE>(args),
+        e => panic!("Unknown endianness: {}", e),
+    }
+}
+
+fn subgraph<E: Endianness + 'static>(args: CliArgs) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let file = std::fs::File::open(&args.nodes)
+        .with_context(|| format!("Could not open {}", args.nodes.display()))?;
+    let mut nodes = vec![];
+    for (line_num, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line.with_context(|| format!("Could not read {}", args.nodes.display()))?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        nodes.push(line.parse::<usize>().with_context(|| {
+            format!("Could not parse node {:?} on line {}", line, line_num + 1)
+        })?);
+    }
+
+    let seq_graph = crate::graphs::bvgraph::sequential::BVGraphSeq::with_basename(&args.src)
+        .endianness::<E>()
+        .load()?;
+
+    let sub = crate::transform::subgraph(&seq_graph, &nodes, args.pa.probed_batch_size())?;
+
+    let target_endianness = args.ca.endianess.clone();
+    let dir = Builder::new().prefix("CompressSubgraph").tempdir()?;
+    BVComp::parallel_endianness(
+        args.dst,
+        &sub,
+        sub.num_nodes(),
+        args.ca.into(),
+        Threads::Num(args.num_cpus.num_cpus),
+        dir,
+        &target_endianness.unwrap_or_else(|| E::NAME.into()),
+    )?;
+
+    Ok(())
+}
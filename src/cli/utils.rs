@@ -9,6 +9,7 @@ use std::path::PathBuf;
 
 use crate::graphs::Code;
 use crate::prelude::CompFlags;
+use crate::utils::SortPairs;
 use anyhow::anyhow;
 use anyhow::ensure;
 use clap::Args;
@@ -111,6 +112,55 @@ pub struct PermutationArgs {
     pub batch_size: usize,
 }
 
+impl PermutationArgs {
+    /// Returns the batch size, clamped to the maximum batch size fitting in
+    /// the memory currently available (see [`clamp_batch_size`]).
+    pub fn probed_batch_size(&self) -> usize {
+        clamp_batch_size(self.batch_size, available_memory())
+    }
+}
+
+/// Returns the amount of memory, in bytes, that is currently available, or
+/// zero if it cannot be determined.
+pub fn available_memory() -> u64 {
+    let mut system = System::new();
+    system.refresh_memory();
+    system.available_memory()
+}
+
+/// Clamps a batch size to the maximum batch size of a [`SortPairs`] fitting in
+/// half of `available_memory` bytes, logging the maximum usable batch size.
+///
+/// The other half of the memory is left to the graph and to the rest of the
+/// system. If `available_memory` is zero, the available memory could not be
+/// determined, and `batch_size` is returned unchanged.
+pub fn clamp_batch_size(batch_size: usize, available_memory: u64) -> usize {
+    if available_memory == 0 {
+        log::warn!(
+            "Could not determine the available memory: using batch size {}",
+            batch_size
+        );
+        return batch_size;
+    }
+    let max_batch_size = SortPairs::<(), ()>::batch_size_for_budget(available_memory / 2);
+    if batch_size > max_batch_size {
+        log::warn!(
+            "Batch size {} does not fit in half of the available memory ({} bytes): using the maximum usable batch size {}",
+            batch_size,
+            available_memory,
+            max_batch_size
+        );
+        max_batch_size
+    } else {
+        log::info!(
+            "Batch size {} (maximum usable batch size {})",
+            batch_size,
+            max_batch_size
+        );
+        batch_size
+    }
+}
+
 /// Parses a batch size.
 ///
 /// This function accepts either a number (possibly followed by a
@@ -174,6 +224,11 @@ pub struct CompressArgs {
     /// The minimum interval length
     #[clap(short = 'l', long, default_value_t = 4)]
     pub min_interval_length: usize,
+    /// Whether to represent runs of consecutive successors as intervals; if
+    /// false, all successors that are not copied are encoded as residuals,
+    /// regardless of the minimum interval length
+    #[clap(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub prefer_intervals: bool,
     /// The maximum recursion depth for references (-1 for infinite recursion depth)
     #[clap(short = 'c', long, default_value_t = 3)]
     pub max_ref_count: isize,
@@ -207,7 +262,12 @@ impl From<CompressArgs> for CompFlags {
             blocks: value.blocks.into(),
             intervals: PrivCode::Gamma.into(),
             residuals: value.residuals.into(),
-            min_interval_length: value.min_interval_length,
+            // A minimum interval length of zero disables intervals
+            min_interval_length: if value.prefer_intervals {
+                value.min_interval_length
+            } else {
+                0
+            },
             compression_window: value.compression_window,
             max_ref_count: match value.max_ref_count {
                 -1 => usize::MAX,
@@ -216,3 +276,38 @@ impl From<CompressArgs> for CompFlags {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamp_batch_size() {
+        let memory = 1 << 30;
+        let max = SortPairs::<(), ()>::batch_size_for_budget(memory / 2);
+        assert_eq!(clamp_batch_size(max + 1, memory), max);
+        assert_eq!(clamp_batch_size(max, memory), max);
+        assert_eq!(clamp_batch_size(1000, memory), 1000);
+        // A batch contains at least one pair
+        assert_eq!(clamp_batch_size(1000, 1), 1);
+        // The available memory is unknown
+        assert_eq!(clamp_batch_size(1000, 0), 1000);
+    }
+
+    #[derive(clap::Parser)]
+    struct TestCli {
+        #[clap(flatten)]
+        ca: CompressArgs,
+    }
+
+    #[test]
+    fn test_prefer_intervals() {
+        use clap::Parser;
+        let cf: CompFlags = TestCli::parse_from(["test"]).ca.into();
+        assert_eq!(cf.min_interval_length, 4);
+        let cf: CompFlags = TestCli::parse_from(["test", "--prefer-intervals", "false"])
+            .ca
+            .into();
+        assert_eq!(cf.min_interval_length, 0);
+    }
+}
@@ -222,6 +222,7 @@ pub fn harness(data: FuzzCase) {
         graph.num_arcs(),
         comp_flags.compression_window,
         comp_flags.min_interval_length,
+        comp_flags.max_ref_count,
     );
     let graph_le: BVGraph<_> = BVGraph::new(
         codes_reader_le,
@@ -229,6 +230,7 @@ pub fn harness(data: FuzzCase) {
         graph.num_arcs(),
         comp_flags.compression_window,
         comp_flags.min_interval_length,
+        comp_flags.max_ref_count,
     );
 
     // Compare the three graphs
@@ -74,10 +74,16 @@ unsafe impl<L: Clone + 'static, I: IntoIterator<Item = (usize, usize, L)> + Clon
 }
 
 impl<L: Clone + 'static, I: IntoIterator<Item = (usize, usize, L)>> Iter<L, I> {
-    pub fn new(num_nodes: usize, mut iter: I::IntoIter) -> Self {
+    pub fn new(num_nodes: usize, iter: I::IntoIter) -> Self {
+        Self::new_from(num_nodes, 0, iter)
+    }
+
+    /// Creates an iterator starting at node `from`, assuming that `iter`
+    /// does not return arcs with source smaller than `from`.
+    fn new_from(num_nodes: usize, from: usize, mut iter: I::IntoIter) -> Self {
         Iter {
             num_nodes,
-            curr_node: 0_usize.wrapping_sub(1), // No node seen yet
+            curr_node: from.wrapping_sub(1), // No node seen yet
             next_pair: iter.next().unwrap_or((usize::MAX, usize::MAX, unsafe {
                 #[allow(clippy::uninit_assumed_init)]
                 // SAFETY: L is Copy
@@ -202,6 +208,138 @@ impl<'a, L, I: IntoIterator<Item = (usize, usize, L)>> Iterator for Succ<'a, L,
     }
 }
 
+/// An [`ArcListGraph`] whose arcs are stored in a slice sorted by source,
+/// together with an index of the position of the first arc of each node.
+///
+/// The index makes it possible to start iterating from any node in constant
+/// time. Thus, [`split_iter`](SplitLabeling::split_iter) hands out to each
+/// parallel worker an iterator positioned at the start of its node range,
+/// whereas the [sequential implementation](split::seq) used by
+/// [`ArcListGraph`] must scan the arcs of all preceding nodes. In particular,
+/// a [left projection](crate::prelude::proj::Left) of an unlabeled instance
+/// can be compressed in parallel using
+/// [`BVComp::parallel_graph`](crate::graphs::bvgraph::BVComp::parallel_graph).
+///
+/// If for every source the arcs are sorted by destination, the
+/// successors of the graph will be sorted.
+#[derive(Clone)]
+pub struct IndexedArcListGraph<L, A: AsRef<[(usize, usize, L)]>> {
+    num_nodes: usize,
+    arcs: A,
+    /// The position in `arcs` of the first arc of each node, followed by the
+    /// number of arcs.
+    index: Box<[usize]>,
+    _marker: core::marker::PhantomData<L>,
+}
+
+impl<L: Copy + 'static, A: AsRef<[(usize, usize, L)]>> IndexedArcListGraph<L, A> {
+    /// Creates a new indexed arc list graph from the given slice of arcs.
+    ///
+    /// # Panics
+    ///
+    /// If the arcs are not sorted by source, or if some source is not smaller
+    /// than `num_nodes`.
+    pub fn new_labeled(num_nodes: usize, arcs: A) -> Self {
+        let slice = arcs.as_ref();
+        let mut index = Vec::with_capacity(num_nodes + 1);
+        let mut pos = 0;
+        for node in 0..num_nodes {
+            index.push(pos);
+            while pos < slice.len() && slice[pos].0 == node {
+                pos += 1;
+            }
+        }
+        index.push(pos);
+        assert_eq!(
+            pos,
+            slice.len(),
+            "The arcs must be sorted by source, and sources must be smaller than the number of nodes"
+        );
+        Self {
+            num_nodes,
+            arcs,
+            index: index.into_boxed_slice(),
+            _marker: core::marker::PhantomData,
+        }
+    }
+
+    /// Returns the number of arcs of the graph.
+    #[inline(always)]
+    pub fn num_arcs(&self) -> u64 {
+        self.arcs.as_ref().len() as u64
+    }
+}
+
+impl<L: Copy + 'static, A: AsRef<[(usize, usize, L)]>> SequentialLabeling
+    for IndexedArcListGraph<L, A>
+{
+    type Label = (usize, L);
+    type Lender<'node> = Iter<L, core::iter::Copied<core::slice::Iter<'node, (usize, usize, L)>>>
+    where
+        Self: 'node;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.num_nodes
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        Some(self.num_arcs())
+    }
+
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.num_arcs())
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        Iter::new_from(
+            self.num_nodes,
+            from,
+            self.arcs.as_ref()[self.index[from]..].iter().copied(),
+        )
+    }
+}
+
+impl<'lend, 'a, L: Copy + 'static, A: AsRef<[(usize, usize, L)]>> Lending<'lend>
+    for &'a IndexedArcListGraph<L, A>
+{
+    type Lend = (
+        usize,
+        Succ<'lend, L, core::iter::Copied<core::slice::Iter<'a, (usize, usize, L)>>>,
+    );
+}
+
+impl<'a, L: Copy + 'static, A: AsRef<[(usize, usize, L)]>> IntoLender
+    for &'a IndexedArcListGraph<L, A>
+{
+    type Lender = Iter<L, core::iter::Copied<core::slice::Iter<'a, (usize, usize, L)>>>;
+
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl<L: Copy + Send + Sync + 'static, A: AsRef<[(usize, usize, L)]>> SplitLabeling
+    for IndexedArcListGraph<L, A>
+{
+    type SplitLender<'a> = lender::Take<<Self as SequentialLabeling>::Lender<'a>> where Self: 'a;
+    type IntoIterator<'a> = std::vec::IntoIter<Self::SplitLender<'a>> where Self: 'a;
+
+    fn split_iter(&self, how_many: usize) -> Self::IntoIterator<'_> {
+        let nodes_per_iter = self.num_nodes.div_ceil(how_many.max(1));
+        (0..how_many)
+            .map(|i| {
+                self.iter_from((i * nodes_per_iter).min(self.num_nodes))
+                    .take(nodes_per_iter)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
 #[cfg(test)]
 #[cfg_attr(test, test)]
 fn test() -> anyhow::Result<()> {
@@ -221,3 +359,62 @@ fn test() -> anyhow::Result<()> {
     assert_eq!(g, g2);
     Ok(())
 }
+
+#[cfg(test)]
+#[cfg_attr(test, test)]
+fn test_indexed() -> anyhow::Result<()> {
+    use crate::graphs::random::ErdosRenyi;
+    // Node 0 and the last two nodes are isolated
+    let num_nodes = 102;
+    let mut arcs = vec![];
+    for_!((node, succ) in ErdosRenyi::new(99, 0.1, 0).iter() {
+        arcs.extend(succ.into_iter().map(|dst| (node + 1, dst + 1, ())));
+    });
+    let serial = ArcListGraph::new_labeled(num_nodes, arcs.iter().copied());
+    let indexed = IndexedArcListGraph::new_labeled(num_nodes, arcs.as_slice());
+    assert_eq!(indexed.num_arcs(), arcs.len() as u64);
+
+    for how_many in [1, 3, 7, 200] {
+        let mut serial_iter = serial.iter();
+        let mut num_splits = 0;
+        for lender in indexed.split_iter(how_many) {
+            num_splits += 1;
+            for_!((node, succ) in lender {
+                let (serial_node, serial_succ) = serial_iter.next().unwrap();
+                assert_eq!(node, serial_node);
+                assert!(itertools::equal(succ, serial_succ));
+            });
+        }
+        assert_eq!(num_splits, how_many);
+        assert!(serial_iter.next().is_none());
+    }
+
+    for from in [0, 1, 50, 101, 102] {
+        let mut serial_iter = serial.iter_from(from);
+        for_!((node, succ) in indexed.iter_from(from) {
+            let (serial_node, serial_succ) = serial_iter.next().unwrap();
+            assert_eq!(node, serial_node);
+            assert!(itertools::equal(succ, serial_succ));
+        });
+        assert!(serial_iter.next().is_none());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[cfg_attr(test, test)]
+#[should_panic]
+fn test_indexed_unsorted() {
+    IndexedArcListGraph::new_labeled(3, [(0, 1, ()), (2, 0, ()), (1, 2, ())]);
+}
+
+#[cfg(test)]
+#[cfg_attr(test, test)]
+fn test_num_arcs_exact() {
+    let arcs = [(0, 1, ()), (1, 2, ()), (2, 0, ())];
+    // The number of arcs of an iterator is unknown
+    let serial = ArcListGraph::new_labeled(3, arcs.iter().copied());
+    assert_eq!(serial.num_arcs_exact(), None);
+    let indexed = IndexedArcListGraph::new_labeled(3, arcs.as_slice());
+    assert_eq!(indexed.num_arcs_exact(), Some(3));
+}
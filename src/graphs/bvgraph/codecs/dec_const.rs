@@ -270,6 +270,11 @@ where
             _marker: PhantomData,
         })
     }
+
+    #[inline(always)]
+    fn offset(&self, node: usize) -> Option<u64> {
+        Some(self.offsets.get(node) as u64)
+    }
 }
 
 impl<
@@ -5,6 +5,7 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use std::collections::HashMap;
 use std::marker::PhantomData;
 
 use super::super::*;
@@ -76,6 +77,16 @@ impl<E: Endianness, CR: CodeRead<E>> DynCodesDecoder<E, CR> {
             _marker: core::marker::PhantomData,
         })
     }
+
+    /// Creates a decoder using the codes specified by the `compressionflags`
+    /// (and `zetak`) entries of a decoded `.properties` file.
+    ///
+    /// This makes it possible to read a graph compressed with any
+    /// combination of supported codes without knowing them at compile time.
+    /// See [`CompFlags::from_properties`] for the accepted properties.
+    pub fn from_properties(code_reader: CR, map: &HashMap<String, String>) -> anyhow::Result<Self> {
+        Self::new(code_reader, &CompFlags::from_properties::<E>(map)?)
+    }
 }
 
 impl<E: Endianness, CR: CodeRead<E> + BitSeek> BitSeek for DynCodesDecoder<E, CR> {
@@ -233,6 +244,18 @@ where
             _marker: core::marker::PhantomData,
         })
     }
+
+    /// Create a new builder from the data and the compression flags
+    /// specified in a decoded `.properties` file.
+    ///
+    /// See [`CompFlags::from_properties`] for the accepted properties.
+    pub fn from_properties(
+        factory: F,
+        offsets: MemCase<OFF>,
+        map: &HashMap<String, String>,
+    ) -> anyhow::Result<Self> {
+        Self::new(factory, offsets, CompFlags::from_properties::<E>(map)?)
+    }
 }
 
 impl<E: Endianness, F: BitReaderFactory<E>, OFF: IndexedDict<Input = usize, Output = usize>>
@@ -263,6 +286,11 @@ where
             _marker: PhantomData,
         })
     }
+
+    #[inline(always)]
+    fn offset(&self, node: usize) -> Option<u64> {
+        Some(self.offsets.get(node) as u64)
+    }
 }
 
 impl<E: Endianness, F: BitReaderFactory<E>> SequentialDecoderFactory
@@ -14,8 +14,10 @@ Implementations of the [`BitReaderFactory`] trait can be used to create
 bit readers accessing a graph data using different techniques.
 - [`FileFactory`] uses a [std::fs::File] to create a bit reader.
 - [`MemoryFactory`] creates bit readers from a slice of memory,
-either [allocated](MemoryFactory::new_mem) or [mapped](MemoryFactory::new_mmap).
+  either [allocated](MemoryFactory::new_mem) or [mapped](MemoryFactory::new_mmap).
 - [`MmapHelper`] can be used to create a bit reader from a memory-mapped file.
+- [`ZstdFactory`] creates bit readers decompressing on the fly a
+  zstd-compressed file (requires the `zstd` feature).
 
 Any factory can be plugged either into a
 [`SequentialDecoderFactory`](super::SequentialDecoderFactory)
@@ -79,6 +81,72 @@ impl<E: Endianness> BitReaderFactory<E> for FileFactory<E> {
     }
 }
 
+/// The reader used by [`ZstdFactory`].
+///
+/// The decompressed stream is followed by a few zeroes, so that it can be
+/// read one `u32` at a time even if its length is not a multiple of four.
+#[cfg(feature = "zstd")]
+pub type ZstdReader = std::io::Chain<
+    zstd::stream::read::Decoder<'static, BufReader<File>>,
+    std::io::Take<std::io::Repeat>,
+>;
+
+/// A factory creating bit readers that decompress on the fly a
+/// zstd-compressed file.
+///
+/// Since a zstd stream cannot be accessed randomly, this factory is suitable
+/// only for sequential access: every reader decompresses the file from the
+/// start.
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone)]
+pub struct ZstdFactory<E: Endianness> {
+    path: Box<Path>,
+    _marker: core::marker::PhantomData<E>,
+}
+
+#[cfg(feature = "zstd")]
+impl<E: Endianness> ZstdFactory<E> {
+    /// Creates a new factory for the given zstd-compressed file.
+    ///
+    /// The file is opened and the beginning of the stream is decompressed, so
+    /// that a missing or invalid file is reported here rather than when
+    /// creating a reader.
+    pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path: Box<Path> = path.as_ref().into();
+        let metadata = std::fs::metadata(&path)
+            .with_context(|| format!("Could not stat {}", path.display()))?;
+        ensure!(metadata.is_file(), "File {} is not a file", path.display());
+        let file =
+            File::open(&path).with_context(|| format!("Could not open {}", path.display()))?;
+        zstd::stream::read::Decoder::new(file)
+            .and_then(|mut decoder| decoder.read(&mut [0; 1]))
+            .with_context(|| format!("File {} is not a valid zstd file", path.display()))?;
+
+        Ok(Self {
+            path,
+            _marker: core::marker::PhantomData,
+        })
+    }
+}
+
+#[cfg(feature = "zstd")]
+impl<E: Endianness> BitReaderFactory<E> for ZstdFactory<E> {
+    type BitReader<'a> = BufBitReader<E, WordAdapter<u32, ZstdReader>>
+    where
+        Self: 'a;
+
+    fn new_reader(&self) -> Self::BitReader<'_> {
+        // the file has been checked when creating the factory
+        let file = File::open(&self.path)
+            .unwrap_or_else(|e| panic!("Could not open {}: {}", self.path.display(), e));
+        let decoder = zstd::stream::read::Decoder::new(file)
+            .unwrap_or_else(|e| panic!("Could not decompress {}: {}", self.path.display(), e));
+        BufBitReader::<E, _>::new(WordAdapter::<u32, _>::new(
+            decoder.chain(std::io::repeat(0).take(2 * core::mem::size_of::<u32>() as u64)),
+        ))
+    }
+}
+
 bitflags! {
     /// Flags for [`MemoryFactory`] and [`MmapHelper`].
     #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -184,7 +252,9 @@ impl<E: Endianness> MemoryFactory<E, Box<[u32]>> {
         file.read_exact(&mut bytes[..file_len])
             .with_context(|| format!("Could not read {}", path.display()))?;
         // Fixes the last few bytes to guarantee zero-extension semantics
-        // for bit vectors and full-vector initialization.
+        // for bit vectors and full-vector initialization: as the capacity is
+        // rounded up to a multiple of 16, a last partial word is completed
+        // with zeroes, whatever the length of the file modulo 4.
         bytes[file_len..].fill(0);
         Ok(Self {
             // Safety: the length is a multiple of 16.
@@ -203,7 +273,8 @@ impl<E: Endianness> MemoryFactory<E, MmapHelper<u32>> {
             .len() as usize;
         let mut file = std::fs::File::open(path)
             .with_context(|| format!("Could not open {}", path.display()))?;
-        let capacity = file_len.align_to(16);
+        // Length must be > 0, or we get a panic.
+        let capacity = file_len.align_to(16).max(16);
 
         let mut mmap = mmap_rs::MmapOptions::new(capacity)?
             .with_flags(flags.into())
@@ -212,7 +283,7 @@ impl<E: Endianness> MemoryFactory<E, MmapHelper<u32>> {
         file.read_exact(&mut mmap[..file_len])
             .with_context(|| format!("Could not read {}", path.display()))?;
         // Fixes the last few bytes to guarantee zero-extension semantics
-        // for bit vectors.
+        // for bit vectors, whatever the length of the file modulo 4.
         mmap[file_len..].fill(0);
 
         Ok(Self {
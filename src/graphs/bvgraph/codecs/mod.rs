@@ -102,6 +102,15 @@ pub trait RandomAccessDecoderFactory {
 
     /// Create a new reader starting at the given node.
     fn new_decoder(&self, node: usize) -> anyhow::Result<Self::Decoder<'_>>;
+
+    /// Return the bit offset of the successor list of the given node, if
+    /// available.
+    ///
+    /// If `node` is the number of nodes, the length in bits of the bitstream
+    /// should be returned. The default implementation returns `None`.
+    fn offset(&self, _node: usize) -> Option<u64> {
+        None
+    }
 }
 
 /// A trait providing decoders on the whole graph.
@@ -5,7 +5,7 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
-use anyhow::{bail, ensure, Result};
+use anyhow::{bail, ensure, Context, Result};
 use dsi_bitstream::traits::{BigEndian, Endianness, LittleEndian};
 use std::collections::HashMap;
 
@@ -62,7 +62,127 @@ impl core::default::Default for CompFlags {
     }
 }
 
+/// A builder for [`CompFlags`] that checks the resulting configuration.
+///
+/// The builder starts from the [default flags](CompFlags::default) and
+/// [`build`](CompFlagsBuilder::build) fails if the result cannot be written
+/// and read back:
+/// ```
+/// # use webgraph::prelude::*;
+/// let cf = CompFlags::builder()
+///     .compression_window(16)
+///     .max_ref_count(5)
+///     .residuals(Code::Zeta { k: 2 })
+///     .build()?;
+/// assert_eq!(cf.compression_window, 16);
+/// assert!(CompFlags::builder().outdegrees(Code::Zeta { k: 9 }).build().is_err());
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CompFlagsBuilder {
+    flags: CompFlags,
+}
+
+impl CompFlagsBuilder {
+    /// Sets the code used for outdegrees.
+    pub fn outdegrees(mut self, code: Code) -> Self {
+        self.flags.outdegrees = code;
+        self
+    }
+
+    /// Sets the code used for reference offsets.
+    pub fn references(mut self, code: Code) -> Self {
+        self.flags.references = code;
+        self
+    }
+
+    /// Sets the code used for block counts and blocks.
+    pub fn blocks(mut self, code: Code) -> Self {
+        self.flags.blocks = code;
+        self
+    }
+
+    /// Sets the code used for interval counts, starts, and lengths.
+    pub fn intervals(mut self, code: Code) -> Self {
+        self.flags.intervals = code;
+        self
+    }
+
+    /// Sets the code used for residuals.
+    pub fn residuals(mut self, code: Code) -> Self {
+        self.flags.residuals = code;
+        self
+    }
+
+    /// Sets the minimum length of an interval; zero disables intervals.
+    pub fn min_interval_length(mut self, min_interval_length: usize) -> Self {
+        self.flags.min_interval_length = min_interval_length;
+        self
+    }
+
+    /// Sets the number of previous nodes considered as references; zero
+    /// disables reference compression.
+    pub fn compression_window(mut self, compression_window: usize) -> Self {
+        self.flags.compression_window = compression_window;
+        self
+    }
+
+    /// Sets the maximum length of a reference chain.
+    pub fn max_ref_count(mut self, max_ref_count: usize) -> Self {
+        self.flags.max_ref_count = max_ref_count;
+        self
+    }
+
+    /// Checks the configuration and returns the flags.
+    ///
+    /// See [`CompFlags::validate`] for the checks performed.
+    pub fn build(self) -> Result<CompFlags> {
+        self.flags.validate()?;
+        Ok(self.flags)
+    }
+}
+
 impl CompFlags {
+    /// Returns a [`CompFlagsBuilder`] starting from the default flags.
+    pub fn builder() -> CompFlagsBuilder {
+        CompFlagsBuilder::default()
+    }
+
+    /// Checks that the flags describe a graph that can be compressed and
+    /// then decoded.
+    ///
+    /// Only ζ₁-ζ₇ are supported, and since the `.properties` file stores a
+    /// single value of *k*, all ζ codes must use the same one.
+    pub fn validate(&self) -> Result<()> {
+        let mut k = None;
+        for (component, code) in [
+            ("outdegrees", self.outdegrees),
+            ("references", self.references),
+            ("blocks", self.blocks),
+            ("intervals", self.intervals),
+            ("residuals", self.residuals),
+        ] {
+            if let Code::Zeta { k: new_k } = code {
+                ensure!(
+                    (1..=7).contains(&new_k),
+                    "Only ζ₁-ζ₇ are supported, got ζ{} for {}",
+                    new_k,
+                    component
+                );
+                if let Some(old_k) = k {
+                    ensure!(
+                        old_k == new_k,
+                        "Only one value of k is supported, got {} and {}",
+                        old_k,
+                        new_k
+                    );
+                }
+                k = Some(new_k);
+            }
+        }
+        Ok(())
+    }
+
     /// Convert a string from the `compflags` field from the `.properties` file
     /// into which code to use.
     ///
@@ -133,7 +253,7 @@ impl CompFlags {
             ));
             cflags = true;
         }
-        if matches!(self.residuals, Code::Zeta { k: _ }) {
+        if !matches!(self.residuals, Code::Zeta { k: _ }) {
             s.push_str(&format!(
                 "RESIDUALS_{}|",
                 Self::code_to_str(self.residuals).unwrap()
@@ -186,8 +306,15 @@ impl CompFlags {
         );
         // check that the version was properly set for LE
         if core::any::TypeId::of::<E>() == core::any::TypeId::of::<LittleEndian>() {
+            let version = map
+                .get("version")
+                .map(|x| {
+                    x.parse::<u32>()
+                        .with_context(|| format!("Could not parse version {}", x))
+                })
+                .transpose()?;
             anyhow::ensure!(
-                map.get("version").map(|x| x.parse::<u32>().unwrap()) == Some(1),
+                version == Some(1),
                 "Wrong version, got {} while expected 1",
                 map.get("version").unwrap_or(&"None".to_string())
             );
@@ -195,40 +322,149 @@ impl CompFlags {
 
         let mut cf = CompFlags::default();
         let mut k = 3;
-        if let Some(spec_k) = map.get("zeta_k") {
-            let spec_k = spec_k.parse::<usize>()?;
+        // The Java implementation uses "zetak"; older versions of this crate
+        // used "zeta_k"
+        if let Some(spec_k) = map.get("zetak").or_else(|| map.get("zeta_k")) {
+            let spec_k = spec_k
+                .trim()
+                .parse::<usize>()
+                .with_context(|| format!("Could not parse zetak value {}", spec_k))?;
             if !(1..=7).contains(&spec_k) {
                 bail!("Only ζ₁-ζ₇ are supported");
             }
             k = spec_k;
         }
         if let Some(comp_flags) = map.get("compressionflags") {
-            if !comp_flags.is_empty() {
-                for flag in comp_flags.split('|') {
-                    let s: Vec<_> = flag.split('_').collect();
-                    // FIXME: this is a hack to avoid having to implement
-                    // FromStr for Code
-                    let code = CompFlags::code_from_str(s[1], k).unwrap();
-                    match s[0] {
-                        "OUTDEGREES" => cf.outdegrees = code,
-                        "REFERENCES" => cf.references = code,
-                        "BLOCKS" => cf.blocks = code,
-                        "INTERVALS" => cf.intervals = code,
-                        "RESIDUALS" => cf.residuals = code,
-                        "OFFSETS" => {
-                            ensure!(code == Code::Gamma, "Only γ code is supported for offsets")
-                        }
-                        _ => bail!("Unknown compression flag {}", flag),
+            for flag in comp_flags
+                .split('|')
+                .map(str::trim)
+                .filter(|flag| !flag.is_empty())
+            {
+                let Some((component, code)) = flag.split_once('_') else {
+                    bail!("Malformed compression flag {}", flag);
+                };
+                let code = CompFlags::code_from_str(code, k).with_context(|| {
+                    format!("Unsupported code {} in compression flag {}", code, flag)
+                })?;
+                match component {
+                    "OUTDEGREES" => cf.outdegrees = code,
+                    "REFERENCES" => cf.references = code,
+                    "BLOCKS" => cf.blocks = code,
+                    "INTERVALS" => cf.intervals = code,
+                    "RESIDUALS" => cf.residuals = code,
+                    "OFFSETS" => {
+                        ensure!(code == Code::Gamma, "Only γ code is supported for offsets")
                     }
+                    _ => bail!("Unknown compression flag {}", flag),
                 }
             }
         }
+        // A ζ code for residuals without an explicit flag uses the specified k
+        if let Code::Zeta { .. } = cf.residuals {
+            cf.residuals = Code::Zeta { k };
+        }
         if let Some(compression_window) = map.get("windowsize") {
-            cf.compression_window = compression_window.parse()?;
+            cf.compression_window = compression_window.parse::<usize>().with_context(|| {
+                format!("Could not parse windowsize value {}", compression_window)
+            })?;
         }
         if let Some(min_interval_length) = map.get("minintervallength") {
-            cf.min_interval_length = min_interval_length.parse()?;
+            cf.min_interval_length = min_interval_length.parse::<usize>().with_context(|| {
+                format!(
+                    "Could not parse minintervallength value {}",
+                    min_interval_length
+                )
+            })?;
+        }
+        if let Some(max_ref_count) = map.get("maxrefcount") {
+            cf.max_ref_count = max_ref_count
+                .parse::<usize>()
+                .with_context(|| format!("Could not parse maxrefcount value {}", max_ref_count))?;
         }
         Ok(cf)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dsi_bitstream::traits::{BE, LE};
+
+    #[test]
+    fn test_from_properties() -> Result<()> {
+        let mut map = HashMap::new();
+        map.insert("compressionflags".to_string(), "".to_string());
+        let cf = CompFlags::from_properties::<BE>(&map)?;
+        assert_eq!(cf.outdegrees, Code::Gamma);
+        assert_eq!(cf.residuals, Code::Zeta { k: 3 });
+
+        // Java-style flags
+        map.insert(
+            "compressionflags".to_string(),
+            "OUTDEGREES_DELTA|RESIDUALS_GAMMA".to_string(),
+        );
+        let cf = CompFlags::from_properties::<BE>(&map)?;
+        assert_eq!(cf.outdegrees, Code::Delta);
+        assert_eq!(cf.references, Code::Unary);
+        assert_eq!(cf.blocks, Code::Gamma);
+        assert_eq!(cf.intervals, Code::Gamma);
+        assert_eq!(cf.residuals, Code::Gamma);
+
+        map.insert("compressionflags".to_string(), "".to_string());
+        map.insert("zetak".to_string(), "5".to_string());
+        let cf = CompFlags::from_properties::<BE>(&map)?;
+        assert_eq!(cf.residuals, Code::Zeta { k: 5 });
+
+        map.insert(
+            "compressionflags".to_string(),
+            "RESIDUALS_NIBBLE".to_string(),
+        );
+        assert!(CompFlags::from_properties::<BE>(&map).is_err());
+        map.insert("compressionflags".to_string(), "RESIDUALS".to_string());
+        assert!(CompFlags::from_properties::<BE>(&map).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_malformed_properties() {
+        let mut map = HashMap::new();
+        map.insert("windowsize".to_string(), "seven".to_string());
+        assert!(CompFlags::from_properties::<BE>(&map).is_err());
+        map.insert("windowsize".to_string(), "7".to_string());
+        map.insert("minintervallength".to_string(), "".to_string());
+        assert!(CompFlags::from_properties::<BE>(&map).is_err());
+        map.insert("minintervallength".to_string(), "4".to_string());
+        map.insert("maxrefcount".to_string(), "-3".to_string());
+        assert!(CompFlags::from_properties::<BE>(&map).is_err());
+        map.insert("maxrefcount".to_string(), "3".to_string());
+        assert!(CompFlags::from_properties::<BE>(&map).is_ok());
+
+        // A malformed version must not panic
+        map.insert("endianness".to_string(), LE::NAME.to_string());
+        map.insert("version".to_string(), "one".to_string());
+        assert!(CompFlags::from_properties::<LE>(&map).is_err());
+        map.insert("version".to_string(), "1".to_string());
+        assert!(CompFlags::from_properties::<LE>(&map).is_ok());
+    }
+
+    #[test]
+    fn test_round_trip() -> Result<()> {
+        let cf = CompFlags {
+            outdegrees: Code::Delta,
+            references: Code::Gamma,
+            blocks: Code::Unary,
+            intervals: Code::Delta,
+            residuals: Code::Gamma,
+            ..Default::default()
+        };
+        let properties = cf.to_properties::<BE>(10, 20)?;
+        let map: HashMap<String, String> = java_properties::read(properties.as_bytes())?;
+        let cf2 = CompFlags::from_properties::<BE>(&map)?;
+        assert_eq!(cf.outdegrees, cf2.outdegrees);
+        assert_eq!(cf.references, cf2.references);
+        assert_eq!(cf.blocks, cf2.blocks);
+        assert_eq!(cf.intervals, cf2.intervals);
+        assert_eq!(cf.residuals, cf2.residuals);
+        Ok(())
+    }
+}
@@ -5,10 +5,12 @@
  * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
  */
 
+use crate::graphs::arc_list_graph::ArcListGraph;
 use crate::prelude::*;
 use anyhow::{ensure, Context, Result};
 use dsi_bitstream::prelude::*;
 use dsi_progress_logger::prelude::*;
+use itertools::Itertools;
 use lender::prelude::*;
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
@@ -106,11 +108,7 @@ impl BVComp<()> {
                 .with_context(|| format!("Could not create {}", graph_path.display()))?,
         )));
 
-        let comp_flags = CompFlags {
-            ..Default::default()
-        };
-
-        let codes_writer = DynCodesEncoder::new(bit_write, &comp_flags);
+        let codes_writer = DynCodesEncoder::new(bit_write, &compression_flags);
 
         let mut bvcomp = BVComp::new(
             codes_writer,
@@ -168,7 +166,7 @@ impl BVComp<()> {
 
         log::info!("Writing the .properties file");
         let properties = compression_flags
-            .to_properties::<BE>(real_num_nodes, bvcomp.arcs)
+            .to_properties::<E>(real_num_nodes, bvcomp.arcs)
             .context("Could not serialize properties")?;
         let properties_path = basename.with_extension(PROPERTIES_EXTENSION);
         std::fs::write(&properties_path, properties)
@@ -261,11 +259,129 @@ impl BVComp<()> {
         )
     }
 
+    /// Compresses in parallel a graph given as a list of arcs and returns the
+    /// lenght in bits of the graph bitstream.
+    ///
+    /// The arcs need not be sorted, and might contain duplicates: they are
+    /// sorted using [`SortPairs`], which keeps in memory at most `batch_size`
+    /// arcs at a time, and duplicates are removed before compression.
+    /// Temporary files are created in `tmp_dir`, which is not removed.
+    ///
+    /// # Errors
+    ///
+    /// If some arc has a source or a target that is not smaller than
+    /// `num_nodes`.
+    pub fn compress_from_arcs<E: Endianness>(
+        basename: impl AsRef<Path> + Send + Sync,
+        arcs: impl IntoIterator<Item = (usize, usize)>,
+        num_nodes: usize,
+        batch_size: usize,
+        compression_flags: CompFlags,
+        threads: impl AsMut<rayon::ThreadPool>,
+        tmp_dir: impl AsRef<Path>,
+    ) -> Result<u64>
+    where
+        BufBitWriter<E, WordAdapter<usize, BufWriter<std::fs::File>>>: CodeWrite<E>,
+        BufBitReader<E, WordAdapter<u32, BufReader<std::fs::File>>>: BitRead<E>,
+    {
+        let tmp_dir = tmp_dir.as_ref();
+        let pairs_dir = tempfile::Builder::new()
+            .prefix("CompressFromArcs")
+            .tempdir_in(tmp_dir)
+            .with_context(|| format!("Could not create a directory in {}", tmp_dir.display()))?;
+        let mut sorted = SortPairs::new(batch_size, pairs_dir.path())?;
+
+        let mut pl = progress_logger!(display_memory = true, item_name = "arc");
+        pl.start("Sorting arcs...");
+        for (src, dst) in arcs {
+            ensure!(
+                src < num_nodes && dst < num_nodes,
+                "Arc ({}, {}) is incompatible with a graph with {} nodes",
+                src,
+                dst,
+                num_nodes
+            );
+            sorted.push(src, dst)?;
+            pl.light_update();
+        }
+        pl.done();
+
+        let graph = Left(ArcListGraph::new(
+            num_nodes,
+            Itertools::dedup(
+                sorted
+                    .iter()
+                    .context("Could not read arcs")?
+                    .map(|(src, dst, _)| (src, dst)),
+            ),
+        ));
+
+        let comp_dir = tempfile::Builder::new()
+            .prefix("CompressFromArcs")
+            .tempdir_in(tmp_dir)
+            .with_context(|| format!("Could not create a directory in {}", tmp_dir.display()))?;
+        Self::parallel_graph::<E>(
+            basename,
+            &graph,
+            compression_flags,
+            threads,
+            comp_dir.path(),
+        )
+    }
+
     /// Compresses multiple [`NodeLabelsLender`] in parallel and returns the lenght in bits
     /// of the graph bitstream.
+    ///
+    /// Progress is logged per compressed chunk; see
+    /// [`parallel_iter_with_progress`](Self::parallel_iter_with_progress) if
+    /// you need to handle it yourself.
     pub fn parallel_iter<
         E: Endianness,
         L: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + Send,
+    >(
+        basename: impl AsRef<Path> + Send + Sync,
+        iter: impl Iterator<Item = L>,
+        num_nodes: usize,
+        compression_flags: CompFlags,
+        threads: impl AsMut<rayon::ThreadPool>,
+        tmp_dir: impl AsRef<Path>,
+    ) -> Result<u64>
+    where
+        BufBitWriter<E, WordAdapter<usize, BufWriter<std::fs::File>>>: CodeWrite<E>,
+        BufBitReader<E, WordAdapter<u32, BufReader<std::fs::File>>>: BitRead<E>,
+    {
+        let mut pl = progress_logger!(
+            display_memory = true,
+            item_name = "node",
+            expected_updates = Some(num_nodes),
+        );
+        pl.start("Compressing successors in parallel...");
+        let result = Self::parallel_iter_with_progress::<E, L>(
+            basename,
+            iter,
+            num_nodes,
+            compression_flags,
+            threads,
+            tmp_dir,
+            |nodes, _arcs| pl.update_with_count(nodes),
+        )?;
+        pl.done();
+        Ok(result)
+    }
+
+    /// Compresses multiple [`NodeLabelsLender`] in parallel, reporting
+    /// progress to a callback, and returns the lenght in bits of the graph
+    /// bitstream.
+    ///
+    /// The callback is invoked on the calling thread each time the bitstream
+    /// of a chunk (i.e., of one of the lenders returned by `iter`) is appended
+    /// to the final graph, with the number of nodes and the number of arcs of
+    /// the chunk. Chunks are appended in order, so the sum of the counts
+    /// reported so far is the number of nodes and arcs written to the graph
+    /// file. Lenders that do not return any node do not generate a call.
+    pub fn parallel_iter_with_progress<
+        E: Endianness,
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + Send,
     >(
         basename: impl AsRef<Path> + Send + Sync,
         iter: impl Iterator<Item = L>,
@@ -273,6 +389,7 @@ impl BVComp<()> {
         compression_flags: CompFlags,
         mut threads: impl AsMut<rayon::ThreadPool>,
         tmp_dir: impl AsRef<Path>,
+        mut progress: impl FnMut(usize, u64),
     ) -> Result<u64>
     where
         BufBitWriter<E, WordAdapter<usize, BufWriter<std::fs::File>>>: CodeWrite<E>,
@@ -404,6 +521,8 @@ impl BVComp<()> {
                             graph_path.display()
                         )
                     })?;
+
+                progress(last_node - first_node + 1, num_arcs);
             }
 
             log::info!("Flushing the merged Compression bitstream");
@@ -411,7 +530,7 @@ impl BVComp<()> {
 
             log::info!("Writing the .properties file");
             let properties = compression_flags
-                .to_properties::<BE>(num_nodes, total_arcs)
+                .to_properties::<E>(num_nodes, total_arcs)
                 .context("Could not serialize properties")?;
             let properties_path = basename.with_extension(PROPERTIES_EXTENSION);
             std::fs::write(&properties_path, properties).with_context(|| {
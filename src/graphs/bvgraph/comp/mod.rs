@@ -10,5 +10,8 @@ pub use bvcomp::*;
 
 mod impls;
 
+mod writer;
+pub use writer::BVGraphWriter;
+
 mod flags;
 pub use flags::*;
@@ -0,0 +1,385 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use anyhow::{ensure, Context, Result};
+use dsi_bitstream::prelude::*;
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// The bit writer of the graph bitstream.
+///
+/// The underlying [`SharedWriter`] is boxed, so that it does not appear in the
+/// bounds of [`BVGraphWriter`].
+type BitWriter<E> = BufBitWriter<E, WordAdapter<usize, Box<dyn Write>>>;
+
+/// The bit writer of the `.offsets` file, which is always big endian.
+type OffsetsWriter = BufBitWriter<BE, WordAdapter<usize, SharedWriter>>;
+
+/// A buffered file writer shared between a bit writer and a
+/// [`BVGraphWriter`], so that the latter can flush the words already written
+/// by the former without padding the bitstream.
+#[derive(Clone)]
+struct SharedWriter(Rc<RefCell<BufWriter<File>>>);
+
+impl SharedWriter {
+    fn new(file: File, capacity: usize) -> Self {
+        Self(Rc::new(RefCell::new(BufWriter::with_capacity(
+            capacity, file,
+        ))))
+    }
+}
+
+impl Write for SharedWriter {
+    #[inline(always)]
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.borrow_mut().write(buf)
+    }
+
+    #[inline(always)]
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.0.borrow_mut().write_all(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.borrow_mut().flush()
+    }
+}
+
+/// A streaming writer for BVGraphs.
+///
+/// Differently from [`BVComp::single_thread`], which needs a
+/// [`NodeLabelsLender`], this writer accepts successor lists one node at a
+/// time through [`push`](BVGraphWriter::push), so it can be fed by any
+/// producer (e.g., a database cursor) without buffering the graph.
+///
+/// Node ids must be strictly increasing, but they need not be contiguous:
+/// skipped nodes are written with an empty successor list. Successors must be
+/// sorted and without duplicates, as for [`BVComp::push`].
+///
+/// The `.graph` file (and, optionally, the `.offsets` file) is written while
+/// pushing, and the `.properties` file is written by
+/// [`close`](BVGraphWriter::close), which must be called to complete the
+/// graph. The number of nodes of the resulting graph is the largest pushed
+/// node id plus one: to add isolated nodes at the end of the graph, push an
+/// empty successor list for the last node.
+///
+/// The current position in the graph bitstream is available at any time
+/// from [`bit_position`](BVGraphWriter::bit_position), and
+/// [`flush`](BVGraphWriter::flush) makes the data written so far visible in
+/// the files, so that long-running writes can be checkpointed.
+pub struct BVGraphWriter<E: Endianness>
+where
+    BitWriter<E>: CodeWrite<E>,
+{
+    /// The basename of the graph.
+    basename: PathBuf,
+    /// The compression flags, which are serialized in the `.properties` file.
+    compression_flags: CompFlags,
+    /// The compressor.
+    bvcomp: BVComp<DynCodesEncoder<E, BitWriter<E>>>,
+    /// The file writer underlying the compressor.
+    graph_file: SharedWriter,
+    /// The writer for the `.offsets` file, if requested, and its underlying
+    /// file writer.
+    offsets_writer: Option<(OffsetsWriter, SharedWriter)>,
+    /// The next node to be written.
+    next_node: usize,
+    /// The number of bits written so far in the graph bitstream.
+    written_bits: u64,
+}
+
+impl<E: Endianness> BVGraphWriter<E>
+where
+    BitWriter<E>: CodeWrite<E>,
+{
+    /// Creates a new writer for a graph with the given basename.
+    ///
+    /// If `build_offsets` is true, the `.offsets` file is written, too. As
+    /// expected by its readers, the `.offsets` file is big endian,
+    /// independently of the endianness of the graph.
+    pub fn new(
+        basename: impl AsRef<Path>,
+        compression_flags: CompFlags,
+        build_offsets: bool,
+    ) -> Result<Self> {
+        let basename = basename.as_ref().to_owned();
+        let graph_path = basename.with_extension(GRAPH_EXTENSION);
+        let graph_file = SharedWriter::new(
+            File::create(&graph_path)
+                .with_context(|| format!("Could not create {}", graph_path.display()))?,
+            1 << 16,
+        );
+        let graph_write: Box<dyn Write> = Box::new(graph_file.clone());
+        let bit_write = <BufBitWriter<E, _>>::new(<WordAdapter<usize, _>>::new(graph_write));
+        let codes_writer = DynCodesEncoder::new(bit_write, &compression_flags);
+        let bvcomp = BVComp::new(
+            codes_writer,
+            compression_flags.compression_window,
+            compression_flags.max_ref_count,
+            compression_flags.min_interval_length,
+            0,
+        );
+
+        let offsets_writer = if build_offsets {
+            let offsets_path = basename.with_extension(OFFSETS_EXTENSION);
+            let file = File::create(&offsets_path)
+                .with_context(|| format!("Could not create {}", offsets_path.display()))?;
+            let offsets_file = SharedWriter::new(file, 1 << 20);
+            let mut writer =
+                <BufBitWriter<BE, _>>::new(<WordAdapter<usize, _>>::new(offsets_file.clone()));
+            writer
+                .write_gamma(0)
+                .context("Could not write initial delta")?;
+            Some((writer, offsets_file))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            basename,
+            compression_flags,
+            bvcomp,
+            graph_file,
+            offsets_writer,
+            next_node: 0,
+            written_bits: 0,
+        })
+    }
+
+    /// Returns the number of nodes written so far.
+    pub fn num_nodes(&self) -> usize {
+        self.next_node
+    }
+
+    /// Returns the number of arcs written so far.
+    pub fn num_arcs(&self) -> u64 {
+        self.bvcomp.arcs
+    }
+
+    /// Returns the current position in the graph bitstream, that is, the
+    /// number of bits written so far, including those still buffered.
+    ///
+    /// This is the offset of the next node, so it can be used to build
+    /// offsets incrementally.
+    pub fn bit_position(&self) -> u64 {
+        self.written_bits
+    }
+
+    /// Flushes to the underlying files the complete words written so far.
+    ///
+    /// Differently from [`BitWrite::flush`], this method does not pad the
+    /// bitstreams: the last, partial word of each bitstream (at most 64 bits)
+    /// remains buffered, so this method can be called at any time without
+    /// affecting subsequent writes.
+    pub fn flush(&mut self) -> Result<()> {
+        self.graph_file
+            .flush()
+            .context("Could not flush the graph")?;
+        if let Some((_, offsets_file)) = &mut self.offsets_writer {
+            offsets_file
+                .flush()
+                .context("Could not flush the offsets")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the successors of `node_id`, writing an empty successor list
+    /// for each node between the last pushed node and `node_id`.
+    ///
+    /// Returns the number of bits written, including those of skipped nodes.
+    pub fn push(
+        &mut self,
+        node_id: usize,
+        successors: impl IntoIterator<Item = usize>,
+    ) -> Result<u64> {
+        ensure!(
+            node_id >= self.next_node,
+            "Node ids must be strictly increasing, but node {} was pushed after node {}",
+            node_id,
+            self.next_node - 1
+        );
+        let mut written_bits = 0;
+        while self.next_node < node_id {
+            written_bits += self.push_successors(core::iter::empty())?;
+        }
+        written_bits += self.push_successors(successors)?;
+        Ok(written_bits)
+    }
+
+    fn push_successors(&mut self, successors: impl IntoIterator<Item = usize>) -> Result<u64> {
+        let delta = self
+            .bvcomp
+            .push(successors)
+            .with_context(|| format!("Could not push successors of node {}", self.next_node))?;
+        if let Some((writer, _)) = &mut self.offsets_writer {
+            writer.write_gamma(delta).context("Could not write delta")?;
+        }
+        self.written_bits += delta;
+        self.next_node += 1;
+        Ok(delta)
+    }
+
+    /// Flushes the bitstreams and writes the `.properties` file.
+    ///
+    /// Returns the length in bits of the graph bitstream.
+    pub fn close(self) -> Result<u64> {
+        let num_nodes = self.next_node;
+        let num_arcs = self.bvcomp.arcs;
+        self.bvcomp.flush().context("Could not flush bvcomp")?;
+        if let Some((mut writer, _)) = self.offsets_writer {
+            BitWrite::flush(&mut writer).context("Could not flush offsets")?;
+        }
+
+        let properties = self
+            .compression_flags
+            .to_properties::<E>(num_nodes, num_arcs)
+            .context("Could not serialize properties")?;
+        let properties_path = self.basename.with_extension(PROPERTIES_EXTENSION);
+        std::fs::write(&properties_path, properties)
+            .with_context(|| format!("Could not write {}", properties_path.display()))?;
+        Ok(self.written_bits)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use lender::*;
+
+    #[test]
+    fn test_writer_matches_batch() -> Result<()> {
+        let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+            .endianness::<BE>()
+            .load()?;
+        let tmp_dir = tempfile::tempdir()?;
+        let batch_basename = tmp_dir.path().join("batch");
+        let stream_basename = tmp_dir.path().join("stream");
+
+        let batch_bits = BVComp::single_thread::<BE, _>(
+            &batch_basename,
+            &graph,
+            CompFlags::default(),
+            true,
+            Some(graph.num_nodes()),
+        )?;
+
+        // Push only nodes with successors, so that the writer has to fill gaps
+        let mut writer = BVGraphWriter::<BE>::new(&stream_basename, CompFlags::default(), true)?;
+        for_!((node, succ) in graph.iter() {
+            let succ = succ.into_iter().collect::<Vec<_>>();
+            if !succ.is_empty() || node == graph.num_nodes() - 1 {
+                writer.push(node, succ)?;
+            }
+        });
+        assert!(writer.push(0, []).is_err());
+        assert_eq!(writer.num_nodes(), graph.num_nodes());
+        let stream_bits = writer.close()?;
+        assert_eq!(stream_bits, batch_bits);
+
+        for extension in [GRAPH_EXTENSION, OFFSETS_EXTENSION] {
+            assert_eq!(
+                std::fs::read(batch_basename.with_extension(extension))?,
+                std::fs::read(stream_basename.with_extension(extension))?
+            );
+        }
+
+        let stream_graph = BVGraphSeq::with_basename(&stream_basename)
+            .endianness::<BE>()
+            .load()?;
+        assert_eq!(stream_graph.num_nodes(), graph.num_nodes());
+        assert_eq!(stream_graph.num_arcs_hint(), graph.num_arcs_hint());
+        let mut iter = stream_graph.iter();
+        for_!((node, succ) in graph.iter() {
+            let (new_node, new_succ) = iter.next().unwrap();
+            assert_eq!(node, new_node);
+            assert!(succ.into_iter().eq(new_succ.into_iter()));
+        });
+        Ok(())
+    }
+
+    #[test]
+    fn test_bit_position_flush() -> Result<()> {
+        let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+            .endianness::<BE>()
+            .load()?;
+        let tmp_dir = tempfile::tempdir()?;
+        let basename = tmp_dir.path().join("stream");
+        let graph_path = basename.with_extension(GRAPH_EXTENSION);
+
+        let mut writer = BVGraphWriter::<BE>::new(&basename, CompFlags::default(), true)?;
+        let mut positions = vec![];
+        let mut flushed = vec![];
+        for_!((node, succ) in graph.iter() {
+            positions.push(writer.bit_position());
+            let written_bits = writer.push(node, succ)?;
+            assert_eq!(writer.bit_position(), positions[node] + written_bits);
+            if node == graph.num_nodes() / 2 {
+                // All complete words must be in the file
+                writer.flush()?;
+                flushed = std::fs::read(&graph_path)?;
+                let flushed_bits = flushed.len() as u64 * 8;
+                assert!(flushed_bits <= writer.bit_position());
+                assert!(writer.bit_position() - flushed_bits <= 64);
+            }
+        });
+        let final_position = writer.bit_position();
+        let bits = writer.close()?;
+        assert_eq!(bits, final_position);
+
+        // Flushing did not alter the bitstream
+        let bytes = std::fs::read(&graph_path)?;
+        assert_eq!(&bytes[..flushed.len()], flushed.as_slice());
+
+        // Positions are the offsets of the nodes, and the graph is unchanged
+        let stream_graph = BVGraphSeq::with_basename(&basename)
+            .endianness::<BE>()
+            .load()?;
+        let mut iter = stream_graph.iter();
+        for_!((node, succ) in graph.iter() {
+            assert_eq!(iter.bit_pos().unwrap(), positions[node]);
+            let (new_node, new_succ) = iter.next().unwrap();
+            assert_eq!(node, new_node);
+            assert!(succ.into_iter().eq(new_succ.into_iter()));
+        });
+        assert_eq!(iter.bit_pos().unwrap(), bits);
+        Ok(())
+    }
+
+    #[cfg(feature = "cli")]
+    #[test]
+    fn test_le_offsets() -> Result<()> {
+        use crate::cli::build::ef::{build_eliasfano, CliArgs};
+        let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+            .endianness::<BE>()
+            .load()?;
+        let tmp_dir = tempfile::tempdir()?;
+        let basename = tmp_dir.path().join("stream");
+
+        let mut writer = BVGraphWriter::<LE>::new(&basename, CompFlags::default(), true)?;
+        for_!((node, succ) in graph.iter() {
+            writer.push(node, succ)?;
+        });
+        writer.close()?;
+
+        // The .ef file is built from the big-endian .offsets file
+        build_eliasfano::<LE>(CliArgs {
+            basename: basename.clone(),
+            n: None,
+        })?;
+        let le_graph = BVGraph::with_basename(&basename)
+            .endianness::<LE>()
+            .load()?;
+        for_!((node, succ) in graph.iter() {
+            assert!(succ.into_iter().eq(le_graph.successors(node)));
+        });
+        Ok(())
+    }
+}
@@ -7,7 +7,7 @@
 
 use super::*;
 use crate::prelude::*;
-use anyhow::{Context, Result};
+use anyhow::{bail, ensure, Context, Result};
 use dsi_bitstream::prelude::*;
 use epserde::prelude::*;
 use sealed::sealed;
@@ -203,12 +203,49 @@ impl LoadMode for LoadMmap {
     }
 }
 
+/// The graph is decompressed on the fly from a zstd-compressed file with
+/// extension `.graph.zst`.
+///
+/// This mode supports only sequential access, as random access would require
+/// decompressing the whole graph: loading offsets always fails. Use it as
+/// [graph mode](LoadConfig::graph_mode) of a [`BVGraphSeq`].
+#[cfg(feature = "zstd")]
+#[derive(Debug, Clone)]
+pub struct Zstd {}
+#[cfg(feature = "zstd")]
+#[sealed]
+impl LoadMode for Zstd {
+    type Factory<E: Endianness> = ZstdFactory<E>;
+    type Offsets = EF;
+
+    fn new_factory<E: Endianness, P: AsRef<Path>>(
+        graph: P,
+        _flags: MemoryFlags,
+    ) -> Result<Self::Factory<E>> {
+        let mut path = graph.as_ref().as_os_str().to_owned();
+        path.push(".zst");
+        ZstdFactory::<E>::new(path)
+    }
+
+    fn load_offsets<P: AsRef<Path>>(
+        offsets: P,
+        _flags: MemoryFlags,
+    ) -> Result<MemCase<Self::Offsets>> {
+        anyhow::bail!(
+            "Cannot load offsets {} in zstd mode, which supports only sequential access",
+            offsets.as_ref().display()
+        )
+    }
+}
+
 #[doc(hidden)]
 #[derive(Debug, Clone)]
 pub struct LoadConfig<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode> {
     pub(crate) basename: PathBuf,
     pub(crate) graph_load_flags: MemoryFlags,
     pub(crate) offsets_load_flags: MemoryFlags,
+    pub(crate) verify: bool,
+    pub(crate) build_missing_ef: bool,
     pub(crate) _marker: std::marker::PhantomData<(E, A, D, GLM, OLM)>,
 }
 
@@ -216,11 +253,25 @@ impl<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode>
     LoadConfig<E, A, D, GLM, OLM>
 {
     /// Set the endianness of the graph and offsets file.
+    ///
+    /// Loading fails if the endianness does not match that recorded in the
+    /// properties file. To load graphs whose endianness is known only at
+    /// runtime, use [`get_endianness`] to dispatch to the right
+    /// instantiation, as in:
+    /// ```ignore
+    /// match get_endianness(&basename)?.as_str() {
+    ///     BE::NAME => visit(BVGraphSeq::with_basename(&basename).endianness::<BE>().load()?),
+    ///     LE::NAME => visit(BVGraphSeq::with_basename(&basename).endianness::<LE>().load()?),
+    ///     e => bail!("Unknown endianness: {}", e),
+    /// }
+    /// ```
     pub fn endianness<E2: Endianness>(self) -> LoadConfig<E2, A, D, GLM, OLM> {
         LoadConfig {
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -235,6 +286,8 @@ impl<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode>
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -249,11 +302,47 @@ impl<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode>
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
 }
 
+impl<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode>
+    LoadConfig<E, A, D, GLM, OLM>
+{
+    /// Scan the whole graph after loading it, checking that every successor
+    /// is smaller than the number of nodes and that the number of arcs is
+    /// the one recorded in the properties file.
+    ///
+    /// Loading fails at the first violation, so corrupted or truncated graph
+    /// files are detected before using the graph. Since a complete scan is
+    /// necessary, verification is disabled by default.
+    pub fn verify(self) -> LoadConfig<E, A, D, GLM, OLM> {
+        LoadConfig {
+            verify: true,
+            ..self
+        }
+    }
+}
+
+impl<E: Endianness, D: Dispatch, GLM: LoadMode, OLM: LoadMode> LoadConfig<E, Random, D, GLM, OLM> {
+    /// Build the Elias–Fano offsets file if it is missing.
+    ///
+    /// By default, loading a random-access graph without an `.ef` file fails.
+    /// With this option, the file is built from the `.obl` file of the Java
+    /// implementation, if present, or by a sequential scan of the graph, and
+    /// it is stored next to the graph, so the directory must be writable.
+    /// Errors in reading the `.obl` file are returned.
+    pub fn build_missing_ef(self) -> LoadConfig<E, Random, D, GLM, OLM> {
+        LoadConfig {
+            build_missing_ef: true,
+            ..self
+        }
+    }
+}
+
 impl<E: Endianness, A: Access, D: Dispatch> LoadConfig<E, A, D, Mmap, Mmap> {
     /// Set flags for memory-mapping (both graph and offsets).
     pub fn flags(self, flags: MemoryFlags) -> LoadConfig<E, A, D, Mmap, Mmap> {
@@ -261,6 +350,8 @@ impl<E: Endianness, A: Access, D: Dispatch> LoadConfig<E, A, D, Mmap, Mmap> {
             basename: self.basename,
             graph_load_flags: flags,
             offsets_load_flags: flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -273,6 +364,8 @@ impl<E: Endianness, A: Access, D: Dispatch> LoadConfig<E, A, D, LoadMmap, LoadMm
             basename: self.basename,
             graph_load_flags: flags,
             offsets_load_flags: flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -287,6 +380,8 @@ impl<E: Endianness, A: Access, D: Dispatch, GLM: LoadMode, OLM: LoadMode>
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -299,6 +394,8 @@ impl<E: Endianness, A: Access, D: Dispatch, OLM: LoadMode> LoadConfig<E, A, D, M
             basename: self.basename,
             graph_load_flags: flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -311,6 +408,8 @@ impl<E: Endianness, A: Access, D: Dispatch, OLM: LoadMode> LoadConfig<E, A, D, L
             basename: self.basename,
             graph_load_flags: flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -323,6 +422,8 @@ impl<E: Endianness, D: Dispatch, GLM: LoadMode, OLM: LoadMode> LoadConfig<E, Ran
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: self.offsets_load_flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -335,6 +436,8 @@ impl<E: Endianness, D: Dispatch, GLM: LoadMode> LoadConfig<E, Random, D, GLM, Mm
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -347,6 +450,8 @@ impl<E: Endianness, D: Dispatch, GLM: LoadMode> LoadConfig<E, Random, D, GLM, Lo
             basename: self.basename,
             graph_load_flags: self.graph_load_flags,
             offsets_load_flags: flags,
+            verify: self.verify,
+            build_missing_ef: self.build_missing_ef,
             _marker: std::marker::PhantomData,
         }
     }
@@ -367,15 +472,21 @@ impl<E: Endianness, GLM: LoadMode, OLM: LoadMode> LoadConfig<E, Random, Dynamic,
         self.basename.set_extension(GRAPH_EXTENSION);
         let factory = GLM::new_factory(&self.basename, self.graph_load_flags)?;
         self.basename.set_extension(EF_EXTENSION);
+        ensure_offsets(&self.basename, self.build_missing_ef)?;
         let offsets = OLM::load_offsets(&self.basename, self.offsets_load_flags)?;
 
-        Ok(BVGraph::new(
+        let graph = BVGraph::new(
             DynCodesDecoderFactory::new(factory, offsets, comp_flags)?,
             num_nodes,
             num_arcs,
             comp_flags.compression_window,
             comp_flags.min_interval_length,
-        ))
+            comp_flags.max_ref_count,
+        );
+        if self.verify {
+            verify(graph.iter(), num_nodes, num_arcs)?;
+        }
+        Ok(graph)
     }
 }
 
@@ -395,13 +506,17 @@ impl<E: Endianness, GLM: LoadMode, OLM: LoadMode> LoadConfig<E, Sequential, Dyna
         self.basename.set_extension(GRAPH_EXTENSION);
         let factory = GLM::new_factory(&self.basename, self.graph_load_flags)?;
 
-        Ok(BVGraphSeq::new(
+        let graph = BVGraphSeq::new(
             DynCodesDecoderFactory::new(factory, MemCase::from(EmptyDict::default()), comp_flags)?,
             num_nodes,
             Some(num_arcs),
             comp_flags.compression_window,
             comp_flags.min_interval_length,
-        ))
+        );
+        if self.verify {
+            verify(graph.iter(), num_nodes, num_arcs)?;
+        }
+        Ok(graph)
     }
 }
 
@@ -446,15 +561,21 @@ impl<
         self.basename.set_extension(GRAPH_EXTENSION);
         let factory = GLM::new_factory(&self.basename, self.graph_load_flags)?;
         self.basename.set_extension(EF_EXTENSION);
+        ensure_offsets(&self.basename, self.build_missing_ef)?;
         let offsets = OLM::load_offsets(&self.basename, self.offsets_load_flags)?;
 
-        Ok(BVGraph::new(
+        let graph = BVGraph::new(
             ConstCodesDecoderFactory::new(factory, offsets, comp_flags)?,
             num_nodes,
             num_arcs,
             comp_flags.compression_window,
             comp_flags.min_interval_length,
-        ))
+            comp_flags.max_ref_count,
+        );
+        if self.verify {
+            verify(graph.iter(), num_nodes, num_arcs)?;
+        }
+        Ok(graph)
     }
 }
 
@@ -504,7 +625,7 @@ impl<
         self.basename.set_extension(GRAPH_EXTENSION);
         let factory = GLM::new_factory(&self.basename, self.graph_load_flags)?;
 
-        Ok(BVGraphSeq::new(
+        let graph = BVGraphSeq::new(
             ConstCodesDecoderFactory::new(
                 factory,
                 MemCase::from(EmptyDict::default()),
@@ -514,8 +635,94 @@ impl<
             Some(num_arcs),
             comp_flags.compression_window,
             comp_flags.min_interval_length,
-        ))
+        );
+        if self.verify {
+            verify(graph.iter(), num_nodes, num_arcs)?;
+        }
+        Ok(graph)
+    }
+}
+
+/// Checks that the Elias–Fano offsets file exists, building it if requested.
+///
+/// If the `.ef` file is missing and `build` is false, an error suggesting how
+/// to build it is returned. Otherwise, the file is built from the `.obl` file
+/// of the Java implementation, if present, or by a sequential scan of the
+/// graph, and stored next to the graph.
+fn ensure_offsets(ef_path: &Path, build: bool) -> Result<()> {
+    if ef_path.exists() {
+        return Ok(());
+    }
+    let basename = ef_path.with_extension("");
+    let obl_path = ef_path.with_extension(OBL_EXTENSION);
+    if !build {
+        if obl_path.exists() {
+            bail!(
+                "Missing Elias-Fano pointer list {}: build it from {} with `build_ef_from_obl`, or load the graph with `build_missing_ef`",
+                ef_path.display(),
+                obl_path.display()
+            );
+        }
+        bail!(
+            "Missing Elias-Fano pointer list {}: build it with `webgraph build ef {}`, or load the graph with `build_missing_ef`",
+            ef_path.display(),
+            basename.display()
+        );
+    }
+    if obl_path.exists() {
+        log::info!(
+            "Building Elias-Fano pointer list {} from {}",
+            ef_path.display(),
+            obl_path.display()
+        );
+        build_ef_from_obl(&basename).with_context(|| {
+            format!(
+                "Cannot build Elias-Fano pointer list {} from {}",
+                ef_path.display(),
+                obl_path.display()
+            )
+        })?;
+    } else {
+        log::info!(
+            "Building Elias-Fano pointer list {} from the graph",
+            ef_path.display()
+        );
+        build_ef(&basename).with_context(|| {
+            format!("Cannot build Elias-Fano pointer list {}", ef_path.display())
+        })?;
+    }
+    Ok(())
+}
+
+/// Scans a graph using the given iterator, checking that successors are
+/// smaller than `num_nodes` and that the number of arcs is `num_arcs`.
+///
+/// Decoding errors, such as copy blocks or intervals out of range, are
+/// returned rather than causing a panic.
+fn verify<D: Decode>(mut iter: sequential::Iter<D>, num_nodes: usize, num_arcs: u64) -> Result<()> {
+    let mut arcs = 0_u64;
+    for node in 0..num_nodes {
+        let succ = iter
+            .next_successors()
+            .with_context(|| format!("Cannot decode the successors of node {}", node))?;
+        for &s in succ {
+            ensure!(
+                s < num_nodes,
+                "Node {} has successor {}, but the graph has {} nodes",
+                node,
+                s,
+                num_nodes
+            );
+        }
+        arcs += succ.len() as u64;
     }
+    ensure!(
+        arcs == num_arcs,
+        "The graph has {} arcs, but the properties file says {}",
+        arcs,
+        num_arcs
+    );
+    Ok(())
 }
 
 /// Read the .properties file and return the endianness
@@ -11,15 +11,20 @@ pub const GRAPH_EXTENSION: &str = "graph";
 pub const PROPERTIES_EXTENSION: &str = "properties";
 pub const OFFSETS_EXTENSION: &str = "offsets";
 pub const EF_EXTENSION: &str = "ef";
+/// The extension of the offsets cache of the Java implementation, which is a
+/// Java-serialized Elias–Fano list; it is read by [`load_obl`] and converted
+/// into an `.ef` file by [`build_ef_from_obl`].
+pub const OBL_EXTENSION: &str = "obl";
 pub const LABELS_EXTENSION: &str = "labels";
 pub const LABELOFFSETS_EXTENSION: &str = "labeloffsets";
 pub const DEG_CUMUL_EXTENSION: &str = "dcf";
+pub const IDS_EXTENSION: &str = "ids";
 
 mod offset_deg_iter;
 pub use offset_deg_iter::OffsetDegIter;
 
 pub mod sequential;
-pub use sequential::BVGraphSeq;
+pub use sequential::{BVGraphSeq, DecodeStats, NarrowestIter, NodeId};
 
 pub mod random_access;
 pub use random_access::BVGraph;
@@ -36,6 +41,12 @@ pub use comp::*;
 mod load;
 pub use load::*;
 
+mod offsets;
+pub use offsets::*;
+
+mod obl;
+pub use obl::*;
+
 /// The default version of EliasFano we use for the CLI.
 pub type EF = sux::dict::EliasFano<
     sux::rank_sel::SelectFixed2<sux::bits::CountBitVec, Vec<u64>, 8>,
@@ -47,3 +58,31 @@ pub type DCF = sux::dict::EliasFano<
     sux::rank_sel::SelectZeroFixed2<sux::bits::CountBitVec, Vec<u64>, 8>,
     sux::bits::BitFieldVec,
 >;
+
+/// Builds in memory the [cumulative function of degrees](DCF) of a graph.
+///
+/// The graph is scanned twice if its number of arcs is not
+/// [known exactly](SequentialLabeling::num_arcs_exact). To build the function
+/// of a graph on disk, use the `build dcf` command.
+#[doc(hidden)]
+pub fn build_dcf(graph: &impl SequentialGraph) -> anyhow::Result<DCF> {
+    use sux::traits::ConvertTo;
+    let num_arcs = match graph.num_arcs_exact() {
+        Some(num_arcs) => num_arcs as usize,
+        None => {
+            let mut num_arcs = 0;
+            lender::for_!((_node, succ) in graph.iter() {
+                num_arcs += succ.into_iter().count();
+            });
+            num_arcs
+        }
+    };
+    let mut efb = sux::dict::EliasFanoBuilder::new(graph.num_nodes() + 1, num_arcs + 1);
+    let mut cumul_deg = 0;
+    efb.push(0)?;
+    lender::for_!((_node, succ) in graph.iter() {
+        cumul_deg += succ.into_iter().count();
+        efb.push(cumul_deg)?;
+    });
+    efb.build().convert_to()
+}
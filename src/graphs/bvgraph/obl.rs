@@ -0,0 +1,619 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::*;
+use anyhow::{bail, ensure, Context, Result};
+use dsi_bitstream::prelude::*;
+use epserde::prelude::Serialize;
+use std::cell::RefCell;
+use std::io::{BufReader, Read};
+use std::path::Path;
+use std::rc::Rc;
+use sux::dict::EliasFanoBuilder;
+use sux::traits::{ConvertTo, IndexedDict};
+
+// Constants of the Java Object Serialization Stream Protocol
+const STREAM_MAGIC: u16 = 0xACED;
+const STREAM_VERSION: u16 = 5;
+const TC_NULL: u8 = 0x70;
+const TC_REFERENCE: u8 = 0x71;
+const TC_CLASSDESC: u8 = 0x72;
+const TC_OBJECT: u8 = 0x73;
+const TC_STRING: u8 = 0x74;
+const TC_ARRAY: u8 = 0x75;
+const TC_CLASS: u8 = 0x76;
+const TC_BLOCKDATA: u8 = 0x77;
+const TC_ENDBLOCKDATA: u8 = 0x78;
+const TC_RESET: u8 = 0x79;
+const TC_BLOCKDATALONG: u8 = 0x7A;
+const TC_EXCEPTION: u8 = 0x7B;
+const TC_LONGSTRING: u8 = 0x7C;
+const TC_PROXYCLASSDESC: u8 = 0x7D;
+const TC_ENUM: u8 = 0x7E;
+const BASE_WIRE_HANDLE: i32 = 0x7E0000;
+const SC_WRITE_METHOD: u8 = 0x01;
+const SC_EXTERNALIZABLE: u8 = 0x04;
+const SC_BLOCK_DATA: u8 = 0x08;
+
+/// A value read from a Java serialization stream.
+///
+/// Only the values needed to rebuild an Elias–Fano list are kept: the content
+/// of strings, of object arrays and of primitive arrays other than `long[]`
+/// is discarded.
+#[derive(Debug, Clone)]
+enum Value {
+    Null,
+    Primitive(i64),
+    LongArray(Rc<Vec<u64>>),
+    OtherArray,
+    ObjectArray,
+    String,
+    Object(Rc<RefCell<Object>>),
+    Class,
+}
+
+/// A deserialized object: the fields of all the classes in its hierarchy,
+/// from the topmost serializable superclass down.
+#[derive(Debug)]
+struct Object {
+    class_name: String,
+    fields: Vec<(String, Value)>,
+}
+
+impl Object {
+    /// Returns the value of the field with given name in the most specific
+    /// class declaring it.
+    fn field(&self, name: &str) -> Option<&Value> {
+        self.fields
+            .iter()
+            .rev()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v)
+    }
+}
+
+#[derive(Debug)]
+struct ClassDesc {
+    name: String,
+    flags: u8,
+    /// Type codes and names of the serializable fields.
+    fields: Vec<(u8, String)>,
+    super_desc: Option<Rc<ClassDesc>>,
+}
+
+#[derive(Debug, Clone)]
+enum Handle {
+    /// A handle assigned to an object whose reading is not complete.
+    Pending,
+    Desc(Rc<ClassDesc>),
+    Value(Value),
+}
+
+/// A minimal reader of the Java Object Serialization Stream Protocol.
+///
+/// It can parse any stream written by `ObjectOutputStream` (except for
+/// externalizable objects written with the obsolete protocol version 1),
+/// but it does not interpret the data written by custom `writeObject`
+/// methods, which is skipped.
+struct Deserializer<R: Read> {
+    reader: R,
+    handles: Vec<Handle>,
+}
+
+impl<R: Read> Deserializer<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            handles: vec![],
+        }
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.reader
+            .read_exact(&mut buf)
+            .context("Unexpected end of the serialization stream")?;
+        Ok(buf)
+    }
+
+    fn read_u8(&mut self) -> Result<u8> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.read_array()?))
+    }
+
+    fn read_i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.read_array()?))
+    }
+
+    fn read_i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.read_array()?))
+    }
+
+    fn skip(&mut self, len: u64) -> Result<()> {
+        let skipped = std::io::copy(&mut (&mut self.reader).take(len), &mut std::io::sink())?;
+        ensure!(skipped == len, "Unexpected end of the serialization stream");
+        Ok(())
+    }
+
+    /// Reads a string in modified UTF-8 of given length.
+    fn read_utf_bytes(&mut self, len: u64) -> Result<String> {
+        let mut bytes = vec![];
+        let read = (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+        ensure!(
+            read as u64 == len,
+            "Unexpected end of the serialization stream"
+        );
+        // Modified UTF-8 differs from UTF-8 only in the encoding of NUL and
+        // of supplementary characters, which do not appear in Java identifiers
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    fn read_utf(&mut self) -> Result<String> {
+        let len = self.read_u16()?;
+        self.read_utf_bytes(len as u64)
+    }
+
+    fn new_handle(&mut self) -> usize {
+        self.handles.push(Handle::Pending);
+        self.handles.len() - 1
+    }
+
+    fn handle(&mut self) -> Result<Handle> {
+        let wire_handle = self.read_i32()?;
+        let handle = wire_handle
+            .checked_sub(BASE_WIRE_HANDLE)
+            .and_then(|index| self.handles.get(usize::try_from(index).ok()?))
+            .with_context(|| format!("Invalid handle {:#x}", wire_handle))?;
+        if let Handle::Pending = handle {
+            bail!("Unsupported reference to an incomplete object");
+        }
+        Ok(handle.clone())
+    }
+
+    /// Reads an object of the stream.
+    fn content(&mut self) -> Result<Value> {
+        let type_code = self.read_u8()?;
+        self.object(type_code)
+    }
+
+    /// Reads an object of the stream whose type code has already been read.
+    fn object(&mut self, type_code: u8) -> Result<Value> {
+        Ok(match type_code {
+            TC_NULL => Value::Null,
+            TC_REFERENCE => match self.handle()? {
+                Handle::Value(value) => value,
+                _ => Value::Class,
+            },
+            TC_OBJECT => self.new_object()?,
+            TC_ARRAY => self.new_array()?,
+            TC_STRING => {
+                let len = self.read_u16()?;
+                self.new_string(len as u64)?
+            }
+            TC_LONGSTRING => {
+                let len = self.read_i64()?;
+                self.new_string(len as u64)?
+            }
+            TC_CLASS => {
+                self.class_desc()?;
+                let index = self.new_handle();
+                self.handles[index] = Handle::Value(Value::Class);
+                Value::Class
+            }
+            TC_ENUM => {
+                self.class_desc()?;
+                let index = self.new_handle();
+                let name = self.content()?;
+                self.handles[index] = Handle::Value(name.clone());
+                name
+            }
+            TC_CLASSDESC | TC_PROXYCLASSDESC => {
+                self.new_class_desc(type_code)?;
+                Value::Class
+            }
+            TC_RESET => {
+                self.handles.clear();
+                self.content()?
+            }
+            TC_EXCEPTION => bail!("The serialization stream contains an exception"),
+            _ => bail!("Unknown type code {:#x}", type_code),
+        })
+    }
+
+    fn new_string(&mut self, len: u64) -> Result<Value> {
+        let index = self.new_handle();
+        self.read_utf_bytes(len)?;
+        self.handles[index] = Handle::Value(Value::String);
+        Ok(Value::String)
+    }
+
+    /// Reads a class descriptor, which might be null.
+    fn class_desc(&mut self) -> Result<Option<Rc<ClassDesc>>> {
+        match self.read_u8()? {
+            TC_NULL => Ok(None),
+            TC_REFERENCE => match self.handle()? {
+                Handle::Desc(desc) => Ok(Some(desc)),
+                _ => bail!("A reference to a class descriptor was expected"),
+            },
+            type_code @ (TC_CLASSDESC | TC_PROXYCLASSDESC) => {
+                self.new_class_desc(type_code).map(Some)
+            }
+            type_code => bail!(
+                "A class descriptor was expected, but type code {:#x} was found",
+                type_code
+            ),
+        }
+    }
+
+    fn new_class_desc(&mut self, type_code: u8) -> Result<Rc<ClassDesc>> {
+        let (name, index, flags, mut fields) = if type_code == TC_PROXYCLASSDESC {
+            let index = self.new_handle();
+            let num_interfaces = self.read_i32()?;
+            for _ in 0..num_interfaces {
+                self.read_utf()?;
+            }
+            ("<proxy>".to_owned(), index, 0, vec![])
+        } else {
+            let name = self.read_utf()?;
+            let _serial_version_uid = self.read_i64()?;
+            let index = self.new_handle();
+            let flags = self.read_u8()?;
+            let num_fields = self.read_u16()?;
+            let mut fields = vec![];
+            for _ in 0..num_fields {
+                let field_type = self.read_u8()?;
+                let field_name = self.read_utf()?;
+                if field_type == b'L' || field_type == b'[' {
+                    // the class name of the field, as a string
+                    self.content()?;
+                }
+                fields.push((field_type, field_name));
+            }
+            (name, index, flags, fields)
+        };
+        self.annotation()?;
+        let super_desc = self.class_desc()?;
+        fields.shrink_to_fit();
+        let desc = Rc::new(ClassDesc {
+            name,
+            flags,
+            fields,
+            super_desc,
+        });
+        self.handles[index] = Handle::Desc(desc.clone());
+        Ok(desc)
+    }
+
+    /// Skips block data and objects up to the end of an annotation.
+    fn annotation(&mut self) -> Result<()> {
+        loop {
+            match self.read_u8()? {
+                TC_ENDBLOCKDATA => return Ok(()),
+                TC_BLOCKDATA => {
+                    let len = self.read_u8()?;
+                    self.skip(len as u64)?;
+                }
+                TC_BLOCKDATALONG => {
+                    let len = self.read_i32()?;
+                    self.skip(len as u32 as u64)?;
+                }
+                type_code => {
+                    self.object(type_code)?;
+                }
+            }
+        }
+    }
+
+    fn new_object(&mut self) -> Result<Value> {
+        let desc = self
+            .class_desc()?
+            .context("An object must have a class descriptor")?;
+        let index = self.new_handle();
+        let object = Rc::new(RefCell::new(Object {
+            class_name: desc.name.clone(),
+            fields: vec![],
+        }));
+        self.handles[index] = Handle::Value(Value::Object(object.clone()));
+
+        let mut hierarchy = vec![];
+        let mut current = Some(desc);
+        while let Some(desc) = current {
+            current = desc.super_desc.clone();
+            hierarchy.push(desc);
+        }
+        for desc in hierarchy.iter().rev() {
+            if desc.flags & SC_EXTERNALIZABLE != 0 {
+                ensure!(
+                    desc.flags & SC_BLOCK_DATA != 0,
+                    "Class {} has been serialized with protocol version 1, which is not supported",
+                    desc.name
+                );
+                self.annotation()?;
+                continue;
+            }
+            for (field_type, field_name) in &desc.fields {
+                let value = self.field_value(*field_type)?;
+                object.borrow_mut().fields.push((field_name.clone(), value));
+            }
+            if desc.flags & SC_WRITE_METHOD != 0 {
+                self.annotation()?;
+            }
+        }
+        Ok(Value::Object(object))
+    }
+
+    fn field_value(&mut self, field_type: u8) -> Result<Value> {
+        Ok(match field_type {
+            b'B' | b'Z' => Value::Primitive(self.read_u8()? as i8 as i64),
+            b'C' => Value::Primitive(self.read_u16()? as i64),
+            b'S' => Value::Primitive(self.read_u16()? as i16 as i64),
+            b'I' | b'F' => Value::Primitive(self.read_i32()? as i64),
+            b'J' | b'D' => Value::Primitive(self.read_i64()?),
+            b'L' | b'[' => self.content()?,
+            _ => bail!("Unknown field type {:?}", field_type as char),
+        })
+    }
+
+    fn new_array(&mut self) -> Result<Value> {
+        let desc = self
+            .class_desc()?
+            .context("An array must have a class descriptor")?;
+        let index = self.new_handle();
+        let len = self.read_i32()?;
+        ensure!(len >= 0, "Negative array length {}", len);
+        let len = len as u64;
+        let element_type = desc.name.as_bytes().get(1).copied().unwrap_or(0);
+        let value = match element_type {
+            b'J' => {
+                // read in chunks, so that a corrupted length cannot cause a
+                // huge allocation
+                let mut words = vec![];
+                let mut buf = vec![0; 1 << 16];
+                let mut to_read = len * 8;
+                while to_read != 0 {
+                    let chunk = &mut buf[..to_read.min(1 << 16) as usize];
+                    self.reader
+                        .read_exact(chunk)
+                        .context("Unexpected end of the serialization stream")?;
+                    words.extend(
+                        chunk
+                            .chunks_exact(8)
+                            .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap())),
+                    );
+                    to_read -= chunk.len() as u64;
+                }
+                Value::LongArray(Rc::new(words))
+            }
+            b'B' | b'Z' => {
+                self.skip(len)?;
+                Value::OtherArray
+            }
+            b'C' | b'S' => {
+                self.skip(len * 2)?;
+                Value::OtherArray
+            }
+            b'I' | b'F' => {
+                self.skip(len * 4)?;
+                Value::OtherArray
+            }
+            b'D' => {
+                self.skip(len * 8)?;
+                Value::OtherArray
+            }
+            b'L' | b'[' => {
+                for _ in 0..len {
+                    self.content()?;
+                }
+                Value::ObjectArray
+            }
+            _ => bail!("Unknown array class {}", desc.name),
+        };
+        self.handles[index] = Handle::Value(value.clone());
+        Ok(value)
+    }
+}
+
+/// The fields of a serialized `EliasFanoMonotoneLongBigList`.
+struct JavaEliasFano {
+    len: usize,
+    l: usize,
+    lower_bits: Rc<Vec<u64>>,
+    upper_bits: Rc<Vec<u64>>,
+}
+
+impl JavaEliasFano {
+    fn new(object: &Object) -> Result<Self> {
+        let primitive = |name: &str| match object.field(name) {
+            Some(Value::Primitive(value)) => Ok(*value),
+            _ => bail!(
+                "Object of class {} has no primitive field {}",
+                object.class_name,
+                name
+            ),
+        };
+        let long_array = |name: &str| match object.field(name) {
+            Some(Value::LongArray(words)) => Ok(words.clone()),
+            _ => bail!(
+                "Object of class {} has no long[] field {}",
+                object.class_name,
+                name
+            ),
+        };
+        let len = primitive("length")?;
+        let l = primitive("l")?;
+        ensure!(len >= 0, "Negative length {}", len);
+        ensure!((0..64).contains(&l), "Invalid number of lower bits {}", l);
+        let (len, l) = (len as usize, l as usize);
+        let lower_bits = long_array("lowerBits")?;
+        ensure!(
+            (lower_bits.len() as u128) * 64 >= len as u128 * l as u128,
+            "The lower bits of {} elements of {} bits need more than {} words",
+            len,
+            l,
+            lower_bits.len()
+        );
+        Ok(Self {
+            len,
+            l,
+            lower_bits,
+            upper_bits: long_array("upperBits")?,
+        })
+    }
+
+    /// Calls `f` on each element of the list, in order.
+    ///
+    /// As in sux4j, the lower bits of the elements are stored consecutively
+    /// starting from the least significant bit of the first word, and the
+    /// upper bits of the `i`-th element are given by the position of the
+    /// `i`-th one in the upper bits minus `i`.
+    fn for_each(&self, mut f: impl FnMut(u64) -> Result<()>) -> Result<()> {
+        let mut i = 0;
+        'words: for (word_index, &word) in self.upper_bits.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                if i == self.len {
+                    break 'words;
+                }
+                let pos = word_index * 64 + word.trailing_zeros() as usize;
+                word &= word - 1;
+                let upper = (pos - i) as u64;
+                ensure!(
+                    self.l == 0 || upper >> (64 - self.l) == 0,
+                    "Element {} does not fit in 64 bits",
+                    i
+                );
+                f(upper << self.l | self.lower(i))?;
+                i += 1;
+            }
+        }
+        ensure!(
+            i == self.len,
+            "The upper bits contain {} elements, but the length is {}",
+            i,
+            self.len
+        );
+        Ok(())
+    }
+
+    fn lower(&self, index: usize) -> u64 {
+        if self.l == 0 {
+            return 0;
+        }
+        let start = index * self.l;
+        let (word, bit) = (start / 64, start % 64);
+        let mut value = self.lower_bits[word] >> bit;
+        if bit + self.l > 64 {
+            value |= self.lower_bits[word + 1] << (64 - bit);
+        }
+        value & ((1 << self.l) - 1)
+    }
+}
+
+/// Reads an `.obl` file of the Java implementation, returning the Elias–Fano
+/// representation of the offsets it contains.
+///
+/// An `.obl` file contains the offsets of a graph (the bit offset of each
+/// node, followed by the length in bits of the bitstream) as a
+/// `it.unimi.dsi.sux4j.util.EliasFanoMonotoneLongBigList` serialized with
+/// the Java Object Serialization Stream Protocol. The stream is parsed
+/// generically, and the list is rebuilt from the fields `length`, `l`,
+/// `lowerBits` and `upperBits` of the serialized object; the other fields,
+/// such as the selection structure, are ignored.
+///
+/// Nothing is checked about the graph the offsets refer to: see
+/// [`build_ef_from_obl`].
+pub fn load_obl(path: impl AsRef<Path>) -> Result<EF> {
+    let path = path.as_ref();
+    let file =
+        std::fs::File::open(path).with_context(|| format!("Could not open {}", path.display()))?;
+    let mut deserializer = Deserializer::new(BufReader::new(file));
+    ensure!(
+        deserializer.read_u16()? == STREAM_MAGIC,
+        "{} is not a Java serialization stream",
+        path.display()
+    );
+    let version = deserializer.read_u16()?;
+    ensure!(
+        version == STREAM_VERSION,
+        "Unsupported Java serialization stream version {}",
+        version
+    );
+    let object = match deserializer
+        .content()
+        .with_context(|| format!("Could not parse {}", path.display()))?
+    {
+        Value::Object(object) => object,
+        _ => bail!("{} does not contain a Java object", path.display()),
+    };
+    let list = JavaEliasFano::new(&object.borrow())
+        .with_context(|| format!("Could not find an Elias-Fano list in {}", path.display()))?;
+
+    // The first pass computes the upper bound for the builder
+    let mut last = 0;
+    list.for_each(|value| {
+        ensure!(
+            value >= last,
+            "The offsets are not monotone: {} < {}",
+            value,
+            last
+        );
+        last = value;
+        Ok(())
+    })?;
+    let mut efb = EliasFanoBuilder::new(list.len, last as usize);
+    list.for_each(|value| efb.push(value as usize))?;
+    efb.build()
+        .convert_to()
+        .context("Could not build the selection structure")
+}
+
+/// Builds and stores the `.ef` file of the graph with given basename from the
+/// `.obl` file of the Java implementation, returning the Elias–Fano
+/// representation of the offsets.
+///
+/// The offsets are read with [`load_obl`], and they are checked to be as
+/// many as the nodes of the graph plus one, and not to exceed the length in
+/// bits of the `.graph` file. A preexisting `.ef` file is overwritten.
+pub fn build_ef_from_obl(basename: impl AsRef<Path>) -> Result<EF> {
+    let basename = basename.as_ref();
+    let properties_path = basename.with_extension(PROPERTIES_EXTENSION);
+    let (num_nodes, _, _) = match get_endianness(basename)?.as_str() {
+        BE::NAME => parse_properties::<BE>(&properties_path)?,
+        LE::NAME => parse_properties::<LE>(&properties_path)?,
+        e => bail!("Unknown endianness: {}", e),
+    };
+    let graph_path = basename.with_extension(GRAPH_EXTENSION);
+    let graph_bits = 8 * std::fs::metadata(&graph_path)
+        .with_context(|| format!("Could not stat {}", graph_path.display()))?
+        .len();
+
+    let obl_path = basename.with_extension(OBL_EXTENSION);
+    let ef = load_obl(&obl_path)?;
+    ensure!(
+        ef.len() == num_nodes + 1,
+        "{} contains {} offsets, but the graph has {} nodes",
+        obl_path.display(),
+        ef.len(),
+        num_nodes
+    );
+    let last = ef.get(num_nodes) as u64;
+    ensure!(
+        last <= graph_bits,
+        "{} contains offset {}, but {} has {} bits",
+        obl_path.display(),
+        last,
+        graph_path.display(),
+        graph_bits
+    );
+
+    let ef_path = basename.with_extension(EF_EXTENSION);
+    ef.store(&ef_path)
+        .with_context(|| format!("Could not store {}", ef_path.display()))?;
+    Ok(ef)
+}
@@ -109,6 +109,14 @@ impl<D: Decode> OffsetDegIter<D> {
         };
         // if we copy nodes from a previous one
         if ref_delta != 0 {
+            // a reference outside the window would read stale data
+            anyhow::ensure!(
+                ref_delta <= self.compression_window && ref_delta <= self.node_id,
+                "Node {} has reference offset {}, but the compression window is {}; maybe the properties do not match the graph",
+                self.node_id,
+                ref_delta,
+                self.compression_window
+            );
             // compute the node id of the reference
             let reference_node_id = self.node_id - ref_delta;
             // retrieve the data
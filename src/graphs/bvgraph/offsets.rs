@@ -0,0 +1,246 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use super::*;
+use anyhow::{bail, Context, Result};
+use dsi_bitstream::prelude::*;
+use dsi_progress_logger::prelude::*;
+use epserde::prelude::Serialize;
+use std::io::BufWriter;
+use std::path::Path;
+use sux::dict::EliasFanoBuilder;
+use sux::traits::ConvertTo;
+
+/// Builds the `.offsets` file of the graph with the given basename, detecting
+/// its endianness from the `.properties` file.
+///
+/// See [`build_offsets_with_endianness`].
+pub fn build_offsets(basename: impl AsRef<Path>) -> Result<()> {
+    let basename = basename.as_ref();
+    match get_endianness(basename)?.as_str() {
+        BE::NAME => build_offsets_with_endianness::<BE>(basename),
+        LE::NAME => build_offsets_with_endianness::<LE>(basename),
+        e => bail!("Unknown endianness: {}", e),
+    }
+}
+
+/// Builds the `.offsets` file of the graph with the given basename and
+/// endianness.
+///
+/// The graph is scanned using an [`OffsetDegIter`], and the differences
+/// between the bit offsets of consecutive nodes, followed by the difference
+/// between the length of the bitstream and the offset of the last node, are
+/// written as big-endian γ codes, as in the Java implementation. A
+/// preexisting `.offsets` file is overwritten.
+pub fn build_offsets_with_endianness<E: Endianness + 'static>(
+    basename: impl AsRef<Path>,
+) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let basename = basename.as_ref();
+    // Create the sequential iterator over the graph
+    let seq_graph = BVGraphSeq::with_basename(basename)
+        .endianness::<E>()
+        .load()?;
+    let offsets = basename.with_extension(OFFSETS_EXTENSION);
+    let file = std::fs::File::create(&offsets)
+        .with_context(|| format!("Could not create {}", offsets.display()))?;
+    // create a bit writer on the file
+    let mut writer = <BufBitWriter<BE, _>>::new(<WordAdapter<u64, _>>::new(
+        BufWriter::with_capacity(1 << 20, file),
+    ));
+    // progress bar
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true)
+        .item_name("offset")
+        .expected_updates(Some(seq_graph.num_nodes()));
+    pl.start("Computing offsets...");
+    // read the graph a write the offsets
+    let mut offset = 0;
+    let mut degs_iter = seq_graph.offset_deg_iter();
+    for (new_offset, _degree) in &mut degs_iter {
+        // write where
+        writer
+            .write_gamma((new_offset - offset) as _)
+            .context("Could not write gamma")?;
+        offset = new_offset;
+        // decode the next nodes so we know where the next node_id starts
+        pl.light_update();
+    }
+    // write the last offset, this is done to avoid decoding the last node
+    writer
+        .write_gamma((degs_iter.get_pos() - offset) as _)
+        .context("Could not write final gamma")?;
+    BitWrite::flush(&mut writer).context("Could not flush offsets")?;
+    pl.light_update();
+    pl.done();
+    Ok(())
+}
+
+/// Builds the `.offsets` file of the graph with the given basename, detecting
+/// its endianness from the `.properties` file, writing offsets in a separate
+/// thread.
+///
+/// See [`build_offsets_pipelined_with_endianness`].
+pub fn build_offsets_pipelined(basename: impl AsRef<Path>) -> Result<()> {
+    let basename = basename.as_ref();
+    match get_endianness(basename)?.as_str() {
+        BE::NAME => build_offsets_pipelined_with_endianness::<BE>(basename),
+        LE::NAME => build_offsets_pipelined_with_endianness::<LE>(basename),
+        e => bail!("Unknown endianness: {}", e),
+    }
+}
+
+/// Builds the `.offsets` file of the graph with the given basename and
+/// endianness, writing offsets in a separate thread.
+///
+/// The result is bit-for-bit identical to that of
+/// [`build_offsets_with_endianness`].
+///
+/// Note that the bitstream of a graph cannot be split into ranges scanned
+/// independently: the position at which a node starts is known only after
+/// decoding all previous nodes, so decoding is inherently sequential. Thus,
+/// this function uses exactly two threads, overlapping decoding with the
+/// γ-coding and writing of offsets, which are performed by a separate thread
+/// on batches of offsets.
+pub fn build_offsets_pipelined_with_endianness<E: Endianness + 'static>(
+    basename: impl AsRef<Path>,
+) -> Result<()>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    /// The number of offsets passed at a time to the writing thread.
+    const BATCH_SIZE: usize = 1 << 16;
+    /// The number of batches that can be queued for the writing thread.
+    const QUEUE_LEN: usize = 4;
+
+    let basename = basename.as_ref();
+    let seq_graph = BVGraphSeq::with_basename(basename)
+        .endianness::<E>()
+        .load()?;
+    let offsets = basename.with_extension(OFFSETS_EXTENSION);
+    let file = std::fs::File::create(&offsets)
+        .with_context(|| format!("Could not create {}", offsets.display()))?;
+
+    let (tx, rx) = std::sync::mpsc::sync_channel::<Vec<u64>>(QUEUE_LEN);
+
+    std::thread::scope(|s| {
+        let writer_thread = s.spawn(move || -> Result<()> {
+            let mut writer = <BufBitWriter<BE, _>>::new(<WordAdapter<u64, _>>::new(
+                BufWriter::with_capacity(1 << 20, file),
+            ));
+            let mut offset = 0;
+            for batch in rx {
+                for new_offset in batch {
+                    writer
+                        .write_gamma(new_offset - offset)
+                        .context("Could not write gamma")?;
+                    offset = new_offset;
+                }
+            }
+            BitWrite::flush(&mut writer).context("Could not flush offsets")?;
+            Ok(())
+        });
+
+        let mut pl = ProgressLogger::default();
+        pl.display_memory(true)
+            .item_name("offset")
+            .expected_updates(Some(seq_graph.num_nodes()));
+        pl.start("Computing offsets...");
+        let mut degs_iter = seq_graph.offset_deg_iter();
+        let mut batch = Vec::with_capacity(BATCH_SIZE);
+        // if sending fails, the writing thread has stopped because of an
+        // error, which is reported when joining it
+        let mut sent = true;
+        for (offset, _degree) in &mut degs_iter {
+            batch.push(offset);
+            if batch.len() == BATCH_SIZE {
+                sent = tx.send(core::mem::take(&mut batch)).is_ok();
+                if !sent {
+                    break;
+                }
+                batch.reserve(BATCH_SIZE);
+            }
+            pl.light_update();
+        }
+        if sent {
+            // the position after the last node
+            batch.push(degs_iter.get_pos());
+            let _ = tx.send(batch);
+        }
+        drop(tx);
+        pl.done();
+
+        writer_thread
+            .join()
+            .map_err(|_| anyhow::anyhow!("The writing thread panicked"))?
+    })
+}
+
+/// Builds and stores the `.ef` file of the graph with the given basename,
+/// detecting its endianness from the `.properties` file.
+///
+/// See [`build_ef_with_endianness`].
+pub fn build_ef(basename: impl AsRef<Path>) -> Result<EF> {
+    let basename = basename.as_ref();
+    match get_endianness(basename)?.as_str() {
+        BE::NAME => build_ef_with_endianness::<BE>(basename),
+        LE::NAME => build_ef_with_endianness::<LE>(basename),
+        e => bail!("Unknown endianness: {}", e),
+    }
+}
+
+/// Builds and stores the `.ef` file of the graph with the given basename and
+/// endianness, returning the Elias–Fano representation of the offsets.
+///
+/// The offsets are computed by a sequential scan of the graph using an
+/// [`OffsetDegIter`], so no `.offsets` file is needed, and are never
+/// materialized in a vector. The result contains the bit offset of each node,
+/// followed by the length in bits of the bitstream, and provides constant-time
+/// access through [`IndexedDict::get`](sux::traits::IndexedDict::get). It is
+/// the representation used by [`BVGraph`] for random access, which
+/// memory-maps or loads the `.ef` file depending on the
+/// [offsets load mode](LoadConfig::offsets_mode). A preexisting `.ef` file is
+/// overwritten.
+pub fn build_ef_with_endianness<E: Endianness + 'static>(basename: impl AsRef<Path>) -> Result<EF>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let basename = basename.as_ref();
+    let seq_graph = BVGraphSeq::with_basename(basename)
+        .endianness::<E>()
+        .load()?;
+    let graph_path = basename.with_extension(GRAPH_EXTENSION);
+    let graph_bits = 8 * std::fs::metadata(&graph_path)
+        .with_context(|| format!("Could not stat {}", graph_path.display()))?
+        .len();
+
+    let mut efb = EliasFanoBuilder::new(seq_graph.num_nodes() + 1, graph_bits as usize);
+    let mut pl = ProgressLogger::default();
+    pl.display_memory(true)
+        .item_name("offset")
+        .expected_updates(Some(seq_graph.num_nodes()));
+    pl.start("Building Elias-Fano...");
+    let mut degs_iter = seq_graph.offset_deg_iter();
+    for (offset, _degree) in &mut degs_iter {
+        efb.push(offset as usize).context("Could not push offset")?;
+        pl.light_update();
+    }
+    efb.push(degs_iter.get_pos() as usize)
+        .context("Could not push final offset")?;
+    pl.done();
+
+    let ef: EF = efb
+        .build()
+        .convert_to()
+        .context("Could not build the selection structure")?;
+    let ef_path = basename.with_extension(EF_EXTENSION);
+    ef.store(&ef_path)
+        .with_context(|| format!("Could not store {}", ef_path.display()))?;
+    Ok(ef)
+}
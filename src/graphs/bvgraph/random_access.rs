@@ -22,6 +22,7 @@ pub struct BVGraph<F> {
     number_of_arcs: u64,
     compression_window: usize,
     min_interval_length: usize,
+    max_ref_count: usize,
 }
 
 impl BVGraph<()> {
@@ -32,6 +33,8 @@ impl BVGraph<()> {
             basename: PathBuf::from(basename.as_ref()),
             graph_load_flags: Flags::empty(),
             offsets_load_flags: Flags::empty(),
+            verify: false,
+            build_missing_ef: false,
             _marker: std::marker::PhantomData,
         }
     }
@@ -41,8 +44,14 @@ impl<F: RandomAccessDecoderFactory> SplitLabeling for BVGraph<F>
 where
     for<'a> <F as RandomAccessDecoderFactory>::Decoder<'a>: Send + Sync,
 {
-    type SplitLender<'a> = split::ra::Lender<'a, BVGraph<F>> where Self: 'a;
-    type IntoIterator<'a> = split::ra::IntoIterator<'a, BVGraph<F>> where Self: 'a;
+    type SplitLender<'a>
+        = split::ra::Lender<'a, BVGraph<F>>
+    where
+        Self: 'a;
+    type IntoIterator<'a>
+        = split::ra::IntoIterator<'a, BVGraph<F>>
+    where
+        Self: 'a;
 
     fn split_iter(&self, how_many: usize) -> Self::IntoIterator<'_> {
         split::ra::Iter::new(self, how_many)
@@ -67,6 +76,7 @@ where
     /// reference each other.
     /// - `number_of_nodes`: the number of nodes in the graph.
     /// - `number_of_arcs`: the number of arcs in the graph.
+    /// - `max_ref_count`: the maximum length of a chain of references.
     ///
     pub fn new(
         factory: F,
@@ -74,6 +84,7 @@ where
         number_of_arcs: u64,
         compression_window: usize,
         min_interval_length: usize,
+        max_ref_count: usize,
     ) -> Self {
         Self {
             factory,
@@ -81,6 +92,7 @@ where
             number_of_arcs,
             compression_window,
             min_interval_length,
+            max_ref_count,
         }
     }
 
@@ -89,6 +101,27 @@ where
     pub fn into_inner(self) -> F {
         self.factory
     }
+
+    /// Return the number of bits used to encode the successor list of a node,
+    /// or `None` if the factory does not provide
+    /// [offsets](RandomAccessDecoderFactory::offset).
+    ///
+    /// The result is computed by difference of offsets, so no decoding is
+    /// involved, and it is exact.
+    ///
+    /// # Panics
+    ///
+    /// If `node_id` is not smaller than the number of nodes.
+    #[inline(always)]
+    pub fn estimated_node_bits(&self, node_id: usize) -> Option<u64> {
+        assert!(
+            node_id < self.number_of_nodes,
+            "Node {} out of range 0..{}",
+            node_id,
+            self.number_of_nodes
+        );
+        Some(self.factory.offset(node_id + 1)? - self.factory.offset(node_id)?)
+    }
 }
 
 impl<F> SequentialLabeling for BVGraph<F>
@@ -96,7 +129,8 @@ where
     F: RandomAccessDecoderFactory,
 {
     type Label = usize;
-    type Lender<'b> = Iter<F::Decoder<'b>>
+    type Lender<'b>
+        = Iter<F::Decoder<'b>>
     where
         Self: 'b,
         F: 'b;
@@ -111,6 +145,11 @@ where
         Some(self.number_of_arcs)
     }
 
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.number_of_arcs)
+    }
+
     /// Return a fast sequential iterator over the nodes of the graph and their successors.
     fn iter_from(&self, start_node: usize) -> Self::Lender<'_> {
         let codes_reader = self.factory.new_decoder(start_node).unwrap();
@@ -124,10 +163,13 @@ where
         Iter {
             decoder: codes_reader,
             backrefs,
+            unsorted: CircularBuffer::new(self.compression_window + 1),
+            has_unsorted: false,
             compression_window: self.compression_window,
             min_interval_length: self.min_interval_length,
             number_of_nodes: self.number_of_nodes,
             current_node: start_node,
+            stats: None,
         }
     }
 }
@@ -138,15 +180,28 @@ impl<F> RandomAccessLabeling for BVGraph<F>
 where
     F: RandomAccessDecoderFactory,
 {
-    type Labels<'a> = Succ<F::Decoder<'a>>
-    where Self: 'a, F: 'a;
+    type Labels<'a>
+        = Succ<F::Decoder<'a>>
+    where
+        Self: 'a,
+        F: 'a;
 
     fn num_arcs(&self) -> u64 {
         self.number_of_arcs
     }
 
     /// Return the outdegree of a node.
+    ///
+    /// # Panics
+    ///
+    /// If `node_id` is not smaller than the number of nodes.
     fn outdegree(&self, node_id: usize) -> usize {
+        assert!(
+            node_id < self.number_of_nodes,
+            "Node {} out of range 0..{}",
+            node_id,
+            self.number_of_nodes
+        );
         let mut codes_reader = self
             .factory
             .new_decoder(node_id)
@@ -156,7 +211,28 @@ where
 
     #[inline(always)]
     /// Return a random access iterator over the successors of a node.
+    ///
+    /// Only the bit offset of `node_id` is looked up, so when the offsets are
+    /// memory-mapped (the default) only the pages containing the offsets and
+    /// the successor lists actually accessed are read. The back-reference
+    /// window is not reconstructed: if the successor list of the node refers
+    /// to a previous list, the latter is decoded recursively starting from
+    /// its own offset. Since a chain of references is at most
+    /// `maxrefcount` long, in the worst case `maxrefcount` + 1 successor
+    /// lists, each from a node at most `windowsize` nodes before the
+    /// previous one, are decoded; lists that do not use references are
+    /// decoded in isolation.
+    ///
+    /// # Panics
+    ///
+    /// If `node_id` is not smaller than the number of nodes.
     fn labels(&self, node_id: usize) -> Succ<F::Decoder<'_>> {
+        assert!(
+            node_id < self.number_of_nodes,
+            "Node {} out of range 0..{}",
+            node_id,
+            self.number_of_nodes
+        );
         let codes_reader = self
             .factory
             .new_decoder(node_id)
@@ -269,6 +345,203 @@ impl<F: RandomAccessDecoderFactory> BVGraph<F>
 where
     for<'a> F::Decoder<'a>: Decode,
 {
+    /// Returns the successors of a node in a vector, decoding only its
+    /// successor list and the lists it refers to.
+    ///
+    /// Differently from [`successors`](RandomAccessGraph::successors), which
+    /// returns a lazy iterator and panics on invalid data, this method decodes
+    /// the whole list eagerly, and returns an error if `node_id` is out of
+    /// range or if the list cannot be decoded (e.g., because of a reference,
+    /// a copy block or an interval out of range, or of a chain of references
+    /// longer than `maxrefcount`, in a corrupted graph).
+    ///
+    /// Only the offsets of `node_id` and of the nodes it refers to are looked
+    /// up, so if the offsets are memory-mapped (the default) the rest of the
+    /// offsets is never read, and no back-reference window is reconstructed:
+    /// the chain of references is followed first, and then the lists are
+    /// decoded starting from the last one, each from its own offset.
+    ///
+    /// # Cost
+    ///
+    /// Since a chain of references is at most `maxrefcount` long, in the worst
+    /// case `maxrefcount` + 1 successor lists are decoded, each from a node at
+    /// most `windowsize` nodes before the previous one. Lists that do not use
+    /// references are decoded in isolation.
+    pub fn successors_at(&self, node_id: usize) -> anyhow::Result<Vec<usize>> {
+        anyhow::ensure!(
+            node_id < self.number_of_nodes,
+            "Node {} out of range 0..{}",
+            node_id,
+            self.number_of_nodes
+        );
+        // follow the chain of references, keeping each decoder positioned
+        // after the reference offset
+        let mut chain = vec![];
+        let mut node = node_id;
+        loop {
+            let mut decoder = self.factory.new_decoder(node)?;
+            let degree = decoder.read_outdegree() as usize;
+            anyhow::ensure!(
+                degree <= self.number_of_nodes,
+                "Node {} has outdegree {}, but the graph has {} nodes",
+                node,
+                degree,
+                self.number_of_nodes
+            );
+            let ref_delta = if degree != 0 && self.compression_window != 0 {
+                decoder.read_reference_offset() as usize
+            } else {
+                0
+            };
+            anyhow::ensure!(
+                ref_delta <= node && ref_delta <= self.compression_window,
+                "Node {} has reference offset {}, but the compression window is {}",
+                node,
+                ref_delta,
+                self.compression_window
+            );
+            chain.push((node, decoder, degree, ref_delta));
+            if ref_delta == 0 {
+                break;
+            }
+            anyhow::ensure!(
+                chain.len() <= self.max_ref_count,
+                "The chain of references of node {} is longer than the maximum reference count {}",
+                node_id,
+                self.max_ref_count
+            );
+            node -= ref_delta;
+        }
+        // decode the lists, each using the next one in the chain
+        let mut successors = vec![];
+        for (node, decoder, degree, ref_delta) in chain.into_iter().rev() {
+            successors = self.decode_list(node, decoder, degree, ref_delta, &successors)?;
+        }
+        Ok(successors)
+    }
+
+    /// Decodes the successor list of `node_id`, whose outdegree and reference
+    /// offset have already been read from `decoder`, given the successors of
+    /// its reference (empty if there is no reference).
+    fn decode_list(
+        &self,
+        node_id: usize,
+        mut decoder: F::Decoder<'_>,
+        degree: usize,
+        ref_delta: usize,
+        neighbours: &[usize],
+    ) -> anyhow::Result<Vec<usize>> {
+        let overflow = || {
+            anyhow::anyhow!(
+                "The successor list of node {} is corrupted: its successors overflow",
+                node_id
+            )
+        };
+        let mut results = Vec::with_capacity(degree);
+        // if we copy nodes from a previous one
+        if ref_delta != 0 {
+            // get the info on which destinations to copy
+            let number_of_blocks = decoder.read_block_count() as usize;
+            // no blocks, we copy everything
+            if number_of_blocks == 0 {
+                results.extend_from_slice(neighbours);
+            } else {
+                // otherwise we copy only the blocks of even index
+                let mut idx = 0;
+                for block_id in 0..number_of_blocks {
+                    // the first block could be zero, while the others can't
+                    let block = (decoder.read_block() as usize)
+                        .checked_add((block_id != 0) as usize)
+                        .ok_or_else(overflow)?;
+                    anyhow::ensure!(
+                        block <= neighbours.len() - idx,
+                        "The copy blocks of node {} exceed the {} successors of its reference",
+                        node_id,
+                        neighbours.len()
+                    );
+                    let end = idx + block;
+                    if block_id % 2 == 0 {
+                        results.extend_from_slice(&neighbours[idx..end]);
+                    }
+                    idx = end;
+                }
+                if number_of_blocks & 1 == 0 {
+                    results.extend_from_slice(&neighbours[idx..]);
+                }
+            }
+        }
+        anyhow::ensure!(
+            results.len() <= degree,
+            "Node {} copies {} successors, but its outdegree is {}",
+            node_id,
+            results.len(),
+            degree
+        );
+
+        // if we still have to read nodes
+        if results.len() != degree && self.min_interval_length != 0 {
+            // read the number of intervals
+            let number_of_intervals = decoder.read_interval_count() as usize;
+            let mut end = 0_usize;
+            for interval_id in 0..number_of_intervals {
+                // the first interval starts relatively to the node
+                let start = if interval_id == 0 {
+                    let first = (node_id as i64)
+                        .checked_add(nat2int(decoder.read_interval_start()))
+                        .ok_or_else(overflow)?;
+                    anyhow::ensure!(
+                        first >= 0,
+                        "The first interval of node {} starts at negative node {}",
+                        node_id,
+                        first
+                    );
+                    first as usize
+                } else {
+                    (decoder.read_interval_start() as usize)
+                        .checked_add(1)
+                        .and_then(|gap| end.checked_add(gap))
+                        .ok_or_else(overflow)?
+                };
+                let delta =
+                    (decoder.read_interval_len() as usize).saturating_add(self.min_interval_length);
+                anyhow::ensure!(
+                    delta <= degree - results.len(),
+                    "The intervals of node {} exceed its outdegree {}",
+                    node_id,
+                    degree
+                );
+                end = start.checked_add(delta).ok_or_else(overflow)?;
+                results.extend(start..end);
+            }
+        }
+
+        // decode the residuals
+        let nodes_left_to_decode = degree - results.len();
+        if nodes_left_to_decode != 0 {
+            let first = (node_id as i64)
+                .checked_add(nat2int(decoder.read_first_residual()))
+                .ok_or_else(overflow)?;
+            anyhow::ensure!(
+                first >= 0,
+                "The first residual of node {} is the negative node {}",
+                node_id,
+                first
+            );
+            let mut extra = first as usize;
+            results.push(extra);
+            for _ in 1..nodes_left_to_decode {
+                extra = (decoder.read_residual() as usize)
+                    .checked_add(1)
+                    .and_then(|gap| extra.checked_add(gap))
+                    .ok_or_else(overflow)?;
+                results.push(extra);
+            }
+        }
+
+        results.sort();
+        Ok(results)
+    }
+
     #[inline(always)]
     /// Creates an iterator specialized in the degrees of the nodes.
     /// This is slightly faster because it can avoid decoding some of the nodes
@@ -282,7 +555,20 @@ where
         )
     }
 }
-impl<F> RandomAccessGraph for BVGraph<F> where F: RandomAccessDecoderFactory {}
+impl<F> RandomAccessGraph for BVGraph<F>
+where
+    F: RandomAccessDecoderFactory,
+{
+    /// Returns whether there is an arc going from `src_node_id` to `dst_node_id`.
+    ///
+    /// Since successors are returned in sorted order, decoding stops at the
+    /// first successor larger than or equal to `dst_node_id`.
+    fn has_arc(&self, src_node_id: usize, dst_node_id: usize) -> bool {
+        self.successors(src_node_id)
+            .find(|&succ| succ >= dst_node_id)
+            == Some(dst_node_id)
+    }
+}
 
 /// The iterator returend from [`BVGraph`] that returns the successors of a
 /// node in sorted order.
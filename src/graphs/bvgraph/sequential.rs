@@ -10,11 +10,16 @@ use std::path::PathBuf;
 use super::*;
 use crate::utils::nat2int;
 use crate::utils::CircularBuffer;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use bitflags::Flags;
+use dsi_bitstream::prelude::*;
 use dsi_bitstream::traits::BitSeek;
 use dsi_bitstream::traits::BE;
+use dsi_progress_logger::prelude::*;
+use epserde::prelude::MemCase;
 use lender::*;
+use sealed::sealed;
+use sux::traits::IndexedDict;
 
 /// A sequential BVGraph that can be read from a `codes_reader_builder`.
 /// The builder is needed because we should be able to create multiple iterators
@@ -29,6 +34,21 @@ pub struct BVGraphSeq<F> {
 }
 
 impl BVGraphSeq<()> {
+    /// Returns a load configuration for the sequential graph with the given
+    /// basename.
+    ///
+    /// By default, the graph is memory-mapped without any advice; when
+    /// scanning large graphs on cold caches or on spinning disks, hinting
+    /// sequential access using [`flags`](LoadConfig::flags) might increase
+    /// throughput significantly:
+    /// ```ignore
+    /// let graph = BVGraphSeq::with_basename(&basename)
+    ///     .endianness::<BE>()
+    ///     .flags(MemoryFlags::SEQUENTIAL)
+    ///     .load()?;
+    /// ```
+    /// Flags are just hints, so they do not affect the data returned by
+    /// iterators, including [`offset_deg_iter`](BVGraphSeq::offset_deg_iter).
     pub fn with_basename(
         basename: impl AsRef<std::path::Path>,
     ) -> LoadConfig<BE, Sequential, Dynamic, Mmap, Mmap> {
@@ -36,17 +56,68 @@ impl BVGraphSeq<()> {
             basename: PathBuf::from(basename.as_ref()),
             graph_load_flags: Flags::empty(),
             offsets_load_flags: Flags::empty(),
+            verify: false,
+            build_missing_ef: false,
             _marker: std::marker::PhantomData,
         }
     }
+
+    /// Creates a sequential graph reading the content of a `.graph` file from
+    /// a byte buffer.
+    ///
+    /// Nothing is read from the filesystem, so the number of nodes, the
+    /// (optional) number of arcs, and the compression flags, which are usually
+    /// read from the `.properties` file, must be provided explicitly. The
+    /// buffer is copied into memory and zero-extended to a multiple of 16
+    /// bytes, as it happens when loading a graph in memory with
+    /// [`LoadMem`].
+    #[allow(clippy::type_complexity)]
+    pub fn from_bytes<E: Endianness>(
+        data: impl AsRef<[u8]>,
+        num_nodes: usize,
+        num_arcs: Option<u64>,
+        comp_flags: CompFlags,
+    ) -> Result<
+        BVGraphSeq<
+            DynCodesDecoderFactory<E, MemoryFactory<E, Box<[u32]>>, EmptyDict<usize, usize>>,
+        >,
+    >
+    where
+        for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E>,
+    {
+        let data = data.as_ref();
+        let mut words = vec![0_u32; data.len().div_ceil(16) * 4];
+        for (word, chunk) in words.iter_mut().zip(data.chunks(4)) {
+            let mut bytes = [0; 4];
+            bytes[..chunk.len()].copy_from_slice(chunk);
+            *word = u32::from_ne_bytes(bytes);
+        }
+        Ok(BVGraphSeq::new(
+            DynCodesDecoderFactory::new(
+                MemoryFactory::from_data(words.into_boxed_slice()),
+                MemCase::from(EmptyDict::default()),
+                comp_flags,
+            )?,
+            num_nodes,
+            num_arcs,
+            comp_flags.compression_window,
+            comp_flags.min_interval_length,
+        ))
+    }
 }
 
 impl<F: SequentialDecoderFactory> SplitLabeling for BVGraphSeq<F>
 where
     for<'a> <F as SequentialDecoderFactory>::Decoder<'a>: Clone + Send + Sync,
 {
-    type SplitLender<'a> = split::seq::Lender<'a, BVGraphSeq<F>> where Self: 'a;
-    type IntoIterator<'a> = split::seq::IntoIterator<'a, BVGraphSeq<F>> where Self: 'a;
+    type SplitLender<'a>
+        = split::seq::Lender<'a, BVGraphSeq<F>>
+    where
+        Self: 'a;
+    type IntoIterator<'a>
+        = split::seq::IntoIterator<'a, BVGraphSeq<F>>
+    where
+        Self: 'a;
 
     fn split_iter(&self, how_many: usize) -> Self::IntoIterator<'_> {
         split::seq::Iter::new(self.iter(), how_many)
@@ -55,7 +126,8 @@ where
 
 impl<F: SequentialDecoderFactory> SequentialLabeling for BVGraphSeq<F> {
     type Label = usize;
-    type Lender<'a> = Iter<F::Decoder<'a>>
+    type Lender<'a>
+        = Iter<F::Decoder<'a>>
     where
         Self: 'a;
 
@@ -70,6 +142,11 @@ impl<F: SequentialDecoderFactory> SequentialLabeling for BVGraphSeq<F> {
         self.number_of_arcs
     }
 
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.number_of_arcs
+    }
+
     #[inline(always)]
     fn iter_from(&self, from: usize) -> Self::Lender<'_> {
         let mut iter = Iter::new(
@@ -155,27 +232,390 @@ where
             self.min_interval_length,
         )
     }
+
+    /// Returns a sequential iterator storing successors as `u32`.
+    ///
+    /// The iterator behaves like [`iter`](SequentialLabeling::iter), but
+    /// its [`next_slice`](Iter::next_slice) and
+    /// [`next_successors`](Iter::next_successors) methods return slices of
+    /// `u32`, halving the memory bandwidth needed by scans.
+    ///
+    /// # Errors
+    ///
+    /// If the graph has more than 2³² nodes, or if the decoder cannot be
+    /// created.
+    pub fn iter_u32(&self) -> Result<Iter<F::Decoder<'_>, u32>> {
+        anyhow::ensure!(
+            self.number_of_nodes as u64 <= 1 << 32,
+            "The graph has {} nodes, but u32 successors support at most 2^32 nodes",
+            self.number_of_nodes
+        );
+        Ok(Iter {
+            number_of_nodes: self.number_of_nodes,
+            compression_window: self.compression_window,
+            min_interval_length: self.min_interval_length,
+            decoder: self.factory.new_decoder()?,
+            backrefs: CircularBuffer::new(self.compression_window + 1),
+            unsorted: CircularBuffer::new(self.compression_window + 1),
+            has_unsorted: false,
+            current_node: 0,
+            stats: None,
+        })
+    }
+
+    /// Returns a sequential iterator storing successors as `u32` if the
+    /// graph has at most 2³² nodes, and as `usize` otherwise.
+    ///
+    /// The type of successors is thus chosen at runtime, depending on the
+    /// number of nodes of the graph. Code generic over [`NodeId`] can be
+    /// dispatched on the result, as in:
+    /// ```ignore
+    /// match graph.iter_narrowest()? {
+    ///     NarrowestIter::U32(iter) => scan(iter),
+    ///     NarrowestIter::Usize(iter) => scan(iter),
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// If the decoder cannot be created.
+    pub fn iter_narrowest(&self) -> Result<NarrowestIter<F::Decoder<'_>>> {
+        Ok(if self.number_of_nodes as u64 <= 1 << 32 {
+            NarrowestIter::U32(self.iter_u32()?)
+        } else {
+            NarrowestIter::Usize(Iter::new(
+                self.factory.new_decoder()?,
+                self.number_of_nodes,
+                self.compression_window,
+                self.min_interval_length,
+            ))
+        })
+    }
+}
+
+impl<F: SequentialDecoderFactory> BVGraphSeq<F>
+where
+    for<'a> F::Decoder<'a>: Decode + BitSeek,
+    for<'a> <F::Decoder<'a> as BitSeek>::Error: std::error::Error + Send + Sync + 'static,
+{
+    /// Applies `func` to each node and its successors in parallel, and
+    /// reduces the results using `reduce`.
+    ///
+    /// `offsets` must contain the position in the bitstream of each node, as
+    /// stored in the `.offsets` or `.ef` files (for example, the Elias–Fano
+    /// representation of the offsets loaded by [`BVGraph`]).
+    ///
+    /// Nodes are split into chunks of `node_granularity` nodes, and each chunk
+    /// is scanned by a thread of `thread_pool` using a sequential iterator
+    /// that is moved with [`skip_to`](Iter::skip_to) to the start of the
+    /// chunk. The back-reference window of the first node of the chunk is
+    /// rebuilt by seeking to the previous `compression_window` nodes and
+    /// following their chains of references, so the overhead of each chunk
+    /// is a few decodings per node of the window. Thus, whole-graph scans can
+    /// be parallelized with a speedup close to the number of threads,
+    /// provided that `node_granularity` is large with respect to the
+    /// compression window.
+    ///
+    /// The results of the nodes of a chunk are reduced in order, starting
+    /// from `T::default()`, but the results of the chunks are reduced in
+    /// arbitrary order, so `reduce` should be associative and commutative,
+    /// with `T::default()` as neutral element.
+    ///
+    /// See [`par_node_apply`](SequentialLabeling::par_node_apply) for the
+    /// meaning of the other arguments.
+    ///
+    /// # Errors
+    ///
+    /// If `offsets` contains fewer than [`num_nodes`](SequentialLabeling::num_nodes)
+    /// positions, or if decoding fails (e.g., because `offsets` does not
+    /// match the graph).
+    pub fn par_iter<OFF, T, M, R>(
+        &self,
+        offsets: &OFF,
+        func: M,
+        reduce: R,
+        node_granularity: usize,
+        thread_pool: &rayon::ThreadPool,
+        pl: Option<&mut ProgressLogger>,
+    ) -> Result<T>
+    where
+        F: Sync,
+        OFF: IndexedDict<Input = usize, Output = usize> + Sync + ?Sized,
+        M: Fn(usize, &[usize]) -> T + Send + Sync,
+        R: Fn(T, T) -> T + Send + Sync,
+        T: Default + Send,
+    {
+        anyhow::ensure!(
+            offsets.len() >= self.number_of_nodes,
+            "The graph has {} nodes, but only {} offsets were provided",
+            self.number_of_nodes,
+            offsets.len()
+        );
+        let (result, err) = self.par_node_apply(
+            |range| -> Result<T> {
+                let mut iter = self.iter();
+                let start = range.start;
+                let window_len = start.min(self.compression_window);
+                let window = (start - window_len..start)
+                    .map(|node_id| self.seek_successors(&mut iter, offsets, node_id))
+                    .collect::<Result<Vec<_>>>()?;
+                iter.skip_to(start, offsets.get(start) as u64, window)?;
+                let mut result = T::default();
+                for node_id in range {
+                    result = reduce(result, func(node_id, iter.next_successors()?));
+                }
+                Ok(result)
+            },
+            |(acc, err): (T, Option<anyhow::Error>), chunk| match chunk {
+                Ok(chunk) => (reduce(acc, chunk), err),
+                Err(e) => (acc, err.or(Some(e))),
+            },
+            node_granularity,
+            thread_pool,
+            pl,
+        );
+        err.map_or(Ok(result), Err)
+    }
+
+    /// Returns the successors of `node_id` by seeking with `offsets`.
+    ///
+    /// The chain of references of the node is first followed by reading
+    /// only the outdegrees and reference offsets; then, the lists of the chain
+    /// are decoded by `iter` in reverse order, each one providing the
+    /// back-reference window of the next one.
+    fn seek_successors<OFF: IndexedDict<Input = usize, Output = usize> + ?Sized>(
+        &self,
+        iter: &mut Iter<F::Decoder<'_>>,
+        offsets: &OFF,
+        node_id: usize,
+    ) -> Result<Vec<usize>> {
+        // (node, reference offset) pairs; references strictly decrease,
+        // so the loop terminates
+        let mut chain = vec![];
+        let mut node = node_id;
+        loop {
+            iter.decoder.set_bit_pos(offsets.get(node) as u64)?;
+            let ref_delta = if self.compression_window != 0 && iter.decoder.read_outdegree() != 0 {
+                iter.decoder.read_reference_offset() as usize
+            } else {
+                0
+            };
+            anyhow::ensure!(
+                ref_delta <= node.min(self.compression_window),
+                "Node {} has reference offset {}, but the compression window is {}; maybe the offsets do not match the graph",
+                node,
+                ref_delta,
+                self.compression_window
+            );
+            chain.push((node, ref_delta));
+            if ref_delta == 0 {
+                break;
+            }
+            node -= ref_delta;
+        }
+
+        let mut succ = vec![];
+        for (node, ref_delta) in chain.into_iter().rev() {
+            // only the referenced list is needed, so the rest of the window
+            // is left empty
+            let window_len = node.min(self.compression_window);
+            let mut window = vec![vec![]; window_len];
+            if ref_delta != 0 {
+                window[window_len - ref_delta] = succ;
+            }
+            iter.skip_to(node, offsets.get(node) as u64, window)?;
+            succ = iter.next_successors()?.to_vec();
+        }
+        Ok(succ)
+    }
+}
+
+/// A sequential [`Iter`] whose type of successors has been chosen at runtime
+/// by [`BVGraphSeq::iter_narrowest`].
+#[derive(Debug, Clone)]
+pub enum NarrowestIter<D: Decode> {
+    /// Successors are stored as `u32`.
+    U32(Iter<D, u32>),
+    /// Successors are stored as `usize`, as the graph has more than 2³² nodes.
+    Usize(Iter<D>),
+}
+
+/// Statistics about the origin of the successors decoded by a sequential
+/// [`Iter`].
+///
+/// Each successor is either copied from a reference list, part of an interval,
+/// or a residual, so the sum of [`copied`](DecodeStats::copied),
+/// [`intervals`](DecodeStats::intervals), and
+/// [`residuals`](DecodeStats::residuals) is the number of arcs decoded so far.
+///
+/// Statistics are collected only if requested with
+/// [`Iter::enable_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    /// The number of nodes decoded.
+    pub nodes: u64,
+    /// The number of successors copied from a reference list.
+    pub copied: u64,
+    /// The number of successors decoded from intervals.
+    pub intervals: u64,
+    /// The number of successors decoded as residuals.
+    pub residuals: u64,
+    /// The number of nodes whose successors refer to a previous list.
+    pub reference_hops: u64,
+}
+
+impl DecodeStats {
+    /// Returns the number of successors decoded, that is, the sum of copied,
+    /// interval, and residual successors.
+    pub fn num_arcs(&self) -> u64 {
+        self.copied + self.intervals + self.residuals
+    }
+}
+
+/// The type of the successors decoded by an [`Iter`].
+///
+/// This trait is sealed and implemented only by `usize`, the default, and by
+/// `u32`, which halves the memory traffic on the back-reference window, and
+/// can be used when the graph has at most 2³² nodes (see
+/// [`BVGraphSeq::iter_u32`]). Gaps are always decoded as `usize` or `i64` and
+/// narrowed only when stored; successors that do not fit, which can only
+/// appear in corrupted graphs, cause a decoding error.
+#[sealed]
+pub trait NodeId: Copy + Ord + Default + core::fmt::Debug + Send + Sync + 'static {
+    /// Narrows a node identifier, returning `None` if it does not fit.
+    fn from_usize(x: usize) -> Option<Self>;
+    /// Widens a node identifier.
+    fn to_usize(self) -> usize;
+}
+
+#[sealed]
+impl NodeId for usize {
+    #[inline(always)]
+    fn from_usize(x: usize) -> Option<Self> {
+        Some(x)
+    }
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self
+    }
+}
+
+#[sealed]
+impl NodeId for u32 {
+    #[inline(always)]
+    fn from_usize(x: usize) -> Option<Self> {
+        u32::try_from(x).ok()
+    }
+    #[inline(always)]
+    fn to_usize(self) -> usize {
+        self as usize
+    }
 }
 
 /// A fast sequential iterator over the nodes of the graph and their successors.
 /// This iterator does not require to know the offsets of each node in the graph.
+///
+/// The parameter `N` is the type used to store successors; only iterators
+/// using `usize` (the default) are [lenders](Lender), whereas the others
+/// can be used through [`next_slice`](Iter::next_slice) and
+/// [`next_successors`](Iter::next_successors).
 #[derive(Debug, Clone)]
-pub struct Iter<D: Decode> {
+pub struct Iter<D: Decode, N = usize> {
     pub(crate) number_of_nodes: usize,
     pub(crate) compression_window: usize,
     pub(crate) min_interval_length: usize,
     pub(crate) decoder: D,
-    pub(crate) backrefs: CircularBuffer<Vec<usize>>,
+    pub(crate) backrefs: CircularBuffer<Vec<N>>,
+    /// Whether the successor list of a node in the back-reference window
+    /// has been returned by [`next_successors_unsorted`](Iter::next_successors_unsorted)
+    /// and not sorted yet; it is used only if `has_unsorted` is true.
+    pub(crate) unsorted: CircularBuffer<bool>,
+    /// Whether [`next_successors_unsorted`](Iter::next_successors_unsorted)
+    /// has ever been called, so that iterators using only sorted lists
+    /// do not pay for maintaining `unsorted`.
+    pub(crate) has_unsorted: bool,
     pub(crate) current_node: usize,
+    pub(crate) stats: Option<DecodeStats>,
 }
 
-impl<D: Decode + BitSeek> Iter<D> {
+impl<D: Decode + BitSeek, N: NodeId> Iter<D, N> {
     #[inline(always)]
     /// Forward the call of `get_pos` to the inner `codes_reader`.
     /// This returns the current bits offset in the bitstream.
     pub fn bit_pos(&mut self) -> Result<u64, <D as BitSeek>::Error> {
         self.decoder.bit_pos()
     }
+
+    /// Moves the iterator to `node_id`, so that the next call to
+    /// [`next`](Lender::next) returns its successors.
+    ///
+    /// `bit_offset` must be the position of `node_id` in the bitstream, as
+    /// stored, for example, in the `.offsets` file.
+    ///
+    /// Since the successor list of a node might be copied from one of the
+    /// previous `compression_window` nodes, the back-reference window cannot
+    /// be recovered by seeking, and must be provided by the caller: `window`
+    /// must contain, in order, the successor lists of the
+    /// min(`node_id`, `compression_window`) nodes preceding `node_id`
+    /// (hence, it is empty at node zero or when the graph has been compressed
+    /// with a compression window of zero). If you have random access to the
+    /// graph, they can be obtained from
+    /// [`successors`](crate::traits::RandomAccessGraph::successors); otherwise,
+    /// a worker can skip to `node_id - compression_window` (whose window is
+    /// available, e.g., from a previous scan) and discard the first
+    /// `compression_window` nodes.
+    ///
+    /// Statistics, if [enabled](Iter::enable_stats), are not reset.
+    pub fn skip_to<I: IntoIterator<Item = usize>>(
+        &mut self,
+        node_id: usize,
+        bit_offset: u64,
+        window: impl IntoIterator<Item = I>,
+    ) -> Result<()>
+    where
+        <D as BitSeek>::Error: std::error::Error + Send + Sync + 'static,
+    {
+        anyhow::ensure!(
+            node_id <= self.number_of_nodes,
+            "Node {} does not exist (the graph has {} nodes)",
+            node_id,
+            self.number_of_nodes
+        );
+        let window_len = node_id.min(self.compression_window);
+        // validate the window and the offset before modifying any state
+        let window = window
+            .into_iter()
+            .take(window_len + 1)
+            .map(|succ| {
+                succ.into_iter()
+                    .map(|x| {
+                        N::from_usize(x).with_context(|| {
+                            format!(
+                                "Successor {} in the back-reference window does not fit into a {}",
+                                x,
+                                core::any::type_name::<N>()
+                            )
+                        })
+                    })
+                    .collect::<Result<Vec<_>>>()
+            })
+            .collect::<Result<Vec<_>>>()?;
+        anyhow::ensure!(
+            window.len() == window_len,
+            "The back-reference window must contain {} successor lists, but {} were provided",
+            window_len,
+            window.len()
+        );
+        self.decoder.set_bit_pos(bit_offset)?;
+        for (node, succ_vec) in (node_id - window_len..).zip(window) {
+            self.backrefs.replace(node, succ_vec);
+            if self.has_unsorted {
+                self.unsorted[node] = false;
+            }
+        }
+        self.current_node = node_id;
+        Ok(())
+    }
 }
 
 impl<D: Decode> Iter<D> {
@@ -192,15 +632,88 @@ impl<D: Decode> Iter<D> {
             min_interval_length,
             decoder,
             backrefs: CircularBuffer::new(compression_window + 1),
+            unsorted: CircularBuffer::new(compression_window + 1),
+            has_unsorted: false,
             current_node: 0,
+            stats: None,
         }
     }
+}
+
+impl<D: Decode, N: NodeId> Iter<D, N> {
+    /// Starts collecting [statistics](DecodeStats) about the decoded
+    /// successors, resetting any previously collected statistics.
+    pub fn enable_stats(&mut self) {
+        self.stats = Some(DecodeStats::default());
+    }
+
+    /// Returns the statistics collected so far, or `None` if
+    /// [`enable_stats`](Iter::enable_stats) has not been called.
+    pub fn stats(&self) -> Option<&DecodeStats> {
+        self.stats.as_ref()
+    }
+
+    /// Returns the next node and its successors as a slice, or `None` if all
+    /// nodes have been returned.
+    ///
+    /// This method is equivalent to [`next`](Lender::next), which is
+    /// implemented using it, but returns directly the buffer in which the
+    /// successors have been decoded. Buffers are reused by the iterator, so no
+    /// allocation happens once the back-reference window has been filled.
+    ///
+    /// # Panics
+    ///
+    /// If the successors of the next node cannot be decoded, for example
+    /// because the graph is corrupted. Use
+    /// [`next_successors`](Iter::next_successors) to handle such errors.
+    #[inline(always)]
+    pub fn next_slice(&mut self) -> Option<(usize, &[N])> {
+        if self.current_node >= self.number_of_nodes {
+            return None;
+        }
+        let node_id = self.current_node;
+        Some((
+            node_id,
+            self.next_successors()
+                .unwrap_or_else(|e| panic!("Cannot decode node {}: {:#}", node_id, e)),
+        ))
+    }
 
     /// Get the successors of the next node in the stream
-    pub fn next_successors(&mut self) -> Result<&[usize]> {
+    pub fn next_successors(&mut self) -> Result<&[N]> {
+        self.next_successors_priv(true)
+    }
+
+    /// Returns the successors of the next node in the stream, in no
+    /// particular order.
+    ///
+    /// This method is the same as [`next_successors`](Iter::next_successors),
+    /// but skips the final sort of the successor list, which is useful for
+    /// high-degree nodes when the caller needs just, say, a degree or a set
+    /// of successors. The order is unspecified, so the result must not be used
+    /// where sorted successors are assumed, for example by binary searches or
+    /// merges (in particular, the iterator is a [`SortedLender`] only when
+    /// the two methods are not mixed).
+    ///
+    /// Since the decoding of a successor list might copy blocks from one of
+    /// the previous lists, which must be sorted, lists returned by this
+    /// method are sorted lazily when they are used as a reference: thus, the
+    /// speedup depends on the number of lists that are not references.
+    pub fn next_successors_unsorted(&mut self) -> Result<&[N]> {
+        self.next_successors_priv(false)
+    }
+
+    #[inline(always)]
+    fn next_successors_priv(&mut self, sort: bool) -> Result<&[N]> {
         let mut res = self.backrefs.take(self.current_node);
         res.clear();
-        self.get_successors_iter_priv(self.current_node, &mut res)?;
+        self.get_successors_iter_priv(self.current_node, &mut res, sort)?;
+        if !sort {
+            self.has_unsorted = true;
+        }
+        if self.has_unsorted {
+            self.unsorted[self.current_node] = !sort;
+        }
         let res = self.backrefs.replace(self.current_node, res);
         self.current_node += 1;
         Ok(res)
@@ -208,12 +721,27 @@ impl<D: Decode> Iter<D> {
 
     #[inline(always)]
     /// Inner method called by `next_successors` and the iterator `next` method
-    fn get_successors_iter_priv(&mut self, node_id: usize, results: &mut Vec<usize>) -> Result<()> {
+    fn get_successors_iter_priv(
+        &mut self,
+        node_id: usize,
+        results: &mut Vec<N>,
+        sort: bool,
+    ) -> Result<()> {
         let degree = self.decoder.read_outdegree() as usize;
+        if let Some(stats) = &mut self.stats {
+            stats.nodes += 1;
+        }
         // no edges, we are done!
         if degree == 0 {
             return Ok(());
         }
+        anyhow::ensure!(
+            degree <= self.number_of_nodes,
+            "Node {} has outdegree {}, but the graph has {} nodes",
+            node_id,
+            degree,
+            self.number_of_nodes
+        );
 
         // ensure that we have enough capacity in the vector for not reallocating
         results.reserve(degree.saturating_sub(results.capacity()));
@@ -225,8 +753,21 @@ impl<D: Decode> Iter<D> {
         };
         // if we copy nodes from a previous one
         if ref_delta != 0 {
+            // a reference outside the window would read stale data
+            anyhow::ensure!(
+                ref_delta <= node_id && self.backrefs.contains_node(node_id - ref_delta),
+                "Node {} has reference offset {}, but the compression window is {}; maybe the properties do not match the graph",
+                node_id,
+                ref_delta,
+                self.compression_window
+            );
             // compute the node id of the reference
             let reference_node_id = node_id - ref_delta;
+            // blocks refer to the sorted list
+            if self.has_unsorted && self.unsorted[reference_node_id] {
+                self.backrefs[reference_node_id].sort();
+                self.unsorted[reference_node_id] = false;
+            }
             // retrieve the data
             let neighbours = &self.backrefs[reference_node_id];
             //debug_assert!(!neighbours.is_empty());
@@ -239,11 +780,23 @@ impl<D: Decode> Iter<D> {
                 // otherwise we copy only the blocks of even index
                 // the first block could be zero
                 let mut idx = self.decoder.read_block() as usize;
+                anyhow::ensure!(
+                    idx <= neighbours.len(),
+                    "The copy blocks of node {} exceed the {} successors of its reference",
+                    node_id,
+                    neighbours.len()
+                );
                 results.extend_from_slice(&neighbours[..idx]);
 
                 // while the other can't
                 for block_id in 1..number_of_blocks {
                     let block = self.decoder.read_block() as usize;
+                    anyhow::ensure!(
+                        block < neighbours.len() - idx,
+                        "The copy blocks of node {} exceed the {} successors of its reference",
+                        node_id,
+                        neighbours.len()
+                    );
                     let end = idx + block + 1;
                     if block_id % 2 == 0 {
                         results.extend_from_slice(&neighbours[idx..end]);
@@ -256,52 +809,132 @@ impl<D: Decode> Iter<D> {
             }
         };
 
+        let copied = results.len();
+        anyhow::ensure!(
+            copied <= degree,
+            "Node {} copies {} successors, but its outdegree is {}",
+            node_id,
+            copied,
+            degree
+        );
+
         // if we still have to read nodes
-        let nodes_left_to_decode = degree - results.len();
+        let nodes_left_to_decode = degree - copied;
         if nodes_left_to_decode != 0 && self.min_interval_length != 0 {
             // read the number of intervals
             let number_of_intervals = self.decoder.read_interval_count() as usize;
             if number_of_intervals != 0 {
                 // pre-allocate with capacity for efficency
                 let node_id_offset = nat2int(self.decoder.read_interval_start());
-                let mut start = (node_id as i64 + node_id_offset) as usize;
-                let mut delta = self.decoder.read_interval_len() as usize;
-                delta += self.min_interval_length;
+                let start = (node_id as i64)
+                    .checked_add(node_id_offset)
+                    .ok_or_else(|| overflow(node_id))?;
+                anyhow::ensure!(
+                    start >= 0,
+                    "The first interval of node {} starts at negative node {}",
+                    node_id,
+                    start
+                );
+                let mut start = start as usize;
+                let mut delta = (self.decoder.read_interval_len() as usize)
+                    .saturating_add(self.min_interval_length);
+                anyhow::ensure!(
+                    delta <= degree - results.len(),
+                    "The intervals of node {} exceed its outdegree {}",
+                    node_id,
+                    degree
+                );
+                let mut end = start.checked_add(delta).ok_or_else(|| overflow(node_id))?;
+                // the last successor of the interval fits, so all do
+                narrow::<N>(node_id, end - 1)?;
                 // save the first interval
-                results.extend(start..(start + delta));
-                start += delta;
+                results.extend((start..end).filter_map(N::from_usize));
                 // decode the intervals
                 for _ in 1..number_of_intervals {
-                    start += 1 + self.decoder.read_interval_start() as usize;
-                    delta = self.decoder.read_interval_len() as usize;
-                    delta += self.min_interval_length;
-
-                    results.extend(start..(start + delta));
-
-                    start += delta;
+                    start = (self.decoder.read_interval_start() as usize)
+                        .checked_add(1)
+                        .and_then(|gap| end.checked_add(gap))
+                        .ok_or_else(|| overflow(node_id))?;
+                    delta = (self.decoder.read_interval_len() as usize)
+                        .saturating_add(self.min_interval_length);
+                    anyhow::ensure!(
+                        delta <= degree - results.len(),
+                        "The intervals of node {} exceed its outdegree {}",
+                        node_id,
+                        degree
+                    );
+                    end = start.checked_add(delta).ok_or_else(|| overflow(node_id))?;
+                    narrow::<N>(node_id, end - 1)?;
+
+                    results.extend((start..end).filter_map(N::from_usize));
                 }
             }
         }
 
         // decode the extra nodes if needed
         let nodes_left_to_decode = degree - results.len();
+
+        if let Some(stats) = &mut self.stats {
+            stats.copied += copied as u64;
+            stats.intervals += (results.len() - copied) as u64;
+            stats.residuals += nodes_left_to_decode as u64;
+            stats.reference_hops += (ref_delta != 0) as u64;
+        }
+
         if nodes_left_to_decode != 0 {
             // pre-allocate with capacity for efficency
             let node_id_offset = nat2int(self.decoder.read_first_residual());
-            let mut extra = (node_id as i64 + node_id_offset) as usize;
-            results.push(extra);
+            let extra = (node_id as i64)
+                .checked_add(node_id_offset)
+                .ok_or_else(|| overflow(node_id))?;
+            anyhow::ensure!(
+                extra >= 0,
+                "The first residual of node {} is the negative node {}",
+                node_id,
+                extra
+            );
+            let mut extra = extra as usize;
+            results.push(narrow(node_id, extra)?);
             // decode the successive extra nodes
             for _ in 1..nodes_left_to_decode {
-                extra += 1 + self.decoder.read_residual() as usize;
-                results.push(extra);
+                extra = (self.decoder.read_residual() as usize)
+                    .checked_add(1)
+                    .and_then(|gap| extra.checked_add(gap))
+                    .ok_or_else(|| overflow(node_id))?;
+                results.push(narrow(node_id, extra)?);
             }
         }
 
-        results.sort();
+        if sort {
+            results.sort();
+        }
         Ok(())
     }
 }
 
+/// Narrows a successor of a node, returning an error if it does not fit into
+/// `N`, which can happen only in corrupted graphs.
+#[inline(always)]
+fn narrow<N: NodeId>(node_id: usize, successor: usize) -> Result<N> {
+    N::from_usize(successor).with_context(|| {
+        format!(
+            "Node {} has successor {}, which does not fit into a {}",
+            node_id,
+            successor,
+            core::any::type_name::<N>()
+        )
+    })
+}
+
+/// The error returned when the successors of a node in a corrupted graph
+/// overflow.
+fn overflow(node_id: usize) -> anyhow::Error {
+    anyhow::anyhow!(
+        "The successor list of node {} is corrupted: its successors overflow",
+        node_id
+    )
+}
+
 impl<'succ, D: Decode> NodeLabelsLender<'succ> for Iter<D> {
     type Label = usize;
     type IntoIterator = std::iter::Copied<std::slice::Iter<'succ, Self::Label>>;
@@ -312,19 +945,10 @@ impl<'succ, D: Decode> Lending<'succ> for Iter<D> {
 }
 
 impl<D: Decode> Lender for Iter<D> {
+    #[inline(always)]
     fn next(&mut self) -> Option<Lend<'_, Self>> {
-        if self.current_node >= self.number_of_nodes as _ {
-            return None;
-        }
-        let mut res = self.backrefs.take(self.current_node);
-        res.clear();
-        self.get_successors_iter_priv(self.current_node, &mut res)
-            .unwrap();
-
-        let res = self.backrefs.replace(self.current_node, res);
-        let node_id = self.current_node;
-        self.current_node += 1;
-        Some((node_id, res.iter().copied()))
+        self.next_slice()
+            .map(|(node_id, succ)| (node_id, succ.iter().copied()))
     }
 }
 
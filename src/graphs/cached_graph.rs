@@ -0,0 +1,266 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use lender::*;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// A least-recently-used cache of successor lists.
+#[derive(Debug)]
+struct Lru {
+    capacity: usize,
+    /// The last access time and the successors of each cached node.
+    lists: HashMap<usize, (u64, Arc<[usize]>)>,
+    /// The cached nodes by last access time.
+    by_time: BTreeMap<u64, usize>,
+    time: u64,
+}
+
+impl Lru {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lists: HashMap::with_capacity(capacity),
+            by_time: BTreeMap::new(),
+            time: 0,
+        }
+    }
+
+    fn get(&mut self, node: usize) -> Option<Arc<[usize]>> {
+        let (time, list) = self.lists.get_mut(&node)?;
+        self.by_time.remove(&*time);
+        self.time += 1;
+        *time = self.time;
+        self.by_time.insert(self.time, node);
+        Some(list.clone())
+    }
+
+    fn insert(&mut self, node: usize, list: Arc<[usize]>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if let Some((time, _)) = self.lists.remove(&node) {
+            self.by_time.remove(&time);
+        } else if self.lists.len() == self.capacity {
+            let (_, evicted) = self.by_time.pop_first().unwrap();
+            self.lists.remove(&evicted);
+        }
+        self.time += 1;
+        self.by_time.insert(self.time, node);
+        self.lists.insert(node, (self.time, list));
+    }
+}
+
+/// A wrapper caching the most recently used successor lists of a
+/// random-access graph.
+///
+/// Every call to [`successors`](RandomAccessGraph::successors) on a
+/// [`BVGraph`] positions a bit reader at the start of the list and decodes it,
+/// possibly together with the lists it refers to. Workloads that access
+/// repeatedly the same nodes, such as visits of graphs with a good locality,
+/// can avoid part of this work by keeping a fixed number of decoded lists in
+/// a least-recently-used cache. On a miss, the list is decoded by the
+/// underlying graph and stored in the cache, evicting the least recently used
+/// list if the cache is full.
+///
+/// The cache is protected by a mutex, so the wrapper can be shared among
+/// threads, and the number of hits and misses is available through
+/// [`hits`](CachedRandomAccessGraph::hits) and
+/// [`misses`](CachedRandomAccessGraph::misses). Sequential iteration and
+/// [`outdegree`](RandomAccessLabeling::outdegree) are delegated to the
+/// underlying graph and do not use the cache.
+#[derive(Debug)]
+pub struct CachedRandomAccessGraph<G: RandomAccessGraph> {
+    graph: G,
+    cache: Mutex<Lru>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl<G: RandomAccessGraph> CachedRandomAccessGraph<G> {
+    /// Wraps a graph with a cache containing at most `capacity` successor
+    /// lists.
+    ///
+    /// If `capacity` is zero, nothing is cached and every access is a miss.
+    pub fn new(graph: G, capacity: usize) -> Self {
+        Self {
+            graph,
+            cache: Mutex::new(Lru::new(capacity)),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the maximum number of successor lists in the cache.
+    pub fn capacity(&self) -> usize {
+        self.cache.lock().unwrap().capacity
+    }
+
+    /// Returns the number of successor lists returned from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of successor lists decoded by the underlying graph.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Returns the underlying graph.
+    pub fn into_inner(self) -> G {
+        self.graph
+    }
+}
+
+impl<G: RandomAccessGraph> SequentialLabeling for CachedRandomAccessGraph<G> {
+    type Label = usize;
+    type Lender<'b> = G::Lender<'b>
+        where
+            Self: 'b;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        self.graph.num_arcs_hint()
+    }
+
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.graph.num_arcs_exact()
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        self.graph.iter_from(from)
+    }
+}
+
+impl<G: RandomAccessGraph> SequentialGraph for CachedRandomAccessGraph<G> {}
+
+impl<'a, G: RandomAccessGraph> IntoLender for &'a CachedRandomAccessGraph<G> {
+    type Lender = <CachedRandomAccessGraph<G> as SequentialLabeling>::Lender<'a>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl<G: RandomAccessGraph> RandomAccessLabeling for CachedRandomAccessGraph<G> {
+    type Labels<'succ> = CachedSuccessors
+        where
+            Self: 'succ;
+
+    #[inline(always)]
+    fn num_arcs(&self) -> u64 {
+        self.graph.num_arcs()
+    }
+
+    fn labels(&self, node_id: usize) -> <Self as RandomAccessLabeling>::Labels<'_> {
+        if let Some(list) = self.cache.lock().unwrap().get(node_id) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return CachedSuccessors { list, pos: 0 };
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        // Decode without holding the lock
+        let list: Arc<[usize]> = self.graph.successors(node_id).into_iter().collect();
+        self.cache.lock().unwrap().insert(node_id, list.clone());
+        CachedSuccessors { list, pos: 0 }
+    }
+
+    #[inline(always)]
+    fn outdegree(&self, node_id: usize) -> usize {
+        self.graph.outdegree(node_id)
+    }
+}
+
+impl<G: RandomAccessGraph> RandomAccessGraph for CachedRandomAccessGraph<G> {}
+
+/// An iterator over a successor list stored in the cache of a
+/// [`CachedRandomAccessGraph`].
+#[derive(Debug, Clone)]
+pub struct CachedSuccessors {
+    list: Arc<[usize]>,
+    pos: usize,
+}
+
+impl Iterator for CachedSuccessors {
+    type Item = usize;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<usize> {
+        let succ = self.list.get(self.pos).copied()?;
+        self.pos += 1;
+        Some(succ)
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.list.len() - self.pos;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for CachedSuccessors {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_cached_graph() {
+        let graph = Left(VecGraph::from_arcs([
+            (0, 1),
+            (0, 3),
+            (1, 2),
+            (2, 0),
+            (2, 3),
+            (3, 3),
+        ]));
+        let cached = CachedRandomAccessGraph::new(&graph, 2);
+        assert_eq!(cached.num_nodes(), 4);
+        assert_eq!(cached.num_arcs(), 6);
+        for _ in 0..2 {
+            for node in 0..graph.num_nodes() {
+                assert_eq!(
+                    cached.successors(node).collect::<Vec<_>>(),
+                    graph.successors(node).into_iter().collect::<Vec<_>>(),
+                    "Node {} differs",
+                    node
+                );
+            }
+        }
+        // Scanning 4 nodes twice with capacity 2 always misses
+        assert_eq!(cached.hits(), 0);
+        assert_eq!(cached.misses(), 8);
+
+        // 2 and 3 are now cached
+        assert_eq!(cached.successors(3).len(), 1);
+        assert_eq!(cached.successors(2).collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(cached.hits(), 2);
+        // 3 is evicted
+        assert_eq!(cached.successors(0).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(cached.misses(), 9);
+        assert_eq!(cached.successors(2).collect::<Vec<_>>(), vec![0, 3]);
+        assert_eq!(cached.hits(), 3);
+        assert_eq!(cached.successors(3).collect::<Vec<_>>(), vec![3]);
+        assert_eq!(cached.misses(), 10);
+
+        let uncached = CachedRandomAccessGraph::new(&graph, 0);
+        assert_eq!(uncached.successors(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(uncached.successors(1).collect::<Vec<_>>(), vec![2]);
+        assert_eq!(uncached.hits(), 0);
+        assert_eq!(uncached.misses(), 2);
+    }
+}
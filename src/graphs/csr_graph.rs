@@ -0,0 +1,270 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use lender::prelude::*;
+
+/// An immutable graph in compressed sparse row (CSR) format.
+///
+/// The successors of all nodes are stored contiguously in a slice of targets,
+/// and the successors of node _x_ are the targets between `offsets[x]`
+/// (included) and `offsets[x + 1]` (excluded). Thus, the slice of offsets has
+/// one element more than the number of nodes, its first element is zero, and
+/// its last element is the number of arcs.
+///
+/// Random access requires just two memory accesses, as opposed to the
+/// decoding of a bitstream in the case of a [`BVGraph`], and the structure is
+/// much more compact than a [`VecGraph`], so this is the structure of choice
+/// for repeated random access to graphs that fit in memory. The successors of
+/// a node are available as a slice using
+/// [`successors_slice`](Self::successors_slice).
+///
+/// Successor lists are returned in the same order of the graph the structure
+/// was built from. If all lists are sorted, which is checked at construction
+/// time, [`has_arc`](RandomAccessGraph::has_arc) uses a binary search.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsrGraph {
+    offsets: Box<[usize]>,
+    targets: Box<[usize]>,
+    /// Whether all successor lists are sorted.
+    sorted: bool,
+}
+
+/// Returns whether all successor lists are sorted.
+fn lists_sorted(offsets: &[usize], targets: &[usize]) -> bool {
+    offsets
+        .windows(2)
+        .all(|w| targets[w[0]..w[1]].windows(2).all(|p| p[0] <= p[1]))
+}
+
+impl CsrGraph {
+    /// Creates a new graph from a slice of offsets and a slice of targets.
+    ///
+    /// # Panics
+    ///
+    /// If `offsets` is empty, if its first element is not zero, if it is not
+    /// nondecreasing, if its last element is not the length of `targets`, or
+    /// if some target is not smaller than the number of nodes.
+    pub fn new(offsets: impl Into<Box<[usize]>>, targets: impl Into<Box<[usize]>>) -> Self {
+        let offsets = offsets.into();
+        let targets = targets.into();
+        assert!(offsets.first() == Some(&0), "The first offset must be zero");
+        assert!(
+            offsets.windows(2).all(|w| w[0] <= w[1]),
+            "The offsets must be nondecreasing"
+        );
+        assert_eq!(
+            offsets[offsets.len() - 1],
+            targets.len(),
+            "The last offset must be the number of targets"
+        );
+        let num_nodes = offsets.len() - 1;
+        if let Some(&max) = targets.iter().max() {
+            assert!(
+                max < num_nodes,
+                "Target {} does not exist (the graph has {} nodes)",
+                max,
+                num_nodes
+            );
+        }
+        let sorted = lists_sorted(&offsets, &targets);
+        Self {
+            offsets,
+            targets,
+            sorted,
+        }
+    }
+
+    /// Creates a new graph by scanning sequentially the given graph.
+    ///
+    /// If [`num_arcs_hint`](SequentialLabeling::num_arcs_hint) is available,
+    /// it is used to preallocate the slice of targets.
+    pub fn from_graph(graph: impl SequentialGraph) -> Self {
+        let mut offsets = Vec::with_capacity(graph.num_nodes() + 1);
+        let mut targets = Vec::with_capacity(graph.num_arcs_hint().unwrap_or(0) as usize);
+        offsets.push(0);
+        for_!((_node, succ) in graph.iter() {
+            targets.extend(succ);
+            offsets.push(targets.len());
+        });
+        let sorted = lists_sorted(&offsets, &targets);
+        Self {
+            offsets: offsets.into_boxed_slice(),
+            targets: targets.into_boxed_slice(),
+            sorted,
+        }
+    }
+
+    /// Returns the successors of a node as a slice.
+    #[inline(always)]
+    pub fn successors_slice(&self, node: usize) -> &[usize] {
+        &self.targets[self.offsets[node]..self.offsets[node + 1]]
+    }
+
+    /// Returns the slice of offsets.
+    #[inline(always)]
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+
+    /// Returns the slice of targets.
+    #[inline(always)]
+    pub fn targets(&self) -> &[usize] {
+        &self.targets
+    }
+
+    /// Returns the slice of offsets and the slice of targets.
+    pub fn into_inner(self) -> (Box<[usize]>, Box<[usize]>) {
+        (self.offsets, self.targets)
+    }
+}
+
+impl<'a> IntoLender for &'a CsrGraph {
+    type Lender = <CsrGraph as SequentialLabeling>::Lender<'a>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl SequentialLabeling for CsrGraph {
+    type Label = usize;
+    type Lender<'a> = IteratorImpl<'a, Self> where Self: 'a;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        Some(self.num_arcs())
+    }
+
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.num_arcs())
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        IteratorImpl {
+            labeling: self,
+            nodes: (from..self.num_nodes()),
+        }
+    }
+}
+
+impl SequentialGraph for CsrGraph {}
+
+impl SplitLabeling for CsrGraph {
+    type SplitLender<'a> = split::ra::Lender<'a, CsrGraph> where Self: 'a;
+    type IntoIterator<'a> = split::ra::IntoIterator<'a, CsrGraph> where Self: 'a;
+
+    fn split_iter(&self, how_many: usize) -> Self::IntoIterator<'_> {
+        split::ra::Iter::new(self, how_many)
+    }
+}
+
+impl RandomAccessLabeling for CsrGraph {
+    type Labels<'succ> = core::iter::Copied<core::slice::Iter<'succ, usize>>;
+
+    #[inline(always)]
+    fn num_arcs(&self) -> u64 {
+        self.targets.len() as u64
+    }
+
+    #[inline(always)]
+    fn outdegree(&self, node: usize) -> usize {
+        self.offsets[node + 1] - self.offsets[node]
+    }
+
+    #[inline(always)]
+    fn labels(&self, node: usize) -> <Self as RandomAccessLabeling>::Labels<'_> {
+        self.successors_slice(node).iter().copied()
+    }
+}
+
+impl RandomAccessGraph for CsrGraph {
+    /// Returns whether there is an arc going from `src_node_id` to `dst_node_id`.
+    ///
+    /// If all successor lists are sorted, this method performs a binary
+    /// search; otherwise, it performs a linear scan.
+    #[inline]
+    fn has_arc(&self, src_node_id: usize, dst_node_id: usize) -> bool {
+        let succ = self.successors_slice(src_node_id);
+        if self.sorted {
+            succ.binary_search(&dst_node_id).is_ok()
+        } else {
+            succ.contains(&dst_node_id)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::random::ErdosRenyi;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_csr_graph() {
+        let graph = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+        let csr = CsrGraph::from_graph(&graph);
+        assert_eq!(csr.num_nodes(), graph.num_nodes());
+        assert_eq!(csr.num_arcs(), csr.targets().len() as u64);
+        assert_eq!(csr.num_arcs(), graph.num_arcs());
+        for node in 0..graph.num_nodes() {
+            assert_eq!(csr.outdegree(node), graph.outdegree(node));
+            assert!(csr.successors(node).eq(graph.successors(node)));
+            assert!(csr
+                .successors_slice(node)
+                .iter()
+                .copied()
+                .eq(graph.successors(node)));
+        }
+        assert!(crate::algo::graphs_equal(&csr, &graph));
+
+        let (offsets, targets) = csr.clone().into_inner();
+        assert_eq!(CsrGraph::new(offsets, targets), csr);
+    }
+
+    #[test]
+    fn test_new() {
+        let csr = CsrGraph::new(vec![0, 2, 2, 3], vec![1, 2, 0]);
+        assert_eq!(csr.num_nodes(), 3);
+        assert_eq!(csr.num_arcs(), 3);
+        assert_eq!(csr.successors_slice(0), &[1, 2]);
+        assert_eq!(csr.outdegree(1), 0);
+        assert!(csr.successors(2).eq([0]));
+
+        let empty = CsrGraph::new(vec![0], vec![]);
+        assert_eq!(empty.num_nodes(), 0);
+        assert_eq!(empty.num_arcs(), 0);
+    }
+
+    #[test]
+    fn test_has_arc() {
+        let sorted = CsrGraph::new(vec![0, 3, 3, 4], vec![0, 1, 2, 0]);
+        let unsorted = CsrGraph::new(vec![0, 3, 3, 4], vec![2, 0, 1, 0]);
+        for csr in [sorted, unsorted] {
+            assert!(csr.has_arc(0, 0));
+            assert!(csr.has_arc(0, 1));
+            assert!(csr.has_arc(0, 2));
+            assert!(!csr.has_arc(1, 0));
+            assert!(csr.has_arc(2, 0));
+            assert!(!csr.has_arc(2, 1));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_new_target_out_of_bounds() {
+        CsrGraph::new(vec![0, 1], vec![1]);
+    }
+}
@@ -0,0 +1,231 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use lender::*;
+
+#[derive(Debug, Clone)]
+/// A wrapper exhibiting the intersection of two graphs.
+///
+/// The successors of each node are the successors the node has in both
+/// graphs, and are obtained by intersecting the (sorted) successor lists of
+/// the node in the two graphs. The intersection is computed on the fly while
+/// iterating, and nothing is materialized.
+///
+/// As for [`UnionGraph`](crate::graphs::UnionGraph), the two graphs need not
+/// have the same number of nodes: the intersection has as many nodes as the
+/// larger graph, and the nodes missing in the smaller graph are considered to
+/// have no successors (so they have no successors in the intersection, too).
+pub struct IntersectionGraph<G: SequentialGraph, H: SequentialGraph>(pub G, pub H);
+
+impl<G: SequentialGraph, H: SequentialGraph> SequentialLabeling for IntersectionGraph<G, H>
+where
+    for<'a> G::Lender<'a>: SortedLender,
+    for<'a, 'b> LenderIntoIter<'b, G::Lender<'a>>: SortedIterator,
+    for<'a> H::Lender<'a>: SortedLender,
+    for<'a, 'b> LenderIntoIter<'b, H::Lender<'a>>: SortedIterator,
+{
+    type Label = usize;
+    type Lender<'b> = Iter<G::Lender<'b>, H::Lender<'b>>
+        where
+            Self: 'b;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.0.num_nodes().max(self.1.num_nodes())
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        None
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        Iter(
+            self.0.iter_from(from.min(self.0.num_nodes())),
+            self.1.iter_from(from.min(self.1.num_nodes())),
+        )
+    }
+}
+
+impl<G: SequentialGraph, H: SequentialGraph> SplitLabeling for IntersectionGraph<G, H>
+where
+    for<'a> G::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, G::Lender<'a>>: SortedIterator,
+    for<'a> H::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, H::Lender<'a>>: SortedIterator,
+{
+    type SplitLender<'a> = split::seq::Lender<'a, IntersectionGraph<G, H>> where Self: 'a;
+    type IntoIterator<'a> = split::seq::IntoIterator<'a, IntersectionGraph<G, H>> where Self: 'a;
+
+    fn split_iter(&self, how_many: usize) -> Self::IntoIterator<'_> {
+        split::seq::Iter::new(self.iter(), how_many)
+    }
+}
+
+impl<G: SequentialGraph, H: SequentialGraph> SequentialGraph for IntersectionGraph<G, H>
+where
+    for<'a> G::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, G::Lender<'a>>: SortedIterator,
+    for<'a> H::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, H::Lender<'a>>: SortedIterator,
+{
+}
+
+impl<'c, G: SequentialGraph, H: SequentialGraph> IntoLender for &'c IntersectionGraph<G, H>
+where
+    for<'a> G::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, G::Lender<'a>>: SortedIterator,
+    for<'a> H::Lender<'a>: SortedLender + Clone + ExactSizeLender + Send + Sync,
+    for<'a, 'b> LenderIntoIter<'b, H::Lender<'a>>: SortedIterator,
+{
+    type Lender = <IntersectionGraph<G, H> as SequentialLabeling>::Lender<'c>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct Iter<L, M>(L, M);
+
+impl<
+        'succ,
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+    > NodeLabelsLender<'succ> for Iter<L, M>
+{
+    type Label = usize;
+    type IntoIterator = Succ<LenderIntoIter<'succ, L>, LenderIntoIter<'succ, M>>;
+}
+
+impl<
+        'succ,
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+    > Lending<'succ> for Iter<L, M>
+{
+    type Lend = (usize, <Self as NodeLabelsLender<'succ>>::IntoIterator);
+}
+
+impl<
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+    > Lender for Iter<L, M>
+{
+    #[inline(always)]
+    fn next(&mut self) -> Option<Lend<'_, Self>> {
+        let (node0, iter0) = self.0.next().unzip();
+        let (node1, iter1) = self.1.next().unzip();
+        Some((
+            node0.or(node1)?,
+            Succ::new(
+                iter0.map(IntoIterator::into_iter),
+                iter1.map(IntoIterator::into_iter),
+            ),
+        ))
+    }
+}
+
+impl<
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + ExactSizeLender,
+        M: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + ExactSizeLender,
+    > ExactSizeLender for Iter<L, M>
+{
+    fn len(&self) -> usize {
+        self.0.len().max(self.1.len())
+    }
+}
+
+unsafe impl<
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + SortedLender,
+        M: Lender + for<'next> NodeLabelsLender<'next, Label = usize> + SortedLender,
+    > SortedLender for Iter<L, M>
+{
+}
+
+#[derive(Debug, Clone)]
+pub struct Succ<I: Iterator<Item = usize>, J: Iterator<Item = usize>> {
+    iter0: Option<core::iter::Peekable<I>>,
+    iter1: Option<core::iter::Peekable<J>>,
+}
+
+impl<I: Iterator<Item = usize>, J: Iterator<Item = usize>> Succ<I, J> {
+    pub fn new(iter0: Option<I>, iter1: Option<J>) -> Self {
+        Self {
+            iter0: iter0.map(Iterator::peekable),
+            iter1: iter1.map(Iterator::peekable),
+        }
+    }
+}
+
+impl<I: Iterator<Item = usize>, J: Iterator<Item = usize>> Iterator for Succ<I, J> {
+    type Item = usize;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        // A missing list is empty, and so is the intersection
+        let iter0 = self.iter0.as_mut()?;
+        let iter1 = self.iter1.as_mut()?;
+        loop {
+            let next0 = *iter0.peek()?;
+            let next1 = *iter1.peek()?;
+            match next0.cmp(&next1) {
+                std::cmp::Ordering::Less => {
+                    iter0.next();
+                }
+                std::cmp::Ordering::Greater => {
+                    iter1.next();
+                }
+                std::cmp::Ordering::Equal => {
+                    iter1.next();
+                    return iter0.next();
+                }
+            }
+        }
+    }
+}
+
+unsafe impl<I: Iterator<Item = usize> + SortedIterator, J: Iterator<Item = usize> + SortedIterator>
+    SortedIterator for Succ<I, J>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graphs::vec_graph::VecGraph, prelude::proj::Left};
+
+    #[test]
+    fn test_intersection_graph() {
+        let a = [(0, 1), (0, 2), (1, 2), (2, 0), (2, 3), (3, 1), (4, 0)];
+        let b = [(0, 2), (1, 0), (2, 0), (2, 1), (2, 3), (3, 1)];
+        let g = [
+            Left(VecGraph::from_arc_list(a)),
+            Left(VecGraph::from_arc_list(b)),
+        ];
+        let expected = [
+            vec![2],
+            vec![],
+            vec![0, 3],
+            vec![1],
+            // Node 4 does not exist in the second graph
+            vec![],
+        ];
+        for i in 0..2 {
+            let intersection = IntersectionGraph(g[i].clone(), g[1 - i].clone());
+            assert_eq!(intersection.num_nodes(), 5);
+            let mut num_nodes = 0;
+            for_!((node, succ) in intersection.iter() {
+                assert_eq!(succ.collect::<Vec<_>>(), expected[node]);
+                num_nodes += 1;
+            });
+            assert_eq!(num_nodes, 5);
+        }
+    }
+}
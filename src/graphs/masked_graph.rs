@@ -0,0 +1,264 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use core::ops::Index;
+use lender::*;
+
+#[derive(Debug, Clone)]
+/// A wrapper removing logically a set of nodes from an underlying graph.
+///
+/// The mask is any structure indexable by node that returns `true` for the
+/// nodes to be removed, such as a [`BitVec`](sux::bits::BitVec) or a slice of
+/// booleans. Removed nodes are still part of the graph, but they become
+/// isolated: arcs towards them are filtered out lazily, while enumerating
+/// successors, and their successor lists are empty. In this way, it is
+/// possible, for example, to run a visit on a graph with certain nodes
+/// removed without rewriting it.
+///
+/// If the underlying graph supports random access, so does this wrapper, but
+/// [`num_arcs`](RandomAccessLabeling::num_arcs) requires a scan of all
+/// successor lists.
+pub struct MaskedGraph<'a, G: SequentialGraph, M: Index<usize, Output = bool> + ?Sized> {
+    pub graph: &'a G,
+    pub mask: &'a M,
+}
+
+impl<'a, G: SequentialGraph, M: Index<usize, Output = bool> + ?Sized> SequentialLabeling
+    for MaskedGraph<'a, G, M>
+{
+    type Label = usize;
+    type Lender<'b> = Iter<'b, G::Lender<'b>, M>
+        where
+            Self: 'b;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        None
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        Iter {
+            iter: self.graph.iter_from(from),
+            mask: self.mask,
+        }
+    }
+}
+
+impl<'a, G: SequentialGraph, M: Index<usize, Output = bool> + ?Sized> SequentialGraph
+    for MaskedGraph<'a, G, M>
+{
+}
+
+impl<'a, 'b, G: SequentialGraph, M: Index<usize, Output = bool> + ?Sized> IntoLender
+    for &'b MaskedGraph<'a, G, M>
+{
+    type Lender = <MaskedGraph<'a, G, M> as SequentialLabeling>::Lender<'b>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl<'a, G: RandomAccessGraph, M: Index<usize, Output = bool> + ?Sized> RandomAccessLabeling
+    for MaskedGraph<'a, G, M>
+{
+    type Labels<'succ> = MaskedSuccessors<
+        'succ,
+        <<G as RandomAccessLabeling>::Labels<'succ> as IntoIterator>::IntoIter,
+        M,
+    >
+        where
+            Self: 'succ;
+
+    fn num_arcs(&self) -> u64 {
+        (0..self.num_nodes())
+            .map(|node| self.outdegree(node) as u64)
+            .sum()
+    }
+
+    #[inline(always)]
+    fn labels(&self, node_id: usize) -> <Self as RandomAccessLabeling>::Labels<'_> {
+        MaskedSuccessors {
+            iter: (!self.mask[node_id]).then(|| self.graph.successors(node_id).into_iter()),
+            mask: self.mask,
+        }
+    }
+
+    #[inline(always)]
+    fn outdegree(&self, node_id: usize) -> usize {
+        self.successors(node_id).count()
+    }
+}
+
+impl<'a, G: RandomAccessGraph, M: Index<usize, Output = bool> + ?Sized> RandomAccessGraph
+    for MaskedGraph<'a, G, M>
+{
+}
+
+/// An iterator over the nodes of a graph that removes on the fly the masked
+/// nodes.
+#[derive(Debug, Clone)]
+pub struct Iter<'node, I, M: ?Sized> {
+    iter: I,
+    mask: &'node M,
+}
+
+impl<
+        'node,
+        'succ,
+        I: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Index<usize, Output = bool> + ?Sized,
+    > NodeLabelsLender<'succ> for Iter<'node, I, M>
+{
+    type Label = usize;
+    type IntoIterator = MaskedSuccessors<'succ, LenderIntoIter<'succ, I>, M>;
+}
+
+impl<
+        'node,
+        'succ,
+        I: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Index<usize, Output = bool> + ?Sized,
+    > Lending<'succ> for Iter<'node, I, M>
+{
+    type Lend = (usize, <Self as NodeLabelsLender<'succ>>::IntoIterator);
+}
+
+impl<
+        'a,
+        L: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Index<usize, Output = bool> + ?Sized,
+    > Lender for Iter<'a, L, M>
+{
+    #[inline(always)]
+    fn next(&mut self) -> Option<Lend<'_, Self>> {
+        let mask = self.mask;
+        self.iter.next().map(|x| {
+            let (node, succ) = x.into_pair();
+            (
+                node,
+                MaskedSuccessors {
+                    iter: (!mask[node]).then(|| succ.into_iter()),
+                    mask,
+                },
+            )
+        })
+    }
+}
+
+impl<
+        'a,
+        L: ExactSizeLender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Index<usize, Output = bool> + ?Sized,
+    > ExactSizeLender for Iter<'a, L, M>
+{
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+unsafe impl<
+        'a,
+        L: SortedLender + for<'next> NodeLabelsLender<'next, Label = usize>,
+        M: Index<usize, Output = bool> + ?Sized,
+    > SortedLender for Iter<'a, L, M>
+{
+}
+
+/// An iterator over successors that filters out lazily masked nodes.
+///
+/// This adapter can be used directly on any iterator over successors; the
+/// mask must return `true` for the nodes to be skipped.
+#[derive(Debug, Clone)]
+pub struct MaskedSuccessors<'a, I: Iterator<Item = usize>, M: ?Sized> {
+    /// The underlying iterator, or `None` if the source node is masked.
+    iter: Option<I>,
+    mask: &'a M,
+}
+
+impl<'a, I: Iterator<Item = usize>, M: Index<usize, Output = bool> + ?Sized>
+    MaskedSuccessors<'a, I, M>
+{
+    /// Creates a new iterator returning the elements of `iter` that are not
+    /// masked by `mask`.
+    pub fn new(iter: I, mask: &'a M) -> Self {
+        Self {
+            iter: Some(iter),
+            mask,
+        }
+    }
+}
+
+impl<'a, I: Iterator<Item = usize>, M: Index<usize, Output = bool> + ?Sized> Iterator
+    for MaskedSuccessors<'a, I, M>
+{
+    type Item = usize;
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mask = self.mask;
+        self.iter.as_mut()?.find(|&succ| !mask[succ])
+    }
+}
+
+unsafe impl<'a, I: Iterator<Item = usize> + SortedIterator, M: Index<usize, Output = bool> + ?Sized>
+    SortedIterator for MaskedSuccessors<'a, I, M>
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::algo::BfsOrder;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_masked_graph() -> anyhow::Result<()> {
+        let g = Left(VecGraph::from_arcs([
+            (0, 1),
+            (0, 2),
+            (1, 3),
+            (2, 3),
+            (3, 4),
+            (4, 1),
+        ]));
+        assert_eq!(BfsOrder::new(&g).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+
+        // Removing node 1 makes it isolated, so it is visited last
+        let mask = [false, true, false, false, false];
+        let masked = MaskedGraph {
+            graph: &g,
+            mask: &mask,
+        };
+        assert_eq!(
+            BfsOrder::new(&masked).collect::<Vec<_>>(),
+            vec![0, 2, 3, 4, 1]
+        );
+        assert_eq!(masked.num_arcs(), 3);
+        assert_eq!(masked.outdegree(0), 1);
+        assert_eq!(masked.outdegree(1), 0);
+        assert_eq!(masked.successors(4).count(), 0);
+
+        // The sequential iterator agrees with random access
+        let v = Left(VecGraph::from_lender(masked.iter()));
+        assert_eq!(v.num_nodes(), 5);
+        for node in 0..5 {
+            assert!(v.successors(node).into_iter().eq(masked.successors(node)));
+        }
+
+        // The adapter can be used on any iterator over successors
+        assert!(MaskedSuccessors::new(0..5, &mask).eq([0, 2, 3, 4]));
+        Ok(())
+    }
+}
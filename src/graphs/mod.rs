@@ -9,16 +9,30 @@ pub mod arc_list_graph;
 
 pub mod bvgraph;
 pub use bvgraph::*;
+
+mod cached_graph;
+pub use cached_graph::{CachedRandomAccessGraph, CachedSuccessors};
+
+pub mod csr_graph;
+pub mod masked_graph;
 pub mod permuted_graph;
 
 mod union_graph;
 pub use union_graph::UnionGraph;
 
+mod intersection_graph;
+pub use intersection_graph::IntersectionGraph;
+
+mod sharded_graph;
+pub use sharded_graph::{BVGraphShard, ShardedGraph};
+
 pub mod random;
 
 pub mod vec_graph;
 pub mod prelude {
     pub use super::bvgraph::*;
-    pub use super::permuted_graph::PermutedGraph;
+    pub use super::csr_graph::CsrGraph;
+    pub use super::masked_graph::{MaskedGraph, MaskedSuccessors};
+    pub use super::permuted_graph::{PermutedGraph, PermutedRandomAccessGraph};
     pub use super::vec_graph::VecGraph;
 }
@@ -37,6 +37,11 @@ impl<'a, G: SequentialGraph, P: BitFieldSlice<usize>> SequentialLabeling
         self.graph.num_arcs_hint()
     }
 
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.graph.num_arcs_exact()
+    }
+
     #[inline(always)]
     fn iter_from(&self, from: usize) -> Self::Lender<'_> {
         Iter {
@@ -155,6 +160,167 @@ impl<'a, I: ExactSizeIterator<Item = usize>, P: BitFieldSlice<usize>> ExactSizeI
     }
 }
 
+#[derive(Debug, Clone)]
+/// A wrapper applying a permutation to an underlying random-access graph,
+/// providing both sequential and random access.
+///
+/// Differently from [`PermutedGraph`], this wrapper needs the inverse
+/// permutation `inv_perm`, which must satisfy `inv_perm[perm[x]] = x` for
+/// every node `x`: the successors of node `x` are the images through `perm`
+/// of the successors of node `inv_perm[x]` of the underlying graph. Thus, the
+/// cost of random access is that of the underlying graph, plus O(1) per
+/// successor. Nodes are enumerated in increasing order, but successors are
+/// not sorted.
+pub struct PermutedRandomAccessGraph<
+    'a,
+    G: RandomAccessGraph,
+    P: BitFieldSlice<usize> + ?Sized,
+    Q: BitFieldSlice<usize> + ?Sized,
+> {
+    pub graph: &'a G,
+    pub perm: &'a P,
+    pub inv_perm: &'a Q,
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> SequentialLabeling
+    for PermutedRandomAccessGraph<'a, G, P, Q>
+{
+    type Label = usize;
+    type Lender<'b> = RandomAccessIter<'b, G, P, Q>
+        where
+            Self: 'b;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.graph.num_nodes()
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        Some(self.graph.num_arcs())
+    }
+
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.graph.num_arcs())
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        RandomAccessIter {
+            graph: self.graph,
+            perm: self.perm,
+            inv_perm: self.inv_perm,
+            nodes: from..self.graph.num_nodes(),
+        }
+    }
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> SequentialGraph
+    for PermutedRandomAccessGraph<'a, G, P, Q>
+{
+}
+
+impl<'a, 'b, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> IntoLender
+    for &'b PermutedRandomAccessGraph<'a, G, P, Q>
+{
+    type Lender = <PermutedRandomAccessGraph<'a, G, P, Q> as SequentialLabeling>::Lender<'b>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>>
+    RandomAccessLabeling for PermutedRandomAccessGraph<'a, G, P, Q>
+{
+    type Labels<'succ> =
+        Succ<'succ, <<G as RandomAccessLabeling>::Labels<'succ> as IntoIterator>::IntoIter, P>
+        where
+            Self: 'succ;
+
+    #[inline(always)]
+    fn num_arcs(&self) -> u64 {
+        self.graph.num_arcs()
+    }
+
+    #[inline(always)]
+    fn labels(&self, node_id: usize) -> <Self as RandomAccessLabeling>::Labels<'_> {
+        Succ {
+            iter: self
+                .graph
+                .successors(self.inv_perm.get(node_id))
+                .into_iter(),
+            perm: self.perm,
+        }
+    }
+
+    #[inline(always)]
+    fn outdegree(&self, node_id: usize) -> usize {
+        self.graph.outdegree(self.inv_perm.get(node_id))
+    }
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> RandomAccessGraph
+    for PermutedRandomAccessGraph<'a, G, P, Q>
+{
+}
+
+/// An iterator over the nodes of a [`PermutedRandomAccessGraph`], in
+/// increasing order, using random access on the underlying graph.
+#[derive(Debug, Clone)]
+pub struct RandomAccessIter<'a, G, P, Q> {
+    graph: &'a G,
+    perm: &'a P,
+    inv_perm: &'a Q,
+    nodes: core::ops::Range<usize>,
+}
+
+impl<'a, 'succ, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>>
+    NodeLabelsLender<'succ> for RandomAccessIter<'a, G, P, Q>
+{
+    type Label = usize;
+    type IntoIterator =
+        Succ<'a, <<G as RandomAccessLabeling>::Labels<'a> as IntoIterator>::IntoIter, P>;
+}
+
+impl<'a, 'succ, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>>
+    Lending<'succ> for RandomAccessIter<'a, G, P, Q>
+{
+    type Lend = (usize, <Self as NodeLabelsLender<'succ>>::IntoIterator);
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> Lender
+    for RandomAccessIter<'a, G, P, Q>
+{
+    #[inline(always)]
+    fn next(&mut self) -> Option<Lend<'_, Self>> {
+        self.nodes.next().map(|node| {
+            (
+                node,
+                Succ {
+                    iter: self.graph.successors(self.inv_perm.get(node)).into_iter(),
+                    perm: self.perm,
+                },
+            )
+        })
+    }
+}
+
+impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>> ExactSizeLender
+    for RandomAccessIter<'a, G, P, Q>
+{
+    fn len(&self) -> usize {
+        self.nodes.len()
+    }
+}
+
+unsafe impl<'a, G: RandomAccessGraph, P: BitFieldSlice<usize>, Q: BitFieldSlice<usize>>
+    SortedLender for RandomAccessIter<'a, G, P, Q>
+{
+}
+
 #[cfg(test)]
 #[test]
 fn test_permuted_graph() -> anyhow::Result<()> {
@@ -0,0 +1,90 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+
+use crate::graphs::vec_graph::VecGraph;
+
+/// Returns a Barabási-Albert preferential-attachment random graph.
+///
+/// The graph is built by adding nodes one at a time: the first `m` nodes have
+/// no successors, node `m` has as successors all previous nodes, and each
+/// following node has as successors `m` distinct previous nodes, chosen with
+/// probability proportional to their current degree (counting both
+/// successors and predecessors). Thus, the graph has exactly (`num_nodes` −
+/// `m`) · `m` arcs, all going from a node to a smaller node, and no loops or
+/// multiple arcs, so, differently from other generators, there is no
+/// [configuration](super::GeneratorConfig) but just a `seed` for the
+/// pseudorandom number generator. The degree distribution follows a power law;
+/// use [`symmetrize`](crate::transform::symmetrize) to obtain the undirected
+/// version of the model.
+///
+/// # Panics
+///
+/// If `m` is zero or not smaller than `num_nodes`.
+pub fn barabasi_albert(num_nodes: usize, m: usize, seed: u64) -> VecGraph {
+    assert!(
+        m >= 1 && m < num_nodes,
+        "m must be positive and smaller than the number of nodes ({}), but it is {}",
+        num_nodes,
+        m
+    );
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(seed);
+    let mut graph = VecGraph::empty(num_nodes);
+    // every node appears once for each arc it is incident to
+    let mut endpoints = Vec::with_capacity(2 * (num_nodes - m) * m);
+    let mut targets = (0..m).collect::<Vec<_>>();
+    for x in m..num_nodes {
+        if x > m {
+            targets.clear();
+            while targets.len() < m {
+                let y = endpoints[rng.gen_range(0..endpoints.len() as u64) as usize];
+                if !targets.contains(&y) {
+                    targets.push(y);
+                }
+            }
+        }
+        for &y in &targets {
+            graph.add_arc(x, y);
+            endpoints.push(x);
+            endpoints.push(y);
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::Left;
+    use crate::traits::{RandomAccessGraph, RandomAccessLabeling, SequentialLabeling};
+
+    #[test]
+    fn test_barabasi_albert() {
+        for m in [1, 3, 10] {
+            let g = Left(barabasi_albert(100, m, m as u64));
+            assert_eq!(g.num_nodes(), 100);
+            assert_eq!(g.num_arcs(), ((100 - m) * m) as u64);
+            for x in 0..100 {
+                assert_eq!(g.outdegree(x), if x < m { 0 } else { m });
+                assert!(g.successors(x).into_iter().all(|y| y < x));
+            }
+            // The same seed yields the same graph
+            assert_eq!(g, Left(barabasi_albert(100, m, m as u64)));
+        }
+        assert_ne!(
+            Left(barabasi_albert(100, 3, 0)),
+            Left(barabasi_albert(100, 3, 1))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_barabasi_albert_m_too_large() {
+        barabasi_albert(10, 10, 0);
+    }
+}
@@ -6,15 +6,24 @@
 
 use lender::{Lend, Lender, Lending};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
 
+use super::GeneratorConfig;
 use crate::prelude::{NodeLabelsLender, SequentialGraph, SequentialLabeling};
 
 /// Provides a sequential implementation of Erdös-Rényi random graphs.
 ///
 /// The Erdös-Rényi random graph model is a simple model for generating random
 /// graphs. It is parameterized by the number of nodes `n` and the probability
-/// `p` of an arc between any two nodes. In this implementation, loops are never
-/// included.
+/// `p` of an arc between any two nodes. Loops are included only if
+/// [`remove_self_loops`](GeneratorConfig::remove_self_loops) is false; multiple
+/// arcs are never generated.
+///
+/// Graphs built with [`new`](ErdosRenyi::new) use a [`SmallRng`] and are
+/// always loopless, so a given seed yields the same graph as in previous
+/// versions of this crate, but the output might depend on the platform. Use
+/// [`with_config`](ErdosRenyi::with_config) to obtain the reproducibility
+/// guarantees described in the [module documentation](super).
 ///
 /// Note that the time required to iterate over the graph is quadratic in `n`,
 /// so if you plan to reuse it you should store the result in a more efficient
@@ -24,16 +33,36 @@ use crate::prelude::{NodeLabelsLender, SequentialGraph, SequentialLabeling};
 pub struct ErdosRenyi {
     n: usize,
     p: f64,
-    seed: u64,
+    config: GeneratorConfig,
+    /// Whether to use the legacy [`SmallRng`] stream of [`ErdosRenyi::new`].
+    legacy: bool,
 }
 
 impl ErdosRenyi {
-    /// Create a new Erdös-Rényi random graph, given the number of
+    /// Create a new loopless Erdös-Rényi random graph, given the number of
     /// nodes, the probability of an edge between any two nodes, and a
     /// seed for the [pseudorandom number generator](SmallRng).
     pub fn new(n: usize, p: f64, seed: u64) -> Self {
         assert!((0.0..=1.0).contains(&p), "p must be in [0..1]");
-        Self { n, p, seed }
+        Self {
+            n,
+            p,
+            config: GeneratorConfig::with_seed(seed),
+            legacy: true,
+        }
+    }
+
+    /// Create a new Erdös-Rényi random graph, given the number of
+    /// nodes, the probability of an edge between any two nodes, and a
+    /// [configuration](GeneratorConfig).
+    pub fn with_config(n: usize, p: f64, config: GeneratorConfig) -> Self {
+        assert!((0.0..=1.0).contains(&p), "p must be in [0..1]");
+        Self {
+            n,
+            p,
+            config,
+            legacy: false,
+        }
     }
 }
 
@@ -45,25 +74,59 @@ impl SequentialLabeling for ErdosRenyi {
     }
 
     fn iter_from(&self, from: usize) -> Iter {
-        let mut rng = SmallRng::seed_from_u64(self.seed);
-        for _ in 0..from * (self.n - 1) {
+        let seed = self.config.seed;
+        let (mut rng, skip) = if self.legacy {
+            // The legacy stream draws no random bits for loops
+            (
+                ErRng::Small(SmallRng::seed_from_u64(seed)),
+                from * self.n.saturating_sub(1),
+            )
+        } else {
+            // One random bit is drawn for every pair of nodes, loops included
+            (
+                ErRng::Xoshiro(Xoshiro256PlusPlus::seed_from_u64(seed)),
+                from * self.n,
+            )
+        };
+        for _ in 0..skip {
             rng.gen_bool(self.p);
         }
         Iter {
             n: self.n,
             p: self.p,
+            remove_self_loops: self.config.remove_self_loops,
             x: from,
             rng,
         }
     }
 }
 
+/// The pseudorandom number generator of an [`Iter`].
+#[derive(Debug, Clone)]
+enum ErRng {
+    /// The legacy stream of [`ErdosRenyi::new`].
+    Small(SmallRng),
+    /// The portable stream of [`ErdosRenyi::with_config`].
+    Xoshiro(Xoshiro256PlusPlus),
+}
+
+impl ErRng {
+    #[inline(always)]
+    fn gen_bool(&mut self, p: f64) -> bool {
+        match self {
+            ErRng::Small(rng) => rng.gen_bool(p),
+            ErRng::Xoshiro(rng) => rng.gen_bool(p),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Iter {
     n: usize,
     p: f64,
+    remove_self_loops: bool,
     x: usize,
-    rng: SmallRng,
+    rng: ErRng,
 }
 
 impl<'succ> NodeLabelsLender<'succ> for Iter {
@@ -82,12 +145,16 @@ impl Lender for Iter {
             return None;
         }
 
-        let result = Some((
-            self.x,
-            (0..self.n)
-                .filter(|&y| y != self.x && self.rng.gen_bool(self.p))
+        let x = self.x;
+        let successors = match self.rng {
+            ErRng::Small(_) => (0..self.n)
+                .filter(|&y| y != x && self.rng.gen_bool(self.p))
+                .collect::<Vec<_>>(),
+            ErRng::Xoshiro(_) => (0..self.n)
+                .filter(|&y| self.rng.gen_bool(self.p) && !(self.remove_self_loops && y == x))
                 .collect::<Vec<_>>(),
-        ));
+        };
+        let result = Some((x, successors));
         self.x += 1;
         result
     }
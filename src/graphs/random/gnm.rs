@@ -0,0 +1,127 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256PlusPlus;
+use std::collections::HashSet;
+
+use super::GeneratorConfig;
+use crate::graphs::vec_graph::VecGraph;
+
+/// Returns an Erdös-Rényi random graph with a given number of nodes and arcs.
+///
+/// Contrarily to [`ErdosRenyi`](super::ErdosRenyi), in which each arc is
+/// present independently with a given probability, the number of arcs of the
+/// result is exactly `num_arcs`: the arcs are chosen uniformly at random among
+/// all possible arcs, without duplicates. Loops are included only if
+/// [`remove_self_loops`](GeneratorConfig::remove_self_loops) is false.
+///
+/// Arcs are sampled by rejection, or, if `num_arcs` is more than half of the
+/// possible arcs, the missing arcs are sampled instead, so the expected
+/// number of draws is at most twice the number of sampled arcs.
+///
+/// # Panics
+///
+/// If `num_arcs` is larger than the number of possible arcs, or if the number
+/// of possible arcs does not fit into a `u64`.
+pub fn erdos_renyi(num_nodes: usize, num_arcs: u64, config: GeneratorConfig) -> VecGraph {
+    let n = num_nodes as u64;
+    let row_len = if config.remove_self_loops {
+        n.saturating_sub(1)
+    } else {
+        n
+    };
+    let num_pairs = n.checked_mul(row_len).unwrap_or_else(|| {
+        panic!(
+            "The number of possible arcs of a graph with {} nodes overflows a u64",
+            num_nodes
+        )
+    });
+    assert!(
+        num_arcs <= num_pairs,
+        "A graph with {} nodes has at most {} arcs, but {} were requested",
+        num_nodes,
+        num_pairs,
+        num_arcs
+    );
+
+    let mut rng = Xoshiro256PlusPlus::seed_from_u64(config.seed);
+    let complement = num_arcs > num_pairs / 2;
+    let num_samples = if complement {
+        num_pairs - num_arcs
+    } else {
+        num_arcs
+    };
+    let mut sampled = HashSet::with_capacity(num_samples as usize);
+    while (sampled.len() as u64) < num_samples {
+        sampled.insert(rng.gen_range(0..num_pairs));
+    }
+
+    // map pair indices to arcs, skipping loops if necessary
+    let arc = |pair: u64| {
+        let x = pair / row_len;
+        let mut y = pair % row_len;
+        if config.remove_self_loops && y >= x {
+            y += 1;
+        }
+        (x as usize, y as usize)
+    };
+    let mut graph = VecGraph::empty(num_nodes);
+    if complement {
+        for pair in (0..num_pairs).filter(|pair| !sampled.contains(pair)) {
+            let (x, y) = arc(pair);
+            graph.add_arc(x, y);
+        }
+    } else {
+        for &pair in &sampled {
+            let (x, y) = arc(pair);
+            graph.add_arc(x, y);
+        }
+    }
+    graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::labels::Left;
+    use crate::traits::{RandomAccessGraph, RandomAccessLabeling, SequentialLabeling};
+
+    #[test]
+    fn test_erdos_renyi() {
+        for num_arcs in [0, 1, 100, 2000, 2449, 2450] {
+            let config = GeneratorConfig::with_seed(num_arcs);
+            let g = Left(erdos_renyi(50, num_arcs, config));
+            assert_eq!(g.num_nodes(), 50);
+            assert_eq!(g.num_arcs(), num_arcs);
+            assert!((0..50).all(|x| g.successors(x).into_iter().all(|y| y != x)));
+            // The same configuration yields the same graph
+            assert_eq!(g, Left(erdos_renyi(50, num_arcs, config)));
+        }
+
+        let config = GeneratorConfig {
+            remove_self_loops: false,
+            ..GeneratorConfig::with_seed(0)
+        };
+        assert_eq!(erdos_renyi(50, 2500, config).num_arcs(), 2500);
+        assert_ne!(
+            Left(erdos_renyi(50, 100, GeneratorConfig::with_seed(0))),
+            Left(erdos_renyi(50, 100, GeneratorConfig::with_seed(1)))
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_erdos_renyi_too_many_arcs() {
+        erdos_renyi(50, 2451, GeneratorConfig::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "overflows a u64")]
+    fn test_erdos_renyi_too_many_nodes() {
+        erdos_renyi(1 << 33, 1, GeneratorConfig::default());
+    }
+}
@@ -0,0 +1,251 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::prelude::*;
+use anyhow::{Context, Result};
+use dsi_bitstream::prelude::*;
+use lender::*;
+use std::path::Path;
+
+/// The type of the shards loaded by [`ShardedGraph::new`].
+pub type BVGraphShard =
+    BVGraphSeq<DynCodesDecoderFactory<BE, MmapHelper<u32>, EmptyDict<usize, usize>>>;
+
+#[derive(Debug, Clone)]
+/// A sequence of graphs exhibited as a single graph.
+///
+/// A graph too large to be handled as a single file can be split by node
+/// range into shards, each stored as a separate graph: the node ids of each
+/// shard start from zero, whereas successors are global node ids. This
+/// wrapper presents the shards as a single graph in which the nodes of a
+/// shard are offset by the number of nodes of the previous shards; shards
+/// are decoded one after the other, and only one is being decoded at any
+/// time.
+///
+/// The number of nodes is the sum of the number of nodes of the shards.
+/// Successors are returned unchanged, so it is responsibility of the user to
+/// make them consistent with the global numbering.
+pub struct ShardedGraph<G: SequentialGraph> {
+    shards: Vec<G>,
+    /// The first global node id of each shard, followed by the number of
+    /// nodes.
+    offsets: Box<[usize]>,
+}
+
+impl ShardedGraph<BVGraphShard> {
+    /// Loads (using memory mapping) the big-endian graphs with the given
+    /// basenames as the shards of a graph, in the given order.
+    ///
+    /// Use [`from_shards`](ShardedGraph::from_shards) for graphs with
+    /// different endianness or loaded with a different configuration.
+    pub fn new(basenames: &[impl AsRef<Path>]) -> Result<Self> {
+        let shards = basenames
+            .iter()
+            .map(|basename| {
+                let basename = basename.as_ref();
+                BVGraphSeq::with_basename(basename)
+                    .endianness::<BE>()
+                    .load()
+                    .with_context(|| format!("Could not load shard {}", basename.display()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self::from_shards(shards))
+    }
+}
+
+impl<G: SequentialGraph> ShardedGraph<G> {
+    /// Creates a sharded graph from the given shards, in the given order.
+    pub fn from_shards(shards: Vec<G>) -> Self {
+        let mut offsets = Vec::with_capacity(shards.len() + 1);
+        offsets.push(0);
+        for shard in &shards {
+            offsets.push(offsets.last().unwrap() + shard.num_nodes());
+        }
+        Self {
+            shards,
+            offsets: offsets.into_boxed_slice(),
+        }
+    }
+
+    /// Returns the shards.
+    pub fn shards(&self) -> &[G] {
+        &self.shards
+    }
+
+    /// Returns the global id of the first node of each shard, followed by the
+    /// number of nodes of the graph.
+    pub fn offsets(&self) -> &[usize] {
+        &self.offsets
+    }
+}
+
+impl<G: SequentialGraph> SequentialLabeling for ShardedGraph<G> {
+    type Label = usize;
+    type Lender<'b> = Iter<'b, G>
+        where
+            Self: 'b;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        *self.offsets.last().unwrap()
+    }
+
+    fn num_arcs_hint(&self) -> Option<u64> {
+        self.shards.iter().map(G::num_arcs_hint).sum()
+    }
+
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.shards.iter().map(G::num_arcs_exact).sum()
+    }
+
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        let from = from.min(self.num_nodes());
+        // The last shard starting at or before from: empty shards are skipped
+        let shard = self.offsets.partition_point(|&offset| offset <= from) - 1;
+        Iter {
+            shards: &self.shards,
+            offsets: &self.offsets,
+            shard,
+            next_node: from,
+            iter: self
+                .shards
+                .get(shard)
+                .map(|graph| graph.iter_from(from - self.offsets[shard])),
+        }
+    }
+}
+
+impl<G: SequentialGraph> SequentialGraph for ShardedGraph<G> {}
+
+impl<'a, G: SequentialGraph> IntoLender for &'a ShardedGraph<G> {
+    type Lender = <ShardedGraph<G> as SequentialLabeling>::Lender<'a>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+/// An iterator over the nodes of a [`ShardedGraph`].
+pub struct Iter<'a, G: SequentialGraph> {
+    shards: &'a [G],
+    offsets: &'a [usize],
+    /// The shard being decoded, or the number of shards if the iteration is
+    /// over.
+    shard: usize,
+    /// The global id of the next node to be returned.
+    next_node: usize,
+    iter: Option<G::Lender<'a>>,
+}
+
+impl<'a, 'succ, G: SequentialGraph> NodeLabelsLender<'succ> for Iter<'a, G> {
+    type Label = usize;
+    type IntoIterator = LenderIntoIterator<'succ, G::Lender<'a>>;
+}
+
+impl<'a, 'succ, G: SequentialGraph> Lending<'succ> for Iter<'a, G> {
+    type Lend = (usize, <Self as NodeLabelsLender<'succ>>::IntoIterator);
+}
+
+impl<'a, G: SequentialGraph> Lender for Iter<'a, G> {
+    fn next(&mut self) -> Option<Lend<'_, Self>> {
+        while self.shard < self.shards.len() && self.next_node >= self.offsets[self.shard + 1] {
+            self.shard += 1;
+            self.iter = self.shards.get(self.shard).map(|graph| graph.iter());
+        }
+        let offset = self.offsets[self.shard];
+        let (node, succ) = self.iter.as_mut()?.next()?;
+        self.next_node += 1;
+        Some((offset + node, succ))
+    }
+}
+
+impl<'a, G: SequentialGraph> ExactSizeLender for Iter<'a, G> {
+    fn len(&self) -> usize {
+        self.offsets[self.shards.len()] - self.next_node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::arc_list_graph::ArcListGraph;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::labels::Left;
+
+    #[test]
+    fn test_sharded_graph() -> Result<()> {
+        let arcs = [
+            (0, 1),
+            (0, 4),
+            (1, 2),
+            (2, 0),
+            (2, 5),
+            (3, 4),
+            (4, 3),
+            (4, 5),
+            (5, 0),
+        ];
+        let graph = Left(VecGraph::from_arcs(arcs));
+        assert_eq!(graph.num_nodes(), 6);
+
+        // Nodes 0..3 and 3..6, with local sources and global targets
+        let tmp_dir = tempfile::tempdir()?;
+        let mut basenames = vec![];
+        for (i, range) in [0..3, 3..6].into_iter().enumerate() {
+            let basename = tmp_dir.path().join(format!("shard-{}", i));
+            let shard = Left(ArcListGraph::new(
+                range.len(),
+                arcs.into_iter()
+                    .filter(|(src, _)| range.contains(src))
+                    .map(|(src, dst)| (src - range.start, dst))
+                    .collect::<Vec<_>>(),
+            ));
+            BVComp::single_thread::<BE, _>(&basename, &shard, CompFlags::default(), false, None)?;
+            basenames.push(basename);
+        }
+
+        let sharded = ShardedGraph::new(&basenames)?;
+        assert_eq!(sharded.num_nodes(), 6);
+        assert_eq!(sharded.offsets(), &[0, 3, 6]);
+        assert_eq!(sharded.num_arcs_hint(), Some(arcs.len() as u64));
+
+        let mut iter = sharded.iter();
+        assert_eq!(iter.len(), 6);
+        for_!((node, succ) in graph.iter() {
+            let (sharded_node, sharded_succ) = iter.next().unwrap();
+            assert_eq!(node, sharded_node);
+            assert!(itertools::equal(succ, sharded_succ), "Node {} differs", node);
+        });
+        assert!(iter.next().is_none());
+
+        // Starting in the middle of a shard
+        let mut iter = sharded.iter_from(4);
+        assert_eq!(iter.len(), 2);
+        let (node, succ) = iter.next().unwrap();
+        assert_eq!(node, 4);
+        assert_eq!(succ.into_iter().collect::<Vec<_>>(), vec![3, 5]);
+
+        // Empty shards are skipped
+        let sharded = ShardedGraph::from_shards(vec![
+            Left(VecGraph::<()>::empty(0)),
+            Left(VecGraph::from_arcs([(0, 1)])),
+            Left(VecGraph::<()>::empty(0)),
+            Left(VecGraph::from_arcs([(1, 0)])),
+        ]);
+        assert_eq!(sharded.num_nodes(), 4);
+        let mut iter = sharded.iter();
+        let mut nodes = vec![];
+        while let Some((node, succ)) = iter.next() {
+            nodes.push((node, succ.into_iter().collect::<Vec<_>>()));
+        }
+        assert_eq!(
+            nodes,
+            vec![(0, vec![1]), (1, vec![]), (2, vec![]), (3, vec![0])]
+        );
+        Ok(())
+    }
+}
@@ -9,6 +9,15 @@ use lender::*;
 
 #[derive(Debug, Clone)]
 /// A wrapper exhibiting the union of two graphs.
+///
+/// The successors of each node are obtained by merging the (sorted)
+/// successor lists of the node in the two graphs and removing duplicates, so
+/// an arc appearing in both graphs appears once in the union. The union is
+/// computed on the fly while iterating, and nothing is materialized.
+///
+/// The two graphs need not have the same number of nodes: the union has as
+/// many nodes as the larger graph, and the nodes missing in the smaller
+/// graph are considered to have no successors.
 pub struct UnionGraph<G: SequentialGraph, H: SequentialGraph>(pub G, pub H);
 
 impl<G: SequentialGraph, H: SequentialGraph> SequentialLabeling for UnionGraph<G, H>
@@ -245,4 +254,26 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_union_graph_arcs() {
+        use crate::{graphs::vec_graph::VecGraph, prelude::proj::Left};
+        let a = [(0, 1), (0, 2), (1, 2), (2, 0), (3, 1)];
+        let b = [(0, 2), (1, 0), (2, 0), (3, 1), (3, 4), (4, 3)];
+        let union = UnionGraph(
+            Left(VecGraph::from_arc_list(a)),
+            Left(VecGraph::from_arc_list(b)),
+        );
+        assert_eq!(union.num_nodes(), 5);
+
+        // Three arcs are shared
+        let expected = Left(VecGraph::from_arc_list(a.into_iter().chain(b)));
+        let mut num_arcs = 0;
+        for_!((node, succ) in union.iter() {
+            let succ = succ.into_iter().collect::<Vec<_>>();
+            assert_eq!(succ, expected.successors(node).into_iter().collect::<Vec<_>>());
+            num_arcs += succ.len();
+        });
+        assert_eq!(num_arcs, a.len() + b.len() - 3);
+    }
 }
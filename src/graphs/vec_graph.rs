@@ -42,6 +42,15 @@ impl<L: Copy + 'static> Ord for Successor<L> {
     }
 }
 
+/// Successors can be looked up in a [`BTreeSet`] by node only, as equality
+/// and order depend on the first coordinate only.
+impl<L: Copy + 'static> core::borrow::Borrow<usize> for Successor<L> {
+    #[inline(always)]
+    fn borrow(&self) -> &usize {
+        &self.0
+    }
+}
+
 /// A mutable [`LabeledRandomAccessGraph`] implementation based on a vector of [`BTreeSet`].
 ///
 /// Choosing [`()`](https://doc.rust-lang.org/std/primitive.unit.html)
@@ -119,6 +128,38 @@ impl<L: Copy + 'static> VecGraph<L> {
         result
     }
 
+    /// Remove all arcs incident to a node (both outgoing and incoming)
+    /// and return whether at least one arc was removed.
+    ///
+    /// The node is not actually removed: it becomes an isolated node, leaving
+    /// a hole, so that the ids of all other nodes remain stable. In particular,
+    /// the number of nodes does not change, and arcs can be later added again
+    /// to the node.
+    ///
+    /// Note that this method scans the successors of all nodes to remove
+    /// incoming arcs.
+    ///
+    /// # Panics
+    ///
+    /// If `node` is not smaller than the number of nodes.
+    pub fn remove_node(&mut self, node: usize) -> bool {
+        if node >= self.succ.len() {
+            panic!(
+                "Node {} does not exist (the graph has {} nodes)",
+                node,
+                self.succ.len(),
+            );
+        }
+        let outdegree = self.succ[node].len();
+        self.succ[node].clear();
+        self.number_of_arcs -= outdegree as u64;
+        let mut result = outdegree != 0;
+        for u in 0..self.succ.len() {
+            result |= self.remove_arc(u, node);
+        }
+        result
+    }
+
     /// Add nodes and labeled successors from an [`IntoLender`] yielding a [`NodeLabelsLender`].
     pub fn add_labeled_lender<I: IntoLender>(&mut self, iter_nodes: I)
     where
@@ -166,6 +207,17 @@ impl<L: Copy + 'static> VecGraph<L> {
         g.add_labeled_arcs(arcs);
         g
     }
+
+    /// Creates a new graph from an [`IntoIterator`] of labeled arcs.
+    ///
+    /// The number of nodes is the maximum endpoint plus one (zero if there
+    /// are no arcs). Successor lists are sorted, and duplicate arcs are
+    /// inserted only once, keeping the first label.
+    ///
+    /// This is a synonym for [`from_labeled_arc_list`](Self::from_labeled_arc_list).
+    pub fn from_labeled_arcs(arcs: impl IntoIterator<Item = (usize, usize, L)>) -> Self {
+        Self::from_labeled_arc_list(arcs)
+    }
 }
 
 impl VecGraph<()> {
@@ -222,6 +274,17 @@ impl VecGraph<()> {
         g.add_arc_list(arcs);
         g
     }
+
+    /// Creates a new graph from an [`IntoIterator`] of arcs.
+    ///
+    /// The number of nodes is the maximum endpoint plus one (zero if there
+    /// are no arcs). Successor lists are sorted, and duplicate arcs are
+    /// inserted only once.
+    ///
+    /// This is a synonym for [`from_arc_list`](Self::from_arc_list).
+    pub fn from_arcs(arcs: impl IntoIterator<Item = (usize, usize)>) -> Self {
+        Self::from_arc_list(arcs)
+    }
 }
 
 impl<'a, L: Copy + 'static> IntoLender for &'a VecGraph<L> {
@@ -247,6 +310,11 @@ impl<L: Copy + 'static> SequentialLabeling for VecGraph<L> {
         Some(self.num_arcs())
     }
 
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.num_arcs())
+    }
+
     #[inline(always)]
     fn iter_from(&self, from: usize) -> Self::Lender<'_> {
         IteratorImpl {
@@ -276,7 +344,16 @@ impl<L: Copy + 'static> RandomAccessLabeling for VecGraph<L> {
     }
 }
 
-impl<L: Copy + 'static> LabeledRandomAccessGraph<L> for VecGraph<L> {}
+impl<L: Copy + 'static> LabeledRandomAccessGraph<L> for VecGraph<L> {
+    /// Returns whether there is an arc going from `src_node_id` to `dst_node_id`.
+    ///
+    /// This method performs a logarithmic lookup in the [`BTreeSet`] of
+    /// successors of `src_node_id`.
+    #[inline(always)]
+    fn has_arc(&self, src_node_id: usize, dst_node_id: usize) -> bool {
+        self.succ[src_node_id].contains(&dst_node_id)
+    }
+}
 
 #[doc(hidden)]
 #[repr(transparent)]
@@ -305,3 +382,65 @@ fn test_remove() {
     assert!(g.remove_arc(0, 2));
     assert!(!g.remove_arc(0, 2));
 }
+
+#[test]
+fn test_remove_arc_loops() {
+    let mut g = VecGraph::from_arc_list([(0, 0), (0, 1), (1, 1), (1, 0)]);
+    assert!(g.remove_arc(0, 0));
+    assert!(!g.remove_arc(0, 0));
+    assert_eq!(g.num_arcs(), 3);
+    assert!(g.remove_arc(1, 1));
+    assert_eq!(g.num_arcs(), 2);
+    assert_eq!(g, VecGraph::from_arc_list([(0, 1), (1, 0)]));
+}
+
+#[test]
+fn test_remove_node() {
+    let mut g = VecGraph::from_arc_list([(0, 1), (1, 1), (1, 2), (2, 1), (2, 0), (3, 0)]);
+    assert!(g.remove_node(1));
+    // Node ids are stable: node 1 is now an isolated node
+    assert_eq!(g.num_nodes(), 4);
+    assert_eq!(g.num_arcs(), 2);
+    assert_eq!(g.outdegree(1), 0);
+    for node in 0..g.num_nodes() {
+        assert!(!RandomAccessLabeling::labels(&g, node).any(|(v, _)| v == 1));
+    }
+    assert!(RandomAccessLabeling::labels(&g, 2).map(|(v, _)| v).eq([0]));
+    assert!(RandomAccessLabeling::labels(&g, 3).map(|(v, _)| v).eq([0]));
+    // Removing again is a no-op
+    assert!(!g.remove_node(1));
+    assert_eq!(g.num_arcs(), 2);
+    // Only incoming arcs
+    assert!(g.remove_node(0));
+    assert_eq!(g.num_arcs(), 0);
+    assert!(!g.remove_node(0));
+    // The hole can be reused
+    assert!(g.add_arc(1, 1));
+    assert_eq!(g.num_arcs(), 1);
+}
+
+#[test]
+#[should_panic(expected = "Node 3 does not exist (the graph has 3 nodes)")]
+fn test_remove_node_out_of_range() {
+    let mut g = VecGraph::from_arc_list([(0, 1), (1, 2)]);
+    g.remove_node(3);
+}
+
+#[test]
+fn test_from_arcs() {
+    let g = VecGraph::from_arcs([]);
+    assert_eq!(g.num_nodes(), 0);
+    assert_eq!(g.num_arcs(), 0);
+
+    // Node 10 has no successors, but it is the maximum endpoint
+    let g = VecGraph::from_arcs([(2, 1), (0, 10), (2, 0), (2, 1)]);
+    assert_eq!(g.num_nodes(), 11);
+    assert_eq!(g.num_arcs(), 3);
+    assert!(RandomAccessLabeling::labels(&g, 2).map(|(v, _)| v).eq([0, 1]));
+    assert_eq!(g.outdegree(10), 0);
+
+    let g = VecGraph::from_labeled_arcs([(1, 0, 'a'), (0, 1, 'b'), (1, 0, 'c')]);
+    assert_eq!(g.num_nodes(), 2);
+    assert_eq!(g.num_arcs(), 2);
+    assert!(RandomAccessLabeling::labels(&g, 1).eq([(0, 'a')]));
+}
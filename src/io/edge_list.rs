@@ -0,0 +1,113 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::traits::SequentialGraph;
+use anyhow::{bail, Context, Result};
+use lender::*;
+use std::io::{BufRead, Write};
+
+/// An iterator over the arcs of an edge list.
+///
+/// See [`read_edge_list`].
+#[derive(Debug)]
+pub struct EdgeList<R> {
+    lines: std::io::Lines<R>,
+    line_num: usize,
+}
+
+impl<R: BufRead> Iterator for EdgeList<R> {
+    type Item = Result<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e).context("Could not read edge list")),
+            };
+            self.line_num += 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return Some(parse_arc(line, self.line_num));
+        }
+    }
+}
+
+fn parse_arc(line: &str, line_num: usize) -> Result<(usize, usize)> {
+    let mut fields = line.split_whitespace();
+    let (Some(src), Some(dst), None) = (fields.next(), fields.next(), fields.next()) else {
+        bail!(
+            "Line {} is not of the form \"src dst\": {:?}",
+            line_num,
+            line
+        );
+    };
+    let src = src
+        .parse()
+        .with_context(|| format!("Could not parse source {:?} on line {}", src, line_num))?;
+    let dst = dst
+        .parse()
+        .with_context(|| format!("Could not parse target {:?} on line {}", dst, line_num))?;
+    Ok((src, dst))
+}
+
+/// Returns an iterator over the arcs of an edge list.
+///
+/// Each line of the edge list contains the source and the target of an arc,
+/// separated by whitespace. Blank lines and lines starting with `#` are
+/// skipped. Arcs are returned in the order in which they appear, so they can
+/// be unsorted and contain duplicates, as accepted by
+/// [`BVComp::compress_from_arcs`](crate::graphs::bvgraph::BVComp::compress_from_arcs).
+///
+/// The iterator returns an error, with the offending line number, if a line
+/// cannot be read or parsed.
+pub fn read_edge_list<R: BufRead>(reader: R) -> EdgeList<R> {
+    EdgeList {
+        lines: reader.lines(),
+        line_num: 0,
+    }
+}
+
+/// Writes the arcs of a graph as an edge list.
+///
+/// Arcs are written one per line, with source and target separated by a tab,
+/// in the order in which they are returned by the graph: for a
+/// [`BVGraph`](crate::graphs::bvgraph::BVGraph) they are thus sorted.
+/// The output can be read back by [`read_edge_list`].
+pub fn write_edge_list(graph: &impl SequentialGraph, mut writer: impl Write) -> Result<()> {
+    for_!((src, succ) in graph.iter() {
+        for dst in succ {
+            writeln!(writer, "{}\t{}", src, dst).context("Could not write edge list")?;
+        }
+    });
+    writer.flush().context("Could not flush edge list")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_edge_list() -> Result<()> {
+        let text = "# a comment\n0 1\n\n  2\t0 \n# another\n1 2\n";
+        let arcs = read_edge_list(text.as_bytes()).collect::<Result<Vec<_>>>()?;
+        assert_eq!(arcs, vec![(0, 1), (2, 0), (1, 2)]);
+
+        assert!(read_edge_list("0 1\n2\n".as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .is_err());
+        assert!(read_edge_list("0 1 2\n".as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .is_err());
+        let err = read_edge_list("0 1\n0 x\n".as_bytes())
+            .collect::<Result<Vec<_>>>()
+            .unwrap_err();
+        assert!(err.to_string().contains("line 2"));
+        Ok(())
+    }
+}
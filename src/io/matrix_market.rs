@@ -0,0 +1,272 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::{bail, ensure, Context, Result};
+use std::io::BufRead;
+
+/// An iterator over the arcs of a Matrix Market file.
+///
+/// See [`read_matrix_market`].
+#[derive(Debug)]
+pub struct MatrixMarket<R> {
+    lines: std::io::Lines<R>,
+    line_num: usize,
+    num_rows: usize,
+    num_cols: usize,
+    num_entries: usize,
+    symmetric: bool,
+    /// The number of entries read so far.
+    read: usize,
+    /// The transposed arc of the last entry of a symmetric matrix.
+    pending: Option<(usize, usize)>,
+}
+
+impl<R: BufRead> MatrixMarket<R> {
+    /// Returns the number of nodes, that is, the maximum between the number
+    /// of rows and the number of columns.
+    pub fn num_nodes(&self) -> usize {
+        self.num_rows.max(self.num_cols)
+    }
+
+    /// Returns the number of rows of the matrix.
+    pub fn num_rows(&self) -> usize {
+        self.num_rows
+    }
+
+    /// Returns the number of columns of the matrix.
+    pub fn num_cols(&self) -> usize {
+        self.num_cols
+    }
+
+    /// Returns the number of entries declared in the file.
+    ///
+    /// For symmetric matrices, the number of arcs might be larger, as each
+    /// entry off the diagonal yields two arcs.
+    pub fn num_entries(&self) -> usize {
+        self.num_entries
+    }
+
+    /// Returns whether the matrix is symmetric.
+    pub fn is_symmetric(&self) -> bool {
+        self.symmetric
+    }
+
+    /// Returns the next line that is not blank or a comment, together with
+    /// its line number.
+    fn next_line(&mut self) -> Option<Result<String>> {
+        loop {
+            let line = match self.lines.next()? {
+                Ok(line) => line,
+                Err(e) => return Some(Err(e).context("Could not read Matrix Market file")),
+            };
+            self.line_num += 1;
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('%') {
+                continue;
+            }
+            return Some(Ok(trimmed.to_owned()));
+        }
+    }
+
+    fn parse_entry(&self, line: &str) -> Result<(usize, usize)> {
+        let mut fields = line.split_whitespace();
+        let (Some(row), Some(col)) = (fields.next(), fields.next()) else {
+            bail!("Line {} is not a valid entry: {:?}", self.line_num, line);
+        };
+        let row = row
+            .parse::<usize>()
+            .with_context(|| format!("Could not parse row {:?} on line {}", row, self.line_num))?;
+        let col = col.parse::<usize>().with_context(|| {
+            format!("Could not parse column {:?} on line {}", col, self.line_num)
+        })?;
+        ensure!(
+            (1..=self.num_rows).contains(&row) && (1..=self.num_cols).contains(&col),
+            "Entry ({}, {}) on line {} is outside of a {}x{} matrix",
+            row,
+            col,
+            self.line_num,
+            self.num_rows,
+            self.num_cols
+        );
+        Ok((row - 1, col - 1))
+    }
+}
+
+impl<R: BufRead> Iterator for MatrixMarket<R> {
+    type Item = Result<(usize, usize)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(arc) = self.pending.take() {
+            return Some(Ok(arc));
+        }
+        let line = match self.next_line() {
+            Some(Ok(line)) => line,
+            Some(Err(e)) => return Some(Err(e)),
+            None if self.read < self.num_entries => {
+                let read = std::mem::replace(&mut self.read, self.num_entries);
+                return Some(Err(anyhow::anyhow!(
+                    "The file declares {} entries, but contains only {}",
+                    self.num_entries,
+                    read
+                )));
+            }
+            None => return None,
+        };
+        self.read += 1;
+        if self.read > self.num_entries {
+            return Some(Err(anyhow::anyhow!(
+                "The file declares {} entries, but contains more",
+                self.num_entries
+            )));
+        }
+        let (row, col) = match self.parse_entry(&line) {
+            Ok(arc) => arc,
+            Err(e) => return Some(Err(e)),
+        };
+        if self.symmetric && row != col {
+            self.pending = Some((col, row));
+        }
+        Some(Ok((row, col)))
+    }
+}
+
+/// Reads the header of a Matrix Market file in coordinate format and returns
+/// an iterator over the arcs given by its entries.
+///
+/// The header must be of the form `%%MatrixMarket matrix coordinate <field>
+/// <symmetry>`, where `<field>` is `pattern`, `integer` or `real` (values are
+/// ignored) and `<symmetry>` is `general` or `symmetric`. Entry indices are
+/// 1-based in the file and are converted to 0-based arcs; for symmetric
+/// matrices, which store only the lower triangle, both orientations of each
+/// entry off the diagonal are returned.
+///
+/// The number of nodes of the resulting graph is available through
+/// [`MatrixMarket::num_nodes`], so the arcs can be fed to
+/// [`BVComp::compress_from_arcs`](crate::graphs::bvgraph::BVComp::compress_from_arcs).
+///
+/// # Errors
+///
+/// If the header is missing or not supported, or if the size line cannot be
+/// parsed. The iterator returns an error if an entry cannot be parsed or is
+/// out of bounds, or if the number of entries does not match the size line.
+pub fn read_matrix_market<R: BufRead>(reader: R) -> Result<MatrixMarket<R>> {
+    let mut lines = reader.lines();
+    let header = lines
+        .next()
+        .context("Empty Matrix Market file")?
+        .context("Could not read Matrix Market file")?
+        .to_lowercase();
+    let fields = header.split_whitespace().collect::<Vec<_>>();
+    ensure!(
+        fields.len() == 5 && fields[0] == "%%matrixmarket" && fields[1] == "matrix",
+        "Invalid Matrix Market header: {:?}",
+        header
+    );
+    ensure!(
+        fields[2] == "coordinate",
+        "Unsupported Matrix Market format {:?} (only coordinate is supported)",
+        fields[2]
+    );
+    ensure!(
+        matches!(fields[3], "pattern" | "integer" | "real"),
+        "Unsupported Matrix Market field {:?} (only pattern, integer and real are supported)",
+        fields[3]
+    );
+    let symmetric = match fields[4] {
+        "general" => false,
+        "symmetric" => true,
+        s => bail!(
+            "Unsupported Matrix Market symmetry {:?} (only general and symmetric are supported)",
+            s
+        ),
+    };
+
+    let mut mm = MatrixMarket {
+        lines,
+        line_num: 1,
+        num_rows: 0,
+        num_cols: 0,
+        num_entries: 0,
+        symmetric,
+        read: 0,
+        pending: None,
+    };
+
+    let size = mm
+        .next_line()
+        .context("Missing Matrix Market size line")??;
+    let sizes = size
+        .split_whitespace()
+        .map(|s| s.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("Could not parse size line {:?}", size))?;
+    ensure!(
+        sizes.len() == 3,
+        "The size line {:?} does not contain rows, columns and entries",
+        size
+    );
+    ensure!(
+        !symmetric || sizes[0] == sizes[1],
+        "A symmetric matrix must be square, but it is {}x{}",
+        sizes[0],
+        sizes[1]
+    );
+    mm.num_rows = sizes[0];
+    mm.num_cols = sizes[1];
+    mm.num_entries = sizes[2];
+    Ok(mm)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_matrix_market() -> Result<()> {
+        let text = "%%MatrixMarket matrix coordinate pattern general\n\
+                    % A comment\n\
+                    3 4 3\n\
+                    1 2\n\
+                    3 1\n\
+                    2 4\n";
+        let mm = read_matrix_market(text.as_bytes())?;
+        assert_eq!(mm.num_nodes(), 4);
+        assert!(!mm.is_symmetric());
+        let arcs = mm.collect::<Result<Vec<_>>>()?;
+        assert_eq!(arcs, vec![(0, 1), (2, 0), (1, 3)]);
+
+        let text = "%%MatrixMarket matrix coordinate integer symmetric\n\
+                    3 3 3\n\
+                    2 1 5\n\
+                    2 2 1\n\
+                    3 1 -2\n";
+        let mm = read_matrix_market(text.as_bytes())?;
+        assert_eq!(mm.num_nodes(), 3);
+        assert_eq!(mm.num_entries(), 3);
+        let arcs = mm.collect::<Result<Vec<_>>>()?;
+        assert_eq!(arcs, vec![(1, 0), (0, 1), (1, 1), (2, 0), (0, 2)]);
+
+        // Unsupported formats
+        assert!(
+            read_matrix_market("%%MatrixMarket matrix array real general\n".as_bytes()).is_err()
+        );
+        assert!(read_matrix_market(
+            "%%MatrixMarket matrix coordinate complex general\n1 1 0\n".as_bytes()
+        )
+        .is_err());
+
+        // Out of bounds and missing entries
+        let text = "%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 3\n";
+        assert!(read_matrix_market(text.as_bytes())?
+            .collect::<Result<Vec<_>>>()
+            .is_err());
+        let text = "%%MatrixMarket matrix coordinate pattern general\n2 2 2\n1 2\n";
+        assert!(read_matrix_market(text.as_bytes())?
+            .collect::<Result<Vec<_>>>()
+            .is_err());
+        Ok(())
+    }
+}
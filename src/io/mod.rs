@@ -0,0 +1,13 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+//! Import and export of graphs in textual formats.
+
+mod edge_list;
+pub use edge_list::*;
+
+mod matrix_market;
+pub use matrix_market::*;
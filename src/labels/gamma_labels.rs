@@ -0,0 +1,289 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+Integer arc labels stored in a bitstream parallel to a graph.
+
+*/
+
+use crate::graphs::bvgraph::{LABELOFFSETS_EXTENSION, LABELS_EXTENSION};
+use crate::prelude::*;
+use anyhow::{ensure, Context, Result};
+use dsi_bitstream::prelude::*;
+use lender::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+/// A [random-access labeling](RandomAccessLabeling) with `u64` labels, such
+/// as arc weights, stored in a bitstream.
+///
+/// The labels of a graph are stored by [`GammaLabels::store`] in a file with
+/// extension `.labels`, which contains, for each node, its outdegree followed
+/// by the labels of its arcs, in the order of the successors, all written in
+/// big-endian [γ code](GammaWrite); the bit offset of each node is stored in a
+/// file with extension `.labeloffsets`, using the same format of the
+/// `.offsets` file of a [`BVGraph`](crate::graphs::bvgraph::BVGraph).
+///
+/// The labels can be then attached to the graph they were stored from using
+/// [`Zip`], obtaining a [labeled graph](LabeledSequentialGraph):
+/// ```ignore
+/// let graph = BVGraph::with_basename(basename).load()?;
+/// let labels = GammaLabels::load(basename, graph.num_nodes())?;
+/// let labeled = Zip(graph, labels);
+/// ```
+///
+/// Since the γ code cannot represent `u64::MAX`, labels must be smaller.
+#[derive(Debug, Clone)]
+pub struct GammaLabels {
+    factory: MemoryFactory<BE, Box<[u32]>>,
+    /// The bit offset of each node, plus a final entry with the length in
+    /// bits of the labels.
+    offsets: Box<[u64]>,
+    num_arcs: u64,
+}
+
+impl GammaLabels {
+    /// Stores the labels of a labeled graph with the given basename, and
+    /// returns the length in bits of the label bitstream.
+    ///
+    /// See the [type documentation](GammaLabels) for the format.
+    pub fn store(
+        basename: impl AsRef<Path>,
+        graph: &impl LabeledSequentialGraph<u64>,
+    ) -> Result<u64> {
+        let basename = basename.as_ref();
+        let labels_path = basename.with_extension(LABELS_EXTENSION);
+        let mut labels_writer =
+            <BufBitWriter<BE, _>>::new(<WordAdapter<usize, _>>::new(BufWriter::new(
+                File::create(&labels_path)
+                    .with_context(|| format!("Could not create {}", labels_path.display()))?,
+            )));
+        let offsets_path = basename.with_extension(LABELOFFSETS_EXTENSION);
+        let mut offsets_writer =
+            <BufBitWriter<BE, _>>::new(<WordAdapter<usize, _>>::new(BufWriter::with_capacity(
+                1 << 20,
+                File::create(&offsets_path)
+                    .with_context(|| format!("Could not create {}", offsets_path.display()))?,
+            )));
+
+        offsets_writer
+            .write_gamma(0)
+            .context("Could not write initial offset")?;
+        let mut result = 0;
+        let mut labels = vec![];
+        for_!((_node, succ) in graph.iter() {
+            labels.clear();
+            labels.extend(succ.into_iter().map(|(_, label)| label));
+            let mut bits = labels_writer
+                .write_gamma(labels.len() as u64)
+                .context("Could not write outdegree")?;
+            for &label in &labels {
+                bits += labels_writer
+                    .write_gamma(label)
+                    .context("Could not write label")?;
+            }
+            offsets_writer
+                .write_gamma(bits as u64)
+                .context("Could not write offset")?;
+            result += bits as u64;
+        });
+
+        labels_writer
+            .flush()
+            .with_context(|| format!("Could not flush {}", labels_path.display()))?;
+        offsets_writer
+            .flush()
+            .with_context(|| format!("Could not flush {}", offsets_path.display()))?;
+        Ok(result)
+    }
+
+    /// Loads the labels stored with the given basename for a graph with
+    /// `num_nodes` nodes.
+    ///
+    /// The labels are loaded in memory, and the offsets are decoded into an
+    /// array, so the number of arcs is known after loading.
+    pub fn load(basename: impl AsRef<Path>, num_nodes: usize) -> Result<Self> {
+        let basename = basename.as_ref();
+        let labels_path = basename.with_extension(LABELS_EXTENSION);
+        let factory = MemoryFactory::<BE, _>::new_mem(&labels_path)
+            .with_context(|| format!("Could not load {}", labels_path.display()))?;
+
+        let offsets_path = basename.with_extension(LABELOFFSETS_EXTENSION);
+        let mut offsets_reader =
+            <BufBitReader<BE, _>>::new(<WordAdapter<u32, _>>::new(BufReader::with_capacity(
+                1 << 20,
+                File::open(&offsets_path)
+                    .with_context(|| format!("Could not open {}", offsets_path.display()))?,
+            )));
+
+        let mut labels_reader = factory.new_reader();
+        let mut offsets = Vec::with_capacity(num_nodes + 1);
+        let mut offset = 0;
+        let mut num_arcs = 0;
+        for node in 0..=num_nodes {
+            offset += offsets_reader
+                .read_gamma()
+                .with_context(|| format!("Could not read the offset of node {}", node))?;
+            offsets.push(offset);
+            if node < num_nodes {
+                labels_reader
+                    .set_bit_pos(offset)
+                    .with_context(|| format!("Could not seek to node {}", node))?;
+                num_arcs += labels_reader
+                    .read_gamma()
+                    .with_context(|| format!("Could not read the outdegree of node {}", node))?;
+            }
+        }
+        ensure!(
+            offsets[0] == 0,
+            "The first offset in {} is not zero",
+            offsets_path.display()
+        );
+
+        Ok(Self {
+            factory,
+            offsets: offsets.into_boxed_slice(),
+            num_arcs,
+        })
+    }
+
+    #[inline(always)]
+    fn reader_at(&self, node: usize) -> BufBitReader<BE, MemWordReader<u32, &[u32]>> {
+        let mut reader = self.factory.new_reader();
+        reader.set_bit_pos(self.offsets[node]).unwrap();
+        reader
+    }
+}
+
+impl SequentialLabeling for GammaLabels {
+    type Label = u64;
+    type Lender<'a> = IteratorImpl<'a, Self> where Self: 'a;
+
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    #[inline(always)]
+    fn num_arcs_hint(&self) -> Option<u64> {
+        Some(self.num_arcs)
+    }
+
+    #[inline(always)]
+    fn num_arcs_exact(&self) -> Option<u64> {
+        Some(self.num_arcs)
+    }
+
+    #[inline(always)]
+    fn iter_from(&self, from: usize) -> Self::Lender<'_> {
+        IteratorImpl {
+            labeling: self,
+            nodes: (from..self.num_nodes()),
+        }
+    }
+}
+
+impl<'a> IntoLender for &'a GammaLabels {
+    type Lender = <GammaLabels as SequentialLabeling>::Lender<'a>;
+
+    #[inline(always)]
+    fn into_lender(self) -> Self::Lender {
+        self.iter()
+    }
+}
+
+impl RandomAccessLabeling for GammaLabels {
+    type Labels<'succ> = GammaLabelsIter<'succ>;
+
+    #[inline(always)]
+    fn num_arcs(&self) -> u64 {
+        self.num_arcs
+    }
+
+    fn labels(&self, node: usize) -> <Self as RandomAccessLabeling>::Labels<'_> {
+        let mut reader = self.reader_at(node);
+        let remaining = reader.read_gamma().unwrap() as usize;
+        GammaLabelsIter { reader, remaining }
+    }
+
+    fn outdegree(&self, node: usize) -> usize {
+        self.reader_at(node).read_gamma().unwrap() as usize
+    }
+}
+
+/// The iterator over the labels of a node returned by [`GammaLabels`].
+pub struct GammaLabelsIter<'a> {
+    reader: BufBitReader<BE, MemWordReader<u32, &'a [u32]>>,
+    remaining: usize,
+}
+
+impl Iterator for GammaLabelsIter<'_> {
+    type Item = u64;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(self.reader.read_gamma().unwrap())
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for GammaLabelsIter<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+
+    #[test]
+    fn test_gamma_labels() -> Result<()> {
+        // Node 2 has no successors, and node 4 is isolated
+        let mut g = VecGraph::<u64>::empty(5);
+        g.add_labeled_arcs([
+            (0, 1, 10),
+            (0, 3, 0),
+            (1, 0, 1 << 40),
+            (3, 0, 7),
+            (3, 1, 7),
+            (3, 2, 3),
+        ]);
+
+        let tmp_dir = tempfile::tempdir()?;
+        let basename = tmp_dir.path().join("labeled");
+        BVComp::single_thread::<BE, _>(
+            &basename,
+            &Left(g.clone()),
+            CompFlags::default(),
+            false,
+            None,
+        )?;
+        GammaLabels::store(&basename, &g)?;
+
+        let labels = GammaLabels::load(&basename, g.num_nodes())?;
+        assert_eq!(labels.num_nodes(), 5);
+        assert_eq!(labels.num_arcs(), 6);
+        assert_eq!(labels.outdegree(3), 3);
+        assert_eq!(labels.labels(1).collect::<Vec<_>>(), vec![1 << 40]);
+        assert_eq!(labels.labels(2).len(), 0);
+
+        let graph = BVGraphSeq::with_basename(&basename)
+            .endianness::<BE>()
+            .load()?;
+        let labeled = Zip(graph, labels);
+        assert!(labeled.verify());
+        assert_eq!(VecGraph::from_labeled_lender(&labeled), g);
+        Ok(())
+    }
+}
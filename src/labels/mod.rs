@@ -15,3 +15,9 @@ pub use zip::*;
 
 pub mod proj;
 pub use proj::*;
+
+pub mod gamma_labels;
+pub use gamma_labels::*;
+
+pub mod node_ids;
+pub use node_ids::*;
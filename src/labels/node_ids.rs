@@ -0,0 +1,150 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+/*!
+
+String identifiers of nodes, such as the ones in the `.ids` and `.urls` files
+distributed with the graphs of the [LAW](http://law.di.unimi.it/).
+
+*/
+
+use crate::graphs::bvgraph::IDS_EXTENSION;
+use crate::traits::NodeLabeling;
+use crate::utils::MmapHelper;
+use anyhow::{Context, Result};
+use mmap_rs::MmapFlags;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// A [node labeling](NodeLabeling) backed by a memory-mapped text file
+/// containing the label of node *i* on line *i*.
+///
+/// The file is memory mapped, and the offsets of the lines are computed when
+/// loading, so labels are accessed in constant time at the cost of a `usize`
+/// per node. Lines can be terminated by `\n` or `\r\n`, and the file must be
+/// valid UTF-8.
+///
+/// Nodes can be also looked up by label using
+/// [`find_by_label`](NodeIds::find_by_label).
+pub struct NodeIds {
+    text: MmapHelper<u8>,
+    /// The starting offset of each line, plus a final entry equal to the
+    /// length of the text plus one.
+    offsets: Box<[usize]>,
+    /// The nodes sorted by label, built on the first reverse lookup.
+    sorted: OnceLock<Box<[usize]>>,
+}
+
+/// Loads the node identifiers of the graph with given basename from the file
+/// with extension `.ids`.
+///
+/// Use [`NodeIds::load`] for files with a different name, such as `.urls`
+/// files.
+pub fn load_ids(basename: impl AsRef<Path>) -> Result<NodeIds> {
+    NodeIds::load(basename.as_ref().with_extension(IDS_EXTENSION))
+}
+
+impl NodeIds {
+    /// Loads node identifiers from a text file containing an identifier per
+    /// line.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let text = MmapHelper::<u8>::mmap(path, MmapFlags::empty())
+            .with_context(|| format!("Could not mmap {}", path.display()))?;
+        let bytes = text.as_ref();
+        std::str::from_utf8(bytes)
+            .with_context(|| format!("{} is not valid UTF-8", path.display()))?;
+
+        let mut offsets = vec![0];
+        offsets.extend(
+            bytes
+                .iter()
+                .enumerate()
+                .filter(|(_, &b)| b == b'\n')
+                .map(|(i, _)| i + 1),
+        );
+        // a last line without a final newline
+        if bytes.last().is_some_and(|&b| b != b'\n') {
+            offsets.push(bytes.len() + 1);
+        }
+
+        Ok(Self {
+            text,
+            offsets: offsets.into_boxed_slice(),
+            sorted: OnceLock::new(),
+        })
+    }
+
+    /// Returns a node with the given label, or `None` if no node has the
+    /// given label.
+    ///
+    /// The lookup is a binary search over the nodes sorted by label, so it
+    /// requires O(log *n*) string comparisons. The sorted index, which uses
+    /// a `usize` per node, is built at the first call, which requires thus
+    /// O(*n* log *n*) comparisons. If several nodes have the same label, any
+    /// of them might be returned.
+    pub fn find_by_label(&self, label: &str) -> Option<usize> {
+        let sorted = self.sorted.get_or_init(|| {
+            let mut sorted = (0..self.num_nodes()).collect::<Vec<_>>();
+            sorted.sort_unstable_by(|&x, &y| self.label(x).cmp(self.label(y)));
+            sorted.into_boxed_slice()
+        });
+        sorted
+            .binary_search_by(|&node| self.label(node).cmp(label))
+            .ok()
+            .map(|pos| sorted[pos])
+    }
+}
+
+impl NodeLabeling for NodeIds {
+    #[inline(always)]
+    fn num_nodes(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn label(&self, node: usize) -> &str {
+        let line = &self.text.as_ref()[self.offsets[node]..self.offsets[node + 1] - 1];
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        // SAFETY: the text has been checked to be valid UTF-8 when loading,
+        // and line boundaries are character boundaries.
+        unsafe { std::str::from_utf8_unchecked(line) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_ids() -> Result<()> {
+        let tmp_dir = tempfile::tempdir()?;
+        let basename = tmp_dir.path().join("example");
+        std::fs::write(
+            basename.with_extension(IDS_EXTENSION),
+            "http://example.org/\nhttp://example.org/ü\r\n\nhttp://example.org/last",
+        )?;
+        let ids = load_ids(&basename)?;
+        assert_eq!(ids.num_nodes(), 4);
+        assert_eq!(ids.label(0), "http://example.org/");
+        assert_eq!(ids.label(1), "http://example.org/ü");
+        assert_eq!(ids.label(2), "");
+        assert_eq!(ids.label(3), "http://example.org/last");
+
+        for node in 0..ids.num_nodes() {
+            assert_eq!(ids.find_by_label(ids.label(node)), Some(node));
+        }
+        assert_eq!(ids.find_by_label("http://example.org/missing"), None);
+
+        std::fs::write(basename.with_extension(IDS_EXTENSION), "a\nb\n")?;
+        let ids = load_ids(&basename)?;
+        assert_eq!(ids.num_nodes(), 2);
+        assert_eq!(ids.label(1), "b");
+
+        std::fs::write(basename.with_extension(IDS_EXTENSION), [0xff, b'\n'])?;
+        assert!(load_ids(&basename).is_err());
+        Ok(())
+    }
+}
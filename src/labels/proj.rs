@@ -143,6 +143,10 @@ where
     fn num_arcs_hint(&self) -> Option<u64> {
         self.0.num_arcs_hint()
     }
+
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.0.num_arcs_exact()
+    }
 }
 
 impl<R: RandomAccessLabeling> RandomAccessLabeling for Left<R>
@@ -298,6 +302,10 @@ where
         self.0.num_arcs_hint()
     }
 
+    fn num_arcs_exact(&self) -> Option<u64> {
+        self.0.num_arcs_exact()
+    }
+
     fn iter_from(&self, from: usize) -> Self::Lender<'_> {
         RightIterator(self.0.iter_from(from))
     }
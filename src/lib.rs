@@ -22,6 +22,7 @@ pub mod cli;
 #[cfg(feature = "fuzz")]
 pub mod fuzz;
 pub mod graphs;
+pub mod io;
 pub mod labels;
 pub mod traits;
 pub mod transform;
@@ -30,6 +31,7 @@ pub mod utils;
 pub mod prelude {
     pub use crate::algo::*;
     pub use crate::graphs::prelude::*;
+    pub use crate::io::*;
     pub use crate::labels::*;
     pub use crate::traits::*;
     pub use crate::transform::*;
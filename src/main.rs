@@ -73,6 +73,7 @@ pub fn main() -> Result<()> {
         bench,
         bfs,
         build,
+        check,
         check_ef,
         convert,
         from_csv,
@@ -82,7 +83,10 @@ pub fn main() -> Result<()> {
         pad,
         rand_perm,
         recompress,
+        sample,
         simplify,
+        stats,
+        subgraph,
         to_csv,
         transpose
     )
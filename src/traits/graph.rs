@@ -51,7 +51,56 @@ struct this_method_cannot_be_called_use_successors_instead;
 /// and [`SortedLabels`](super::labels::SortedLabels) can be used to force these
 /// properties.
 #[autoimpl(for<S: trait + ?Sized> &S, &mut S)]
-pub trait SequentialGraph: SequentialLabeling<Label = usize> {}
+pub trait SequentialGraph: SequentialLabeling<Label = usize> {
+    /// Returns an iterator over the arcs of the graph, that is, over pairs
+    /// `(src, dst)`, obtained by flattening the [lender](SequentialLabeling::iter)
+    /// over the successors of each node.
+    ///
+    /// If nodes are returned in ascending order and their successors are
+    /// sorted, as it happens, for example, with [`BVGraph`](crate::graphs::bvgraph::BVGraph),
+    /// arcs are returned in lexicographical order.
+    ///
+    /// Since a lender cannot be turned into an iterator, the successors of each
+    /// node are buffered, so the memory used by the iterator is linear in the
+    /// maximum outdegree.
+    fn arcs(&self) -> Arcs<Self::Lender<'_>> {
+        Arcs {
+            lender: self.iter(),
+            node: 0,
+            succ: vec![],
+            pos: 0,
+        }
+    }
+}
+
+/// An iterator over the arcs of a [sequential graph](SequentialGraph)
+/// returned by [`arcs`](SequentialGraph::arcs).
+pub struct Arcs<L> {
+    lender: L,
+    node: usize,
+    succ: Vec<usize>,
+    pos: usize,
+}
+
+impl<L> Iterator for Arcs<L>
+where
+    L: Lender + for<'next> NodeLabelsLender<'next, Label = usize>,
+{
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.pos == self.succ.len() {
+            let (node, succ) = self.lender.next()?.into_pair();
+            self.node = node;
+            self.succ.clear();
+            self.succ.extend(succ);
+            self.pos = 0;
+        }
+        let dst = self.succ[self.pos];
+        self.pos += 1;
+        Some((self.node, dst))
+    }
+}
 
 /// Convenience type alias for the iterator over the successors of a node
 /// returned by the [`iter_from`](SequentialLabeling::iter_from) method.
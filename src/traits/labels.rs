@@ -66,10 +66,24 @@ pub trait SequentialLabeling {
     fn num_nodes(&self) -> usize;
 
     /// Returns the number of arcs in the graph, if available.
+    ///
+    /// The value might be an estimate: use
+    /// [`num_arcs_exact`](SequentialLabeling::num_arcs_exact) if you need the
+    /// exact number of arcs.
     fn num_arcs_hint(&self) -> Option<u64> {
         None
     }
 
+    /// Returns the exact number of arcs in the graph, if it is known without
+    /// scanning the graph (e.g., because it has been read from the properties
+    /// of the graph).
+    ///
+    /// Differently from [`num_arcs_hint`](SequentialLabeling::num_arcs_hint),
+    /// implementations must return `None` unless the value is authoritative.
+    fn num_arcs_exact(&self) -> Option<u64> {
+        None
+    }
+
     /// Returns an iterator over the labeling.
     ///
     /// Iterators over the labeling return pairs given by a node of the graph
@@ -85,6 +99,21 @@ pub trait SequentialLabeling {
     /// starting point of the iteration
     fn iter_from(&self, from: usize) -> Self::Lender<'_>;
 
+    /// Returns an iterator over the labeling returning the nodes in `range`.
+    ///
+    /// The iterator is obtained from [`iter_from`](SequentialLabeling::iter_from),
+    /// so it starts at `range.start` without decoding the previous nodes if
+    /// the implementation can seek (e.g., [`BVGraph`](crate::graphs::bvgraph::BVGraph)
+    /// uses its offsets), or by decoding and skipping them otherwise (e.g.,
+    /// [`BVGraphSeq`](crate::graphs::bvgraph::BVGraphSeq)), and stops after
+    /// `range.len()` nodes. Thus, iterating over disjoint consecutive ranges
+    /// covering all nodes is equivalent to [`iter`](SequentialLabeling::iter)
+    /// for [sorted lenders](SortedLender), making it possible to
+    /// partition the processing of a graph.
+    fn iter_range(&self, range: Range<usize>) -> Take<Self::Lender<'_>> {
+        self.iter_from(range.start).take(range.len())
+    }
+
     /// Applies `func` to each chunk of nodes of size `node_granularity` in
     /// parallel, and folds the results using `fold`.
     ///
@@ -166,6 +195,35 @@ pub trait SequentialLabeling {
     /// arcs in the graph) in a form that makes it possible to compute
     /// successors (for example, using the suitable `webgraph build` command).
     ///
+    /// This is the method of choice to parallelize a scan of a graph whose
+    /// cost is proportional to the number of arcs, such as an iteration of
+    /// PageRank, as chunks are balanced by number of arcs rather than by
+    /// number of nodes.
+    ///
+    /// # Contract
+    ///
+    /// `func` is passed nonempty ranges of consecutive nodes; the ranges are
+    /// disjoint, and their union is the set of all nodes, so each node is
+    /// processed exactly once. A range starting at node *x* ends (excluded) at
+    /// the first node *y* such that the cumulative degree at *y* is at least the
+    /// cumulative degree at *x* plus `arc_granularity`, which is computed by a
+    /// [successor query](Succ::succ) on `deg_cumul`; thus, every chunk but
+    /// the last one contains at least `arc_granularity` arcs, and less than
+    /// `arc_granularity` arcs plus the outdegree of its last node. `func` is
+    /// usually implemented by calling
+    /// [`iter_range`](SequentialLabeling::iter_range) or, on random-access
+    /// labelings, by accessing directly the labels of the nodes in the range.
+    ///
+    /// Chunks are processed in arbitrary order, and their results are folded
+    /// starting from `A::default()` in the order in which they are completed,
+    /// so `fold` should be associative and commutative.
+    ///
+    /// # Panics
+    ///
+    /// If the number of arcs returned by
+    /// [`num_arcs_hint`](SequentialLabeling::num_arcs_hint), if any, is not
+    /// the last value of `deg_cumul`.
+    ///
     /// # Arguments
     ///
     /// * `func` - The function to apply to each chunk of nodes.
@@ -195,16 +253,15 @@ pub trait SequentialLabeling {
     {
         let pl_lock = pl.map(std::sync::Mutex::new);
         let num_nodes = self.num_nodes();
-        let num_arcs = self.num_arcs_hint().unwrap();
-        let num_scoped_threads = thread_pool
-            .current_num_threads()
-            .min((num_arcs / arc_granularity as u64) as usize)
-            .max(1);
-        let next_node_next_arc = std::sync::Mutex::new((0, 0));
         let num_arcs = deg_cumul.get(num_nodes);
         if let Some(num_arcs_hint) = self.num_arcs_hint() {
             assert_eq!(num_arcs_hint, num_arcs as u64);
         }
+        let num_scoped_threads = thread_pool
+            .current_num_threads()
+            .min(num_arcs / arc_granularity)
+            .max(1);
+        let next_node_next_arc = std::sync::Mutex::new((0_usize, 0_usize));
 
         thread_pool.in_place_scope(|scope| {
             // create a channel to receive the result
@@ -229,7 +286,7 @@ pub trait SequentialLabeling {
                             }
 
                             start_pos = next_node;
-                            let target = next_arc + arc_granularity;
+                            let target = next_arc.saturating_add(arc_granularity);
                             if target >= num_arcs {
                                 next_node = num_nodes;
                             } else {
@@ -52,6 +52,9 @@ pub use graph::*;
 pub mod labels;
 pub use labels::*;
 
+mod node_labels;
+pub use node_labels::*;
+
 mod serde;
 pub use serde::*;
 
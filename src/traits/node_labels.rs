@@ -0,0 +1,27 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use impl_tools::autoimpl;
+
+/// A labeling associating a string with each node, such as the original
+/// identifier of the node or its URL.
+///
+/// Differently from a [labeling](crate::traits::SequentialLabeling), which
+/// associates labels with arcs, a node labeling associates exactly one label
+/// with each node. See [`NodeIds`](crate::labels::NodeIds) for an
+/// implementation reading the `.ids` and `.urls` files of the Java version.
+#[autoimpl(for<S: trait + ?Sized> &S, &mut S)]
+pub trait NodeLabeling {
+    /// Returns the number of nodes.
+    fn num_nodes(&self) -> usize;
+
+    /// Returns the label of a node.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `node` is not smaller than the number of nodes.
+    fn label(&self, node: usize) -> &str;
+}
@@ -10,8 +10,14 @@
 mod simplify;
 pub use simplify::*;
 
+mod symmetrize;
+pub use symmetrize::*;
+
 mod transpose;
 pub use transpose::*;
 
 mod perm;
 pub use perm::*;
+
+mod subgraph;
+pub use subgraph::*;
@@ -0,0 +1,106 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::graphs::arc_list_graph;
+use crate::prelude::sort_pairs::{BatchIterator, KMergeIters};
+use crate::prelude::*;
+use anyhow::{ensure, Context, Result};
+use dsi_progress_logger::prelude::*;
+use lender::*;
+use tempfile::Builder;
+
+/// Returns the subgraph induced by the given nodes as a
+/// [sequential graph](crate::traits::SequentialGraph).
+///
+/// Node `nodes[i]` of `graph` becomes node _i_ of the subgraph, and the arcs
+/// of the subgraph are the arcs of `graph` whose endpoints both appear in
+/// `nodes`; all other arcs are dropped. Nodes can be listed in any order.
+///
+/// For the meaning of the additional parameter, see
+/// [`SortPairs`](crate::prelude::sort_pairs::SortPairs).
+///
+/// # Errors
+///
+/// If some node is not smaller than the number of nodes of `graph`, or if
+/// some node appears more than once.
+#[allow(clippy::type_complexity)]
+pub fn subgraph(
+    graph: &impl SequentialGraph,
+    nodes: &[usize],
+    batch_size: usize,
+) -> Result<Left<arc_list_graph::ArcListGraph<KMergeIters<BatchIterator<()>, ()>>>> {
+    let num_nodes = graph.num_nodes();
+    // map[x] is the new index of x, or usize::MAX if x is not in the subgraph
+    let mut map = vec![usize::MAX; num_nodes];
+    for (i, &node) in nodes.iter().enumerate() {
+        ensure!(
+            node < num_nodes,
+            "Node {} does not exist (the graph has {} nodes)",
+            node,
+            num_nodes
+        );
+        ensure!(map[node] == usize::MAX, "Node {} appears twice", node);
+        map[node] = i;
+    }
+
+    let dir = Builder::new().prefix("Subgraph").tempdir()?;
+    let mut sorted = SortPairs::new(batch_size, dir.path())?;
+
+    let mut pl = progress_logger!(
+        item_name = "node",
+        expected_updates = Some(num_nodes),
+        display_memory = true
+    );
+    pl.start("Creating batches...");
+    for_!( (src, succ) in graph.iter() {
+        let new_src = map[src];
+        if new_src != usize::MAX {
+            for dst in succ {
+                let new_dst = map[dst];
+                if new_dst != usize::MAX {
+                    sorted.push(new_src, new_dst)?;
+                }
+            }
+        }
+        pl.light_update();
+    });
+    pl.done();
+
+    // get a graph on the sorted data
+    let edges = sorted.iter().context("Could not read arcs")?;
+    Ok(Left(arc_list_graph::ArcListGraph::new_labeled(
+        nodes.len(),
+        edges,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+
+    #[test]
+    fn test_subgraph() -> Result<()> {
+        // 4 -> 0 -> 2
+        //       `-> 3
+        // 1 -> 5
+        let g = Left(VecGraph::from_arcs([(4, 0), (0, 2), (0, 3), (1, 5)]));
+
+        let sub = subgraph(&g, &[3, 0, 2], 10)?;
+        assert_eq!(sub.num_nodes(), 3);
+        let sub = Left(VecGraph::from_lender(&sub));
+        assert_eq!(sub, Left(VecGraph::from_arcs([(1, 0), (1, 2)])));
+
+        // Arcs with an endpoint outside the set are dropped
+        let sub = subgraph(&g, &[4, 1, 5], 10)?;
+        let sub = Left(VecGraph::from_lender(&sub));
+        assert_eq!(sub, Left(VecGraph::from_arcs([(1, 2)])));
+
+        assert!(subgraph(&g, &[0, 6], 10).is_err());
+        assert!(subgraph(&g, &[0, 2, 0], 10).is_err());
+        Ok(())
+    }
+}
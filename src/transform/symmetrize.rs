@@ -0,0 +1,102 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use crate::graphs::arc_list_graph;
+use crate::labels::Left;
+use crate::traits::SequentialGraph;
+use crate::utils::sort_pairs::{BatchIterator, KMergeIters, SortPairs};
+use anyhow::Result;
+use dsi_progress_logger::prelude::*;
+use itertools::{Dedup, Itertools};
+use lender::*;
+use std::path::Path;
+
+/// Returns the symmetric closure of the provided graph as a [sequential
+/// graph](crate::traits::SequentialGraph) with sorted successor lists.
+///
+/// For every arc `x → y` both `x → y` and `y → x` are pushed into a
+/// [`SortPairs`], and coincident pairs are removed during the merge, so the
+/// result contains no duplicate arcs. In particular, symmetrizing a graph that
+/// is already symmetric does not change its number of arcs.
+///
+/// If `keep_loops` is false, self-loops are removed: in this case, the result
+/// is the same as that of [`simplify`](super::simplify).
+///
+/// The sorted batches are stored in `dir`, which must be empty and must not
+/// be shared with other [`SortPairs`] instances. For the meaning of the
+/// additional parameter, see [`SortPairs`].
+#[allow(clippy::type_complexity)]
+pub fn symmetrize(
+    graph: &impl SequentialGraph,
+    batch_size: usize,
+    dir: impl AsRef<Path>,
+    keep_loops: bool,
+) -> Result<Left<arc_list_graph::ArcListGraph<Dedup<KMergeIters<BatchIterator<()>, ()>>>>> {
+    let mut sorted = SortPairs::new(batch_size, dir)?;
+
+    let mut pl = ProgressLogger::default();
+    pl.item_name("node")
+        .expected_updates(Some(graph.num_nodes()));
+    pl.start("Creating batches...");
+    // create batches of sorted edges
+    for_!( (src, succ) in graph.iter() {
+        for dst in succ {
+            if src != dst {
+                sorted.push(src, dst)?;
+                sorted.push(dst, src)?;
+            } else if keep_loops {
+                sorted.push(src, dst)?;
+            }
+        }
+        pl.light_update();
+    });
+    // merge the batches, removing duplicates
+    let sorted =
+        arc_list_graph::ArcListGraph::new_labeled(graph.num_nodes(), sorted.iter()?.dedup());
+    pl.done();
+
+    Ok(Left(sorted))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graphs::vec_graph::VecGraph;
+    use crate::traits::{RandomAccessGraph, RandomAccessLabeling};
+    use tempfile::Builder;
+
+    #[test]
+    fn test_symmetrize() -> Result<()> {
+        // 0 ↔ 1 and 2 ↔ 3 are already bidirectional, 1 → 2 is not, and 2
+        // has a self-loop
+        let g = Left(VecGraph::from_arc_list([
+            (0, 1),
+            (1, 0),
+            (1, 2),
+            (2, 2),
+            (2, 3),
+            (3, 2),
+        ]));
+
+        let dir = Builder::new().prefix("test_symmetrize").tempdir()?;
+        let sym = symmetrize(&g, 3, dir.path(), true)?;
+        let sym = Left(VecGraph::from_lender(&sym));
+        assert_eq!(sym.num_arcs(), 7);
+        assert!(sym.successors(2).into_iter().eq([1, 2, 3]));
+
+        // Symmetrizing a symmetric graph is idempotent
+        let dir = Builder::new().prefix("test_symmetrize").tempdir()?;
+        let sym2 = symmetrize(&sym, 3, dir.path(), true)?;
+        assert_eq!(Left(VecGraph::from_lender(&sym2)), sym);
+
+        let dir = Builder::new().prefix("test_symmetrize").tempdir()?;
+        let loopless = symmetrize(&g, 3, dir.path(), false)?;
+        let loopless = Left(VecGraph::from_lender(&loopless));
+        assert_eq!(loopless.num_arcs(), 6);
+        assert!(loopless.successors(2).into_iter().eq([1, 3]));
+        Ok(())
+    }
+}
@@ -13,6 +13,7 @@ use anyhow::Result;
 use dsi_bitstream::traits::NE;
 use dsi_progress_logger::prelude::*;
 use lender::prelude::*;
+use std::path::Path;
 use tempfile::Builder;
 
 /// Returns the transpose of the provided labeled graph as a [sequential
@@ -35,7 +36,40 @@ where
     D::DeserType: Clone + Copy,
 {
     let dir = Builder::new().prefix("Transpose").tempdir()?;
-    let mut sorted = SortPairs::new_labeled(batch_size, dir.path(), serializer, deserializer)?;
+    transpose_labeled_in(graph, batch_size, dir.path(), serializer, deserializer)
+}
+
+/// Returns the transpose of the provided labeled graph as a [sequential
+/// graph](crate::traits::SequentialGraph), storing the sorted batches of arcs
+/// in the provided directory.
+///
+/// The directory must be empty, and it must not be shared with other
+/// transpositions or [`SortPairs`] instances. The result is just a sorted view
+/// on the batches, so nothing else is written on disk: if you need a
+/// [`BVGraph`](crate::graphs::bvgraph::BVGraph), compress the result with
+/// [`BVComp`](crate::graphs::bvgraph::BVComp).
+///
+/// The transpose has the same number of nodes of the original graph, even if
+/// some nodes are isolated.
+///
+/// For the meaning of the additional parameters, see
+/// [`SortPairs`](crate::prelude::sort_pairs::SortPairs).
+#[allow(clippy::type_complexity)]
+pub fn transpose_labeled_in<
+    S: BitSerializer<NE, BitWriter> + Clone,
+    D: BitDeserializer<NE, BitReader> + Clone + 'static,
+>(
+    graph: &impl LabeledSequentialGraph<S::SerType>,
+    batch_size: usize,
+    dir: impl AsRef<Path>,
+    serializer: S,
+    deserializer: D,
+) -> Result<arc_list_graph::ArcListGraph<KMergeIters<BatchIterator<D>, D::DeserType>>>
+where
+    S::SerType: Send + Sync + Copy,
+    D::DeserType: Clone + Copy,
+{
+    let mut sorted = SortPairs::new_labeled(batch_size, dir, serializer, deserializer)?;
 
     let mut pl = progress_logger!(
         item_name = "node",
@@ -75,6 +109,26 @@ pub fn transpose(
     )?))
 }
 
+/// Returns the transpose of the provided graph as a [sequential
+/// graph](crate::traits::SequentialGraph), storing the sorted batches of arcs
+/// in the provided directory.
+///
+/// See [`transpose_labeled_in`] for the requirements on the directory.
+#[allow(clippy::type_complexity)]
+pub fn transpose_in(
+    graph: impl SequentialGraph,
+    batch_size: usize,
+    dir: impl AsRef<Path>,
+) -> Result<Left<arc_list_graph::ArcListGraph<KMergeIters<BatchIterator<()>, ()>>>> {
+    Ok(Left(transpose_labeled_in(
+        &UnitLabelGraph(graph),
+        batch_size,
+        dir,
+        (),
+        (),
+    )?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +148,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_transposition_isolated() -> anyhow::Result<()> {
+        use crate::graphs::vec_graph::VecGraph;
+        use crate::traits::SequentialLabeling;
+        // node 0 has no predecessors, node 3 is isolated, and node 5 has
+        // no successors
+        let mut g = VecGraph::empty(6);
+        g.add_arc_list([(0, 1), (1, 2), (2, 1), (4, 5), (1, 5)]);
+        let g = Left(g);
+
+        let dir = Builder::new()
+            .prefix("test_transposition_isolated")
+            .tempdir()?;
+        let trans = transpose_in(&g, 2, dir.path())?;
+        assert_eq!(trans.num_nodes(), 6);
+        let mut g2 = VecGraph::empty(trans.num_nodes());
+        g2.add_lender(&trans);
+
+        let dir = Builder::new()
+            .prefix("test_transposition_isolated")
+            .tempdir()?;
+        let trans = transpose_in(Left(g2), 2, dir.path())?;
+        assert_eq!(trans.num_nodes(), 6);
+        let mut g3 = VecGraph::empty(trans.num_nodes());
+        g3.add_lender(&trans);
+
+        assert_eq!(g, Left(g3));
+        Ok(())
+    }
+
     #[test]
     fn test_transposition_labeled() -> anyhow::Result<()> {
         use dsi_bitstream::codes::{GammaRead, GammaWrite};
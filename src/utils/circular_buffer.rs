@@ -16,9 +16,15 @@
 /// which are resolved with modular arithmetic. It is also possible to
 /// [take](CircularBuffer::take) and [replace](CircularBuffer::replace) the
 /// value at a given index.
-#[derive(Debug, Clone)]
+///
+/// For debugging, the buffer keeps track of the last index taken or replaced,
+/// so it is possible to know [which indices](CircularBuffer::contains_node)
+/// are in the current window, and the [`Debug`](core::fmt::Debug)
+/// implementation dumps the content of the window.
+#[derive(Clone)]
 pub(crate) struct CircularBuffer<T: Default> {
     data: Vec<T>,
+    end_node_id: usize,
 }
 
 impl<T: Default> CircularBuffer<T> {
@@ -26,11 +32,13 @@ impl<T: Default> CircularBuffer<T> {
     pub(crate) fn new(len: usize) -> Self {
         Self {
             data: (0..len).map(|_| T::default()).collect::<Vec<_>>(),
+            end_node_id: 0,
         }
     }
 
     /// Takes an element from the buffer, replacing it with its default value.
     pub(crate) fn take(&mut self, index: usize) -> T {
+        self.end_node_id = index + 1;
         let idx = index % self.data.len();
         core::mem::take(&mut self.data[idx])
     }
@@ -38,10 +46,53 @@ impl<T: Default> CircularBuffer<T> {
     /// Replaces an element in the buffer with a new value and
     /// return a reference to the new value in the buffer.
     pub(crate) fn replace(&mut self, index: usize, data: T) -> &T {
+        self.end_node_id = index + 1;
         let idx = index % self.data.len();
         self.data[idx] = data;
         &self.data[idx]
     }
+
+    /// Returns the index following the last index [taken](CircularBuffer::take)
+    /// or [replaced](CircularBuffer::replace), or zero if no index has been
+    /// taken or replaced yet.
+    pub(crate) fn end_node_id(&self) -> usize {
+        self.end_node_id
+    }
+
+    /// Returns whether the element of index `node_id` is in the current
+    /// window, that is, whether `node_id` is one of the last `len` indices
+    /// before [`end_node_id`](CircularBuffer::end_node_id).
+    ///
+    /// Elements outside the window have been overwritten, so accessing them
+    /// returns stale data.
+    pub(crate) fn contains_node(&self, node_id: usize) -> bool {
+        node_id < self.end_node_id && self.end_node_id - node_id <= self.data.len()
+    }
+
+    /// Returns an iterator over the indices in the current window and the
+    /// corresponding elements, in increasing order of index.
+    pub(crate) fn window(&self) -> impl Iterator<Item = (usize, &T)> {
+        (self.end_node_id.saturating_sub(self.data.len())..self.end_node_id)
+            .map(|node_id| (node_id, &self.data[node_id % self.data.len()]))
+    }
+}
+
+impl<T: Default + core::fmt::Debug> core::fmt::Debug for CircularBuffer<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("CircularBuffer")
+            .field("end_node_id", &self.end_node_id())
+            .field("window", &Window(self))
+            .finish()
+    }
+}
+
+/// Helper to format the window of a [`CircularBuffer`] as a map.
+struct Window<'a, T: Default>(&'a CircularBuffer<T>);
+
+impl<T: Default + core::fmt::Debug> core::fmt::Debug for Window<'_, T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_map().entries(self.0.window()).finish()
+    }
 }
 
 impl<T: Default> core::ops::Index<usize> for CircularBuffer<T> {
@@ -79,3 +130,44 @@ impl<T: Default> core::ops::IndexMut<isize> for CircularBuffer<T> {
         &mut self.data[idx]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window() {
+        let mut buffer = CircularBuffer::<usize>::new(3);
+        assert_eq!(buffer.end_node_id(), 0);
+        assert!(!buffer.contains_node(0));
+        assert_eq!(buffer.window().count(), 0);
+
+        for node_id in 0..5 {
+            let old = buffer.take(node_id);
+            // The slot of node_id was used by node_id - 3
+            assert_eq!(old, if node_id >= 3 { 10 * (node_id - 3) } else { 0 });
+            assert_eq!(*buffer.replace(node_id, 10 * node_id), 10 * node_id);
+        }
+        assert_eq!(buffer.end_node_id(), 5);
+        assert!(!buffer.contains_node(1));
+        assert!((2..5).all(|node_id| buffer.contains_node(node_id)));
+        assert!(!buffer.contains_node(5));
+        assert_eq!(
+            buffer.window().collect::<Vec<_>>(),
+            vec![(2, &20), (3, &30), (4, &40)]
+        );
+        // Indexing wraps around
+        assert_eq!(buffer[4_usize], 40);
+        assert_eq!(buffer[-1_isize], 20);
+        assert_eq!(buffer[1_usize], 40);
+
+        // Taking evicts the oldest element from the window
+        assert_eq!(buffer.take(5), 20);
+        assert!(!buffer.contains_node(2));
+        assert!(buffer.contains_node(5));
+        assert_eq!(
+            format!("{:?}", buffer),
+            "CircularBuffer { end_node_id: 6, window: {3: 30, 4: 40, 5: 0} }"
+        );
+    }
+}
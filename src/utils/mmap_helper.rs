@@ -19,14 +19,21 @@ use std::{mem::size_of, path::Path, sync::Arc};
 /// `usize`, but per se `W` has no trait bounds.
 ///
 /// If the length of the file is not a multiple of the size of `W`, the behavior
-/// of [`load`] is platform-dependent:
+/// of [`mmap`](MmapHelper::mmap) is platform-dependent:
 /// - on Linux, files will be silently zero-extended to the smallest length that
-///   is a multiple of  the size of `W`;
+///   is a multiple of the size of `W`: the mapping is longer than the file by
+///   less than `size_of::<W>()` bytes, which thus lie in the last page of the
+///   file, and the part of that page past the end of the file is filled with
+///   zeroes by the kernel;
 /// - on Windows, an error will be returned; you will have to pad manually the
 ///   file using the `pad` command of the `webgraph` CLI.
 ///
-/// On the contrary, [`load_mut`] will always refuse to map a file whose length
-/// is not a multiple of the size of `W`.
+/// Thus, on Linux the slice returned by [`AsRef`] contains the whole file,
+/// followed by at most `size_of::<W>() - 1` zero bytes, whereas on Windows it
+/// contains exactly the (padded) file.
+///
+/// On the contrary, [`mmap_mut`](MmapHelper::mmap_mut) will always refuse to
+/// map a file whose length is not a multiple of the size of `W`.
 ///
 /// If you need clonable version of this structure, consider using [`ArcMmapHelper`].
 #[derive(Clone)]
@@ -98,7 +105,8 @@ impl<W> MmapHelper<W> {
             .len()
             .try_into()
             .with_context(|| "Cannot convert file length to usize")?;
-        // Align to multiple of size_of::<W>
+        // Align to multiple of size_of::<W>; the additional bytes, if any, are
+        // in the last page of the file, so they are zero-filled by the kernel.
         let mmap_len = file_len.align_to(size_of::<W>());
         #[cfg(windows)]
         {
@@ -8,8 +8,9 @@
 //! Facilities to sort externally pairs of nodes with an associated label.
 
 use super::{ArcMmapHelper, MmapHelper};
+use crate::graphs::Code;
 use crate::traits::{BitDeserializer, BitSerializer};
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, ensure, Context};
 use dary_heap::PeekMut;
 use dsi_bitstream::prelude::*;
 use log::debug;
@@ -119,6 +120,8 @@ pub struct SortPairs<
     last_batch_len: usize,
     /// The batch of triples we are currently building.
     batch: Vec<Triple<S::SerType>>,
+    /// The code used to write gaps in batches.
+    gap_code: Code,
 }
 
 impl SortPairs<(), ()> {
@@ -131,6 +134,14 @@ impl SortPairs<(), ()> {
     pub fn new<P: AsRef<Path>>(batch_size: usize, dir: P) -> anyhow::Result<Self> {
         Self::new_labeled(batch_size, dir, (), ())
     }
+
+    /// Creates a new `SortPairs` without labels whose batch size is the
+    /// largest one fitting in `bytes` bytes.
+    ///
+    /// See [`SortPairs::with_memory_budget_labeled`].
+    pub fn with_memory_budget<P: AsRef<Path>>(bytes: u64, dir: P) -> anyhow::Result<Self> {
+        Self::with_memory_budget_labeled(bytes, dir, (), ())
+    }
     /// Adds a unlabeled pair to the graph.
     pub fn push(&mut self, x: usize, y: usize) -> anyhow::Result<()> {
         self.push_labeled(x, y, ())
@@ -167,10 +178,66 @@ where
                 num_batches: 0,
                 last_batch_len: 0,
                 batch: Vec::with_capacity(batch_size),
+                gap_code: Code::Gamma,
             })
         }
     }
 
+    /// Creates a new `SortPairs` with labels whose batch size is the largest
+    /// one fitting in `bytes` bytes, as computed by
+    /// [`SortPairs::batch_size_for_budget`].
+    ///
+    /// The budget accounts only for the in-memory batch, which is the only
+    /// structure whose size depends on the batch size.
+    pub fn with_memory_budget_labeled<P: AsRef<Path>>(
+        bytes: u64,
+        dir: P,
+        serializer: S,
+        deserializer: D,
+    ) -> anyhow::Result<Self> {
+        let batch_size = Self::batch_size_for_budget(bytes);
+        debug!(
+            "Using batch size {} for a memory budget of {} bytes",
+            batch_size, bytes
+        );
+        Self::new_labeled(batch_size, dir, serializer, deserializer)
+    }
+
+    /// Returns the largest batch size such that a batch fits in `bytes` bytes.
+    ///
+    /// The result is never zero, so a batch contains at least one triple
+    /// even if the budget is smaller than a triple.
+    pub fn batch_size_for_budget(bytes: u64) -> usize {
+        let triple_size = core::mem::size_of::<Triple<S::SerType>>().max(1) as u64;
+        usize::try_from(bytes / triple_size)
+            .unwrap_or(usize::MAX)
+            .max(1)
+    }
+
+    /// Sets the instantaneous code used to write gaps in batches.
+    ///
+    /// Since batches are sorted, they are always stored compressed: the gaps
+    /// between sources, and between destinations with the same source, are
+    /// written using an instantaneous code, which is by default γ. The γ code
+    /// is optimal for small gaps, whereas δ is more compact when gaps are
+    /// large, as it happens, for example, when permuting a graph randomly.
+    ///
+    /// Only γ and δ are supported, and the code must be set before adding
+    /// pairs.
+    pub fn set_gap_code(&mut self, code: Code) -> anyhow::Result<()> {
+        ensure!(
+            matches!(code, Code::Gamma | Code::Delta),
+            "Only γ and δ codes are supported for gaps, {:?} is not",
+            code
+        );
+        ensure!(
+            self.num_batches == 0 && self.batch.is_empty(),
+            "The gap code must be set before adding pairs"
+        );
+        self.gap_code = code;
+        Ok(())
+    }
+
     /// Adds a labeled pair to the graph.
     pub fn push_labeled(&mut self, x: usize, y: usize, t: S::SerType) -> anyhow::Result<()> {
         self.batch.push(Triple {
@@ -192,11 +259,12 @@ where
 
         // Creates a batch file where to dump
         let batch_name = self.dir.join(format!("{:06x}", self.num_batches));
-        BatchIterator::new_from_vec_labeled(
+        BatchIterator::sort_and_write(
             batch_name,
             &mut self.batch,
             &self.serializer,
             self.deserializer.clone(),
+            self.gap_code,
         )?;
         self.last_batch_len = self.batch.len();
         self.batch.clear();
@@ -222,7 +290,7 @@ where
     pub fn iter(&mut self) -> anyhow::Result<KMergeIters<BatchIterator<D>, D::DeserType>> {
         self.dump()?;
         Ok(KMergeIters::new((0..self.num_batches).map(|batch_idx| {
-            BatchIterator::new_labeled(
+            BatchIterator::open(
                 self.dir.join(format!("{:06x}", batch_idx)),
                 if batch_idx == self.num_batches - 1 {
                     self.last_batch_len
@@ -230,6 +298,7 @@ where
                     self.batch_size
                 },
                 self.deserializer.clone(),
+                self.gap_code,
             )
             .unwrap()
         })))
@@ -244,6 +313,7 @@ pub struct BatchIterator<D: BitDeserializer<NE, BitReader> = ()> {
     prev_src: usize,
     prev_dst: usize,
     deserializer: D,
+    gap_code: Code,
 }
 
 impl BatchIterator<()> {
@@ -284,10 +354,7 @@ impl<D: BitDeserializer<NE, BitReader>> BatchIterator<D> {
     where
         S::SerType: Send + Sync + Copy,
     {
-        let start = std::time::Instant::now();
-        batch.radix_sort_unstable();
-        debug!("Sorted {} arcs in {:?}", batch.len(), start.elapsed());
-        Self::new_from_vec_sorted_labeled(file_path, batch, serializer, deserializer)
+        Self::sort_and_write(file_path, batch, serializer, deserializer, Code::Gamma)
     }
 
     /// Dumps the given labeled pairs in `file_path` and returns an iterator
@@ -298,6 +365,44 @@ impl<D: BitDeserializer<NE, BitReader>> BatchIterator<D> {
         serializer: &S,
         deserializer: D,
     ) -> anyhow::Result<Self>
+    where
+        S::SerType: Send + Sync + Copy,
+    {
+        Self::write(file_path, batch, serializer, deserializer, Code::Gamma)
+    }
+
+    /// Creates a new iterator over the triples previously serialized in `file_path`.
+    pub fn new_labeled<P: AsRef<std::path::Path>>(
+        file_path: P,
+        len: usize,
+        deserializer: D,
+    ) -> anyhow::Result<Self> {
+        Self::open(file_path, len, deserializer, Code::Gamma)
+    }
+
+    fn sort_and_write<S: BitSerializer<NE, BitWriter>>(
+        file_path: impl AsRef<Path>,
+        batch: &mut [Triple<S::SerType>],
+        serializer: &S,
+        deserializer: D,
+        gap_code: Code,
+    ) -> anyhow::Result<Self>
+    where
+        S::SerType: Send + Sync + Copy,
+    {
+        let start = std::time::Instant::now();
+        batch.radix_sort_unstable();
+        debug!("Sorted {} arcs in {:?}", batch.len(), start.elapsed());
+        Self::write(file_path, batch, serializer, deserializer, gap_code)
+    }
+
+    fn write<S: BitSerializer<NE, BitWriter>>(
+        file_path: impl AsRef<Path>,
+        batch: &[Triple<S::SerType>],
+        serializer: &S,
+        deserializer: D,
+        gap_code: Code,
+    ) -> anyhow::Result<Self>
     where
         S::SerType: Send + Sync + Copy,
     {
@@ -321,17 +426,15 @@ impl<D: BitDeserializer<NE, BitReader>> BatchIterator<D> {
             label,
         } in batch.iter()
         {
-            // write the source gap as gamma
-            stream
-                .write_gamma((src - prev_src) as _)
+            // write the source gap
+            write_gap(&mut stream, (src - prev_src) as _, gap_code)
                 .with_context(|| format!("Could not write {} after {}", src, prev_src))?;
             if *src != prev_src {
                 // Reset prev_y
                 prev_dst = 0;
             }
-            // write the destination gap as gamma
-            stream
-                .write_gamma((dst - prev_dst) as _)
+            // write the destination gap
+            write_gap(&mut stream, (dst - prev_dst) as _, gap_code)
                 .with_context(|| format!("Could not write {} after {}", dst, prev_dst))?;
             // write the label
             serializer
@@ -342,14 +445,14 @@ impl<D: BitDeserializer<NE, BitReader>> BatchIterator<D> {
         // flush the stream and reset the buffer
         stream.flush().context("Could not flush stream")?;
 
-        Self::new_labeled(file_path, batch.len(), deserializer)
+        Self::open(file_path, batch.len(), deserializer, gap_code)
     }
 
-    /// Creates a new iterator over the triples previously serialized in `file_path`.
-    pub fn new_labeled<P: AsRef<std::path::Path>>(
+    fn open<P: AsRef<std::path::Path>>(
         file_path: P,
         len: usize,
         deserializer: D,
+        gap_code: Code,
     ) -> anyhow::Result<Self> {
         let stream = <BufBitReader<NE, _>>::new(MemWordReader::new(ArcMmapHelper(Arc::new(
             MmapHelper::mmap(
@@ -365,6 +468,7 @@ impl<D: BitDeserializer<NE, BitReader>> BatchIterator<D> {
             prev_src: 0,
             prev_dst: 0,
             deserializer,
+            gap_code,
         })
     }
 }
@@ -378,22 +482,43 @@ impl<D: BitDeserializer<NE, BitReader> + Clone> Clone for BatchIterator<D> {
             prev_src: self.prev_src,
             prev_dst: self.prev_dst,
             deserializer: self.deserializer.clone(),
+            gap_code: self.gap_code,
         }
     }
 }
 
+#[inline(always)]
+fn write_gap(
+    stream: &mut BitWriter,
+    gap: u64,
+    code: Code,
+) -> Result<usize, <BitWriter as BitWrite<NE>>::Error> {
+    match code {
+        Code::Delta => stream.write_delta(gap),
+        _ => stream.write_gamma(gap),
+    }
+}
+
+#[inline(always)]
+fn read_gap(stream: &mut BitReader, code: Code) -> u64 {
+    match code {
+        Code::Delta => stream.read_delta().unwrap(),
+        _ => stream.read_gamma().unwrap(),
+    }
+}
+
 impl<D: BitDeserializer<NE, BitReader>> Iterator for BatchIterator<D> {
     type Item = (usize, usize, D::DeserType);
     fn next(&mut self) -> Option<Self::Item> {
         if self.current == self.len {
             return None;
         }
-        let src = self.prev_src + self.stream.read_gamma().unwrap() as usize;
+        let src = self.prev_src + read_gap(&mut self.stream, self.gap_code) as usize;
         if src != self.prev_src {
             // Reset prev_y
             self.prev_dst = 0;
         }
-        let dst = self.prev_dst + self.stream.read_gamma().unwrap() as usize;
+        let dst = self.prev_dst + read_gap(&mut self.stream, self.gap_code) as usize;
         let label = self.deserializer.deserialize(&mut self.stream).unwrap();
         self.prev_src = src;
         self.prev_dst = dst;
@@ -643,4 +768,61 @@ mod tests {
         }
         Ok(())
     }
+
+    #[test]
+    fn test_memory_budget() -> anyhow::Result<()> {
+        let budget = 1_000_000;
+        // (usize, usize) pairs without labels
+        let batch_size = SortPairs::<(), ()>::batch_size_for_budget(budget);
+        let pair_size = core::mem::size_of::<(usize, usize)>();
+        assert!(batch_size * pair_size <= budget as usize);
+        assert!((batch_size + 1) * pair_size > budget as usize);
+        assert_eq!(SortPairs::<(), ()>::batch_size_for_budget(0), 1);
+
+        let dir = tempfile::tempdir()?;
+        let mut sp = SortPairs::with_memory_budget(10 * pair_size as u64, dir.path())?;
+        for i in (0..25).rev() {
+            sp.push(i, i + 1)?;
+        }
+        assert_eq!(sp.num_batches, 2);
+        let pairs = sp.iter()?.map(|(x, y, _)| (x, y)).collect::<Vec<_>>();
+        assert_eq!(pairs, (0..25).map(|i| (i, i + 1)).collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_gap_code() -> anyhow::Result<()> {
+        // Sources are dense, but destinations are spread over a large range
+        let pairs = (0..10_000_usize)
+            .map(|i| (i / 2, i.wrapping_mul(0x9E37_79B9) % (1 << 30)))
+            .collect::<Vec<_>>();
+        let mut merged = vec![];
+        let mut sizes = vec![];
+        for code in [Code::Gamma, Code::Delta] {
+            let dir = tempfile::tempdir()?;
+            let mut sp = SortPairs::new(1000, dir.path())?;
+            sp.set_gap_code(code)?;
+            for &(x, y) in pairs.iter().rev() {
+                sp.push(x, y)?;
+            }
+            merged.push(sp.iter()?.map(|(x, y, _)| (x, y)).collect::<Vec<_>>());
+            let mut size = 0;
+            for entry in std::fs::read_dir(dir.path())? {
+                size += entry?.metadata()?.len();
+            }
+            sizes.push(size);
+        }
+        let mut sorted = pairs.clone();
+        sorted.sort();
+        assert_eq!(merged[0], sorted);
+        assert_eq!(merged[1], sorted);
+        assert!(sizes[1] < sizes[0], "{:?}", sizes);
+
+        let dir = tempfile::tempdir()?;
+        let mut sp = SortPairs::new(10, dir.path())?;
+        assert!(sp.set_gap_code(Code::Unary).is_err());
+        sp.push(0, 1)?;
+        assert!(sp.set_gap_code(Code::Delta).is_err());
+        Ok(())
+    }
 }
@@ -48,3 +48,49 @@ fn test_permuted() {
         ]
     );
 }
+
+#[test]
+fn test_permuted_random_access() {
+    let graph = Left(VecGraph::from_lender(
+        webgraph::graphs::random::ErdosRenyi::new(100, 0.1, 0).iter(),
+    ));
+    // A permutation of the nodes and its inverse
+    let perm = (0..100).map(|x| (x * 37 + 11) % 100).collect::<Vec<_>>();
+    let mut inv_perm = vec![0; 100];
+    for (x, &p) in perm.iter().enumerate() {
+        inv_perm[p] = x;
+    }
+
+    let permuted = Left(VecGraph::from_lender(
+        PermutedGraph {
+            graph: &graph,
+            perm: &perm,
+        }
+        .iter(),
+    ));
+    let random_access = PermutedRandomAccessGraph {
+        graph: &graph,
+        perm: &perm,
+        inv_perm: &inv_perm,
+    };
+    assert_eq!(random_access.num_nodes(), 100);
+    assert_eq!(random_access.num_arcs(), graph.num_arcs());
+    for node in 0..100 {
+        let mut succ = random_access.successors(node).collect::<Vec<_>>();
+        succ.sort();
+        assert_eq!(
+            succ,
+            permuted.successors(node).into_iter().collect::<Vec<_>>()
+        );
+        assert_eq!(random_access.outdegree(node), succ.len());
+    }
+
+    // Sequential iteration enumerates nodes in increasing order
+    let mut next = 0;
+    for_!((node, succ) in random_access.iter() {
+        assert_eq!(node, next);
+        assert!(succ.into_iter().eq(random_access.successors(node)));
+        next += 1;
+    });
+    assert_eq!(next, 100);
+}
@@ -240,3 +240,38 @@ impl<E: MeasurableEncoder> MeasurableEncoder for EncoderValidator<E> {
         EncoderValidator::new_estimator(self.encoder.estimator())
     }
 }
+
+#[test]
+fn test_from_bytes() -> Result<()> {
+    let graph = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    let comp_flags = CompFlags::default();
+    let mut words: Vec<u64> = Vec::new();
+    {
+        let bit_writer = <BufBitWriter<BE, _>>::new(MemWordWriterVec::new(&mut words));
+        let codes_writer = <DynCodesEncoder<BE, _>>::new(bit_writer, &comp_flags);
+        let mut bvcomp = BVComp::new(
+            codes_writer,
+            comp_flags.compression_window,
+            comp_flags.max_ref_count,
+            comp_flags.min_interval_length,
+            0,
+        );
+        bvcomp.extend(graph.iter())?;
+        bvcomp.flush()?;
+    }
+    let bytes = words
+        .iter()
+        .flat_map(|word| word.to_ne_bytes())
+        .collect::<Vec<u8>>();
+
+    let seq_graph = BVGraphSeq::from_bytes::<BE>(
+        &bytes,
+        graph.num_nodes(),
+        Some(graph.num_arcs()),
+        comp_flags,
+    )?;
+    assert_eq!(seq_graph.num_nodes(), graph.num_nodes());
+    assert_eq!(seq_graph.num_arcs_hint(), Some(graph.num_arcs()));
+    assert!(webgraph::algo::graphs_equal(&graph, &seq_graph));
+    Ok(())
+}
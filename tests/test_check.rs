@@ -0,0 +1,70 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use clap::Command;
+use dsi_bitstream::prelude::BE;
+use std::ffi::OsStr;
+use std::path::Path;
+use webgraph::cli;
+use webgraph::graphs::random::ErdosRenyi;
+use webgraph::prelude::*;
+
+fn run_check(basename: &Path) -> Result<()> {
+    let matches = cli::check::cli(Command::new("webgraph")).try_get_matches_from([
+        OsStr::new("webgraph"),
+        OsStr::new(cli::check::COMMAND_NAME),
+        basename.as_os_str(),
+    ])?;
+    let (_, submatches) = matches.subcommand().unwrap();
+    cli::check::main(submatches)
+}
+
+#[test]
+fn test_check() -> Result<()> {
+    run_check(Path::new("tests/data/cnr-2000"))?;
+
+    // Truncate a copy of the graph
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    std::fs::copy(
+        "tests/data/cnr-2000.properties",
+        basename.with_extension("properties"),
+    )?;
+    let graph = std::fs::read("tests/data/cnr-2000.graph")?;
+    std::fs::write(
+        basename.with_extension("graph"),
+        &graph[..graph.len() / 2 / 16 * 16],
+    )?;
+
+    let err = run_check(&basename).unwrap_err().to_string();
+    assert!(err.starts_with("Node "), "{}", err);
+    assert!(err.contains("truncated or corrupted stream"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn test_check_corrupted() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("graph");
+    let er = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    BVComp::single_thread::<BE, _>(&basename, &er, CompFlags::default(), false, None)?;
+    run_check(&basename)?;
+    let graph_path = basename.with_extension(GRAPH_EXTENSION);
+    let graph = std::fs::read(&graph_path)?;
+
+    // Corrupting a byte must never cause a panic, and must be detected
+    // unless the result happens to be a different, valid graph
+    let mut errors = 0;
+    for i in 0..graph.len() {
+        let mut corrupted = graph.clone();
+        corrupted[i] ^= 0x5A;
+        std::fs::write(&graph_path, &corrupted)?;
+        errors += run_check(&basename).is_err() as usize;
+    }
+    assert!(errors > graph.len() / 2, "{} errors", errors);
+    Ok(())
+}
@@ -0,0 +1,231 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use lender::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_non_default_comp_flags() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+
+    let tmp_dir = tempfile::tempdir()?;
+    let tmp_basename = tmp_dir.path().join("cnr-2000-flags");
+    let comp_flags = CompFlags {
+        outdegrees: Code::Delta,
+        references: Code::Gamma,
+        intervals: Code::Delta,
+        residuals: Code::Gamma,
+        ..Default::default()
+    };
+    BVComp::single_thread::<BE, _>(&tmp_basename, &graph, comp_flags, false, None)?;
+
+    let properties = std::fs::read_to_string(tmp_basename.with_extension(PROPERTIES_EXTENSION))?;
+    assert!(properties.contains(
+        "compressionflags=OUTDEGREES_DELTA|REFERENCES_GAMMA|INTERVALS_DELTA|RESIDUALS_GAMMA\n"
+    ));
+
+    let comp_graph = BVGraphSeq::with_basename(&tmp_basename)
+        .endianness::<BE>()
+        .load()?;
+    assert_eq!(comp_graph.num_nodes(), graph.num_nodes());
+
+    let mut iter = comp_graph.iter();
+    for_!((node, succ) in graph.iter() {
+        let (new_node, new_succ) = iter.next().unwrap();
+        assert_eq!(node, new_node);
+        assert_eq!(
+            succ.into_iter().collect::<Vec<_>>(),
+            new_succ.into_iter().collect::<Vec<_>>(),
+            "Node {} differs",
+            node
+        );
+    });
+    assert!(iter.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_no_intervals() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let tmp_dir = tempfile::tempdir()?;
+
+    let mut num_intervals = vec![];
+    for min_interval_length in [4, 0] {
+        let tmp_basename = tmp_dir
+            .path()
+            .join(format!("cnr-2000-{}", min_interval_length));
+        let comp_flags = CompFlags {
+            min_interval_length,
+            ..Default::default()
+        };
+        BVComp::single_thread::<BE, _>(&tmp_basename, &graph, comp_flags, false, None)?;
+
+        let properties =
+            std::fs::read_to_string(tmp_basename.with_extension(PROPERTIES_EXTENSION))?;
+        assert!(properties.contains(&format!("minintervallength={}\n", min_interval_length)));
+
+        let comp_graph = BVGraphSeq::with_basename(&tmp_basename)
+            .endianness::<BE>()
+            .load()?;
+        let mut iter = comp_graph.iter();
+        iter.enable_stats();
+        for_!((node, succ) in graph.iter() {
+            let (new_node, new_succ) = iter.next().unwrap();
+            assert_eq!(node, new_node);
+            assert!(itertools::equal(succ, new_succ), "Node {} differs", node);
+        });
+        num_intervals.push(iter.stats().unwrap().intervals);
+    }
+    assert!(num_intervals[0] > 0);
+    // No successor is represented by an interval
+    assert_eq!(num_intervals[1], 0);
+    Ok(())
+}
+
+#[test]
+fn test_dyn_decoder_from_properties() -> Result<()> {
+    let graph = Left(VecGraph::from_arc_list([
+        (0, 1),
+        (0, 2),
+        (0, 3),
+        (0, 7),
+        (0, 20),
+        (1, 0),
+        (1, 5),
+        (3, 0),
+        (3, 1),
+        (3, 2),
+        (3, 3),
+        (3, 7),
+        (3, 20),
+        (20, 0),
+    ]));
+    let comp_flags = CompFlags {
+        outdegrees: Code::Delta,
+        residuals: Code::Zeta { k: 2 },
+        ..Default::default()
+    };
+    let mut words: Vec<u64> = Vec::new();
+    {
+        let bit_writer = <BufBitWriter<BE, _>>::new(MemWordWriterVec::new(&mut words));
+        let codes_writer = <DynCodesEncoder<BE, _>>::new(bit_writer, &comp_flags);
+        let mut bvcomp = BVComp::new(
+            codes_writer,
+            comp_flags.compression_window,
+            comp_flags.max_ref_count,
+            comp_flags.min_interval_length,
+            0,
+        );
+        bvcomp.extend(graph.iter())?;
+        bvcomp.flush()?;
+    }
+    // Zero-extend the bitstream, as it happens when loading a graph
+    let mut data = words
+        .iter()
+        .flat_map(|word| word.to_ne_bytes())
+        .collect::<Vec<u8>>()
+        .chunks(4)
+        .map(|chunk| u32::from_ne_bytes(chunk.try_into().unwrap()))
+        .collect::<Vec<u32>>();
+    data.extend([0; 4]);
+
+    let map = std::collections::HashMap::from([
+        ("endianness".to_string(), BE::NAME.to_string()),
+        (
+            "compressionflags".to_string(),
+            "OUTDEGREES_DELTA|RESIDUALS_ZETA".to_string(),
+        ),
+        ("zetak".to_string(), "2".to_string()),
+    ]);
+    let decoder = <DynCodesDecoder<BE, _>>::from_properties(
+        <BufBitReader<BE, _>>::new(MemWordReader::new(data.as_slice())),
+        &map,
+    )?;
+    let mut iter = webgraph::graphs::bvgraph::sequential::Iter::new(
+        decoder,
+        graph.num_nodes(),
+        comp_flags.compression_window,
+        comp_flags.min_interval_length,
+    );
+    for node in 0..3 {
+        assert_eq!(iter.next().unwrap().0, node);
+    }
+    let (node, succ) = iter.next().unwrap();
+    assert_eq!(node, 3);
+    assert_eq!(
+        succ.into_iter().collect::<Vec<_>>(),
+        vec![0, 1, 2, 3, 7, 20]
+    );
+
+    // An unknown code must be rejected
+    let map = std::collections::HashMap::from([(
+        "compressionflags".to_string(),
+        "OUTDEGREES_GOLOMB".to_string(),
+    )]);
+    assert!(<DynCodesDecoder<BE, _>>::from_properties(
+        <BufBitReader<BE, _>>::new(MemWordReader::new(data.as_slice())),
+        &map,
+    )
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_builder_window() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let tmp_dir = tempfile::tempdir()?;
+    let mut sizes = vec![];
+    for (window, max_ref_count) in [(1, 1), (16, 10)] {
+        let tmp_basename = tmp_dir.path().join(format!("cnr-2000-w{}", window));
+        let comp_flags = CompFlags::builder()
+            .compression_window(window)
+            .max_ref_count(max_ref_count)
+            .min_interval_length(3)
+            .residuals(Code::Zeta { k: 2 })
+            .build()?;
+        BVComp::single_thread::<BE, _>(&tmp_basename, &graph, comp_flags, false, None)?;
+
+        let properties =
+            std::fs::read_to_string(tmp_basename.with_extension(PROPERTIES_EXTENSION))?;
+        assert!(properties.contains(&format!("windowsize={}\n", window)));
+        assert!(properties.contains(&format!("maxrefcount={}\n", max_ref_count)));
+
+        let comp_graph = BVGraphSeq::with_basename(&tmp_basename)
+            .endianness::<BE>()
+            .load()?;
+        let mut iter = comp_graph.iter();
+        for_!((node, succ) in graph.iter() {
+            let (new_node, new_succ) = iter.next().unwrap();
+            assert_eq!(node, new_node);
+            assert!(itertools::equal(succ, new_succ), "Node {} differs", node);
+        });
+        assert!(iter.next().is_none());
+        sizes.push(std::fs::metadata(tmp_basename.with_extension(GRAPH_EXTENSION))?.len());
+    }
+    // A larger window finds better references
+    assert!(sizes[1] < sizes[0]);
+
+    // Inconsistent ζ codes cannot be stored in the .properties file
+    assert!(CompFlags::builder()
+        .outdegrees(Code::Zeta { k: 2 })
+        .residuals(Code::Zeta { k: 3 })
+        .build()
+        .is_err());
+    assert!(CompFlags::builder()
+        .residuals(Code::Zeta { k: 0 })
+        .build()
+        .is_err());
+    Ok(())
+}
@@ -0,0 +1,58 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::{bail, Result};
+use dsi_bitstream::prelude::*;
+use lender::*;
+use std::path::Path;
+use webgraph::graphs::random::ErdosRenyi;
+use webgraph::prelude::*;
+
+fn successors<E: Endianness + 'static>(basename: &Path) -> Result<Vec<Vec<usize>>>
+where
+    for<'a> BufBitReader<E, MemWordReader<u32, &'a [u32]>>: CodeRead<E> + BitSeek,
+{
+    let graph = BVGraphSeq::with_basename(basename)
+        .endianness::<E>()
+        .load()?;
+    let mut result = vec![];
+    for_!((_node, succ) in graph.iter() {
+        result.push(succ.into_iter().collect());
+    });
+    Ok(result)
+}
+
+/// Loads a graph choosing the endianness from its properties.
+fn load_successors(basename: &Path) -> Result<Vec<Vec<usize>>> {
+    match get_endianness(basename)?.as_str() {
+        BE::NAME => successors::<BE>(basename),
+        LE::NAME => successors::<LE>(basename),
+        e => bail!("Unknown endianness: {}", e),
+    }
+}
+
+#[test]
+fn test_be_le() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let graph = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    let be = tmp_dir.path().join("be");
+    let le = tmp_dir.path().join("le");
+    BVComp::single_thread::<BE, _>(&be, &graph, CompFlags::default(), false, None)?;
+    BVComp::single_thread::<LE, _>(&le, &graph, CompFlags::default(), false, None)?;
+    assert_eq!(get_endianness(&be)?, BE::NAME);
+    assert_eq!(get_endianness(&le)?, LE::NAME);
+
+    let expected = (0..graph.num_nodes())
+        .map(|x| graph.successors(x).into_iter().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(load_successors(&be)?, expected);
+    assert_eq!(load_successors(&le)?, expected);
+
+    // Loading with the wrong endianness fails
+    assert!(successors::<LE>(&be).is_err());
+    assert!(successors::<BE>(&le).is_err());
+    Ok(())
+}
@@ -0,0 +1,49 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use webgraph::graphs::random::ErdosRenyi;
+use webgraph::prelude::*;
+
+#[test]
+fn test_edge_list_round_trip() -> Result<()> {
+    let graph = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    let mut sorted = vec![];
+    write_edge_list(&graph, &mut sorted)?;
+
+    // Shuffle the arcs and add comments and blank lines
+    let mut lines = String::from_utf8(sorted.clone())?
+        .lines()
+        .map(|line| line.replace('\t', " "))
+        .collect::<Vec<_>>();
+    lines.shuffle(&mut SmallRng::seed_from_u64(0));
+    let text = format!("# An edge list\n\n{}\n", lines.join("\n"));
+
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("edge-list");
+    let arcs = read_edge_list(text.as_bytes()).collect::<Result<Vec<_>>>()?;
+    BVComp::compress_from_arcs::<BE>(
+        &basename,
+        arcs,
+        graph.num_nodes(),
+        64,
+        CompFlags::default(),
+        Threads::Num(2),
+        tmp_dir.path(),
+    )?;
+
+    let comp_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let mut result = vec![];
+    write_edge_list(&comp_graph, &mut result)?;
+    assert_eq!(result, sorted);
+    Ok(())
+}
@@ -6,7 +6,7 @@
  */
 
 use anyhow::{bail, Result};
-use dsi_bitstream::prelude::BE;
+use dsi_bitstream::prelude::*;
 use lender::*;
 use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
 use webgraph::{graphs::arc_list_graph::ArcListGraph, prelude::*};
@@ -32,6 +32,30 @@ fn test_iter_nodes() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_next_slice() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+
+    // The lending path, the slice path and an allocating path agree
+    let mut lender = graph.iter();
+    let mut slices = graph.iter();
+    let mut num_nodes = 0;
+    while let Some((node_id, succ)) = lender.next() {
+        let succ = succ.into_iter().collect::<Vec<_>>();
+        let (slice_node_id, slice) = slices.next_slice().unwrap();
+        assert_eq!(node_id, slice_node_id);
+        assert_eq!(succ, slice);
+        assert_eq!(succ, slice.to_vec());
+        num_nodes += 1;
+    }
+    assert!(slices.next_slice().is_none());
+    assert_eq!(num_nodes, graph.num_nodes());
+
+    Ok(())
+}
+
 #[test]
 fn test_iter_nodes_from() -> Result<()> {
     let bvgraph = BVGraph::with_basename("tests/data/cnr-2000")
@@ -130,3 +154,402 @@ where
     assert!(iter.next().is_none(), "Too few nodes in split_iter");
     Ok(())
 }
+
+#[test]
+fn test_decode_stats() -> Result<()> {
+    let bvgraph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+
+    let mut iter = bvgraph.iter();
+    assert!(iter.stats().is_none());
+    iter.enable_stats();
+    while iter.next().is_some() {}
+
+    let stats = *iter.stats().unwrap();
+    assert_eq!(stats.nodes, bvgraph.num_nodes() as u64);
+    assert_eq!(
+        stats.copied + stats.intervals + stats.residuals,
+        bvgraph.num_arcs_hint().unwrap()
+    );
+    assert_eq!(stats.num_arcs(), bvgraph.num_arcs_hint().unwrap());
+    // cnr-2000 uses all three ways of representing successors
+    assert!(stats.copied > 0);
+    assert!(stats.intervals > 0);
+    assert!(stats.residuals > 0);
+    assert!(stats.reference_hops > 0);
+
+    Ok(())
+}
+
+#[test]
+fn test_skip_to() -> Result<()> {
+    let bvgraph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let offsets = bvgraph
+        .offset_deg_iter()
+        .map(|(offset, _)| offset)
+        .collect::<Vec<_>>();
+    let mut all_succ = vec![];
+    for_!((_, succ) in bvgraph.iter() {
+        all_succ.push(succ.into_iter().collect::<Vec<_>>());
+    });
+    let compression_window = 7;
+
+    for k in [0, 1, 5, 7, 100, 1000, bvgraph.num_nodes() - 1] {
+        let mut iter = bvgraph.iter();
+        let window = &all_succ[k.saturating_sub(compression_window)..k];
+        iter.skip_to(k, offsets[k], window.iter().cloned())?;
+        let mut node = k;
+        while let Some((node_id, succ)) = iter.next() {
+            assert_eq!(node_id, node);
+            assert_eq!(succ.into_iter().collect::<Vec<_>>(), all_succ[node]);
+            node += 1;
+        }
+        assert_eq!(node, bvgraph.num_nodes());
+    }
+
+    // A wrong window length is an error
+    let mut iter = bvgraph.iter();
+    assert!(iter
+        .skip_to(100, offsets[100], all_succ[95..100].iter().cloned())
+        .is_err());
+
+    // A failed skip does not modify the state of the iterator
+    let mut iter = bvgraph.iter();
+    for _ in 0..10 {
+        iter.next();
+    }
+    assert!(iter
+        .skip_to(100, offsets[100], all_succ[92..100].iter().cloned())
+        .is_err());
+    let mut node = 10;
+    while let Some((node_id, succ)) = iter.next() {
+        assert_eq!(node_id, node);
+        assert_eq!(succ.into_iter().collect::<Vec<_>>(), all_succ[node]);
+        node += 1;
+    }
+    assert_eq!(node, bvgraph.num_nodes());
+
+    Ok(())
+}
+
+#[test]
+fn test_arcs() -> Result<()> {
+    let bvgraph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let mut count = 0;
+    let mut prev = None;
+    for arc in bvgraph.arcs() {
+        if let Some(prev) = prev {
+            assert!(prev < arc, "{:?} >= {:?}", prev, arc);
+        }
+        prev = Some(arc);
+        count += 1;
+    }
+    assert_eq!(count, bvgraph.num_arcs());
+    assert_eq!(bvgraph.arcs().count() as u64, bvgraph.num_arcs());
+    Ok(())
+}
+
+#[test]
+fn test_window_mismatch() -> Result<()> {
+    let basename = "tests/data/cnr-2000";
+    let (num_nodes, num_arcs, comp_flags) =
+        parse_properties::<BE>(format!("{}.{}", basename, PROPERTIES_EXTENSION))?;
+    assert!(comp_flags.compression_window > 1);
+
+    // Read the graph with a compression window smaller than the real one
+    let factory = BVGraphSeq::with_basename(basename)
+        .endianness::<BE>()
+        .load()?
+        .into_inner();
+    let graph = BVGraphSeq::new(
+        factory,
+        num_nodes,
+        Some(num_arcs),
+        1,
+        comp_flags.min_interval_length,
+    );
+
+    let mut iter = graph.iter();
+    let err = (0..num_nodes)
+        .find_map(|_| iter.next_successors().err())
+        .expect("No out-of-window reference detected");
+    assert!(err.to_string().contains("compression window is 1"));
+
+    let mut deg_iter = graph.offset_deg_iter();
+    let err = (0..num_nodes)
+        .find_map(|_| deg_iter.next_degree().err())
+        .expect("No out-of-window reference detected");
+    assert!(err.to_string().contains("compression window is 1"));
+
+    Ok(())
+}
+
+#[test]
+fn test_next_successors_unsorted() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let mut sorted = graph.iter();
+    let mut unsorted = graph.iter();
+    let mut succ = vec![];
+    for node in 0..graph.num_nodes() {
+        succ.clear();
+        // Mix the two methods, so that references to unsorted lists are tested
+        if node % 3 == 0 {
+            succ.extend_from_slice(unsorted.next_successors()?);
+        } else {
+            succ.extend_from_slice(unsorted.next_successors_unsorted()?);
+        }
+        succ.sort_unstable();
+        assert_eq!(succ, sorted.next_successors()?);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_iter_u32() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let mut wide = graph.iter();
+    let mut narrow = graph.iter_u32()?;
+    for node in 0..graph.num_nodes() {
+        let (narrow_node, succ) = narrow.next_slice().unwrap();
+        assert_eq!(narrow_node, node);
+        let succ = succ.iter().map(|&x| x as usize).collect::<Vec<_>>();
+        assert_eq!(succ, wide.next_successors()?);
+    }
+    assert!(narrow.next_slice().is_none());
+
+    // The narrowest type is chosen from the number of nodes
+    assert!(matches!(graph.iter_narrowest()?, NarrowestIter::U32(_)));
+
+    // Successors that do not fit into a u32 are an error
+    let comp_flags = CompFlags::default();
+    let mut words: Vec<u64> = Vec::new();
+    {
+        let bit_writer = <BufBitWriter<BE, _>>::new(MemWordWriterVec::new(&mut words));
+        let codes_writer = <DynCodesEncoder<BE, _>>::new(bit_writer, &comp_flags);
+        let mut bvcomp = BVComp::new(
+            codes_writer,
+            comp_flags.compression_window,
+            comp_flags.max_ref_count,
+            comp_flags.min_interval_length,
+            0,
+        );
+        bvcomp.push([1 << 32, (1 << 32) + 10])?;
+        bvcomp.push((1 << 32) - 2..(1 << 32) + 2)?;
+        bvcomp.push([])?;
+        bvcomp.push([])?;
+        bvcomp.flush()?;
+    }
+    let bytes = words
+        .iter()
+        .flat_map(|word| word.to_ne_bytes())
+        .collect::<Vec<u8>>();
+    let graph = BVGraphSeq::from_bytes::<BE>(&bytes, 4, None, comp_flags)?;
+    let mut wide = graph.iter();
+    assert_eq!(wide.next_successors()?, [1 << 32, (1 << 32) + 10]);
+    assert_eq!(
+        wide.next_successors()?,
+        ((1 << 32) - 2..(1 << 32) + 2).collect::<Vec<_>>()
+    );
+    let mut narrow = graph.iter_u32()?;
+    let err = narrow.next_successors().unwrap_err().to_string();
+    assert!(err.contains("does not fit into a u32"), "{}", err);
+    Ok(())
+}
+
+#[test]
+fn test_par_iter() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let offsets = graph
+        .offset_deg_iter()
+        .map(|(offset, _)| offset as usize)
+        .collect::<Vec<_>>();
+    let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+
+    // Small chunks, so that many of them start at a node with references
+    for granularity in [1000, 12345, graph.num_nodes()] {
+        let num_arcs = graph.par_iter(
+            offsets.as_slice(),
+            |_, succ| succ.len() as u64,
+            |a, b| a + b,
+            granularity,
+            &thread_pool,
+            None,
+        )?;
+        let mut expected = 0_u64;
+        for_!((_, succ) in graph.iter() {
+            expected += succ.into_iter().count() as u64;
+        });
+        assert_eq!(num_arcs, expected);
+
+        // A checksum that depends on the actual successors
+        let checksum = graph.par_iter(
+            offsets.as_slice(),
+            |node, succ| succ.iter().map(|&s| (node ^ s) as u64).sum::<u64>(),
+            |a, b| a.wrapping_add(b),
+            granularity,
+            &thread_pool,
+            None,
+        )?;
+        let mut expected = 0_u64;
+        for_!((node, succ) in graph.iter() {
+            for s in succ {
+                expected = expected.wrapping_add((node ^ s) as u64);
+            }
+        });
+        assert_eq!(checksum, expected);
+    }
+
+    // Too few offsets
+    assert!(graph
+        .par_iter(
+            &offsets[..10],
+            |_, succ| succ.len(),
+            |a, b| a + b,
+            1000,
+            &thread_pool,
+            None,
+        )
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_iter_range() -> Result<()> {
+    let seq_graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let num_nodes = graph.num_nodes();
+    let bounds = [0, 1, 1000, 1001, 50000, num_nodes / 2, num_nodes];
+
+    let mut full = seq_graph.iter();
+    for range in bounds.windows(2).map(|w| w[0]..w[1]) {
+        let mut seq_range = seq_graph.iter_range(range.clone());
+        let mut ra_range = graph.iter_range(range.clone());
+        for node in range {
+            let (x, succ) = full.next().unwrap();
+            let (y, seq_succ) = seq_range.next().unwrap();
+            let (z, ra_succ) = ra_range.next().unwrap();
+            assert_eq!((x, y, z), (node, node, node));
+            let succ = succ.collect::<Vec<_>>();
+            assert_eq!(succ, seq_succ.collect::<Vec<_>>());
+            assert_eq!(succ, ra_succ.collect::<Vec<_>>());
+        }
+        assert!(seq_range.next().is_none());
+        assert!(ra_range.next().is_none());
+    }
+    assert!(full.next().is_none());
+    Ok(())
+}
+
+/// A decoder returning a given sequence of values, whatever the component.
+struct ScriptedDecoder(std::vec::IntoIter<u64>);
+
+impl Decode for ScriptedDecoder {
+    fn read_outdegree(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_reference_offset(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_block_count(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_block(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_interval_count(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_interval_start(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_interval_len(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_first_residual(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+    fn read_residual(&mut self) -> u64 {
+        self.0.next().unwrap()
+    }
+}
+
+#[test]
+fn test_corrupted_successors() -> Result<()> {
+    // Decodes the successors of the first nodes of a graph with 10 nodes,
+    // compression window 7 and minimum interval length 4
+    let decode = |values: Vec<u64>, nodes: usize| -> Result<Vec<Vec<usize>>> {
+        let mut iter = webgraph::graphs::bvgraph::sequential::Iter::new(
+            ScriptedDecoder(values.into_iter()),
+            10,
+            7,
+            4,
+        );
+        (0..nodes)
+            .map(|_| Ok(iter.next_successors()?.to_vec()))
+            .collect()
+    };
+    // Node 0 has successors 1 and 2 (residuals only)
+    let node_0 = vec![2, 0, 0, 2, 0];
+    let mut values = node_0.clone();
+    // Node 1 copies all of them and adds residual 3
+    values.extend([3, 1, 0, 0, 4]);
+    assert_eq!(decode(values, 2)?, vec![vec![1, 2], vec![1, 2, 3]]);
+
+    // A copy block beyond the end of the reference list
+    let mut values = node_0.clone();
+    values.extend([2, 1, 1, 3]);
+    let err = decode(values, 2).unwrap_err().to_string();
+    assert!(err.contains("copy blocks of node 1"), "{}", err);
+
+    // More copied successors than the outdegree
+    let mut values = node_0.clone();
+    values.extend([1, 1, 0]);
+    let err = decode(values, 2).unwrap_err().to_string();
+    assert!(err.contains("copies 2 successors"), "{}", err);
+
+    // An interval longer than the outdegree
+    let err = decode(vec![2, 0, 1, 0, 0], 1).unwrap_err().to_string();
+    assert!(err.contains("intervals of node 0"), "{}", err);
+
+    // An interval starting before node zero
+    let err = decode(vec![4, 0, 1, 1, 0], 1).unwrap_err().to_string();
+    assert!(err.contains("negative"), "{}", err);
+
+    // A residual before node zero
+    let err = decode(vec![1, 0, 0, 1], 1).unwrap_err().to_string();
+    assert!(err.contains("negative"), "{}", err);
+
+    // An outdegree larger than the number of nodes
+    let err = decode(vec![11], 1).unwrap_err().to_string();
+    assert!(err.contains("outdegree 11"), "{}", err);
+
+    // Intervals and residuals whose successors overflow
+    let err = decode(vec![8, 0, 2, u64::MAX - 1, 0, u64::MAX, 0], 1)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("overflow"), "{}", err);
+    let err = decode(vec![0, 1, 0, 0, u64::MAX - 1], 2)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("overflow"), "{}", err);
+    let err = decode(vec![2, 0, 0, 0, u64::MAX], 1)
+        .unwrap_err()
+        .to_string();
+    assert!(err.contains("overflow"), "{}", err);
+    Ok(())
+}
@@ -0,0 +1,462 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Tommaso Fontana
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use rayon::prelude::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_log_gap_cost() -> Result<()> {
+    use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let deg_cumul = build_dcf(&graph)?;
+
+    let identity = (0..graph.num_nodes()).collect::<Vec<_>>();
+    let mut random = identity.clone();
+    random.shuffle(&mut SmallRng::seed_from_u64(0));
+
+    // Both owned and borrowed permutations are accepted
+    let identity_cost = log_gap_cost(&graph, &identity, &deg_cumul, None)?;
+    let random_cost = log_gap_cost(&graph, random, &deg_cumul, Some(2))?;
+    assert!(identity_cost > 0.0);
+    assert!(identity_cost < random_cost);
+
+    // The cost is the same as that computed by the ordering report
+    let report = ordering_report(
+        &graph,
+        &identity,
+        &deg_cumul,
+        OrderingReport::DEFAULT_LOCALITY_WINDOW,
+        None,
+    )?;
+    assert_eq!(identity_cost, report.log_gap_cost);
+    Ok(())
+}
+
+#[cfg_attr(feature = "slow_tests", test)]
+#[cfg_attr(not(feature = "slow_tests"), allow(dead_code))]
+fn test_llp_locality() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let deg_cumul = build_dcf(&graph)?;
+
+    // LLP needs a symmetric, loopless, random-access graph
+    let sym = Left(VecGraph::from_lender(&simplify(&graph, 1 << 20)?));
+    let sym_deg_cumul = build_dcf(&sym)?;
+    let labels = llp::layered_label_propagation_with_params(
+        &sym,
+        &sym_deg_cumul,
+        llp::preds::MaxUpdates::from(10),
+        &llp::LlpParams::with_gammas(vec![0.0, 1.0 / 16.0, 1.0]),
+    )?;
+    let mut llp_perm = (0..graph.num_nodes()).collect::<Vec<_>>();
+    llp_perm.par_sort_by(|&a, &b| labels[a].cmp(&labels[b]));
+    let mut llp_inv_perm = vec![0; llp_perm.len()];
+    llp::invert_permutation(&llp_perm, &mut llp_inv_perm);
+
+    let identity = (0..graph.num_nodes()).collect::<Vec<_>>();
+    let identity_report = ordering_report(
+        &graph,
+        &identity,
+        &deg_cumul,
+        OrderingReport::DEFAULT_LOCALITY_WINDOW,
+        None,
+    )?;
+    let llp_report = ordering_report(
+        &graph,
+        &llp_inv_perm,
+        &deg_cumul,
+        OrderingReport::DEFAULT_LOCALITY_WINDOW,
+        None,
+    )?;
+    assert_ne!(identity_report, llp_report);
+    assert!(llp_report.locality > identity_report.locality);
+    Ok(())
+}
+
+#[test]
+fn test_llp_concurrent() -> Result<()> {
+    // Two symmetric ring lattices with different spans
+    let graphs = [2, 3].map(|span| {
+        let mut arcs = vec![];
+        for x in 0..100 {
+            for d in 1..=span {
+                arcs.push((x, (x + d) % 100));
+                arcs.push(((x + d) % 100, x));
+            }
+        }
+        Left(VecGraph::from_arcs(arcs))
+    });
+    let work_dir = tempfile::tempdir()?;
+
+    std::thread::scope(|s| -> Result<()> {
+        let handles = graphs
+            .iter()
+            .map(|graph| {
+                let work_dir = work_dir.path();
+                s.spawn(move || -> Result<Box<[usize]>> {
+                    let deg_cumul = build_dcf(graph)?;
+                    llp::layered_label_propagation_with_params(
+                        graph,
+                        &deg_cumul,
+                        llp::preds::MaxUpdates::from(5),
+                        &llp::LlpParams {
+                            gammas: vec![0.0, 1.0],
+                            num_threads: Some(2),
+                            work_dir: Some(work_dir.into()),
+                            ..Default::default()
+                        },
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            let labels = handle.join().unwrap()?;
+            assert_eq!((*labels).len(), 100);
+        }
+        Ok(())
+    })?;
+
+    // The labels of both runs have been removed
+    assert_eq!(std::fs::read_dir(work_dir.path())?.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn test_llp_with_details() -> Result<()> {
+    // A symmetric ring lattice
+    let mut arcs = vec![];
+    for x in 0..100 {
+        for d in 1..=3 {
+            arcs.push((x, (x + d) % 100));
+            arcs.push(((x + d) % 100, x));
+        }
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+    let gammas = vec![0.0, 1.0 / 16.0, 1.0];
+    let (labels, details) = llp::layered_label_propagation_with_details(
+        &graph,
+        &deg_cumul,
+        llp::preds::MaxUpdates::from(5),
+        &llp::LlpParams {
+            gammas: gammas.clone(),
+            num_threads: Some(1),
+            deterministic: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!((*labels).len(), 100);
+    assert_eq!(details.len(), gammas.len());
+
+    for ((gamma, cost, perm), &expected_gamma) in details.iter().zip(&gammas) {
+        assert_eq!(*gamma, expected_gamma);
+        // Each ordering is a permutation, and its cost is the reported one
+        let mut sorted = perm.to_vec();
+        sorted.sort();
+        assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+        let expected_cost = log_gap_cost(&graph, perm, &deg_cumul, None)?;
+        assert!((cost - expected_cost).abs() <= 1E-9 * expected_cost);
+    }
+
+    // The result does not depend on the details being collected
+    let plain_labels = llp::layered_label_propagation_with_params(
+        &graph,
+        &deg_cumul,
+        llp::preds::MaxUpdates::from(5),
+        &llp::LlpParams {
+            gammas,
+            num_threads: Some(1),
+            deterministic: true,
+            ..Default::default()
+        },
+    )?;
+    assert_eq!(labels, plain_labels);
+    Ok(())
+}
+
+#[test]
+fn test_llp_stop_on_negative_gain() -> Result<()> {
+    // A 4x4 grid numbered in row-major order, on which the first update
+    // increases the log-gap cost of the identity
+    let mut arcs = vec![];
+    for x in 0..16 {
+        if x % 4 != 3 {
+            arcs.push((x, x + 1));
+            arcs.push((x + 1, x));
+        }
+        if x < 12 {
+            arcs.push((x, x + 4));
+            arcs.push((x + 4, x));
+        }
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+    let identity_cost = log_gap_cost(&graph, (0..16).collect::<Vec<_>>(), &deg_cumul, None)?;
+
+    let llp = |stop_on_negative_gain| {
+        llp::layered_label_propagation_with_details(
+            &graph,
+            &deg_cumul,
+            llp::preds::MaxUpdates::from(1),
+            &llp::LlpParams {
+                gammas: vec![0.0],
+                num_threads: Some(1),
+                stop_on_negative_gain,
+                ..Default::default()
+            },
+        )
+    };
+    let (_, details) = llp(false)?;
+    assert!(details[0].1 > identity_cost);
+    // The update is reverted, so we are left with the initial labels
+    let (_, details) = llp(true)?;
+    assert_eq!(details[0].1, identity_cost);
+    assert_eq!(*details[0].2, *(0..16).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+fn test_llp_max_time() -> Result<()> {
+    let mut arcs = vec![];
+    for x in 0..100 {
+        arcs.push((x, (x + 1) % 100));
+        arcs.push(((x + 1) % 100, x));
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+    // Without the time limit, the number of updates would be unbounded
+    let labels = llp::layered_label_propagation_with_params(
+        &graph,
+        &deg_cumul,
+        llp::preds::MaxTime::from(std::time::Duration::ZERO),
+        &llp::LlpParams {
+            gammas: vec![0.0],
+            num_threads: Some(1),
+            ..Default::default()
+        },
+    )?;
+    assert_eq!((*labels).len(), 100);
+    Ok(())
+}
+
+/// A predicate recording the number of modified nodes of each update.
+struct RecordModified(std::sync::Arc<std::sync::Mutex<Vec<usize>>>);
+
+impl std::fmt::Display for RecordModified {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("(record modified)")
+    }
+}
+
+impl predicates::reflection::PredicateReflection for RecordModified {}
+impl predicates::Predicate<llp::preds::PredParams> for RecordModified {
+    fn eval(&self, pred_params: &llp::preds::PredParams) -> bool {
+        self.0.lock().unwrap().push(pred_params.modified);
+        false
+    }
+}
+
+#[test]
+fn test_llp_initial_labels() -> Result<()> {
+    // Two disjoint cliques
+    let mut arcs = vec![];
+    for base in [0, 4] {
+        for x in base..base + 4 {
+            for y in base..base + 4 {
+                if x != y {
+                    arcs.push((x, y));
+                }
+            }
+        }
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+    let initial_labels = [0, 0, 0, 0, 4, 4, 4, 4];
+
+    let modified = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let labels = llp::layered_label_propagation_with_params(
+        &graph,
+        &deg_cumul,
+        RecordModified(modified.clone()),
+        &llp::LlpParams {
+            gammas: vec![0.0],
+            num_threads: Some(1),
+            initial_labels: Some(&initial_labels),
+            ..Default::default()
+        },
+    )?;
+    // The initial labeling is already optimal
+    assert_eq!(*modified.lock().unwrap(), vec![0]);
+    assert!(labels[..4].iter().all(|&l| l == labels[0]));
+    assert!(labels[4..].iter().all(|&l| l == labels[4]));
+    assert_ne!(labels[0], labels[4]);
+
+    // Wrong number of labels
+    assert!(llp::layered_label_propagation_with_params(
+        &graph,
+        &deg_cumul,
+        llp::preds::MaxUpdates::from(1),
+        &llp::LlpParams {
+            gammas: vec![0.0],
+            num_threads: Some(1),
+            initial_labels: Some(&initial_labels[..7]),
+            ..Default::default()
+        },
+    )
+    .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_llp_deterministic() -> Result<()> {
+    // A ring, so that there are many ties, large enough to be split in
+    // several chunks
+    let n = 2 * llp::LLP_DETERMINISTIC_CHUNK_SIZE + llp::LLP_DETERMINISTIC_CHUNK_SIZE / 2;
+    let mut arcs = vec![];
+    for x in 0..n {
+        arcs.push((x, (x + 1) % n));
+        arcs.push(((x + 1) % n, x));
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+
+    let llp = |num_threads, granularity| {
+        llp::layered_label_propagation_with_params(
+            &graph,
+            &deg_cumul,
+            llp::preds::MaxUpdates::from(10),
+            &llp::LlpParams {
+                gammas: vec![0.0, 1.0],
+                num_threads: Some(num_threads),
+                granularity,
+                seed: 42,
+                deterministic: true,
+                ..Default::default()
+            },
+        )
+    };
+    // One thread uses the serial loop, more threads the parallel path
+    let labels = llp(1, None)?;
+    assert_eq!(labels, llp(4, None)?);
+    assert_eq!(labels, llp(4, Some(1))?);
+    assert_eq!(labels, llp(4, Some(7))?);
+    assert_eq!(labels, llp(3, None)?);
+    Ok(())
+}
+
+#[test]
+fn test_llp_sequential() -> Result<()> {
+    // A ring split in several deterministic chunks: we check that the serial
+    // loop used below the sequential threshold gives the same labels as a
+    // parallel run over the chunks
+    let n = 3 * llp::LLP_DETERMINISTIC_CHUNK_SIZE + 100;
+    let mut arcs = vec![];
+    for x in 0..n {
+        arcs.push((x, (x + 1) % n));
+        arcs.push(((x + 1) % n, x));
+    }
+    let graph = Left(VecGraph::from_arcs(arcs));
+    let deg_cumul = build_dcf(&graph)?;
+
+    let llp = |sequential_threshold| {
+        llp::layered_label_propagation_with_params(
+            &graph,
+            &deg_cumul,
+            llp::preds::MaxUpdates::from(10),
+            &llp::LlpParams {
+                gammas: vec![0.0, 1.0],
+                num_threads: Some(4),
+                seed: 42,
+                deterministic: true,
+                sequential_threshold,
+                ..Default::default()
+            },
+        )
+    };
+    let sequential = llp(n + 1)?;
+    let parallel = llp(0)?;
+    assert_eq!(sequential, parallel);
+    Ok(())
+}
+
+#[test]
+fn test_llp_u32_labels() -> Result<()> {
+    let work_dir = tempfile::tempdir()?;
+    let mut ring = vec![];
+    for x in 0..200 {
+        ring.push((x, (x + 1) % 200));
+        ring.push(((x + 1) % 200, x));
+    }
+    // Two 4-cliques joined by a bridge
+    let mut cliques = vec![(3, 4), (4, 3)];
+    for base in [0, 4] {
+        for x in base..base + 4 {
+            for y in base..base + 4 {
+                if x != y {
+                    cliques.push((x, y));
+                }
+            }
+        }
+    }
+
+    for arcs in [ring, cliques] {
+        let graph = Left(VecGraph::from_arcs(arcs));
+        let deg_cumul = build_dcf(&graph)?;
+        for stop_on_negative_gain in [false, true] {
+            let usize_labels = llp::layered_label_propagation_generic::<usize, _>(
+                &graph,
+                &deg_cumul,
+                llp::preds::MaxUpdates::from(10),
+                &llp::LlpParams {
+                    gammas: vec![0.0, 1.0],
+                    num_threads: Some(1),
+                    stop_on_negative_gain,
+                    deterministic: true,
+                    work_dir: Some(work_dir.path().to_path_buf()),
+                    ..Default::default()
+                },
+            )?;
+            let u32_labels = llp::layered_label_propagation_generic::<u32, _>(
+                &graph,
+                &deg_cumul,
+                llp::preds::MaxUpdates::from(10),
+                &llp::LlpParams {
+                    gammas: vec![0.0, 1.0],
+                    num_threads: Some(1),
+                    stop_on_negative_gain,
+                    deterministic: true,
+                    work_dir: Some(work_dir.path().to_path_buf()),
+                    ..Default::default()
+                },
+            )?;
+            assert_eq!(usize_labels, u32_labels);
+        }
+    }
+
+    // Too many nodes for 16-bit labels
+    let graph = Left(VecGraph::<()>::empty(1 << 16));
+    let deg_cumul = build_dcf(&graph)?;
+    assert!(llp::layered_label_propagation_generic::<u16, _>(
+        &graph,
+        &deg_cumul,
+        llp::preds::MaxUpdates::from(10),
+        &llp::LlpParams {
+            gammas: vec![0.0],
+            num_threads: Some(1),
+            deterministic: true,
+            work_dir: Some(work_dir.path().to_path_buf()),
+            ..Default::default()
+        },
+    )
+    .is_err());
+    Ok(())
+}
@@ -0,0 +1,217 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use webgraph::algo::graphs_equal;
+use webgraph::graphs::random::ErdosRenyi;
+use webgraph::prelude::*;
+
+#[test]
+fn test_truncated_properties() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    std::fs::copy(
+        "tests/data/cnr-2000.graph",
+        basename.with_extension(GRAPH_EXTENSION),
+    )?;
+    let properties = std::fs::read_to_string("tests/data/cnr-2000.properties")?;
+
+    // cut the properties file at every line, and in the middle of every line
+    let mut prefixes = vec![];
+    for (i, c) in properties.char_indices() {
+        if c == '\n' || c == '=' {
+            prefixes.push(&properties[..i]);
+        }
+    }
+    for prefix in prefixes {
+        std::fs::write(basename.with_extension(PROPERTIES_EXTENSION), prefix)?;
+        let complete = ["nodes", "arcs"].iter().all(|key| {
+            prefix.lines().any(|line| {
+                line.split_once('=')
+                    .is_some_and(|(k, v)| k == *key && !v.is_empty())
+            })
+        });
+        // loading must never panic, and must fail if a required key is missing
+        let result = BVGraphSeq::with_basename(&basename)
+            .endianness::<BE>()
+            .load();
+        if !complete {
+            assert!(result.is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_unaligned_graph_length() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("graph");
+    let graph_path = basename.with_extension(GRAPH_EXTENSION);
+
+    // Trailing zeroes are irrelevant, as readers zero-extend the graph, so we
+    // remove them and look for a graph whose last nonzero byte is the first
+    // byte of a word, that is, of length 4k + 1
+    let mut seed = 0;
+    let graph = loop {
+        let graph = Left(VecGraph::from_lender(
+            ErdosRenyi::new(100, 0.1, seed).iter(),
+        ));
+        BVComp::single_thread::<BE, _>(&basename, &graph, CompFlags::default(), false, None)?;
+        let mut bytes = std::fs::read(&graph_path)?;
+        while bytes.last() == Some(&0) {
+            bytes.pop();
+        }
+        if bytes.len() % 4 == 1 {
+            std::fs::write(&graph_path, bytes)?;
+            break graph;
+        }
+        seed += 1;
+    };
+    assert_eq!(std::fs::metadata(&graph_path)?.len() % 4, 1);
+
+    let seq = BVGraphSeq::with_basename(&basename).endianness::<BE>();
+    assert!(graphs_equal(&graph, seq.clone().mode::<Mmap>().load()?));
+    assert!(graphs_equal(&graph, seq.clone().mode::<LoadMem>().load()?));
+    assert!(graphs_equal(&graph, seq.mode::<LoadMmap>().load()?));
+    Ok(())
+}
+
+#[test]
+fn test_num_arcs_exact() -> Result<()> {
+    let seq_graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    assert_eq!(seq_graph.num_arcs_exact(), Some(3216152));
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    assert_eq!(graph.num_arcs_exact(), Some(3216152));
+    Ok(())
+}
+
+#[test]
+fn test_memory_flags() -> Result<()> {
+    let seq = BVGraphSeq::with_basename("tests/data/cnr-2000").endianness::<BE>();
+    let sequential = seq
+        .clone()
+        .flags(MemoryFlags::SEQUENTIAL | MemoryFlags::TRANSPARENT_HUGE_PAGES)
+        .load()?;
+    let random = seq.flags(MemoryFlags::RANDOM_ACCESS).load()?;
+    // flags are just hints, so the data must be the same
+    assert!(graphs_equal(&sequential, &random));
+    assert!(sequential.offset_deg_iter().eq(random.offset_deg_iter()));
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Node 325557 out of range 0..325557")]
+fn test_successors_out_of_range() {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()
+        .unwrap();
+    // a typical off-by-one error with one-based node ids
+    graph.successors(graph.num_nodes());
+}
+
+#[test]
+#[should_panic(expected = "Node 325557 out of range 0..325557")]
+fn test_outdegree_out_of_range() {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()
+        .unwrap();
+    graph.outdegree(graph.num_nodes());
+}
+
+#[test]
+fn test_verify() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .verify()
+        .load()?;
+    assert_eq!(graph.num_nodes(), 325557);
+
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("graph");
+    let er = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    BVComp::single_thread::<BE, _>(&basename, &er, CompFlags::default(), false, None)?;
+    let properties_path = basename.with_extension(PROPERTIES_EXTENSION);
+    let properties = std::fs::read_to_string(&properties_path)?;
+    let load = || {
+        BVGraphSeq::with_basename(&basename)
+            .endianness::<BE>()
+            .verify()
+            .load()
+    };
+    load()?;
+
+    // Successors beyond the number of nodes
+    assert!(properties.contains("nodes=100\n"));
+    std::fs::write(
+        &properties_path,
+        properties.replace("nodes=100\n", "nodes=50\n"),
+    )?;
+    assert!(BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()
+        .is_ok());
+    let err = load().err().unwrap();
+    assert!(err.to_string().contains("but the graph has 50 nodes"));
+
+    // Wrong number of arcs
+    let arcs = format!("arcs={}\n", er.num_arcs());
+    assert!(properties.contains(&arcs));
+    std::fs::write(
+        &properties_path,
+        properties.replace(&arcs, &format!("arcs={}\n", er.num_arcs() + 1)),
+    )?;
+    let err = load().err().unwrap();
+    assert!(err.to_string().contains("arcs"));
+    Ok(())
+}
+
+#[test]
+fn test_missing_offsets() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("graph");
+    let er = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+    BVComp::single_thread::<BE, _>(&basename, &er, CompFlags::default(), false, None)?;
+    let load = || BVGraph::with_basename(&basename).endianness::<BE>().load();
+    let build = || {
+        BVGraph::with_basename(&basename)
+            .endianness::<BE>()
+            .build_missing_ef()
+            .load()
+    };
+    let ef_path = basename.with_extension(EF_EXTENSION);
+
+    // By default, a missing .ef file is an error
+    let err = load().err().unwrap();
+    assert!(err.to_string().contains("webgraph build ef"));
+    std::fs::write(basename.with_extension(OBL_EXTENSION), [])?;
+    let err = load().err().unwrap();
+    assert!(err.to_string().contains(".obl"));
+    assert!(!ef_path.exists());
+
+    // Errors in reading the .obl file are returned
+    assert!(build().is_err());
+    assert!(!ef_path.exists());
+
+    // Without the .obl file, the .ef file is built from the graph and stored
+    std::fs::remove_file(basename.with_extension(OBL_EXTENSION))?;
+    let graph = build()?;
+    assert!(ef_path.exists());
+    for node in 0..er.num_nodes() {
+        assert!(graph.successors(node).eq(er.successors(node)));
+    }
+    let graph = load()?;
+    for node in 0..er.num_nodes() {
+        assert!(graph.successors(node).eq(er.successors(node)));
+    }
+    Ok(())
+}
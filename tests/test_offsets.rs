@@ -52,3 +52,448 @@ fn test_offsets() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_build_offsets() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/cnr-2000").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    // A stale offsets file must be overwritten
+    std::fs::write(basename.with_extension(OFFSETS_EXTENSION), [0xFF; 100])?;
+    build_offsets(&basename)?;
+
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let offsets_data = std::fs::read(basename.with_extension(OFFSETS_EXTENSION))?;
+    let mut reader = BufBitReader::<BE, _>::new(MemWordReader::new(&offsets_data));
+    let ef_offsets = <webgraph::graphs::bvgraph::EF>::mmap(
+        "tests/data/cnr-2000.ef",
+        deser::Flags::TRANSPARENT_HUGE_PAGES,
+    )?;
+    let mut offset = 0;
+    for i in 0..graph.num_nodes() + 1 {
+        offset += reader.read_gamma().unwrap() as usize;
+        assert_eq!(offset, ef_offsets.get(i));
+    }
+
+    // Random access through the offsets matches a sequential scan of the
+    // copy of the graph
+    let seq_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let mut iter_nodes = seq_graph.iter();
+    while let Some((node_id, seq_succ)) = iter_nodes.next() {
+        assert!(itertools::equal(graph.successors(node_id), seq_succ));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn test_build_offsets_pipelined() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/cnr-2000").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    build_offsets(&basename)?;
+    let expected = std::fs::read(basename.with_extension(OFFSETS_EXTENSION))?;
+    std::fs::remove_file(basename.with_extension(OFFSETS_EXTENSION))?;
+    build_offsets_pipelined(&basename)?;
+    assert_eq!(
+        std::fs::read(basename.with_extension(OFFSETS_EXTENSION))?,
+        expected
+    );
+    Ok(())
+}
+
+#[test]
+fn test_estimated_node_bits() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let seq_graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let mut degs_iter = seq_graph.offset_deg_iter();
+    let mut offsets = (&mut degs_iter)
+        .map(|(offset, _)| offset)
+        .collect::<Vec<_>>();
+    offsets.push(degs_iter.get_pos());
+    assert_eq!(offsets.len(), graph.num_nodes() + 1);
+    for node in 0..graph.num_nodes() {
+        assert_eq!(
+            graph.estimated_node_bits(node),
+            Some(offsets[node + 1] - offsets[node])
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[should_panic(expected = "Node 325557 out of range 0..325557")]
+fn test_estimated_node_bits_out_of_range() {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()
+        .unwrap();
+    graph.estimated_node_bits(graph.num_nodes());
+}
+
+#[test]
+fn test_build_ef() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/cnr-2000").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    let ef = build_ef(&basename)?;
+
+    // Compare against the raw offsets
+    let offsets_data = std::fs::read("tests/data/cnr-2000.offsets")?;
+    let mut reader = BufBitReader::<BE, _>::new(MemWordReader::new(&offsets_data));
+    let stored = <webgraph::graphs::bvgraph::EF>::mmap(
+        basename.with_extension(EF_EXTENSION),
+        deser::Flags::empty(),
+    )?;
+    assert_eq!(ef.len(), 325_557 + 1);
+    let mut offset = 0;
+    for i in 0..ef.len() {
+        offset += reader.read_gamma().unwrap() as usize;
+        assert_eq!(ef.get(i), offset);
+        assert_eq!(stored.get(i), offset);
+    }
+
+    // The stored file can be used for random access
+    let graph = BVGraph::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let seq_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let mut iter_nodes = seq_graph.iter();
+    while let Some((node_id, seq_succ)) = iter_nodes.next() {
+        assert!(itertools::equal(graph.successors(node_id), seq_succ));
+    }
+    Ok(())
+}
+
+#[test]
+fn test_random_access_single_node() -> Result<()> {
+    // Each successor list is decoded starting only from its own offset,
+    // following references recursively
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("test");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/test").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    // The stored .ef file was serialized by an older version of ε-serde
+    build_ef(&basename)?;
+    let graph = BVGraph::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let seq_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let mut iter_nodes = seq_graph.iter();
+    while let Some((node_id, seq_succ)) = iter_nodes.next() {
+        let succ = graph.successors(node_id);
+        assert_eq!(succ.len(), graph.outdegree(node_id));
+        assert!(itertools::equal(succ, seq_succ));
+    }
+    // In reverse order, no list can have been decoded before its references
+    for node_id in (0..graph.num_nodes()).rev() {
+        assert_eq!(
+            graph.successors(node_id).collect::<Vec<_>>(),
+            seq_graph
+                .iter_from(node_id)
+                .next()
+                .unwrap()
+                .1
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            graph.successors_at(node_id)?,
+            graph.successors(node_id).collect::<Vec<_>>()
+        );
+    }
+    assert!(graph.successors_at(graph.num_nodes()).is_err());
+
+    // A larger graph, with long reference chains
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    for node_id in (0..graph.num_nodes()).step_by(97) {
+        assert_eq!(
+            graph.successors_at(node_id)?,
+            graph.successors(node_id).collect::<Vec<_>>()
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_successors_at_reference_chain() -> Result<()> {
+    // All nodes have the same successors, so each list refers to the
+    // previous one, and the chain of references of the last node is as long
+    // as the graph
+    let num_nodes = 1 << 16;
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("chain");
+    let mut vec_graph = VecGraph::empty(num_nodes);
+    for x in 0..num_nodes {
+        for y in 0..3 {
+            vec_graph.add_arc(x, y);
+        }
+    }
+    let comp_flags = CompFlags {
+        compression_window: 1,
+        max_ref_count: num_nodes,
+        ..CompFlags::default()
+    };
+    BVComp::single_thread::<BE, _>(&basename, &Left(vec_graph), comp_flags, false, None)?;
+    build_ef(&basename)?;
+    let load = || BVGraph::with_basename(&basename).endianness::<BE>().load();
+    let graph = load()?;
+    assert_eq!(graph.successors_at(num_nodes - 1)?, vec![0, 1, 2]);
+
+    // A chain longer than the maximum reference count is an error
+    let properties_path = basename.with_extension(PROPERTIES_EXTENSION);
+    let properties = std::fs::read_to_string(&properties_path)?;
+    std::fs::write(
+        &properties_path,
+        properties.replace(&format!("maxrefcount={}", num_nodes), "maxrefcount=3"),
+    )?;
+    let graph = load()?;
+    assert_eq!(graph.successors_at(3)?, vec![0, 1, 2]);
+    let err = graph.successors_at(4).unwrap_err().to_string();
+    assert!(err.contains("maximum reference count 3"), "{}", err);
+    Ok(())
+}
+
+/// Writes `values` as a Java-serialized
+/// `it.unimi.dsi.sux4j.util.EliasFanoMonotoneLongBigList`, with a selection
+/// structure that uses a custom `writeObject` method, so that references,
+/// nested objects and annotations are all exercised.
+fn write_obl(path: impl AsRef<std::path::Path>, values: &[u64]) -> Result<()> {
+    fn utf(out: &mut Vec<u8>, s: &str) {
+        out.extend((s.len() as u16).to_be_bytes());
+        out.extend(s.as_bytes());
+    }
+    fn longs(out: &mut Vec<u8>, words: &[u64]) {
+        out.extend((words.len() as i32).to_be_bytes());
+        for word in words {
+            out.extend(word.to_be_bytes());
+        }
+    }
+
+    let n = values.len();
+    let u = values.last().copied().unwrap_or(0);
+    let l = match u / (n.max(1) as u64) {
+        0 => 0,
+        x => x.ilog2() as usize,
+    };
+    let mut lower = vec![0_u64; (n * l).div_ceil(64)];
+    let mut upper = vec![0_u64; (n + (u >> l) as usize + 1).div_ceil(64)];
+    for (i, &v) in values.iter().enumerate() {
+        for b in 0..l {
+            if v >> b & 1 != 0 {
+                lower[(i * l + b) / 64] |= 1 << ((i * l + b) % 64);
+            }
+        }
+        let pos = (v >> l) as usize + i;
+        upper[pos / 64] |= 1 << (pos % 64);
+    }
+
+    let mut out = vec![0xAC, 0xED, 0x00, 0x05];
+    // handle 0: the class descriptor of the list
+    out.extend([0x73, 0x72]);
+    utf(
+        &mut out,
+        "it.unimi.dsi.sux4j.util.EliasFanoMonotoneLongBigList",
+    );
+    out.extend(4_i64.to_be_bytes());
+    out.extend([0x02]);
+    out.extend(6_u16.to_be_bytes());
+    out.push(b'I');
+    utf(&mut out, "l");
+    out.push(b'J');
+    utf(&mut out, "length");
+    out.push(b'J');
+    utf(&mut out, "lowerBitsMask");
+    // handle 1: the string "[J"
+    out.push(b'[');
+    utf(&mut out, "lowerBits");
+    out.push(0x74);
+    utf(&mut out, "[J");
+    // handle 2: the class name of the selection structure
+    out.push(b'L');
+    utf(&mut out, "selectUpper");
+    out.push(0x74);
+    utf(&mut out, "Lit/unimi/dsi/sux4j/bits/SimpleSelect;");
+    out.push(b'[');
+    utf(&mut out, "upperBits");
+    out.push(0x71);
+    out.extend((0x7E0000 + 1_i32).to_be_bytes());
+    out.extend([0x78, 0x70]);
+    // handle 3: the list; primitive fields
+    out.extend((l as i32).to_be_bytes());
+    out.extend((n as i64).to_be_bytes());
+    out.extend(((1_i64 << l) - 1).to_be_bytes());
+    // handle 4: the class descriptor of long[]; handle 5: the lower bits
+    out.extend([0x75, 0x72]);
+    utf(&mut out, "[J");
+    out.extend(0x782004B512B17593_u64.to_be_bytes());
+    out.extend([0x02, 0x00, 0x00, 0x78, 0x70]);
+    longs(&mut out, &lower);
+    // handle 6: the class descriptor of the selection structure
+    out.extend([0x73, 0x72]);
+    utf(&mut out, "it.unimi.dsi.sux4j.bits.SimpleSelect");
+    out.extend(1_i64.to_be_bytes());
+    out.extend([0x03]);
+    out.extend(1_u16.to_be_bytes());
+    out.push(b'[');
+    utf(&mut out, "inventory");
+    out.push(0x71);
+    out.extend((0x7E0000 + 1_i32).to_be_bytes());
+    out.extend([0x78, 0x70]);
+    // handle 7: the selection structure; handle 8: its inventory
+    out.extend([0x75, 0x71]);
+    out.extend((0x7E0000 + 4_i32).to_be_bytes());
+    longs(&mut out, &[0, 1]);
+    // the data written by writeObject
+    out.extend([0x77, 0x08]);
+    out.extend(42_u64.to_be_bytes());
+    out.push(0x78);
+    // handle 9: the upper bits
+    out.extend([0x75, 0x71]);
+    out.extend((0x7E0000 + 4_i32).to_be_bytes());
+    longs(&mut out, &upper);
+
+    std::fs::write(path, out)?;
+    Ok(())
+}
+
+#[test]
+fn test_load_obl() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/cnr-2000").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    let ef = build_ef(&basename)?;
+    std::fs::remove_file(basename.with_extension(EF_EXTENSION))?;
+    let offsets = (0..ef.len()).map(|i| ef.get(i) as u64).collect::<Vec<_>>();
+    let obl_path = basename.with_extension(OBL_EXTENSION);
+    write_obl(&obl_path, &offsets)?;
+
+    let obl = load_obl(&obl_path)?;
+    assert_eq!(obl.len(), ef.len());
+    for i in 0..ef.len() {
+        assert_eq!(obl.get(i), ef.get(i));
+    }
+
+    // The .ef file built from the .obl file can be used for random access
+    build_ef_from_obl(&basename)?;
+    let graph = BVGraph::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let seq_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    let mut iter_nodes = seq_graph.iter();
+    while let Some((node_id, seq_succ)) = iter_nodes.next() {
+        assert!(itertools::equal(graph.successors(node_id), seq_succ));
+    }
+
+    // Offsets that do not match the graph are rejected
+    write_obl(&obl_path, &offsets[..offsets.len() - 1])?;
+    assert!(load_obl(&obl_path).is_ok());
+    let err = build_ef_from_obl(&basename).unwrap_err().to_string();
+    assert!(err.contains("325557 nodes"), "{}", err);
+
+    // Small lists, including the empty one
+    for values in [vec![], vec![0], vec![0, 0, 3, 1000, 1000, 1 << 40]] {
+        write_obl(&obl_path, &values)?;
+        let obl = load_obl(&obl_path)?;
+        assert_eq!(obl.len(), values.len());
+        for (i, &value) in values.iter().enumerate() {
+            assert_eq!(obl.get(i), value as usize);
+        }
+    }
+
+    // Truncated or non-Java files are rejected
+    write_obl(&obl_path, &offsets)?;
+    let data = std::fs::read(&obl_path)?;
+    std::fs::write(&obl_path, &data[..data.len() / 2])?;
+    assert!(load_obl(&obl_path).is_err());
+    std::fs::write(&obl_path, b"not an .obl file")?;
+    assert!(load_obl(&obl_path).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_load_with_obl_only() -> Result<()> {
+    // A graph distributed with an .obl file, but without .offsets and .ef files
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    for extension in [GRAPH_EXTENSION, PROPERTIES_EXTENSION] {
+        std::fs::copy(
+            std::path::Path::new("tests/data/cnr-2000").with_extension(extension),
+            basename.with_extension(extension),
+        )?;
+    }
+    let offsets_data = std::fs::read("tests/data/cnr-2000.offsets")?;
+    let mut reader = BufBitReader::<BE, _>::new(MemWordReader::new(&offsets_data));
+    let mut offset = 0;
+    let offsets = (0..325_557 + 1)
+        .map(|_| {
+            offset += reader.read_gamma().unwrap();
+            offset
+        })
+        .collect::<Vec<_>>();
+    write_obl(basename.with_extension(OBL_EXTENSION), &offsets)?;
+
+    // The .ef file is built from the .obl file only on request
+    assert!(BVGraph::with_basename(&basename)
+        .endianness::<BE>()
+        .load()
+        .is_err());
+    let graph = BVGraph::with_basename(&basename)
+        .endianness::<BE>()
+        .build_missing_ef()
+        .load()?;
+    let stored = <webgraph::graphs::bvgraph::EF>::mmap(
+        basename.with_extension(EF_EXTENSION),
+        deser::Flags::empty(),
+    )?;
+    for (i, &offset) in offsets.iter().enumerate() {
+        assert_eq!(stored.get(i), offset as usize);
+    }
+    let seq_graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let mut iter_nodes = seq_graph.iter();
+    while let Some((node_id, seq_succ)) = iter_nodes.next() {
+        assert!(itertools::equal(graph.successors(node_id), seq_succ));
+    }
+    Ok(())
+}
@@ -0,0 +1,43 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Tommaso Fontana
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_identity_vs_bfs() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let deg_cumul = build_dcf(&graph)?;
+
+    let identity = (0..graph.num_nodes()).collect::<Vec<_>>();
+    let identity_report = ordering_report(
+        &graph,
+        &identity,
+        &deg_cumul,
+        OrderingReport::DEFAULT_LOCALITY_WINDOW,
+        None,
+    )?;
+    assert_eq!(identity_report.num_arcs, graph.num_arcs());
+
+    let mut bfs = vec![0; graph.num_nodes()];
+    for (i, node) in BfsOrder::new(&graph).enumerate() {
+        bfs[node] = i;
+    }
+    let bfs_report = ordering_report(
+        &graph,
+        &bfs,
+        &deg_cumul,
+        OrderingReport::DEFAULT_LOCALITY_WINDOW,
+        None,
+    )?;
+    assert_eq!(bfs_report.num_arcs, graph.num_arcs());
+    assert_ne!(identity_report, bfs_report);
+    Ok(())
+}
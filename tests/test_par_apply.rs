@@ -0,0 +1,40 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use lender::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_par_apply_num_arcs() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let deg_cumul = build_dcf(&graph)?;
+    let thread_pool = rayon::ThreadPoolBuilder::new().num_threads(4).build()?;
+
+    for arc_granularity in [100, 10_000, usize::MAX] {
+        let (num_nodes, num_arcs) = graph.par_apply(
+            |range| {
+                assert!(!range.is_empty());
+                let mut num_arcs = 0;
+                for_!((_node, succ) in graph.iter_range(range.clone()) {
+                    num_arcs += succ.into_iter().count() as u64;
+                });
+                (range.len(), num_arcs)
+            },
+            |(n0, a0): (usize, u64), (n1, a1)| (n0 + n1, a0 + a1),
+            arc_granularity,
+            &deg_cumul,
+            &thread_pool,
+            None,
+        );
+        assert_eq!(num_nodes, graph.num_nodes());
+        assert_eq!(num_arcs, graph.num_arcs());
+    }
+    Ok(())
+}
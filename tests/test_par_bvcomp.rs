@@ -11,6 +11,10 @@ use anyhow::Result;
 use dsi_bitstream::prelude::*;
 use dsi_progress_logger::prelude::*;
 use lender::*;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use webgraph::graphs::random::ErdosRenyi;
 use webgraph::prelude::*;
 
 #[test]
@@ -96,3 +100,87 @@ fn _test_par_bvcomp(basename: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_par_bvcomp_progress() -> Result<()> {
+    let basename = "tests/data/cnr-2000";
+    let tmp_basename = PathBuf::from(String::from(basename) + "-par-progress");
+    let graph = webgraph::graphs::bvgraph::sequential::BVGraphSeq::with_basename(basename)
+        .endianness::<BE>()
+        .load()?;
+
+    let mut calls = 0;
+    let mut nodes = 0;
+    let mut arcs = 0;
+    BVComp::parallel_iter_with_progress::<BE, _>(
+        &tmp_basename,
+        graph.split_iter(4),
+        graph.num_nodes(),
+        CompFlags::default(),
+        Threads::Num(4),
+        temp_dir(std::env::temp_dir())?,
+        |chunk_nodes, chunk_arcs| {
+            calls += 1;
+            nodes += chunk_nodes;
+            arcs += chunk_arcs;
+        },
+    )?;
+
+    assert_eq!(calls, 4);
+    assert_eq!(nodes, graph.num_nodes());
+    assert_eq!(Some(arcs), graph.num_arcs_hint());
+
+    std::fs::remove_file(tmp_basename.with_extension(GRAPH_EXTENSION))?;
+    std::fs::remove_file(tmp_basename.with_extension(PROPERTIES_EXTENSION))?;
+    Ok(())
+}
+
+#[test]
+fn test_compress_from_arcs() -> Result<()> {
+    let graph = Left(VecGraph::from_lender(ErdosRenyi::new(100, 0.1, 0).iter()));
+
+    // Duplicate some arcs and shuffle them
+    let mut arcs = vec![];
+    for_!((src, succ) in graph.iter() {
+        for dst in succ {
+            arcs.push((src, dst));
+            if dst % 3 == 0 {
+                arcs.push((src, dst));
+            }
+        }
+    });
+    arcs.shuffle(&mut SmallRng::seed_from_u64(0));
+
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("arcs");
+    // A small batch size forces several batches
+    BVComp::compress_from_arcs::<BE>(
+        &basename,
+        arcs,
+        graph.num_nodes(),
+        64,
+        CompFlags::default(),
+        Threads::Num(3),
+        tmp_dir.path(),
+    )?;
+
+    let comp_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .load()?;
+    assert_eq!(comp_graph.num_nodes(), graph.num_nodes());
+    assert_eq!(comp_graph.num_arcs_hint(), Some(graph.num_arcs()));
+    assert!(webgraph::algo::graphs_equal(&graph, &comp_graph));
+
+    // Arcs out of bounds are rejected
+    assert!(BVComp::compress_from_arcs::<BE>(
+        &basename,
+        [(0, 1), (1, 100)],
+        100,
+        64,
+        CompFlags::default(),
+        Threads::Num(1),
+        tmp_dir.path(),
+    )
+    .is_err());
+    Ok(())
+}
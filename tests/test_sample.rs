@@ -0,0 +1,52 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use clap::Command;
+use dsi_bitstream::prelude::*;
+use webgraph::cli;
+use webgraph::prelude::*;
+
+#[test]
+fn test_sample() -> Result<()> {
+    let graph = BVGraph::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+
+    let sample = cli::sample::sample_successors(&graph, 5, 42);
+    assert_eq!(sample.len(), 5);
+    let nodes = sample.iter().map(|(node, _)| *node).collect::<Vec<_>>();
+    assert_eq!(
+        cli::sample::sample_successors(&graph, 5, 42)
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect::<Vec<_>>(),
+        nodes
+    );
+    assert_ne!(
+        cli::sample::sample_successors(&graph, 5, 43)
+            .into_iter()
+            .map(|(node, _)| node)
+            .collect::<Vec<_>>(),
+        nodes
+    );
+    for (node, succ) in sample {
+        assert!(node < graph.num_nodes());
+        assert_eq!(succ, graph.successors(node).collect::<Vec<_>>());
+    }
+
+    let matches = cli::sample::cli(Command::new("webgraph")).try_get_matches_from([
+        "webgraph",
+        cli::sample::COMMAND_NAME,
+        "tests/data/cnr-2000",
+        "-n",
+        "5",
+        "--seed",
+        "42",
+    ])?;
+    let (_, submatches) = matches.subcommand().unwrap();
+    cli::sample::main(submatches)
+}
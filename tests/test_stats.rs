@@ -0,0 +1,41 @@
+/*
+ * SPDX-FileCopyrightText: 2024 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use lender::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_stats_cnr_2000() -> Result<()> {
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let stats = graph_stats(&graph);
+    assert_eq!(stats.num_nodes, 325557);
+    assert_eq!(stats.num_arcs, 3216152);
+
+    // compute the other statistics in a different way
+    let indegrees = indegrees(&graph);
+    let mut outdegrees = vec![];
+    let mut num_loops = 0;
+    for_!((node, succ) in graph.iter() {
+        let succ = succ.into_iter().collect::<Vec<_>>();
+        num_loops += succ.iter().filter(|&&s| s == node).count() as u64;
+        outdegrees.push(succ.len());
+    });
+    assert_eq!(stats.min_outdegree, *outdegrees.iter().min().unwrap());
+    assert_eq!(stats.max_outdegree, *outdegrees.iter().max().unwrap());
+    assert_eq!(stats.num_loops, num_loops);
+    assert_eq!(
+        stats.num_isolated,
+        (0..stats.num_nodes)
+            .filter(|&x| indegrees[x] == 0 && outdegrees[x] == 0)
+            .count()
+    );
+    assert!((stats.avg_outdegree() - 3216152.0 / 325557.0).abs() < 1E-9);
+    Ok(())
+}
@@ -0,0 +1,68 @@
+/*
+ * SPDX-FileCopyrightText: 2023 Inria
+ * SPDX-FileCopyrightText: 2023 Sebastiano Vigna
+ *
+ * SPDX-License-Identifier: Apache-2.0 OR LGPL-2.1-or-later
+ */
+
+#![cfg(feature = "zstd")]
+
+use anyhow::Result;
+use dsi_bitstream::prelude::*;
+use lender::*;
+use webgraph::prelude::*;
+
+#[test]
+fn test_zstd() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    std::fs::copy(
+        "tests/data/cnr-2000.properties",
+        basename.with_extension(PROPERTIES_EXTENSION),
+    )?;
+    let graph_data = std::fs::read("tests/data/cnr-2000.graph")?;
+    std::fs::write(
+        basename.with_extension("graph.zst"),
+        zstd::stream::encode_all(graph_data.as_slice(), 0)?,
+    )?;
+
+    let graph = BVGraphSeq::with_basename("tests/data/cnr-2000")
+        .endianness::<BE>()
+        .load()?;
+    let zstd_graph = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .graph_mode::<Zstd>()
+        .load()?;
+    assert_eq!(zstd_graph.num_nodes(), graph.num_nodes());
+
+    let mut zstd_iter = zstd_graph.iter();
+    for_!((node, succ) in graph.iter() {
+        let (zstd_node, zstd_succ) = zstd_iter.next().unwrap();
+        assert_eq!(node, zstd_node);
+        assert!(itertools::equal(succ, zstd_succ));
+    });
+    assert!(zstd_iter.next().is_none());
+    Ok(())
+}
+
+#[test]
+fn test_zstd_invalid() -> Result<()> {
+    let tmp_dir = tempfile::tempdir()?;
+    let basename = tmp_dir.path().join("cnr-2000");
+    std::fs::copy(
+        "tests/data/cnr-2000.properties",
+        basename.with_extension(PROPERTIES_EXTENSION),
+    )?;
+    // an uncompressed graph is not a valid zstd stream
+    std::fs::copy(
+        "tests/data/cnr-2000.graph",
+        basename.with_extension("graph.zst"),
+    )?;
+    let result = BVGraphSeq::with_basename(&basename)
+        .endianness::<BE>()
+        .graph_mode::<Zstd>()
+        .load();
+    let err = result.err().expect("loading should fail");
+    assert!(format!("{:#}", err).contains("not a valid zstd file"));
+    Ok(())
+}